@@ -1,7 +1,7 @@
 // Example usage of movement logic WASM module in TypeScript/JavaScript
 // This file demonstrates how to use the compiled WASM air acceleration, player movement, and gravity functions
 
-import init, { Vector3, airAccelerate, playerMove, gravityForce, gravityForceAcceleration, RayCollisionHit } from './pkg/movement_logic.js';
+import init, { Vector3, Vector3Pool, airAccelerate, playerMove, gravityForce, gravityForceAcceleration, RayCollisionHit } from './pkg/movement_logic.js';
 
 /**
  * Movement controller class that uses the WASM air acceleration
@@ -50,6 +50,32 @@ export class MovementController {
         );
     }
     
+    /**
+     * Air acceleration without allocating: the vectors live in a `Vector3Pool`
+     * in WASM memory and are passed around as handles
+     *
+     * Acquire the handles once per frame and call `pool.releaseAll()` at the
+     * end of it; once the pool has grown to the frame's peak usage neither
+     * side allocates.
+     * @param pool Pool the handles belong to
+     * @param velocity Handle of the current velocity
+     * @param wishDirection Handle of the desired movement direction
+     * @returns Handle of the acceleration to add, valid until `pool.releaseAll()`
+     */
+    airAccelerationPooled(
+        pool: Vector3Pool,
+        velocity: number,
+        wishDirection: number,
+        wishSpeed: number,
+        airAccelerateValue: number = 10.0,
+        maxAirWishSpeed: number = 30.0,
+        deltaTime: number = 0.016
+    ): number {
+        const out = pool.acquire();
+        pool.airAccelerate(velocity, wishDirection, wishSpeed, airAccelerateValue, maxAirWishSpeed, deltaTime, out);
+        return out;
+    }
+
     /**
      * Apply player movement with ground projection and speed modifiers
      * @param direction Input movement direction
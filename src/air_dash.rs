@@ -0,0 +1,143 @@
+//! Air dashing
+//!
+//! Pressing `PlayerInput::air_dash` while airborne bursts the player along the
+//! aim or the held movement direction at `air_dash_speed`. Unlike dodges, air
+//! dashes never happen on the ground; `air_dash_count` of them are allowed per
+//! airtime and landing gives them back. `air_dash_momentum` decides what
+//! happens to the velocity the player already had.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::events::MovementEvent;
+use crate::fixed::with_fixed;
+use crate::wishdir::compute_wishdir_fixed;
+use crate::{MoveType, MovementConfig, PlayerInput, PlayerState, UpAxis, Vector3};
+
+/// Which direction an air dash goes
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema), schemars(rename_all = "snake_case"))]
+pub enum AirDashDirection {
+    /// The held movement direction, or straight ahead without input
+    #[default]
+    Input,
+    /// The full 3D view direction, including pitch
+    Aim,
+}
+
+/// What an air dash does with the existing velocity
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema), schemars(rename_all = "snake_case"))]
+pub enum AirDashMomentum {
+    /// The dash velocity replaces the old velocity
+    #[default]
+    Replace,
+    /// The dash velocity is added to the old velocity
+    Add,
+    /// Added, then limited to `air_dash_speed`
+    Clamp,
+}
+
+/// The unit direction of an air dash for the input
+pub fn air_dash_direction(input: &PlayerInput, config: &MovementConfig) -> Vector3 {
+    with_fixed!(config.up_axis, Z_UP => air_dash_direction_fixed::<Z_UP>(input, config))
+}
+
+/// `air_dash_direction` for the up axis of a `fixed` instantiation
+pub(crate) fn air_dash_direction_fixed<const Z_UP: bool>(input: &PlayerInput, config: &MovementConfig) -> Vector3 {
+    let axis = UpAxis::from_z_up(Z_UP);
+    match config.air_dash_direction {
+        AirDashDirection::Aim => input.view_forward(axis),
+        AirDashDirection::Input => {
+            let (wish_dir, amount) = compute_wishdir_fixed::<Z_UP>(input.yaw, input.pitch, input, MoveType::Walk, config);
+            if amount > 0.0 {
+                wish_dir
+            } else {
+                input.forward(axis)
+            }
+        }
+    }
+}
+
+/// Dash when the button is pressed in the air with dashes left
+pub(crate) fn air_dash<const Z_UP: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    events: &mut Vec<MovementEvent>,
+) {
+    if !input.air_dash || state.air_dash_held || state.air_dashes >= config.air_dash_count {
+        return;
+    }
+    let dash = air_dash_direction_fixed::<Z_UP>(input, config).multiply_scalar(config.air_dash_speed);
+    state.velocity = match config.air_dash_momentum {
+        AirDashMomentum::Replace => dash,
+        AirDashMomentum::Add => state.velocity + dash,
+        AirDashMomentum::Clamp => {
+            let velocity = state.velocity + dash;
+            let speed = velocity.magnitude();
+            if speed > config.air_dash_speed {
+                velocity.multiply_scalar(config.air_dash_speed / speed)
+            } else {
+                velocity
+            }
+        }
+    };
+    state.air_dashes += 1;
+    events.push(MovementEvent::AirDashed { tick: state.tick, speed: state.velocity.magnitude() });
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = airDashDirection)]
+pub fn air_dash_direction_wasm(input: &PlayerInput, config: &MovementConfig) -> Vector3 {
+    air_dash_direction(input, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_air_dash_momentum_rules() {
+        let dash = PlayerInput { air_dash: true, side_move: 1.0, ..PlayerInput::new() };
+        let falling = PlayerState { velocity: Vector3::new(0.0, -100.0, -400.0), ..PlayerState::default() };
+        let dashed = |air_dash_momentum| {
+            let config = MovementConfig { air_dash_count: 1, air_dash_momentum, ..y_up_config() };
+            let mut state = falling;
+            let events = player_tick(&mut state, &dash, &config, 1.0 / 64.0, None);
+            assert!(matches!(events.as_slice(), [MovementEvent::AirDashed { .. }]));
+            state
+        };
+        let speed = y_up_config().air_dash_speed;
+
+        assert!((dashed(AirDashMomentum::Replace).velocity.x - speed).abs() < 0.01);
+        assert_eq!(dashed(AirDashMomentum::Replace).velocity.z, 0.0);
+        assert_eq!(dashed(AirDashMomentum::Add).velocity.z, -400.0);
+        let clamped = dashed(AirDashMomentum::Clamp);
+        assert!(clamped.velocity.z < 0.0 && clamped.velocity.z > -400.0);
+        let horizontal = Vector3::new(clamped.velocity.x, 0.0, clamped.velocity.z);
+        assert!(horizontal.magnitude() <= speed + 0.01);
+    }
+
+    #[test]
+    fn test_air_dashes_reset_on_landing() {
+        let config = MovementConfig { air_dash_count: 1, ..y_up_config() };
+        let dash = PlayerInput { air_dash: true, ..PlayerInput::new() };
+        let mut state = PlayerState::default();
+
+        assert_eq!(player_tick(&mut state, &dash, &config, 1.0 / 64.0, None).len(), 1);
+        assert!(player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, None).is_empty());
+        // Out of dashes until landing
+        assert!(player_tick(&mut state, &dash, &config, 1.0 / 64.0, None).is_empty());
+        assert_eq!(state.air_dashes, 1);
+
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        state.velocity = Vector3::new(0.0, 0.0, 0.0);
+        player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+        assert_eq!(state.air_dashes, 0);
+    }
+}
@@ -0,0 +1,81 @@
+//! Air drag
+//!
+//! Source-style air movement has no drag: long falls only stop speeding up at
+//! `max_fall_speed`, which makes them feel floaty and then abruptly capped.
+//! `air_drag` slows the whole velocity while walking in the air, either in
+//! proportion to the speed (`k * v`) or, with `air_drag_quadratic`, to its
+//! square (`k * v²`) like real air resistance. Falls then ease towards a
+//! natural terminal speed (`air_drag_terminal_speed`), and `max_fall_speed`
+//! still caps them if it is lower.
+//!
+//! Drag runs after air acceleration and gravity each tick, so it also bleeds
+//! strafe speed. Both forms are integrated implicitly, which keeps them stable
+//! and free of sign flips even with large coefficients or long ticks.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, Vector3};
+
+/// Slow `velocity` by one tick of air drag
+pub(crate) fn apply_air_drag(velocity: &mut Vector3, config: &MovementConfig, delta_time: f32) {
+    if config.air_drag <= 0.0 {
+        return;
+    }
+    let rate = if config.air_drag_quadratic { config.air_drag * velocity.magnitude() } else { config.air_drag };
+    *velocity = velocity.multiply_scalar(1.0 / (1.0 + rate * delta_time));
+}
+
+/// Fall speed at which air drag balances gravity, in config units (infinite without drag)
+pub fn air_drag_terminal_speed(config: &MovementConfig) -> f32 {
+    if config.air_drag <= 0.0 {
+        return f32::INFINITY;
+    }
+    if config.air_drag_quadratic {
+        (config.gravity / config.air_drag).sqrt()
+    } else {
+        config.gravity / config.air_drag
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = airDragTerminalSpeed)]
+pub fn air_drag_terminal_speed_wasm(config: &MovementConfig) -> f32 {
+    air_drag_terminal_speed(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, PlayerState, HAMMER_UNITS_TO_METERS};
+    use crate::tick::tests::y_up_config;
+
+    fn fall_speed(config: &MovementConfig, seconds: f32) -> f32 {
+        let mut state = PlayerState::default();
+        for _ in 0..(seconds * 64.0) as usize {
+            player_tick(&mut state, &PlayerInput::new(), config, 1.0 / 64.0, None);
+        }
+        -state.velocity.y
+    }
+
+    #[test]
+    fn test_falls_settle_at_the_terminal_speed() {
+        for air_drag_quadratic in [false, true] {
+            let air_drag = if air_drag_quadratic { 0.0002 } else { 0.5 };
+            let config = MovementConfig { air_drag, air_drag_quadratic, ..y_up_config() };
+            let terminal = air_drag_terminal_speed(&config);
+            assert!(terminal < config.max_fall_speed);
+            assert!((fall_speed(&config, 30.0) - terminal).abs() < 0.02 * terminal);
+
+            // The same drag in meters
+            let metric = MovementConfig { unit_scale: HAMMER_UNITS_TO_METERS, ..config };
+            let metric_speed = fall_speed(&metric, 30.0) / HAMMER_UNITS_TO_METERS;
+            assert!((metric_speed - fall_speed(&config, 30.0)).abs() < 0.01 * terminal);
+
+            // A lower cap still wins
+            let capped = MovementConfig { max_fall_speed: terminal * 0.5, ..config };
+            assert!((fall_speed(&capped, 30.0) - terminal * 0.5).abs() < 1e-2);
+        }
+        assert_eq!(fall_speed(&y_up_config(), 10.0), 3500.0);
+    }
+}
@@ -0,0 +1,82 @@
+//! Analog stick movement input
+//!
+//! `forward_move` and `side_move` may hold any value between -1.0 and 1.0, as
+//! read from a controller stick. The stick's deflection (the length of the
+//! two axes together) goes through `analog_deadzone` and the power curve of
+//! `analog_response_exponent` before it scales the wish speed. Keyboard input
+//! is always fully deflected, so it moves the same with any curve. A wish
+//! speed below `max_air_wish_speed` also lowers the air strafing cap, so
+//! partial deflection strafes like a slower player instead of a full one.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerInput};
+
+/// Movement amount (0.0 to 1.0) for a stick deflection after deadzone and response curve
+pub fn analog_stick_amount(deflection: f32, config: &MovementConfig) -> f32 {
+    let deadzone = config.analog_deadzone;
+    if deflection <= deadzone || deadzone >= 1.0 {
+        return 0.0;
+    }
+    let amount = ((deflection - deadzone) / (1.0 - deadzone)).min(1.0);
+    amount.powf(config.analog_response_exponent)
+}
+
+/// `forward_move` and `side_move` with the stick response applied
+pub(crate) fn analog_move(input: &PlayerInput, config: &MovementConfig) -> (f32, f32) {
+    let deflection = input.forward_move.hypot(input.side_move);
+    if deflection == 0.0 || deflection >= 1.0 {
+        return (input.forward_move, input.side_move);
+    }
+    let scale = analog_stick_amount(deflection, config) / deflection;
+    (input.forward_move * scale, input.side_move * scale)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = analogStickAmount)]
+pub fn analog_stick_amount_wasm(deflection: f32, config: &MovementConfig) -> f32 {
+    analog_stick_amount(deflection, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerState, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_deadzone_and_curve_scale_ground_speed() {
+        let config = MovementConfig { analog_deadzone: 0.2, analog_response_exponent: 2.0, ..y_up_config() };
+        assert_eq!(analog_stick_amount(0.15, &config), 0.0);
+        assert!((analog_stick_amount(0.6, &config) - 0.25).abs() < 0.0001);
+        assert_eq!(analog_stick_amount(1.0, &config), 1.0);
+
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let run = |input: PlayerInput| {
+            let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+            for _ in 0..256 {
+                player_tick(&mut state, &input, &config, 1.0 / 64.0, ground);
+            }
+            state.velocity.magnitude()
+        };
+        let half = PlayerInput { forward_move: 0.36, side_move: 0.48, ..PlayerInput::new() };
+        assert!((run(half) - config.max_speed * 0.25).abs() < 0.5);
+        assert!((run(PlayerInput { forward_move: 1.0, side_move: 1.0, ..PlayerInput::new() }) - config.max_speed).abs() < 0.5);
+        assert_eq!(run(PlayerInput { forward_move: 0.1, ..PlayerInput::new() }), 0.0);
+    }
+
+    #[test]
+    fn test_partial_deflection_lowers_the_air_cap() {
+        let config = y_up_config();
+        let mut full = PlayerState { velocity: Vector3::new(0.0, 0.0, -400.0), ..PlayerState::default() };
+        let mut light = full;
+        for _ in 0..16 {
+            player_tick(&mut full, &PlayerInput { side_move: 1.0, ..PlayerInput::new() }, &config, 1.0 / 64.0, None);
+            // 0.05 of 320 wish speed = 16, below the 30 cap
+            player_tick(&mut light, &PlayerInput { side_move: 0.05, ..PlayerInput::new() }, &config, 1.0 / 64.0, None);
+        }
+        assert!((full.velocity.x - config.max_air_wish_speed).abs() < 0.01);
+        assert!((light.velocity.x - 16.0).abs() < 0.01);
+    }
+}
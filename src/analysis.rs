@@ -0,0 +1,344 @@
+//! Statistical strafe analysis for cheat detection
+//!
+//! Looks at recorded inputs for patterns that humans can not produce over long
+//! stretches of play: strafe sync that stays near 100%, strafes that never
+//! overcorrect by a varying amount, and bunnyhops that always jump on the very
+//! first ground tick. The result is a scored report; what to do with it (log,
+//! review, kick) is left to the server. Jumpbugs are counted as well, but do
+//! not add to the score since `jumpbug` makes them legitimate movement tech.
+
+use crate::PlayerInput;
+
+/// One recorded tick of player input
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InputFrame {
+    /// The input the player sent for this tick
+    pub input: PlayerInput,
+    /// Whether the player was on the ground during this tick
+    pub on_ground: bool,
+}
+
+/// Tunable limits for `analyze_strafes`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalysisThresholds {
+    /// Number of airborne turning ticks per sync window
+    pub window_ticks: usize,
+    /// Window sync at or above this ratio is considered inhuman
+    pub sync_threshold: f32,
+    /// Minimum number of strafes before overcorrection variance is judged
+    pub min_strafes: usize,
+    /// Overcorrection variance at or below this is considered inhuman
+    pub min_overcorrection_variance: f32,
+    /// Minimum number of bunnyhops before jump timing is judged
+    pub min_jumps: usize,
+    /// Ratio of first-tick jumps at or above this is considered inhuman
+    pub perfect_jump_threshold: f32,
+    /// Reports scoring at or above this are flagged
+    pub flag_score: f32,
+}
+
+impl Default for AnalysisThresholds {
+    fn default() -> Self {
+        AnalysisThresholds {
+            window_ticks: 640,
+            sync_threshold: 0.95,
+            min_strafes: 30,
+            min_overcorrection_variance: 0.0005,
+            min_jumps: 20,
+            perfect_jump_threshold: 0.9,
+            flag_score: 0.6,
+        }
+    }
+}
+
+/// Result of `analyze_strafes`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StrafeAnalysisReport {
+    /// Overall sync ratio over all airborne turning ticks
+    pub sync: f32,
+    /// Number of complete sync windows
+    pub windows: usize,
+    /// Number of windows with sync at or above the threshold
+    pub suspicious_windows: usize,
+    /// Number of strafes (runs of a single held strafe direction while turning)
+    pub strafes: usize,
+    /// Mean per-strafe overcorrection ratio (turning against the held key)
+    pub overcorrection_mean: f32,
+    /// Variance of the per-strafe overcorrection ratio
+    pub overcorrection_variance: f32,
+    /// Number of bunnyhops (landing followed by a jump)
+    pub jumps: usize,
+    /// Ratio of bunnyhops that left the ground on the first ground tick
+    pub perfect_jump_ratio: f32,
+    /// Mean jump presses per bunnyhop, counted since the previous takeoff (scroll binds press many times)
+    pub jump_presses_mean: f32,
+    /// Variance of the jump presses per bunnyhop; scroll macros press the same number every time
+    pub jump_presses_variance: f32,
+    /// Number of landings that stood up from a duck and pressed jump on the landing tick, see `jumpbug`
+    pub jumpbugs: usize,
+    /// Combined suspicion score (0.0 = human-like, 1.0 = certainly automated)
+    pub score: f32,
+    /// Whether `score` reached the flag threshold
+    pub flagged: bool,
+}
+
+/// Analyze recorded inputs for inhumanly consistent strafing
+///
+/// # Arguments
+/// * `frames` - Recorded inputs in tick order
+/// * `thresholds` - Detection limits
+///
+/// # Returns
+/// A scored report of the sync, overcorrection and jump timing statistics
+pub fn analyze_strafes(frames: &[InputFrame], thresholds: &AnalysisThresholds) -> StrafeAnalysisReport {
+    let mut report = StrafeAnalysisReport::default();
+
+    // Sync and per-strafe overcorrection from airborne ticks that turn
+    let mut synced_ticks = 0usize;
+    let mut turning_ticks = 0usize;
+    let mut window_synced = 0usize;
+    let mut window_len = 0usize;
+    let mut strafe_ratios = Vec::new();
+    let mut strafe_side = 0.0f32;
+    let mut strafe_len = 0usize;
+    let mut strafe_opposed = 0usize;
+
+    for pair in frames.windows(2) {
+        let (prev, frame) = (&pair[0], &pair[1]);
+        let side = match frame.input.side_move {
+            s if s > 0.0 => 1.0,
+            s if s < 0.0 => -1.0,
+            _ => 0.0,
+        };
+        // Positive yaw turns left, so a right turn has a negative yaw delta
+        let yaw_delta = wrap_angle(frame.input.yaw - prev.input.yaw);
+        if frame.on_ground || yaw_delta == 0.0 || side == 0.0 {
+            continue;
+        }
+        let turning_right = yaw_delta < 0.0;
+        let synced = turning_right == (side > 0.0);
+
+        turning_ticks += 1;
+        window_len += 1;
+        if synced {
+            synced_ticks += 1;
+            window_synced += 1;
+        }
+        if window_len == thresholds.window_ticks {
+            report.windows += 1;
+            if window_synced as f32 / window_len as f32 >= thresholds.sync_threshold {
+                report.suspicious_windows += 1;
+            }
+            window_len = 0;
+            window_synced = 0;
+        }
+
+        if side != strafe_side {
+            if strafe_len > 0 {
+                strafe_ratios.push(strafe_opposed as f32 / strafe_len as f32);
+            }
+            strafe_side = side;
+            strafe_len = 0;
+            strafe_opposed = 0;
+        }
+        strafe_len += 1;
+        if !synced {
+            strafe_opposed += 1;
+        }
+    }
+    if strafe_len > 0 {
+        strafe_ratios.push(strafe_opposed as f32 / strafe_len as f32);
+    }
+
+    if turning_ticks > 0 {
+        report.sync = synced_ticks as f32 / turning_ticks as f32;
+    }
+    report.strafes = strafe_ratios.len();
+    if !strafe_ratios.is_empty() {
+        let n = strafe_ratios.len() as f32;
+        let mean = strafe_ratios.iter().sum::<f32>() / n;
+        report.overcorrection_mean = mean;
+        report.overcorrection_variance = strafe_ratios.iter().map(|r| (r - mean) * (r - mean)).sum::<f32>() / n;
+    }
+
+    // Jump timing: ground ticks between a landing and the next takeoff
+    let mut perfect = 0usize;
+    let mut ground_run = 0usize;
+    let mut landed_from_air = false;
+    let mut was_on_ground = frames.first().map(|f| f.on_ground).unwrap_or(false);
+    let mut presses = 0usize;
+    let mut jump_presses = Vec::new();
+    let mut jump_was_down = false;
+    let mut duck_was_down = false;
+    for frame in frames {
+        let new_presses = match frame.input.jump_presses {
+            0 => (frame.input.jump && !jump_was_down) as usize,
+            queued => queued as usize,
+        };
+        presses += new_presses;
+        jump_was_down = frame.input.jump || frame.input.jump_presses > 0;
+        if frame.on_ground {
+            if !was_on_ground {
+                landed_from_air = true;
+                ground_run = 0;
+                if duck_was_down && !frame.input.duck && new_presses > 0 {
+                    report.jumpbugs += 1;
+                }
+            }
+            ground_run += 1;
+        } else if was_on_ground && landed_from_air {
+            report.jumps += 1;
+            if ground_run <= 1 {
+                perfect += 1;
+            }
+            landed_from_air = false;
+            jump_presses.push(presses as f32);
+        }
+        if was_on_ground && !frame.on_ground {
+            presses = 0;
+        }
+        was_on_ground = frame.on_ground;
+        duck_was_down = frame.input.duck;
+    }
+    if report.jumps > 0 {
+        report.perfect_jump_ratio = perfect as f32 / report.jumps as f32;
+        let n = jump_presses.len() as f32;
+        let mean = jump_presses.iter().sum::<f32>() / n;
+        report.jump_presses_mean = mean;
+        report.jump_presses_variance = jump_presses.iter().map(|p| (p - mean) * (p - mean)).sum::<f32>() / n;
+    }
+
+    report.score = suspicion_score(&report, thresholds);
+    report.flagged = report.score >= thresholds.flag_score;
+    report
+}
+
+/// Weighted combination of the three indicators
+fn suspicion_score(report: &StrafeAnalysisReport, thresholds: &AnalysisThresholds) -> f32 {
+    let sync_score = if report.windows > 0 {
+        report.suspicious_windows as f32 / report.windows as f32
+    } else {
+        0.0
+    };
+    let overcorrection_score = if report.strafes >= thresholds.min_strafes
+        && report.overcorrection_variance <= thresholds.min_overcorrection_variance
+    {
+        1.0
+    } else {
+        0.0
+    };
+    let jump_score = if report.jumps >= thresholds.min_jumps && report.perfect_jump_ratio >= thresholds.perfect_jump_threshold
+    {
+        1.0
+    } else {
+        0.0
+    };
+    0.45 * sync_score + 0.3 * overcorrection_score + 0.25 * jump_score
+}
+
+/// Wrap an angle difference into [-PI, PI]
+pub(crate) fn wrap_angle(angle: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Alternating strafes of `strafe_ticks`, each followed by one landing tick
+    fn recording(strafes: usize, strafe_ticks: usize, desync: impl Fn(usize) -> usize, ground_ticks: usize) -> Vec<InputFrame> {
+        let mut frames = Vec::new();
+        let mut yaw = 0.0f32;
+        for strafe in 0..strafes {
+            let right = strafe % 2 == 0;
+            for tick in 0..strafe_ticks {
+                // The first `desync(strafe)` ticks still turn the old way
+                let turn_right = if tick < desync(strafe) { !right } else { right };
+                yaw += if turn_right { -0.01 } else { 0.01 };
+                let input = PlayerInput { side_move: if right { 1.0 } else { -1.0 }, yaw, ..PlayerInput::new() };
+                frames.push(InputFrame { input, on_ground: false });
+            }
+            for _ in 0..ground_ticks {
+                frames.push(InputFrame { input: PlayerInput { yaw, ..PlayerInput::new() }, on_ground: true });
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn test_perfect_bot_is_flagged() {
+        let frames = recording(200, 40, |_| 0, 1);
+        let report = analyze_strafes(&frames, &AnalysisThresholds::default());
+
+        assert!((report.sync - 1.0).abs() < 0.0001);
+        assert_eq!(report.suspicious_windows, report.windows);
+        assert_eq!(report.overcorrection_variance, 0.0);
+        assert!(report.perfect_jump_ratio > 0.99);
+        assert!(report.flagged);
+    }
+
+    #[test]
+    fn test_human_like_play_is_not_flagged() {
+        // Varying desync per strafe and jumps that often miss the first tick
+        let frames = recording(200, 40, |strafe| (strafe * 7) % 9, 1);
+        let mut frames_with_late_jumps = Vec::new();
+        for (i, frame) in frames.iter().enumerate() {
+            frames_with_late_jumps.push(*frame);
+            if frame.on_ground && i % 3 == 0 {
+                frames_with_late_jumps.push(*frame);
+            }
+        }
+        let report = analyze_strafes(&frames_with_late_jumps, &AnalysisThresholds::default());
+
+        assert!(report.sync < 0.95);
+        assert!(report.overcorrection_variance > 0.0005);
+        assert!(report.perfect_jump_ratio < 0.9);
+        assert!(!report.flagged);
+    }
+
+    #[test]
+    fn test_counts_scroll_presses_per_jump() {
+        let mut frames = Vec::new();
+        for hop in 0..20 {
+            for tick in 0..30 {
+                // Scrolling near the landing, twice as much on odd hops
+                let jump_presses = if tick >= 27 { 1 + (hop % 2) as u8 } else { 0 };
+                frames.push(InputFrame { input: PlayerInput { jump_presses, ..PlayerInput::new() }, on_ground: false });
+            }
+            frames.push(InputFrame { input: PlayerInput { jump: true, ..PlayerInput::new() }, on_ground: true });
+        }
+        frames.push(InputFrame::default());
+        let report = analyze_strafes(&frames, &AnalysisThresholds::default());
+
+        assert_eq!(report.jumps, 20);
+        // 3 or 6 scroll presses per hop; holding jump through the landing adds no press
+        assert!((report.jump_presses_mean - 4.5).abs() < 1e-4);
+        assert!((report.jump_presses_variance - 2.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_detects_jumpbugs() {
+        let mut frames = Vec::new();
+        for hop in 0..10 {
+            // Every other hop falls ducked and stands up with the jump on the landing tick
+            let duck = hop % 2 == 0;
+            for _ in 0..30 {
+                frames.push(InputFrame { input: PlayerInput { duck, ..PlayerInput::new() }, on_ground: false });
+            }
+            frames.push(InputFrame { input: PlayerInput { jump: true, ..PlayerInput::new() }, on_ground: true });
+        }
+        frames.push(InputFrame::default());
+        let report = analyze_strafes(&frames, &AnalysisThresholds::default());
+
+        assert_eq!(report.jumps, 10);
+        assert_eq!(report.jumpbugs, 5);
+    }
+
+    #[test]
+    fn test_wrap_angle() {
+        use std::f32::consts::PI;
+        assert!((wrap_angle(2.0 * PI - 0.1) + 0.1).abs() < 0.0001);
+        assert!((wrap_angle(-0.2) + 0.2).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,129 @@
+//! World up-axis handling
+//!
+//! Three.js and Bevy worlds are Y-up while Source/Quake content is Z-up. All
+//! ground checks, gravity, jumping and view direction math go through `UpAxis`
+//! so the same controller works in either convention. The `up-axis-y` and
+//! `up-axis-z` features restrict builds to one axis and specialize the tick
+//! for it (see `fixed`).
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::Vector3;
+
+/// Which world axis points up
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema), schemars(rename_all = "snake_case"))]
+pub enum UpAxis {
+    /// Y-up (Three.js, Bevy). Yaw 0 looks down -Z.
+    #[default]
+    Y,
+    /// Z-up (Source, Quake). Yaw 0 looks down +X.
+    Z,
+}
+
+impl UpAxis {
+    /// Unit vector pointing up
+    pub fn up(self) -> Vector3 {
+        match self {
+            UpAxis::Y => Vector3::new(0.0, 1.0, 0.0),
+            UpAxis::Z => Vector3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    /// Component of `v` along the up axis
+    pub fn height(self, v: &Vector3) -> f32 {
+        match self {
+            UpAxis::Y => v.y,
+            UpAxis::Z => v.z,
+        }
+    }
+
+    /// Replace the component of `v` along the up axis
+    pub fn set_height(self, v: &mut Vector3, height: f32) {
+        match self {
+            UpAxis::Y => v.y = height,
+            UpAxis::Z => v.z = height,
+        }
+    }
+
+    /// `v` with the up component removed
+    pub fn horizontal(self, v: &Vector3) -> Vector3 {
+        let mut h = *v;
+        self.set_height(&mut h, 0.0);
+        h
+    }
+
+    /// Horizontal forward direction for a view yaw (radians, positive turns left)
+    pub fn forward(self, yaw: f32) -> Vector3 {
+        match self {
+            UpAxis::Y => Vector3::new(-yaw.sin(), 0.0, -yaw.cos()),
+            UpAxis::Z => Vector3::new(yaw.cos(), yaw.sin(), 0.0),
+        }
+    }
+
+    /// Horizontal right direction for a view yaw
+    pub fn right(self, yaw: f32) -> Vector3 {
+        match self {
+            UpAxis::Y => Vector3::new(yaw.cos(), 0.0, -yaw.sin()),
+            UpAxis::Z => Vector3::new(yaw.sin(), -yaw.cos(), 0.0),
+        }
+    }
+
+    /// Full 3D view direction for a view yaw and pitch (positive pitch looks up)
+    pub fn view_forward(self, yaw: f32, pitch: f32) -> Vector3 {
+        let mut forward = self.forward(yaw).multiply_scalar(pitch.cos());
+        self.set_height(&mut forward, pitch.sin());
+        forward
+    }
+
+    /// The axis the `Z_UP` const parameter of the tick code stands for, see `fixed`
+    pub(crate) const fn from_z_up(z_up: bool) -> UpAxis {
+        if z_up {
+            UpAxis::Z
+        } else {
+            UpAxis::Y
+        }
+    }
+
+    /// Yaw that makes `forward` point along a horizontal direction
+    pub fn yaw_from_direction(self, direction: &Vector3) -> f32 {
+        match self {
+            UpAxis::Y => (-direction.x).atan2(-direction.z),
+            UpAxis::Z => direction.y.atan2(direction.x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basis_is_right_handed_for_both_axes() {
+        for axis in [UpAxis::Y, UpAxis::Z] {
+            for yaw in [0.0f32, 0.7, -2.0] {
+                let forward = axis.forward(yaw);
+                let right = axis.right(yaw);
+                // Right-handed basis: forward x right = -up
+                let cross = Vector3::new(
+                    forward.y * right.z - forward.z * right.y,
+                    forward.z * right.x - forward.x * right.z,
+                    forward.x * right.y - forward.y * right.x,
+                );
+                assert!((cross.dot(&axis.up()) + 1.0).abs() < 0.0001);
+                assert!((axis.yaw_from_direction(&forward) - yaw).abs() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_height_and_horizontal() {
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(UpAxis::Y.height(&v), 2.0);
+        assert_eq!(UpAxis::Z.height(&v), 3.0);
+        assert_eq!(UpAxis::Z.horizontal(&v), Vector3::new(1.0, 2.0, 0.0));
+        assert!((UpAxis::Z.view_forward(0.0, 0.5).z - 0.5f32.sin()).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,88 @@
+//! Counter-Strike style bunnyhop penalties
+//!
+//! Both are off by default so strafe servers keep free bunnyhopping.
+//!
+//! - Stamina: every jump leaves `bhop_stamina_time` seconds of stamina cost
+//!   (CS 1.6's `fuser2`), and a jump made before it wore off loses up to
+//!   `bhop_stamina_penalty` of its jump speed, fading out linearly.
+//! - Speed clamp: jumping faster than `bhop_speed_cap` times `max_speed`
+//!   scales the horizontal velocity down to `bhop_speed_cap_slowdown` of that
+//!   cap (Half-Life/CS `PM_PreventMegaBunnyJumping`).
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerState, UpAxis};
+
+/// Fraction of the jump speed left after the stamina penalty
+pub fn bhop_stamina_jump_factor(state: &PlayerState, config: &MovementConfig) -> f32 {
+    if config.bhop_stamina_time <= 0.0 {
+        return 1.0;
+    }
+    let stamina = (state.bhop_stamina / config.bhop_stamina_time).clamp(0.0, 1.0);
+    1.0 - config.bhop_stamina_penalty * stamina
+}
+
+/// Apply both penalties to a jump leaving the ground this tick
+pub(crate) fn penalize_bhop<const Z_UP: bool>(state: &mut PlayerState, config: &MovementConfig) -> f32 {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let cap = config.bhop_speed_cap * config.max_speed;
+    let horizontal = axis.horizontal(&state.velocity);
+    let speed = horizontal.magnitude();
+    if config.bhop_speed_cap > 0.0 && speed > cap {
+        let mut velocity = horizontal.multiply_scalar(cap / speed * config.bhop_speed_cap_slowdown);
+        axis.set_height(&mut velocity, axis.height(&state.velocity));
+        state.velocity = velocity;
+    }
+    let factor = if config.bhop_stamina_penalty > 0.0 { bhop_stamina_jump_factor(state, config) } else { 1.0 };
+    state.bhop_stamina = config.bhop_stamina_time;
+    factor
+}
+
+/// Let the stamina cost of the last jump wear off
+pub(crate) fn recover_bhop_stamina(state: &mut PlayerState, delta_time: f32) {
+    state.bhop_stamina = (state.bhop_stamina - delta_time).max(0.0);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = bhopStaminaJumpFactor)]
+pub fn bhop_stamina_jump_factor_wasm(state: &PlayerState, config: &MovementConfig) -> f32 {
+    bhop_stamina_jump_factor(state, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_penalties_are_off_by_default_and_slow_successive_hops() {
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        let hop = |config: &MovementConfig, bhop_stamina: f32| {
+            let mut state = PlayerState {
+                on_ground: true,
+                bhop_stamina,
+                velocity: Vector3::new(0.0, 0.0, -600.0),
+                ..PlayerState::default()
+            };
+            player_tick(&mut state, &jump, config, 1.0 / 64.0, ground);
+            state
+        };
+
+        let default = y_up_config();
+        let free = hop(&default, 1.0);
+        assert_eq!(free.velocity.z, -600.0);
+        assert!((free.velocity.y + default.gravity / 64.0 - default.jump_speed).abs() < 0.01);
+
+        let cs = MovementConfig { bhop_stamina_penalty: 0.25, bhop_speed_cap: 1.2, ..default };
+        let rested = hop(&cs, 0.0);
+        let tired = hop(&cs, 2.0 * cs.bhop_stamina_time);
+        assert!((rested.velocity.z + cs.max_speed * 1.2 * cs.bhop_speed_cap_slowdown).abs() < 0.01);
+        assert!((rested.bhop_stamina - cs.bhop_stamina_time).abs() < 0.001);
+        let rested_jump = rested.velocity.y + cs.gravity / 64.0;
+        let tired_jump = tired.velocity.y + cs.gravity / 64.0;
+        assert!((tired_jump - rested_jump * 0.75).abs() < 0.01);
+    }
+}
@@ -0,0 +1,106 @@
+//! Headless scenario runner
+//!
+//! ```text
+//! airstrafe-sim <scenario.json> [--tick-rate <hz>] [--format json|csv] [--output <path>] [--jumps <path>]
+//! ```
+//!
+//! Runs a scenario file (see `movement_logic::harness`) and writes the
+//! trajectory to stdout or `--output`: as JSON with the jump statistics, or as
+//! CSV with one row per tick. `--jumps` additionally writes the jump
+//! statistics as CSV. Failed scenario expectations are printed to stderr and
+//! make the exit code 1, so the runner can gate CI jobs.
+
+use std::process::ExitCode;
+use std::{env, fs};
+
+use movement_logic::Scenario;
+
+const USAGE: &str =
+    "usage: airstrafe-sim <scenario.json> [--tick-rate <hz>] [--format json|csv] [--output <path>] [--jumps <path>]";
+
+struct Options {
+    scenario: String,
+    tick_rate: Option<f32>,
+    csv: bool,
+    output: Option<String>,
+    jumps: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options { scenario: String::new(), tick_rate: None, csv: false, output: None, jumps: None };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--tick-rate" => {
+                let rate = value()?;
+                let rate = rate.parse::<f32>().ok().filter(|rate| *rate > 0.0);
+                options.tick_rate = Some(rate.ok_or("--tick-rate must be a positive number")?);
+            }
+            "--format" => {
+                options.csv = match value()?.as_str() {
+                    "json" => false,
+                    "csv" => true,
+                    other => return Err(format!("unknown format `{other}`")),
+                }
+            }
+            "--output" => options.output = Some(value()?),
+            "--jumps" => options.jumps = Some(value()?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option `{arg}`")),
+            _ if options.scenario.is_empty() => options.scenario = arg,
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    if options.scenario.is_empty() {
+        return Err("missing scenario file".to_string());
+    }
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<bool, String> {
+    let text = fs::read_to_string(&options.scenario).map_err(|error| format!("{}: {error}", options.scenario))?;
+    let mut scenario = Scenario::from_json(&text).map_err(|error| format!("{}: {error}", options.scenario))?;
+    if let Some(tick_rate) = options.tick_rate {
+        scenario = scenario.at_tick_rate(tick_rate);
+    }
+    let trajectory = scenario.run();
+
+    let report = if options.csv { trajectory.to_csv() } else { trajectory.to_json() };
+    match &options.output {
+        Some(path) => fs::write(path, report).map_err(|error| format!("{path}: {error}"))?,
+        None => print!("{report}"),
+    }
+    if let Some(path) = &options.jumps {
+        let mut csv = String::from("distance,raw_distance,height_difference,max_height,pre_speed,max_speed,air_ticks\n");
+        for jump in trajectory.jump_stats() {
+            csv += &format!(
+                "{},{},{},{},{},{},{}\n",
+                jump.distance,
+                jump.raw_distance,
+                jump.height_difference,
+                jump.max_height,
+                jump.pre_speed,
+                jump.max_speed,
+                jump.air_ticks
+            );
+        }
+        fs::write(path, csv).map_err(|error| format!("{path}: {error}"))?;
+    }
+
+    let failures = scenario.check(&trajectory);
+    for failure in &failures {
+        eprintln!("{failure}");
+    }
+    Ok(failures.is_empty())
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(env::args().skip(1)).and_then(|options| run(&options));
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("airstrafe-sim: {error}\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
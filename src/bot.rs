@@ -0,0 +1,199 @@
+//! Strafe bot input generation
+//!
+//! `StrafeBot` produces a `PlayerInput` every tick that bunnyhops and air
+//! strafes along a direction or through a list of waypoints, using the optimal
+//! strafe angle for the active `MovementConfig`. Useful for filling servers with
+//! moving players and for benchmarking how fast a config lets players go.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{optimal_strafe_wish_dir, MovementConfig, PlayerInput, PlayerState, UpAxis, Vector3};
+
+/// Horizontal distance at which a path waypoint counts as reached
+pub const DEFAULT_WAYPOINT_RADIUS: f32 = 64.0;
+
+/// Per-tick input generator that bhops and air strafes towards a target
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct StrafeBot {
+    direction: Vector3,
+    path: Vec<Vector3>,
+    waypoint: usize,
+    waypoint_radius: f32,
+    strafe_right: bool,
+}
+
+impl StrafeBot {
+    /// Bot that keeps heading along a fixed horizontal direction
+    pub fn toward_direction(direction: Vector3) -> StrafeBot {
+        StrafeBot {
+            direction,
+            path: Vec::new(),
+            waypoint: 0,
+            waypoint_radius: DEFAULT_WAYPOINT_RADIUS,
+            strafe_right: true,
+        }
+    }
+
+    /// Bot that visits the given points in order and stops at the last one
+    pub fn along_path(path: Vec<Vector3>) -> StrafeBot {
+        StrafeBot {
+            path,
+            ..StrafeBot::toward_direction(Vector3::new(0.0, 0.0, -1.0))
+        }
+    }
+
+    /// Change how close the bot must get to a waypoint before moving on
+    pub fn set_waypoint_radius(&mut self, radius: f32) {
+        self.waypoint_radius = radius;
+    }
+
+    /// Index of the waypoint the bot is currently heading to
+    pub fn current_waypoint(&self) -> usize {
+        self.waypoint
+    }
+
+    /// Whether the bot has reached the end of its path (always false for a direction)
+    pub fn finished(&self) -> bool {
+        !self.path.is_empty() && self.waypoint >= self.path.len()
+    }
+
+    /// Generate the input for the next tick
+    ///
+    /// # Arguments
+    /// * `state` - The bot player's current state
+    /// * `config` - The movement config the player is simulated with
+    /// * `delta_time` - Tick length in seconds
+    pub fn next_input(&mut self, state: &PlayerState, config: &MovementConfig, delta_time: f32) -> PlayerInput {
+        let config = &config.in_world_units();
+        let axis = config.up_axis;
+        let Some(heading) = self.heading(state, axis) else {
+            return PlayerInput::new();
+        };
+
+        if state.on_ground {
+            // Face the target and jump as soon as the jump button is released
+            return PlayerInput {
+                forward_move: 1.0,
+                yaw: axis.yaw_from_direction(&heading),
+                jump: config.auto_bhop || !state.jump_held,
+                ..PlayerInput::new()
+            };
+        }
+
+        let velocity = axis.horizontal(&state.velocity);
+        let speed = velocity.magnitude();
+        // The bot strafes with sideways-only input
+        let accel = config.effective_air_accelerate(0.0, 1.0, config.max_speed);
+        if speed <= config.max_air_wish_speed - accel * delta_time {
+            // Too slow for strafing to beat accelerating straight at the target
+            return PlayerInput {
+                forward_move: 1.0,
+                yaw: axis.yaw_from_direction(&heading),
+                ..PlayerInput::new()
+            };
+        }
+
+        // Steer the velocity towards the heading, flipping sides once it passes
+        let forward = velocity.multiply_scalar(1.0 / speed);
+        let right_of_velocity = axis.right(axis.yaw_from_direction(&forward));
+        let side = heading.dot(&right_of_velocity);
+        if side.abs() > 0.01 {
+            self.strafe_right = side > 0.0;
+        }
+        let side_hint = if self.strafe_right {
+            right_of_velocity
+        } else {
+            -right_of_velocity
+        };
+        let wish = optimal_strafe_wish_dir(&velocity, &side_hint, accel, config.max_air_wish_speed, delta_time);
+
+        // With side_move = ±1 the wish direction is ±right, so aim right at ±wish;
+        // right is forward turned 90 degrees clockwise
+        let right = if self.strafe_right { wish } else { -wish };
+        PlayerInput {
+            side_move: if self.strafe_right { 1.0 } else { -1.0 },
+            yaw: axis.yaw_from_direction(&right) + std::f32::consts::FRAC_PI_2,
+            ..PlayerInput::new()
+        }
+    }
+
+    /// Horizontal unit direction the bot wants to travel in, `None` when done
+    fn heading(&mut self, state: &PlayerState, axis: UpAxis) -> Option<Vector3> {
+        if self.path.is_empty() {
+            return Some(axis.horizontal(&self.direction).normalized());
+        }
+        while let Some(target) = self.path.get(self.waypoint) {
+            let to_target = axis.horizontal(&(*target - state.origin));
+            if to_target.magnitude() > self.waypoint_radius {
+                return Some(to_target.normalized());
+            }
+            self.waypoint += 1;
+        }
+        None
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl StrafeBot {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(direction: &Vector3) -> StrafeBot {
+        StrafeBot::toward_direction(*direction)
+    }
+
+    #[wasm_bindgen(js_name = nextInput)]
+    pub fn next_input_wasm(&mut self, state: &PlayerState, config: &MovementConfig, delta_time: f32) -> PlayerInput {
+        self.next_input(state, config, delta_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_tick;
+    use crate::tick::tests::{flat_ground, y_up_config};
+
+    #[test]
+    fn test_bot_gains_speed_towards_direction() {
+        let config = y_up_config();
+        let dt = 1.0 / 64.0;
+        let direction = Vector3::new(1.0, 0.0, 0.0);
+        let mut bot = StrafeBot::toward_direction(direction);
+        let mut state = PlayerState::default();
+
+        for _ in 0..64 * 10 {
+            let input = bot.next_input(&state, &config, dt);
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &input, &config, dt, hit);
+        }
+
+        let speed = UpAxis::Y.horizontal(&state.velocity).magnitude();
+        assert!(speed > config.max_speed * 1.5, "speed {speed}");
+        // Zig-zag strafing should still make progress along the direction
+        assert!(state.origin.x > 10.0 * config.max_speed);
+        assert!(state.origin.z.abs() < state.origin.x * 0.25);
+    }
+
+    #[test]
+    fn test_bot_follows_path() {
+        let config = y_up_config();
+        let dt = 1.0 / 64.0;
+        let path = vec![Vector3::new(0.0, 0.0, -1000.0), Vector3::new(1000.0, 0.0, -1000.0)];
+        let mut bot = StrafeBot::along_path(path);
+        bot.set_waypoint_radius(150.0);
+        let mut state = PlayerState::default();
+
+        for _ in 0..64 * 20 {
+            if bot.finished() {
+                break;
+            }
+            let input = bot.next_input(&state, &config, dt);
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &input, &config, dt, hit);
+        }
+        assert!(bot.finished());
+        assert_eq!(bot.next_input(&state, &config, dt), PlayerInput::new());
+    }
+}
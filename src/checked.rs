@@ -0,0 +1,175 @@
+//! Checked variants of the movement functions
+//!
+//! The plain functions trust their inputs, so a NaN delta time or a zero-length
+//! wish direction silently turns the result into NaN. The variants here
+//! validate every argument first and report the problem as
+//! `AirStrafeError::InvalidInput`.
+
+use std::fmt;
+
+use crate::error::AirStrafeError;
+use crate::{
+    air_accelerate_get_acceleration, gravity_velocity_change_native, player_move_core, RayCollisionHit, Vector3,
+};
+
+/// Invalid argument passed to a checked movement function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovementError {
+    /// The argument contains NaN or infinity
+    NonFinite { argument: &'static str },
+    /// The time step is not a positive, finite number of seconds
+    InvalidDeltaTime(f32),
+    /// The tick rate is not a positive, finite number of ticks per second
+    InvalidTickRate(f32),
+    /// The wish direction has zero length, so no direction can be derived from it
+    ZeroWishDir,
+    /// A vector that must have a direction has zero length
+    ZeroLength { argument: &'static str },
+    /// A buffer holds fewer values than the call needs
+    BufferTooShort { expected: usize, actual: usize },
+}
+
+impl fmt::Display for MovementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovementError::NonFinite { argument } => write!(f, "`{argument}` is not finite"),
+            MovementError::InvalidDeltaTime(delta) => write!(f, "delta time must be positive and finite, got {delta}"),
+            MovementError::InvalidTickRate(rate) => write!(f, "tick rate must be positive and finite, got {rate}"),
+            MovementError::ZeroWishDir => write!(f, "wish direction has zero length"),
+            MovementError::ZeroLength { argument } => write!(f, "`{argument}` has zero length"),
+            MovementError::BufferTooShort { expected, actual } => {
+                write!(f, "buffer must hold at least {expected} values, got {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MovementError {}
+
+pub(crate) fn check_delta_time(delta_time: f32) -> Result<(), MovementError> {
+    if delta_time.is_finite() && delta_time > 0.0 {
+        Ok(())
+    } else {
+        Err(MovementError::InvalidDeltaTime(delta_time))
+    }
+}
+
+pub(crate) fn check_tick_rate(tick_rate: f32) -> Result<(), MovementError> {
+    if tick_rate.is_finite() && tick_rate > 0.0 {
+        Ok(())
+    } else {
+        Err(MovementError::InvalidTickRate(tick_rate))
+    }
+}
+
+pub(crate) fn check_scalar(argument: &'static str, value: f32) -> Result<(), MovementError> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(MovementError::NonFinite { argument })
+    }
+}
+
+pub(crate) fn check_vector(argument: &'static str, value: &Vector3) -> Result<(), MovementError> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(MovementError::NonFinite { argument })
+    }
+}
+
+pub(crate) fn check_ray_hit(hit: &Option<RayCollisionHit>) -> Result<(), MovementError> {
+    if let Some(hit) = hit {
+        check_vector("down_ray_hit.normal", hit.normal_native())?;
+        check_scalar("down_ray_hit.distance", hit.distance_native())?;
+    }
+    Ok(())
+}
+
+/// `player_move_core` with argument validation
+pub fn player_move_checked(
+    direction: &Vector3,
+    delta_time: f32,
+    speed_multiplier: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+) -> Result<Vector3, AirStrafeError> {
+    check_vector("direction", direction)?;
+    check_delta_time(delta_time)?;
+    check_scalar("speed_multiplier", speed_multiplier)?;
+    check_ray_hit(&down_ray_hit)?;
+    Ok(player_move_core(direction, delta_time, speed_multiplier, down_ray_hit))
+}
+
+/// `air_accelerate_get_acceleration` with argument validation
+///
+/// Unlike the unchecked version, a zero-length `wish_dir` is an error rather
+/// than a NaN result.
+pub fn air_accelerate_checked(
+    current_vel: &Vector3,
+    wish_dir: &Vector3,
+    wish_speed: f32,
+    air_accelerate: f32,
+    max_air_wish_speed: f32,
+    delta_time: f32,
+) -> Result<Vector3, AirStrafeError> {
+    check_vector("current_vel", current_vel)?;
+    check_vector("wish_dir", wish_dir)?;
+    if wish_dir.length_sq() == 0.0 {
+        return Err(MovementError::ZeroWishDir.into());
+    }
+    check_scalar("wish_speed", wish_speed)?;
+    check_scalar("air_accelerate", air_accelerate)?;
+    check_scalar("max_air_wish_speed", max_air_wish_speed)?;
+    check_delta_time(delta_time)?;
+    Ok(air_accelerate_get_acceleration(
+        current_vel,
+        wish_dir,
+        wish_speed,
+        air_accelerate,
+        max_air_wish_speed,
+        delta_time,
+    ))
+}
+
+/// `gravity_velocity_change_native` with argument validation
+pub fn gravity_velocity_change_checked(
+    gravity: &Vector3,
+    delta: f32,
+    legacy_scale: Option<f32>,
+) -> Result<Vector3, AirStrafeError> {
+    check_vector("gravity", gravity)?;
+    check_delta_time(delta)?;
+    if let Some(scale) = legacy_scale {
+        check_scalar("legacy_scale", scale)?;
+    }
+    Ok(gravity_velocity_change_native(gravity, delta, legacy_scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_arguments_are_rejected() {
+        let vel = Vector3::new(300.0, 0.0, 0.0);
+        let wish = Vector3::new(0.0, 0.0, 1.0);
+
+        assert!(air_accelerate_checked(&vel, &wish, 30.0, 100.0, 30.0, 0.01).is_ok());
+        assert_eq!(
+            air_accelerate_checked(&vel, &Vector3::new(0.0, 0.0, 0.0), 30.0, 100.0, 30.0, 0.01),
+            Err(AirStrafeError::InvalidInput(MovementError::ZeroWishDir))
+        );
+        assert!(matches!(
+            air_accelerate_checked(&vel, &wish, 30.0, 100.0, 30.0, f32::NAN),
+            Err(AirStrafeError::InvalidInput(MovementError::InvalidDeltaTime(_)))
+        ));
+        assert_eq!(
+            player_move_checked(&Vector3::new(f32::INFINITY, 0.0, 0.0), 0.01, 1.0, None),
+            Err(MovementError::NonFinite { argument: "direction" }.into())
+        );
+        assert_eq!(
+            gravity_velocity_change_checked(&Vector3::new(0.0, -800.0, 0.0), 0.01, Some(f32::NAN)),
+            Err(MovementError::NonFinite { argument: "legacy_scale" }.into())
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Wall climbing
+//!
+//! The host casts a ray forward from the player each tick and passes it to
+//! `update_climb_contact` before `player_tick`. Walking into a wall whose
+//! material is flagged `SURFACE_CLIMBABLE` switches to `MoveType::Climb`:
+//! forward input moves up the wall, side input along it, there is no gravity
+//! and stamina drains. Running out of stamina lets go of the wall and jumping
+//! pushes off it. Stamina refills while not climbing.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::events::MovementEvent;
+use crate::materials::{MaterialTable, SURFACE_CLIMBABLE};
+use crate::{MoveType, MovementConfig, PlayerInput, PlayerState, RayCollisionHit, UpAxis, Vector3};
+
+/// Start or stop climbing depending on the wall in front of the player
+///
+/// # Arguments
+/// * `state` - The player, switched between `Walk` and `Climb`
+/// * `input` - Climbing starts only while moving forward
+/// * `config` - Supplies `climb_reach` and `min_ground_normal_up`
+/// * `forward_ray_hit` - Result of a ray cast forward along the view yaw
+/// * `materials` - Decides which surfaces are climbable
+pub fn update_climb_contact(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    forward_ray_hit: Option<RayCollisionHit>,
+    materials: &MaterialTable,
+) {
+    let config = &config.in_world_units();
+    let wall = forward_ray_hit.filter(|hit| {
+        hit.distance_native() <= config.climb_reach
+            && config.up_axis.height(hit.normal_native()).abs() < config.min_ground_normal_up
+            && materials.hit_has(hit, SURFACE_CLIMBABLE)
+    });
+    match (state.move_type, wall) {
+        (MoveType::Walk, Some(hit)) if input.forward_move > 0.0 && state.climb_stamina > 0.0 => {
+            state.move_type = MoveType::Climb;
+            state.climb_normal = hit.normal_native().normalized();
+            state.on_ground = false;
+        }
+        (MoveType::Climb, Some(hit)) => state.climb_normal = hit.normal_native().normalized(),
+        (MoveType::Climb, None) => state.move_type = MoveType::Walk,
+        _ => {}
+    }
+}
+
+/// Movement on the wall plane without gravity
+pub(crate) fn climb_move<const Z_UP: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    events: &mut Vec<MovementEvent>,
+) {
+    let axis = UpAxis::from_z_up(Z_UP);
+    state.on_ground = false;
+    state.time_on_ground = 0.0;
+
+    if input.jump && !state.jump_held {
+        // Push off the wall with a regular jump on top
+        let mut velocity = state.climb_normal.multiply_scalar(config.climb_jump_off_speed);
+        velocity.add(&axis.up().multiply_scalar(config.jump_speed));
+        state.velocity = velocity;
+        state.move_type = MoveType::Walk;
+        state.jump_count += 1;
+        events.push(MovementEvent::Jumped {
+            tick: state.tick,
+            jump_number: state.jump_count,
+            speed: axis.horizontal(&state.velocity).magnitude(),
+        });
+        return;
+    }
+
+    state.climb_stamina = if config.climb_stamina_time > 0.0 {
+        (state.climb_stamina - delta_time / config.climb_stamina_time).max(0.0)
+    } else {
+        state.climb_stamina
+    };
+    if state.climb_stamina == 0.0 {
+        state.move_type = MoveType::Walk;
+        state.velocity = Vector3::new(0.0, 0.0, 0.0);
+        return;
+    }
+
+    let wall_up = axis.up().project_on_plane(&state.climb_normal).normalized();
+    let wall_right = input.right(axis).project_on_plane(&state.climb_normal).normalized();
+    let mut wish = wall_up.multiply_scalar(input.forward_move);
+    wish.add(&wall_right.multiply_scalar(input.side_move));
+    let amount = wish.magnitude().min(1.0);
+    state.velocity = wish.normalized().multiply_scalar(config.climb_speed * amount);
+    state.origin.add(&state.velocity.multiply_scalar(delta_time));
+}
+
+/// Refill climbing stamina while not on a wall
+pub(crate) fn regen_climb_stamina(state: &mut PlayerState, config: &MovementConfig, delta_time: f32) {
+    state.climb_stamina = if config.climb_stamina_regen_time > 0.0 {
+        (state.climb_stamina + delta_time / config.climb_stamina_regen_time).min(1.0)
+    } else {
+        1.0
+    };
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = updateClimbContact)]
+pub fn update_climb_contact_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    forward_ray_hit: Option<RayCollisionHit>,
+    materials: &MaterialTable,
+) {
+    update_climb_contact(state, input, config, forward_ray_hit, materials);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_tick;
+    use crate::tick::tests::y_up_config;
+
+    const ROCK: u32 = 3;
+
+    fn wall(distance: f32, material: u32) -> Option<RayCollisionHit> {
+        // Wall in front of a Y-up player looking down -Z
+        Some(RayCollisionHit::new_native(Vector3::new(0.0, 0.0, 1.0), distance).with_material(material))
+    }
+
+    #[test]
+    fn test_climb_up_flagged_wall_and_run_out_of_stamina() {
+        let config = y_up_config();
+        let materials = MaterialTable::new().with_flags(ROCK, SURFACE_CLIMBABLE);
+        let mut state = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).build().unwrap();
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let dt = 1.0 / 64.0;
+
+        update_climb_contact(&mut state, &input, &config, wall(8.0, 0), &materials);
+        assert_eq!(state.move_type, MoveType::Walk);
+        update_climb_contact(&mut state, &input, &config, wall(8.0, ROCK), &materials);
+        assert_eq!(state.move_type, MoveType::Climb);
+
+        player_tick(&mut state, &input, &config, dt, None);
+        assert!((state.velocity.y - config.climb_speed).abs() < 0.001);
+
+        let mut ticks = 1;
+        while state.move_type == MoveType::Climb && ticks < 1000 {
+            update_climb_contact(&mut state, &input, &config, wall(8.0, ROCK), &materials);
+            player_tick(&mut state, &input, &config, dt, None);
+            ticks += 1;
+        }
+        assert!((ticks as f32 * dt - config.climb_stamina_time).abs() <= dt);
+        assert_eq!(state.climb_stamina, 0.0);
+
+        // Without stamina the wall can't be climbed again until it refills
+        update_climb_contact(&mut state, &input, &config, wall(8.0, ROCK), &materials);
+        assert_eq!(state.move_type, MoveType::Walk);
+        player_tick(&mut state, &input, &config, dt, None);
+        assert!(state.climb_stamina > 0.0);
+    }
+
+    #[test]
+    fn test_jump_pushes_off_the_wall() {
+        let config = y_up_config();
+        let materials = MaterialTable::new().with_flags(ROCK, SURFACE_CLIMBABLE);
+        let mut state = PlayerState::default();
+        let forward = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        update_climb_contact(&mut state, &forward, &config, wall(4.0, ROCK), &materials);
+
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        let events = player_tick(&mut state, &jump, &config, 1.0 / 64.0, None);
+        assert_eq!(state.move_type, MoveType::Walk);
+        assert!((state.velocity.z - config.climb_jump_off_speed).abs() < 0.001);
+        assert!(matches!(events.as_slice(), [MovementEvent::Jumped { .. }]));
+    }
+}
@@ -0,0 +1,212 @@
+//! Decoupling the client command rate from the tick rate
+//!
+//! Clients do not always send one input per simulation tick: a 144 Hz client
+//! on a 64 Hz server sends a couple of commands per tick, a 30 Hz client one
+//! command that spans two ticks. Like Source's `cl_cmdrate`, each command
+//! carries the time it covers. A `CommandResampler` queues them and cuts the
+//! queue at tick boundaries, splitting a command that spans ticks.
+//!
+//! `take_slices` hands out the pieces of one tick with their durations, for
+//! hosts that run `player_tick` once per piece. `resample` merges them into a
+//! single input for the tick instead: movement axes are weighted by the time
+//! each command covered, buttons count as held if any command held them (so
+//! a short jump tap is never lost), queued jump presses add up and the view
+//! angles are the newest ones.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use std::collections::VecDeque;
+
+use crate::PlayerInput;
+
+/// Durations below this (seconds) count as used up, absorbing float error at tick boundaries
+const SLICE_EPSILON: f32 = 1e-6;
+
+/// A client command, or the part of one, that falls inside a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputSlice {
+    pub input: PlayerInput,
+    /// Seconds of the tick this input covers
+    pub duration: f32,
+}
+
+/// Queue of client commands cut into ticks
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default)]
+pub struct CommandResampler {
+    commands: VecDeque<InputSlice>,
+    slices: Vec<InputSlice>,
+}
+
+impl CommandResampler {
+    /// Create an empty queue
+    pub fn new() -> CommandResampler {
+        CommandResampler::default()
+    }
+
+    /// Queue a command covering `duration` seconds (commands without a duration only press buttons)
+    pub fn push(&mut self, input: PlayerInput, duration: f32) {
+        self.commands.push_back(InputSlice { input, duration: duration.max(0.0) });
+    }
+
+    /// Number of queued commands, counting a partly used one
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether no commands are queued
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Seconds of input queued
+    pub fn buffered_time(&self) -> f32 {
+        self.commands.iter().map(|command| command.duration).sum()
+    }
+
+    /// Drop every queued command (e.g. on respawn)
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Replace `out` with the pieces of the next `tick_time` seconds of input
+    ///
+    /// Returns `false` and leaves the queue alone when less than a tick is queued.
+    pub fn take_slices(&mut self, tick_time: f32, out: &mut Vec<InputSlice>) -> bool {
+        out.clear();
+        if self.buffered_time() < tick_time - SLICE_EPSILON {
+            return false;
+        }
+        let mut remaining = tick_time;
+        while let Some(command) = self.commands.front_mut() {
+            if command.duration > remaining + SLICE_EPSILON {
+                out.push(InputSlice { input: command.input, duration: remaining });
+                // Queued presses happen once, in the first tick the command reaches
+                command.input.jump_presses = 0;
+                command.duration -= remaining;
+                break;
+            }
+            out.push(*command);
+            remaining -= command.duration;
+            self.commands.pop_front();
+            if remaining <= SLICE_EPSILON && self.commands.front().is_none_or(|next| next.duration > 0.0) {
+                break;
+            }
+        }
+        true
+    }
+
+    /// One input for the next `tick_time` seconds, or `None` while less than a tick is queued
+    pub fn resample(&mut self, tick_time: f32) -> Option<PlayerInput> {
+        let mut slices = std::mem::take(&mut self.slices);
+        let input = self.take_slices(tick_time, &mut slices).then(|| merge_slices(&slices));
+        self.slices = slices;
+        input
+    }
+}
+
+/// Merge the pieces of one tick into a single input
+///
+/// Movement axes are weighted by duration, buttons are held if any piece held
+/// them, jump presses add up and the view angles come from the last piece.
+pub fn merge_slices(slices: &[InputSlice]) -> PlayerInput {
+    let Some(last) = slices.last() else {
+        return PlayerInput::new();
+    };
+    let total: f32 = slices.iter().map(|slice| slice.duration).sum();
+    let weight = |slice: &InputSlice| if total > 0.0 { slice.duration / total } else { 1.0 / slices.len() as f32 };
+
+    let mut merged = PlayerInput { yaw: last.input.yaw, pitch: last.input.pitch, ..PlayerInput::new() };
+    for slice in slices {
+        let (input, weight) = (&slice.input, weight(slice));
+        merged.forward_move += input.forward_move * weight;
+        merged.side_move += input.side_move * weight;
+        merged.up_move += input.up_move * weight;
+        merged.lean += input.lean * weight;
+        merged.jump |= input.jump;
+        merged.duck |= input.duck;
+        merged.sprint |= input.sprint;
+        merged.jet |= input.jet;
+        merged.glide |= input.glide;
+        merged.ski |= input.ski;
+        merged.dodge |= input.dodge;
+        merged.ground_pound |= input.ground_pound;
+        merged.air_dash |= input.air_dash;
+        merged.fire |= input.fire;
+        merged.detach |= input.detach;
+        merged.jump_presses = merged.jump_presses.saturating_add(input.jump_presses);
+    }
+    merged
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl CommandResampler {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> CommandResampler {
+        CommandResampler::new()
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_wasm(&mut self, input: &PlayerInput, duration: f32) {
+        self.push(*input, duration);
+    }
+
+    #[wasm_bindgen(getter = bufferedTime)]
+    pub fn buffered_time_wasm(&self) -> f32 {
+        self.buffered_time()
+    }
+
+    #[wasm_bindgen(js_name = clear)]
+    pub fn clear_wasm(&mut self) {
+        self.clear();
+    }
+
+    #[wasm_bindgen(js_name = resample)]
+    pub fn resample_wasm(&mut self, tick_time: f32) -> Option<PlayerInput> {
+        self.resample(tick_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fast_commands_merge_with_time_weighting() {
+        let mut resampler = CommandResampler::new();
+        let tick = 1.0 / 64.0;
+        resampler.push(PlayerInput { forward_move: 1.0, yaw: 0.1, ..PlayerInput::new() }, tick * 0.75);
+        resampler.push(PlayerInput { side_move: 1.0, jump: true, yaw: 0.2, ..PlayerInput::new() }, tick * 0.25);
+        resampler.push(PlayerInput { forward_move: -1.0, yaw: 0.3, ..PlayerInput::new() }, tick * 0.5);
+
+        let merged = resampler.resample(tick).unwrap();
+        assert!((merged.forward_move - 0.75).abs() < 1e-6);
+        assert!((merged.side_move - 0.25).abs() < 1e-6);
+        assert!(merged.jump);
+        assert_eq!(merged.yaw, 0.2);
+        // Half a tick left over is not enough for another tick
+        assert_eq!(resampler.resample(tick), None);
+        assert_eq!(resampler.len(), 1);
+    }
+
+    #[test]
+    fn test_slow_commands_split_across_ticks() {
+        let mut resampler = CommandResampler::new();
+        let tick = 1.0 / 64.0;
+        resampler.push(PlayerInput { forward_move: 1.0, ..PlayerInput::new() }, tick * 2.5);
+        resampler.push(PlayerInput { side_move: 1.0, ..PlayerInput::new() }, tick * 0.5);
+
+        let mut slices = Vec::new();
+        for _ in 0..2 {
+            assert!(resampler.take_slices(tick, &mut slices));
+            assert_eq!(slices.len(), 1);
+            assert!((slices[0].duration - tick).abs() < 1e-6);
+        }
+        assert!(resampler.take_slices(tick, &mut slices));
+        assert_eq!(slices.len(), 2);
+        assert!((slices.iter().map(|slice| slice.duration).sum::<f32>() - tick).abs() < 1e-6);
+        assert!(resampler.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+//! `PlayerState` split into ECS components
+//!
+//! ECS engines (Bevy, hecs) prefer small plain-data components so systems only
+//! borrow what they touch. The components below partition every field of
+//! `PlayerState`; `PlayerComponents` holds one of each and converts to and
+//! from a `PlayerState` without losing anything. To tick a player stored as
+//! components, borrow them into a `PlayerComponentsMut` (e.g. from a query) and
+//! call `player_tick_components`.
+//!
+//! The fields keep their `PlayerState` names and meanings.
+
+use crate::determinism::FieldBits;
+use crate::dodge::DodgeDirection;
+use crate::events::MovementEvent;
+use crate::{player_tick_into, MoveType, MovementConfig, PlayerInput, PlayerState, RayCollisionHit, Vector3};
+
+macro_rules! player_components {
+    ($($(#[$doc:meta])* $name:ident: $component:ident { $($field:ident: $ty:ty,)* })*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct $component {
+                $(pub $field: $ty,)*
+            }
+
+            impl Default for $component {
+                fn default() -> Self {
+                    PlayerComponents::from(PlayerState::default()).$name
+                }
+            }
+        )*
+
+        /// One of each component, together a whole `PlayerState`
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
+        pub struct PlayerComponents {
+            $(pub $name: $component,)*
+        }
+
+        /// Borrowed components of one player, as handed out by an ECS query
+        #[derive(Debug)]
+        pub struct PlayerComponentsMut<'a> {
+            $(pub $name: &'a mut $component,)*
+        }
+
+        impl From<PlayerState> for PlayerComponents {
+            fn from(state: PlayerState) -> Self {
+                PlayerComponents {
+                    $($name: $component { $($field: state.$field,)* },)*
+                }
+            }
+        }
+
+        impl From<PlayerComponents> for PlayerState {
+            fn from(components: PlayerComponents) -> Self {
+                PlayerState {
+                    $($($field: components.$name.$field,)*)*
+                }
+            }
+        }
+
+        /// Names of every `PlayerState` field, in component order
+        pub(crate) const STATE_FIELD_NAMES: &[&str] = &[$($(stringify!($field),)*)*];
+
+        /// Append a hash of every `PlayerState` field, in `STATE_FIELD_NAMES` order
+        pub(crate) fn push_state_field_hashes(state: &PlayerState, hashes: &mut Vec<u64>) {
+            $($(hashes.push(state.$field.bits_hash());)*)*
+        }
+
+        impl PlayerComponents {
+            /// Borrow every component mutably
+            pub fn as_mut(&mut self) -> PlayerComponentsMut<'_> {
+                PlayerComponentsMut { $($name: &mut self.$name,)* }
+            }
+        }
+
+        impl PlayerComponentsMut<'_> {
+            /// Gather the components into a `PlayerState`
+            pub fn load(&self) -> PlayerState {
+                PlayerState {
+                    $($($field: self.$name.$field,)*)*
+                }
+            }
+
+            /// Write a `PlayerState` back into the components
+            pub fn store(&mut self, state: &PlayerState) {
+                $(*self.$name = $component { $($field: state.$field,)* };)*
+            }
+        }
+    };
+}
+
+player_components! {
+    /// Movement mode, position and velocity
+    kinematics: Kinematics {
+        move_type: MoveType,
+        origin: Vector3,
+        velocity: Vector3,
+    }
+
+    /// Ground contact and airtime
+    ground: GroundInfo {
+        on_ground: bool,
+        ground_normal: Vector3,
+        sliding: bool,
+        skidding: bool,
+        time_on_ground: f32,
+        time_in_air: f32,
+        footstep_time: f32,
+    }
+
+    /// Ducking, duck spam, crouch sliding and ceiling clearance
+    duck: DuckState {
+        ducked: bool,
+        duck_amount: f32,
+        duck_held: bool,
+        duck_spam: f32,
+        crouch_sliding: bool,
+        ceiling_blocked: bool,
+    }
+
+    /// Cooldowns, meters and charges that count up or down over time
+    timers: AbilityTimers {
+        jump_cooldown: f32,
+        sprint_amount: f32,
+        jump_charge: f32,
+        bhop_stamina: f32,
+        jetpack_fuel: f32,
+        climb_stamina: f32,
+        water_jump_time: f32,
+        dodge_tap_time: f32,
+        dodge_recovery: f32,
+        wall_run_chain: f32,
+        lean: f32,
+    }
+
+    /// Ability flags, counters, held buttons and host-fed surroundings
+    abilities: AbilityState {
+        tick: u64,
+        jump_count: u32,
+        jump_held: bool,
+        jump_cut_ready: bool,
+        jump_charging: bool,
+        jetpack_enabled: bool,
+        climb_normal: Vector3,
+        water_level: u8,
+        water_current: Vector3,
+        water_jump_velocity: Vector3,
+        gravity_scale: f32,
+        dodge_tap: DodgeDirection,
+        dodge_keys_held: u8,
+        dodging: bool,
+        dodge_wall_normal: Vector3,
+        wall_dodge_count: u32,
+        dodge_jumped: bool,
+        ground_pounding: bool,
+        air_dashes: u32,
+        air_dash_held: bool,
+        stick_normal: Vector3,
+    }
+}
+
+/// `player_tick_into` for a player stored as components
+pub fn player_tick_components(
+    components: &mut PlayerComponentsMut,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    events: &mut Vec<MovementEvent>,
+) {
+    let mut state = components.load();
+    player_tick_into(&mut state, input, config, delta_time, down_ray_hit, events);
+    components.store(&state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_components_tick_like_player_state() {
+        let config = y_up_config();
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let input = PlayerInput { forward_move: 1.0, jump: true, ..PlayerInput::new() };
+        let mut state = PlayerState { on_ground: true, duck_amount: 0.5, ..PlayerState::new(Vector3::new(1.0, 2.0, 3.0)) };
+        let mut components = PlayerComponents::from(state);
+        assert_eq!(PlayerState::from(components), state);
+        assert_eq!(PlayerState::from(PlayerComponents::default()), PlayerState::default());
+
+        let mut events = Vec::new();
+        for _ in 0..16 {
+            player_tick_into(&mut state, &input, &config, 1.0 / 64.0, ground, &mut Vec::new());
+            player_tick_components(&mut components.as_mut(), &input, &config, 1.0 / 64.0, ground, &mut events);
+        }
+        assert_eq!(PlayerState::from(components), state);
+        assert_eq!(components.kinematics.velocity, state.velocity);
+        assert!(!events.is_empty());
+    }
+}
@@ -0,0 +1,1178 @@
+//! Movement tuning parameters
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::config_format::{self, ConfigChange, ConfigError, ConfigValue};
+use crate::air_dash::{AirDashDirection, AirDashMomentum};
+use crate::delta_time::DeltaTimePolicy;
+use crate::dodge::DodgeChain;
+use crate::fixed::{check_fixed, FIXED_LEGACY_GRAVITY, FIXED_UP_AXIS};
+use crate::error::AirStrafeError;
+use crate::{gravity_velocity_change_native, MoveType, UpAxis, Vector3, LEGACY_GRAVITY_SCALE};
+
+/// Allowed range of a number field, checked by `validate` and listed in the JSON Schema
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FieldBounds {
+    minimum: Option<f32>,
+    /// Whether `minimum` itself is outside the range
+    exclusive_minimum: bool,
+    maximum: Option<f32>,
+    /// What `validate` reports for a value outside the range
+    expected: &'static str,
+}
+
+/// Adds the bounds to the field's schema, for `#[schemars(transform = ..)]`
+#[cfg(feature = "json-schema")]
+impl schemars::transform::Transform for FieldBounds {
+    fn transform(&mut self, schema: &mut schemars::Schema) {
+        let minimum_keyword = if self.exclusive_minimum { "exclusiveMinimum" } else { "minimum" };
+        if let Some(minimum) = self.minimum {
+            schema.insert(minimum_keyword.to_string(), json_number(minimum));
+        }
+        if let Some(maximum) = self.maximum {
+            schema.insert("maximum".to_string(), json_number(maximum));
+        }
+    }
+}
+
+/// `value` as a JSON number, written the way `ConfigValue` displays it
+#[cfg(feature = "json-schema")]
+fn json_number(value: f32) -> serde_json::Value {
+    serde_json::from_str(&ConfigValue::Number(value).to_string()).unwrap_or_default()
+}
+
+/// `FieldBounds` from the bounds written after a field's type: `[>= min]`,
+/// `[> min]`, `[min, max]`, or nothing for a number of any sign
+macro_rules! field_bounds {
+    () => {
+        FieldBounds { minimum: None, exclusive_minimum: false, maximum: None, expected: "a finite number" }
+    };
+    (>= $minimum:literal) => {
+        FieldBounds {
+            minimum: Some($minimum),
+            exclusive_minimum: false,
+            maximum: None,
+            expected: "a finite, non-negative number",
+        }
+    };
+    (> $minimum:literal) => {
+        FieldBounds {
+            minimum: Some($minimum),
+            exclusive_minimum: true,
+            maximum: None,
+            expected: concat!("greater than ", stringify!($minimum)),
+        }
+    };
+    ($minimum:literal, $maximum:literal) => {
+        FieldBounds {
+            minimum: Some($minimum),
+            exclusive_minimum: false,
+            maximum: Some($maximum),
+            expected: concat!("between ", stringify!($minimum), " and ", stringify!($maximum)),
+        }
+    };
+}
+
+/// Declares `MovementConfig` together with its defaults and a by-name field table,
+/// so every field is automatically available to the config file formats. A
+/// number field may give its allowed range in brackets after its type.
+macro_rules! movement_config {
+    ($( $(#[doc = $doc:literal])* $name:ident: $ty:ty $([$($bounds:tt)+])? = $default:expr, )*) => {
+        /// Tuning parameters for `player_tick`
+        ///
+        /// Defaults follow Source Engine values (units per second, 1 unit = 1 inch).
+        #[cfg_attr(feature = "wasm", wasm_bindgen)]
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(
+            feature = "json-schema",
+            derive(schemars::JsonSchema),
+            schemars(deny_unknown_fields, transform = MovementConfig::add_schema_defaults)
+        )]
+        pub struct MovementConfig {
+            $(
+                $(#[doc = $doc])*
+                $( #[cfg_attr(feature = "json-schema", schemars(transform = field_bounds!($($bounds)+)))] )?
+                pub $name: $ty,
+            )*
+        }
+
+        impl Default for MovementConfig {
+            fn default() -> Self {
+                MovementConfig {
+                    $( $name: $default, )*
+                }
+            }
+        }
+
+        impl MovementConfig {
+            /// Names of all fields, in declaration order
+            pub const FIELD_NAMES: &'static [&'static str] = &[$( stringify!($name), )*];
+
+            /// Doc comment and JSON Schema type keywords of every field, in declaration order
+            #[cfg(not(feature = "json-schema"))]
+            const FIELD_SCHEMAS: &'static [(&'static str, &'static str)] = &[
+                $( (concat!($($doc, "\n",)*), <$ty as config_format::FieldValue>::JSON_SCHEMA), )*
+            ];
+
+            /// Allowed range of every field, in declaration order
+            const FIELD_BOUNDS: &'static [FieldBounds] = &[$( field_bounds!($($($bounds)+)?), )*];
+
+            /// Read a field by name
+            pub fn get(&self, name: &str) -> Option<ConfigValue> {
+                match name {
+                    $( stringify!($name) => Some(ConfigValue::from(self.$name)), )*
+                    _ => None,
+                }
+            }
+
+            /// Write a field by name
+            pub fn set(&mut self, name: &str, value: ConfigValue) -> Result<(), ConfigError> {
+                match name {
+                    $( stringify!($name) => {
+                        self.$name = config_format::FieldValue::from_config_value(name, value)?;
+                        Ok(())
+                    } )*
+                    _ => Err(ConfigError::UnknownField(name.to_string())),
+                }
+            }
+        }
+
+        impl MovementConfigBuilder {
+            $(
+                $(#[doc = $doc])*
+                pub fn $name(mut self, value: $ty) -> MovementConfigBuilder {
+                    self.overrides.push((stringify!($name), ConfigValue::from(value)));
+                    self
+                }
+            )*
+        }
+    };
+}
+
+movement_config! {
+    /// Ground wish speed at full input
+    max_speed: f32 [>= 0.0] = 320.0,
+    /// Ground acceleration (sv_accelerate)
+    accelerate: f32 [>= 0.0] = 10.0,
+    /// Air acceleration (sv_airaccelerate), scaled by wish speed like in Source
+    air_accelerate: f32 [>= 0.0] = 10.0,
+    /// Air acceleration with sideways-only input, i.e. pure strafing (CPM's airstrafeaccelerate)
+    air_strafe_accelerate: f32 [>= 0.0] = 10.0,
+    /// Air acceleration multiplier while moving backwards
+    air_backpedal_factor: f32 [>= 0.0] = 1.0,
+    /// Cap on the velocity projected onto the wish direction while airborne
+    max_air_wish_speed: f32 [>= 0.0] = 30.0,
+    /// Analog stick deflection below which movement input is ignored, see `analog`
+    analog_deadzone: f32 [0.0, 1.0] = 0.0,
+    /// Exponent of the analog stick response curve (1.0 = linear, 2.0 = fine control near the center)
+    analog_response_exponent: f32 [>= 0.0] = 1.0,
+    /// Ground friction (sv_friction)
+    friction: f32 [>= 0.0] = 4.0,
+    /// Speed below which friction uses `stop_speed` as control (sv_stopspeed)
+    stop_speed: f32 [>= 0.0] = 100.0,
+    /// Radians per second the ground wish direction can turn away from the velocity (0.0 = instant), see `momentum`
+    momentum_turn_rate: f32 [>= 0.0] = 0.0,
+    /// Speed above which turning is rate limited and reversing skids, with `momentum_turn_rate` enabled
+    skid_min_speed: f32 [>= 0.0] = 150.0,
+    /// Ground friction while skidding
+    skid_friction: f32 [>= 0.0] = 8.0,
+    /// Gravity acceleration along world down (units/s², sv_gravity)
+    gravity: f32 [>= 0.0] = 800.0,
+    /// Use the legacy `gravity * dt² * legacy_gravity_scale` model instead of
+    /// integrating `gravity` as an acceleration; defaults to the model fixed by
+    /// a `fixed` feature
+    legacy_gravity: bool = FIXED_LEGACY_GRAVITY.unwrap_or(false),
+    /// Downscale factor for the legacy gravity model
+    legacy_gravity_scale: f32 [>= 0.0] = LEGACY_GRAVITY_SCALE,
+    /// Gravity multiplier while moving up, see `gravity_curve`
+    rise_gravity_multiplier: f32 [>= 0.0] = 1.0,
+    /// Gravity multiplier while falling, see `gravity_curve`
+    fall_gravity_multiplier: f32 [>= 0.0] = 1.0,
+    /// Vertical speed below which an airborne player hangs at the apex of a jump (0.0 disables)
+    apex_hang_speed: f32 [>= 0.0] = 0.0,
+    /// Gravity multiplier while hanging at the apex, instead of the rise or fall multiplier
+    apex_gravity_multiplier: f32 [>= 0.0] = 1.0,
+    /// Terminal velocity when falling through air (0.0 = unlimited)
+    max_fall_speed: f32 [>= 0.0] = 3500.0,
+    /// Air drag coefficient while airborne (0.0 disables): per second, or per unit of speed with `air_drag_quadratic`,
+    /// see `air_drag`
+    air_drag: f32 [>= 0.0] = 0.0,
+    /// Make air drag grow with the square of the speed instead of linearly
+    air_drag_quadratic: bool = false,
+    /// Terminal velocity when sinking while swimming (0.0 = unlimited)
+    max_water_fall_speed: f32 [>= 0.0] = 300.0,
+    /// Wish speed while swimming (0.0 = `max_speed`), see `for_move_type`
+    swim_max_speed: f32 [>= 0.0] = 0.0,
+    /// Acceleration while swimming, in place of both `accelerate` and the air accelerations (0.0 = unchanged)
+    swim_accelerate: f32 [>= 0.0] = 0.0,
+    /// Classic swim controls: forward stays horizontal and only `up_move` swims up or down, see `wishdir`
+    classic_swim: bool = false,
+    /// Fraction of gravity cancelled when fully under water (above 1.0 floats up)
+    water_float_buoyancy: f32 [>= 0.0] = 1.2,
+    /// Fraction of gravity cancelled when fully under water while swimming down
+    water_sink_buoyancy: f32 [>= 0.0] = 0.7,
+    /// Upward speed of a water jump out of a pool
+    water_jump_speed: f32 [>= 0.0] = 256.0,
+    /// Speed pushing the player over the ledge during a water jump
+    water_jump_push: f32 [>= 0.0] = 50.0,
+    /// Longest a water jump locks the controls, in seconds
+    water_jump_time: f32 [>= 0.0] = 2.0,
+    /// Largest forward ray distance at which a ledge starts a water jump
+    water_jump_reach: f32 [>= 0.0] = 24.0,
+    /// Landing speed above which a fall would hurt (PLAYER_MAX_SAFE_FALL_SPEED)
+    safe_fall_speed: f32 [>= 0.0] = 526.5,
+    /// Landing speed of a fatal fall (PLAYER_FATAL_FALL_SPEED)
+    fatal_fall_speed: f32 [>= 0.0] = 922.5,
+    /// Landing speed above which landing while ducked converts fall speed into slide speed
+    land_slide_min_speed: f32 [>= 0.0] = 300.0,
+    /// Fraction of the landing speed added along the ground when landing ducked (0.0 disables)
+    land_slide_ratio: f32 [0.0, 1.0] = 0.0,
+    /// Upward velocity applied when jumping
+    jump_speed: f32 [>= 0.0] = 268.3,
+    /// Minimum seconds between two jumps
+    jump_cooldown: f32 [>= 0.0] = 0.0,
+    /// Scale the jump speed with the square root of `PlayerState::gravity_scale`,
+    /// keeping jumps the same height in low-gravity zones
+    jump_gravity_scaling: bool = false,
+    /// Seconds of holding jump to fully charge a jump (0.0 = jump on press), see `jump_charge`
+    jump_charge_time: f32 [>= 0.0] = 0.0,
+    /// Jump speed multiplier of a fully charged jump
+    jump_charge_max_multiplier: f32 [>= 0.0] = 1.5,
+    /// Wish speed multiplier while charging a jump
+    jump_charge_move_multiplier: f32 [0.0, 1.0] = 0.5,
+    /// Share of the upward speed kept when jump is released on the way up (1.0 disables), see `jump_cut`
+    jump_cut_multiplier: f32 [0.0, 1.0] = 1.0,
+    /// Fraction of the jump speed lost by jumping right after a jump (CS 1.6: 0.25, 0.0 disables), see `bhop_penalty`
+    bhop_stamina_penalty: f32 [0.0, 1.0] = 0.0,
+    /// Seconds for the stamina cost of a jump to wear off
+    bhop_stamina_time: f32 [>= 0.0] = 1.316,
+    /// Horizontal speed, as a multiple of `max_speed`, above which jumping slows down (CS: 1.2, 0.0 disables)
+    bhop_speed_cap: f32 [>= 0.0] = 0.0,
+    /// Fraction of the capped speed kept when jumping above `bhop_speed_cap`
+    bhop_speed_cap_slowdown: f32 [0.0, 1.0] = 0.65,
+    /// Enable Unreal Tournament style dodging, see `dodge`
+    dodge_enabled: bool = false,
+    /// Whether double-tapping a direction dodges (otherwise only the dodge button does)
+    dodge_double_tap: bool = true,
+    /// Longest time between the two taps of a double-tap dodge, in seconds
+    dodge_tap_window: f32 [>= 0.0] = 0.25,
+    /// Horizontal speed of a dodge
+    dodge_speed: f32 [>= 0.0] = 600.0,
+    /// Upward speed of a dodge
+    dodge_up_speed: f32 [>= 0.0] = 210.0,
+    /// Seconds after landing from a dodge before the player can dodge or jump again
+    dodge_recovery_time: f32 [>= 0.0] = 0.35,
+    /// Whether airborne players can dodge away from a wall, see `update_dodge_wall`
+    dodge_wall_enabled: bool = false,
+    /// Largest side ray distance at which a wall can be dodged off
+    dodge_wall_reach: f32 [>= 0.0] = 32.0,
+    /// What a dodge can be chained with before landing
+    dodge_chain: DodgeChain = DodgeChain::None,
+    /// Enable the ground pound input, see `ground_pound`
+    ground_pound_enabled: bool = false,
+    /// Downward speed of a ground pound
+    ground_pound_speed: f32 [>= 0.0] = 1200.0,
+    /// Fraction of the horizontal speed cancelled when a ground pound starts
+    ground_pound_horizontal_cancel: f32 [0.0, 1.0] = 1.0,
+    /// Air dashes allowed per airtime (0 disables), see `air_dash`
+    air_dash_count: u32 = 0,
+    /// Speed of an air dash
+    air_dash_speed: f32 [>= 0.0] = 500.0,
+    /// Whether air dashes follow the aim or the held movement direction
+    air_dash_direction: AirDashDirection = AirDashDirection::Input,
+    /// What an air dash does with the existing velocity
+    air_dash_momentum: AirDashMomentum = AirDashMomentum::Replace,
+    /// Whether holding jump re-jumps on landing (otherwise jump must be re-pressed)
+    auto_bhop: bool = false,
+    /// Wish speed multiplier while sprinting
+    sprint_multiplier: f32 [>= 0.0] = 1.5,
+    /// Ramp `sprint_multiplier` in and out instead of applying it instantly, see `sprint`
+    sprint_ramp_enabled: bool = false,
+    /// Seconds to reach full sprint speed with `sprint_ramp_enabled`
+    sprint_ramp_up_time: f32 [>= 0.0] = 0.5,
+    /// Seconds to drop back to walking speed with `sprint_ramp_enabled`
+    sprint_ramp_down_time: f32 [>= 0.0] = 0.25,
+    /// Wish speed multiplier while ducked
+    duck_multiplier: f32 [>= 0.0] = 0.67,
+    /// Horizontal ground speed below which no footsteps are emitted
+    footstep_min_speed: f32 [>= 0.0] = 90.0,
+    /// Seconds between footsteps at `footstep_min_speed`
+    footstep_interval_slow: f32 [>= 0.0] = 0.6,
+    /// Seconds between footsteps at `max_speed` and above
+    footstep_interval_fast: f32 [>= 0.0] = 0.3,
+    /// Seconds to fully duck while on the ground (0.0 = instant)
+    duck_time: f32 [>= 0.0] = 0.4,
+    /// Seconds to stand back up while on the ground (0.0 = instant)
+    unduck_time: f32 [>= 0.0] = 0.2,
+    /// Eye height above the origin while standing
+    eye_height: f32 [>= 0.0] = 64.0,
+    /// Eye height above the origin while fully ducked
+    duck_eye_height: f32 [>= 0.0] = 28.0,
+    /// Height of the standing hull; a ceiling closer than this keeps the player ducked, see `crouch_slide`
+    hull_height: f32 [>= 0.0] = 72.0,
+    /// Height of the ducked hull, see `duck`
+    duck_hull_height: f32 [>= 0.0] = 36.0,
+    /// Horizontal half-width of the standing hull (box half-extent or capsule radius), see `hull`
+    hull_radius: f32 [>= 0.0] = 16.0,
+    /// Horizontal half-width of the ducked hull
+    duck_hull_radius: f32 [>= 0.0] = 16.0,
+    /// Whether the hulls are upright capsules instead of axis-aligned boxes
+    hull_capsule: bool = false,
+    /// Emulate Source's duck state machine: the duck slowdown starts with the transition, and ducking or
+    /// standing up in the air moves the feet instead of the head (duck jumps), see `duck`
+    source_duck: bool = false,
+    /// Share of the duck speed lost per recent duck press (0.0 disables); while any press is left, ducking in the
+    /// air is timed as well, see `duck`
+    duck_spam_penalty: f32 [>= 0.0] = 0.0,
+    /// Seconds for one duck press to stop counting towards `duck_spam_penalty`
+    duck_spam_recovery_time: f32 [>= 0.0] = 1.0,
+    /// Ground speed above which ducking starts a crouch slide (0.0 disables)
+    crouch_slide_min_speed: f32 [>= 0.0] = 0.0,
+    /// Ground friction while crouch sliding
+    crouch_slide_friction: f32 [>= 0.0] = 1.0,
+    /// Speed a crouch slide keeps under a ceiling too low to stand (0.0 lets it stop)
+    crouch_slide_stuck_speed: f32 [>= 0.0] = 100.0,
+    /// Minimum up component of a surface normal to count as walkable ground
+    min_ground_normal_up: f32 [0.0, 1.0] = 0.7,
+    /// Multiplier on air acceleration while sliding on a surf ramp, see `surf`
+    surf_accelerate_multiplier: f32 [>= 0.0] = 1.0,
+    /// Largest angle in radians between the wish direction and the ramp surface that gets
+    /// `surf_accelerate_multiplier` (pi/2 = any direction)
+    surf_board_angle_tolerance: f32 [>= 0.0] = std::f32::consts::FRAC_PI_2,
+    /// Most speed per second a surf ramp tick may add, from gravity and air acceleration together (0.0 = no limit)
+    surf_max_speed_gain: f32 [>= 0.0] = 0.0,
+    /// Ground closer than this below the player snaps the player onto it
+    ground_snap_distance: f32 [>= 0.0] = 2.0,
+    /// Highest ledge the player steps up onto: ground the down ray hits up to this far above the
+    /// origin lifts the player onto it, so cast the ray from this high above the feet
+    step_height: f32 [>= 0.0] = 18.0,
+    /// Lowest lift onto ground that is reported as a `SteppedUp` event
+    min_step_event_height: f32 [>= 0.0] = 1.0,
+    /// Upward speed above which the player can not be considered on the ground
+    non_jump_velocity: f32 [>= 0.0] = 140.0,
+    /// Emulate Quake 3 overbounces: a fall ending just above walkable ground bounces back up, see `overbounce`
+    overbounce: bool = false,
+    /// Height above the ground (units) a falling tick must end within to overbounce
+    overbounce_window: f32 [>= 0.0] = 0.25,
+    /// Keep falling instead of landing when the ground below is the very edge of a surface, see `edgebug`
+    edgebug: bool = false,
+    /// Distance (units) from the edge of a surface within which a landing edgebugs
+    edgebug_tolerance: f32 [>= 0.0] = 1.0,
+    /// Standing up and jumping on the landing tick jumps without landing, see `jumpbug`
+    jumpbug: bool = false,
+    /// Jetpack acceleration (units/s²), integrated like gravity; must exceed `gravity` to climb
+    jetpack_thrust: f32 [>= 0.0] = 1500.0,
+    /// Tilt of the thrust towards the movement input (0.0 = straight up, 1.0 = 45 degrees)
+    jetpack_directional_factor: f32 [>= 0.0] = 0.5,
+    /// Speed along the thrust direction above which the jetpack adds no more speed
+    jetpack_max_speed: f32 [>= 0.0] = 600.0,
+    /// Seconds of thrust on a full tank
+    jetpack_fuel_time: f32 [>= 0.0] = 3.0,
+    /// Seconds to refill an empty tank while not thrusting (0.0 = instant)
+    jetpack_regen_time: f32 [>= 0.0] = 6.0,
+    /// Fraction of the descent speed turned into forward speed per second while gliding
+    glide_lift: f32 [>= 0.0] = 2.0,
+    /// Fraction of the forward speed lost per second while gliding
+    glide_drag: f32 [>= 0.0] = 0.25,
+    /// Radians per second the glider turns towards the view yaw
+    glide_turn_rate: f32 [>= 0.0] = 1.5,
+    /// Terminal velocity while gliding (0.0 = unlimited)
+    glide_max_fall_speed: f32 [>= 0.0] = 200.0,
+    /// Speed bonus per link of a wall-run jump chain, see `wall_run`
+    wall_run_chain_bonus: f32 [>= 0.0] = 40.0,
+    /// Most a wall-run chain can raise horizontal speed above `max_speed`
+    wall_run_chain_max_bonus: f32 [>= 0.0] = 200.0,
+    /// Chain links lost per second while standing on the ground
+    wall_run_chain_decay: f32 [>= 0.0] = 2.0,
+    /// Speed when climbing a wall at full input
+    climb_speed: f32 [>= 0.0] = 120.0,
+    /// Largest forward ray distance at which a climbable wall can be grabbed
+    climb_reach: f32 [>= 0.0] = 24.0,
+    /// Seconds of climbing on full stamina
+    climb_stamina_time: f32 [>= 0.0] = 4.0,
+    /// Seconds to refill empty climbing stamina (0.0 = instant)
+    climb_stamina_regen_time: f32 [>= 0.0] = 2.0,
+    /// Speed away from the wall when jumping off it
+    climb_jump_off_speed: f32 [>= 0.0] = 250.0,
+    /// Acceleration towards a sticky surface while stuck to it, replacing `gravity`
+    sticky_attraction: f32 [>= 0.0] = 1600.0,
+    /// Largest ray distance at which a sticky surface attaches or holds the player
+    sticky_reach: f32 [>= 0.0] = 32.0,
+    /// Strafe assistance factor (0.0 = off, 1.0 = perfect strafes)
+    strafe_assist: f32 [0.0, 1.0] = 0.0,
+    /// Wish speed for `Fly` movement (0.0 = `max_speed`), see `for_move_type`
+    fly_max_speed: f32 [>= 0.0] = 0.0,
+    /// Acceleration for `Fly` movement
+    fly_accelerate: f32 [>= 0.0] = 10.0,
+    /// Friction for `Fly` movement
+    fly_friction: f32 [>= 0.0] = 4.0,
+    /// Wish speed for `Noclip` movement, see `noclip`
+    noclip_speed: f32 [>= 0.0] = 1000.0,
+    /// Time constant (seconds) for noclip speeding up (0.0 = instant)
+    noclip_acceleration_time: f32 [>= 0.0] = 0.1,
+    /// Time constant (seconds) for noclip slowing down (0.0 = instant)
+    noclip_deceleration_time: f32 [>= 0.0] = 0.15,
+    /// Noclip speed multiplier while sprint is held
+    noclip_fast_multiplier: f32 [>= 0.0] = 3.0,
+    /// Noclip speed multiplier while duck is held
+    noclip_slow_multiplier: f32 [>= 0.0] = 0.25,
+    /// Spectator camera speed at full input
+    spectator_speed: f32 [>= 0.0] = 800.0,
+    /// Spectator speed multiplier while sprint is held
+    spectator_boost_multiplier: f32 [>= 0.0] = 3.0,
+    /// Time constant (seconds) for the spectator camera speeding up
+    spectator_acceleration_time: f32 [>= 0.0] = 0.15,
+    /// Time constant (seconds) for the spectator camera slowing down
+    spectator_deceleration_time: f32 [>= 0.0] = 0.25,
+    /// View bob amplitude per unit of horizontal speed (cl_bob, negative inverts the bob)
+    bob_scale: f32 = 0.02,
+    /// Seconds per view bob cycle (cl_bobcycle, 0.0 = no bobbing)
+    bob_cycle: f32 [>= 0.0] = 0.6,
+    /// Fraction of the bob cycle spent rising (cl_bobup)
+    bob_up: f32 [0.0, 1.0] = 0.5,
+    /// Largest camera roll in radians when moving sideways (cl_rollangle, 2 degrees;
+    /// negative rolls the other way)
+    roll_angle: f32 = 0.034906585,
+    /// Sideways speed at which the camera reaches `roll_angle` (cl_rollspeed)
+    roll_speed: f32 [>= 0.0] = 200.0,
+    /// Time constant (seconds) for the camera catching up after a step (0.0 = no smoothing)
+    step_smooth_time: f32 [>= 0.0] = 0.1,
+    /// Sideways camera offset at full lean
+    lean_distance: f32 [>= 0.0] = 16.0,
+    /// Camera roll in radians at full lean (15 degrees, negative rolls against the lean)
+    lean_angle: f32 = 0.2617994,
+    /// Seconds to go from upright to full lean (0.0 = instant)
+    lean_time: f32 [>= 0.0] = 0.2,
+    /// Longest step `player_tick` simulates in seconds (0.0 = no limit), see `delta_time`
+    max_delta_time: f32 [>= 0.0] = 0.25,
+    /// What `player_tick` does with a longer step
+    delta_time_policy: DeltaTimePolicy = DeltaTimePolicy::Clamp,
+    /// World units per config unit; speeds, accelerations and distances in this
+    /// config are in Hammer units and get multiplied by this (0.0254 for meters)
+    unit_scale: f32 [> 0.0] = 1.0,
+    /// Which world axis points up; defaults to the axis fixed by a `fixed` feature
+    up_axis: UpAxis = FIXED_UP_AXIS.unwrap_or(UpAxis::Y),
+}
+
+/// Scale from Hammer units (inches) to meters
+pub const HAMMER_UNITS_TO_METERS: f32 = 0.0254;
+
+/// Known-good starting points for `MovementConfig`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigPreset {
+    /// Half-Life 2 / Counter-Strike: Source defaults
+    #[default]
+    Source,
+    /// Counter-Strike: Global Offensive
+    Csgo,
+    /// Quake 3 vanilla (VQ3): weak air control, no air wish speed cap
+    Quake,
+    /// Challenge ProMode: strong sideways-only air strafing
+    Cpm,
+}
+
+/// Fluent, validating builder for `MovementConfig`
+///
+/// Field setters always win over the preset, regardless of call order.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default)]
+pub struct MovementConfigBuilder {
+    preset: ConfigPreset,
+    overrides: Vec<(&'static str, ConfigValue)>,
+}
+
+impl MovementConfigBuilder {
+    /// Start from a preset instead of the Source defaults
+    pub fn preset(mut self, preset: ConfigPreset) -> MovementConfigBuilder {
+        self.preset = preset;
+        self
+    }
+
+    /// Set a field by name, e.g. for values coming from a UI
+    pub fn set(mut self, name: &str, value: ConfigValue) -> Result<MovementConfigBuilder, AirStrafeError> {
+        let field = MovementConfig::FIELD_NAMES
+            .iter()
+            .copied()
+            .find(|field| *field == name)
+            .ok_or_else(|| ConfigError::UnknownField(name.to_string()))?;
+        // Type-check up front so `build` only has to report range errors
+        MovementConfig::default().set(field, value.clone())?;
+        self.overrides.push((field, value));
+        Ok(self)
+    }
+
+    /// Apply the preset and overrides and validate the result
+    pub fn build(self) -> Result<MovementConfig, AirStrafeError> {
+        let mut config = MovementConfig::preset(self.preset);
+        for (field, value) in self.overrides {
+            config.set(field, value)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl MovementConfig {
+    /// Start building a config from the Source defaults
+    pub fn builder() -> MovementConfigBuilder {
+        MovementConfigBuilder::default()
+    }
+
+    /// The config for a preset
+    pub fn preset(preset: ConfigPreset) -> MovementConfig {
+        let source = MovementConfig::default();
+        match preset {
+            ConfigPreset::Source => source,
+            ConfigPreset::Csgo => MovementConfig {
+                max_speed: 250.0,
+                accelerate: 5.5,
+                air_accelerate: 12.0,
+                air_strafe_accelerate: 12.0,
+                friction: 5.2,
+                stop_speed: 80.0,
+                jump_speed: 301.993,
+                ..source
+            },
+            ConfigPreset::Quake => MovementConfig {
+                air_accelerate: 1.0,
+                air_strafe_accelerate: 1.0,
+                max_air_wish_speed: 320.0,
+                friction: 6.0,
+                jump_speed: 270.0,
+                ..source
+            },
+            ConfigPreset::Cpm => MovementConfig {
+                air_accelerate: 1.0,
+                air_strafe_accelerate: 70.0,
+                friction: 6.0,
+                jump_speed: 270.0,
+                ..source
+            },
+        }
+    }
+
+    /// Check that every field is within its allowed range
+    ///
+    /// Numeric fields must be finite and within the range declared next to
+    /// their type: most are non-negative, fractions lie between 0 and 1, the
+    /// unit scale must be positive and view angles may have either sign.
+    pub fn validate(&self) -> Result<(), AirStrafeError> {
+        let out_of_range = |field: &str, expected| AirStrafeError::ConfigOutOfRange { field: field.to_string(), expected };
+        for (field, bounds) in MovementConfig::FIELD_NAMES.iter().zip(MovementConfig::FIELD_BOUNDS) {
+            if let Some(ConfigValue::Number(n)) = self.get(field) {
+                if !n.is_finite() {
+                    return Err(out_of_range(field, bounds.expected));
+                }
+                let below = bounds.minimum.is_some_and(|min| n < min || (bounds.exclusive_minimum && n == min));
+                if below || bounds.maximum.is_some_and(|max| n > max) {
+                    return Err(out_of_range(field, bounds.expected));
+                }
+            }
+        }
+        check_fixed(self)
+    }
+
+    /// JSON Schema (draft-07) describing the tuning file format
+    ///
+    /// Lists every field with its description, type, allowed range and default,
+    /// so editors can offer autocomplete and validation for tuning files and UIs
+    /// can generate forms from it. With the `json-schema` feature it is
+    /// generated by `schemars` from the derived `JsonSchema`.
+    #[cfg(feature = "json-schema")]
+    pub fn json_schema() -> String {
+        let settings = schemars::generate::SchemaSettings::draft07().with(|s| s.inline_subschemas = true);
+        let schema = settings.into_generator().into_root_schema_for::<MovementConfig>();
+        format!("{:#}\n", schema.as_value())
+    }
+
+    /// `schemars` transform making every field optional with its default, like in tuning files
+    #[cfg(feature = "json-schema")]
+    fn add_schema_defaults(schema: &mut schemars::Schema) {
+        schema.remove("required");
+        let defaults = MovementConfig::default();
+        let Some(properties) = schema.get_mut("properties").and_then(serde_json::Value::as_object_mut) else {
+            return;
+        };
+        for (field, property) in properties.iter_mut() {
+            let default = defaults.get(field).and_then(|value| serde_json::from_str(&value.to_string()).ok());
+            if let (Some(property), Some(default)) = (property.as_object_mut(), default) {
+                property.insert("default".to_string(), default);
+            }
+        }
+    }
+
+    /// JSON Schema (draft-07) describing the tuning file format
+    ///
+    /// Lists every field with its description, type, allowed range and default,
+    /// so editors can offer autocomplete and validation for tuning files and UIs
+    /// can generate forms from it.
+    #[cfg(not(feature = "json-schema"))]
+    pub fn json_schema() -> String {
+        let defaults = MovementConfig::default();
+        let properties: Vec<String> = MovementConfig::FIELD_NAMES
+            .iter()
+            .zip(MovementConfig::FIELD_SCHEMAS)
+            .zip(MovementConfig::FIELD_BOUNDS)
+            .map(|((&field, &(doc, type_keywords)), bounds)| {
+                let description = doc.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+                let mut keywords = vec![
+                    format!("\"description\": {}", config_format::json_string(description.trim())),
+                    type_keywords.to_string(),
+                ];
+                match (bounds.minimum, bounds.exclusive_minimum) {
+                    (Some(minimum), false) => keywords.push(format!("\"minimum\": {minimum}")),
+                    (Some(minimum), true) => keywords.push(format!("\"exclusiveMinimum\": {minimum}")),
+                    (None, _) => {}
+                }
+                if let Some(maximum) = bounds.maximum {
+                    keywords.push(format!("\"maximum\": {maximum}"));
+                }
+                if let Some(default) = defaults.get(field) {
+                    keywords.push(format!("\"default\": {default}"));
+                }
+                format!("    \"{field}\": {{ {} }}", keywords.join(", "))
+            })
+            .collect();
+        format!(
+            "{{\n  \"$schema\": \"http://json-schema.org/draft-07/schema#\",\n  \"title\": \"MovementConfig\",\n  \"type\": \"object\",\n  \"additionalProperties\": false,\n  \"properties\": {{\n{}\n  }}\n}}\n",
+            properties.join(",\n")
+        )
+    }
+
+    /// Air acceleration factor for the given movement input
+    ///
+    /// Sideways-only input uses `air_strafe_accelerate`, anything else
+    /// `air_accelerate`; backwards input is further scaled by `air_backpedal_factor`.
+    pub fn air_accelerate_for(&self, forward_move: f32, side_move: f32) -> f32 {
+        if forward_move == 0.0 && side_move != 0.0 {
+            self.air_strafe_accelerate
+        } else if forward_move < 0.0 {
+            self.air_accelerate * self.air_backpedal_factor
+        } else {
+            self.air_accelerate
+        }
+    }
+
+    /// Acceleration value passed to `air_accelerate_get_acceleration` for a given
+    /// input and wish speed
+    pub fn effective_air_accelerate(&self, forward_move: f32, side_move: f32, wish_speed: f32) -> f32 {
+        self.air_accelerate_for(forward_move, side_move) * wish_speed
+    }
+
+    /// Normalized intensity (0–1) of landing at `landing_speed`
+    ///
+    /// Rises linearly to 0.5 at `safe_fall_speed` and on to 1.0 at
+    /// `fatal_fall_speed`, so anything above 0.5 is a landing that would deal
+    /// fall damage.
+    pub fn landing_impact(&self, landing_speed: f32) -> f32 {
+        let speed = landing_speed.max(0.0);
+        let impact = if speed <= self.safe_fall_speed {
+            if self.safe_fall_speed > 0.0 { 0.5 * speed / self.safe_fall_speed } else { 0.5 }
+        } else {
+            let range = self.fatal_fall_speed - self.safe_fall_speed;
+            if range > 0.0 { 0.5 + 0.5 * (speed - self.safe_fall_speed) / range } else { 1.0 }
+        };
+        impact.min(1.0)
+    }
+
+    /// Seconds between footsteps at a horizontal ground speed
+    ///
+    /// Goes linearly from `footstep_interval_slow` at `footstep_min_speed` to
+    /// `footstep_interval_fast` at `max_speed`. Both speeds are in the same
+    /// units as `speed`, so pass a config converted with `in_world_units` for
+    /// world speeds.
+    pub fn footstep_interval(&self, speed: f32) -> f32 {
+        let range = self.max_speed - self.footstep_min_speed;
+        let t = if range > 0.0 { ((speed - self.footstep_min_speed) / range).clamp(0.0, 1.0) } else { 1.0 };
+        self.footstep_interval_slow + (self.footstep_interval_fast - self.footstep_interval_slow) * t
+    }
+
+    /// Gravity acceleration vector pointing down the configured up axis
+    pub fn gravity_vector(&self) -> Vector3 {
+        self.up_axis.up().multiply_scalar(-self.gravity)
+    }
+
+    /// Whether gravity uses the legacy model
+    #[inline(always)]
+    pub fn uses_legacy_gravity(&self) -> bool {
+        self.legacy_gravity
+    }
+
+    /// Velocity change from gravity over one time step, using the configured gravity model
+    pub fn gravity_velocity_change(&self, delta_time: f32) -> Vector3 {
+        let legacy_scale = self.uses_legacy_gravity().then_some(self.legacy_gravity_scale);
+        gravity_velocity_change_native(&self.gravity_vector(), delta_time, legacy_scale)
+    }
+
+    /// `gravity_velocity_change` for the up axis and gravity model of a `fixed` instantiation
+    pub(crate) fn gravity_velocity_change_fixed<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+        &self,
+        delta_time: f32,
+    ) -> Vector3 {
+        let gravity = UpAxis::from_z_up(Z_UP).up().multiply_scalar(-self.gravity);
+        gravity_velocity_change_native(&gravity, delta_time, LEGACY_GRAVITY.then_some(self.legacy_gravity_scale))
+    }
+
+    /// This config with the speed, acceleration and fall limits resolved for `move_type`
+    ///
+    /// `player_tick` resolves the config once per tick from the move type the
+    /// tick starts in, so the movement code only reads the generic fields.
+    /// Per-type fields left at 0.0 fall back to the generic ones; move types
+    /// without gravity get no fall limit.
+    pub fn for_move_type(&self, move_type: MoveType) -> MovementConfig {
+        let or = |value: f32, fallback: f32| if value > 0.0 { value } else { fallback };
+        match move_type {
+            MoveType::Swim => MovementConfig {
+                max_speed: or(self.swim_max_speed, self.max_speed),
+                accelerate: or(self.swim_accelerate, self.accelerate),
+                air_accelerate: or(self.swim_accelerate, self.air_accelerate),
+                air_strafe_accelerate: or(self.swim_accelerate, self.air_strafe_accelerate),
+                max_fall_speed: self.max_water_fall_speed,
+                ..*self
+            },
+            MoveType::Fly => MovementConfig {
+                max_speed: or(self.fly_max_speed, self.max_speed),
+                accelerate: self.fly_accelerate,
+                max_fall_speed: 0.0,
+                ..*self
+            },
+            MoveType::Noclip => MovementConfig { max_speed: self.noclip_speed, max_fall_speed: 0.0, ..*self },
+            MoveType::Spectator => MovementConfig { max_speed: self.spectator_speed, max_fall_speed: 0.0, ..*self },
+            MoveType::Walk | MoveType::Ladder | MoveType::Climb | MoveType::Glide => *self,
+        }
+    }
+
+    /// Convert a speed, acceleration or distance from config units to world units
+    pub fn to_world_units(&self, value: f32) -> f32 {
+        value * self.unit_scale
+    }
+
+    /// Convert a speed, acceleration or distance from world units to config units
+    pub fn to_config_units(&self, value: f32) -> f32 {
+        if self.unit_scale != 0.0 {
+            value / self.unit_scale
+        } else {
+            value
+        }
+    }
+
+    /// This config with every dimensional field converted to world units
+    ///
+    /// Dimensionless values (acceleration factors, friction, multipliers,
+    /// times and normal thresholds) are unchanged. The result has a unit
+    /// scale of 1.0, so converting twice is harmless.
+    pub fn in_world_units(&self) -> MovementConfig {
+        if self.unit_scale == 1.0 {
+            return *self;
+        }
+        let s = self.unit_scale;
+        MovementConfig {
+            max_speed: self.max_speed * s,
+            max_air_wish_speed: self.max_air_wish_speed * s,
+            stop_speed: self.stop_speed * s,
+            gravity: self.gravity * s,
+            apex_hang_speed: self.apex_hang_speed * s,
+            max_fall_speed: self.max_fall_speed * s,
+            max_water_fall_speed: self.max_water_fall_speed * s,
+            swim_max_speed: self.swim_max_speed * s,
+            fly_max_speed: self.fly_max_speed * s,
+            safe_fall_speed: self.safe_fall_speed * s,
+            fatal_fall_speed: self.fatal_fall_speed * s,
+            skid_min_speed: self.skid_min_speed * s,
+            land_slide_min_speed: self.land_slide_min_speed * s,
+            jump_speed: self.jump_speed * s,
+            footstep_min_speed: self.footstep_min_speed * s,
+            eye_height: self.eye_height * s,
+            duck_eye_height: self.duck_eye_height * s,
+            hull_height: self.hull_height * s,
+            duck_hull_height: self.duck_hull_height * s,
+            hull_radius: self.hull_radius * s,
+            duck_hull_radius: self.duck_hull_radius * s,
+            crouch_slide_min_speed: self.crouch_slide_min_speed * s,
+            crouch_slide_stuck_speed: self.crouch_slide_stuck_speed * s,
+            jetpack_thrust: self.jetpack_thrust * s,
+            jetpack_max_speed: self.jetpack_max_speed * s,
+            glide_max_fall_speed: self.glide_max_fall_speed * s,
+            water_jump_speed: self.water_jump_speed * s,
+            water_jump_push: self.water_jump_push * s,
+            water_jump_reach: self.water_jump_reach * s,
+            dodge_speed: self.dodge_speed * s,
+            dodge_up_speed: self.dodge_up_speed * s,
+            dodge_wall_reach: self.dodge_wall_reach * s,
+            wall_run_chain_bonus: self.wall_run_chain_bonus * s,
+            wall_run_chain_max_bonus: self.wall_run_chain_max_bonus * s,
+            climb_speed: self.climb_speed * s,
+            climb_reach: self.climb_reach * s,
+            air_dash_speed: self.air_dash_speed * s,
+            ground_pound_speed: self.ground_pound_speed * s,
+            sticky_reach: self.sticky_reach * s,
+            sticky_attraction: self.sticky_attraction * s,
+            climb_jump_off_speed: self.climb_jump_off_speed * s,
+            ground_snap_distance: self.ground_snap_distance * s,
+            step_height: self.step_height * s,
+            min_step_event_height: self.min_step_event_height * s,
+            overbounce_window: self.overbounce_window * s,
+            edgebug_tolerance: self.edgebug_tolerance * s,
+            surf_max_speed_gain: self.surf_max_speed_gain * s,
+            non_jump_velocity: self.non_jump_velocity * s,
+            noclip_speed: self.noclip_speed * s,
+            spectator_speed: self.spectator_speed * s,
+            roll_speed: self.roll_speed * s,
+            lean_distance: self.lean_distance * s,
+            // Quadratic drag is per unit of speed, so it shrinks as the units grow
+            air_drag: if self.air_drag_quadratic { self.to_config_units(self.air_drag) } else { self.air_drag },
+            unit_scale: 1.0,
+            ..*self
+        }
+    }
+
+    /// Parse a config from a flat JSON object
+    ///
+    /// Fields missing from the object keep their default value; unknown fields
+    /// are rejected so typos in tuning files don't go unnoticed.
+    pub fn from_json(text: &str) -> Result<MovementConfig, ConfigError> {
+        let mut config = MovementConfig::default();
+        for (key, value) in config_format::parse_json(text)? {
+            config.set(&key, value)?;
+        }
+        Ok(config)
+    }
+
+    /// Parse a config from TOML `key = value` lines, at the top level or under `[movement]`
+    ///
+    /// Fields missing from the file keep their default value.
+    pub fn from_toml(text: &str) -> Result<MovementConfig, ConfigError> {
+        let mut config = MovementConfig::default();
+        for (key, value) in config_format::parse_toml(text)? {
+            config.set(&key, value)?;
+        }
+        Ok(config)
+    }
+
+    /// List every field whose value differs in `other`
+    pub fn diff(&self, other: &MovementConfig) -> Vec<ConfigChange> {
+        Self::FIELD_NAMES
+            .iter()
+            .filter_map(|&field| {
+                let old = self.get(field)?;
+                let new = other.get(field)?;
+                (old != new).then_some(ConfigChange { field, old, new })
+            })
+            .collect()
+    }
+
+    /// Apply changes produced by `diff`
+    ///
+    /// All changes are validated first, so either every change is applied or none is.
+    pub fn apply_changes(&mut self, changes: &[ConfigChange]) -> Result<(), ConfigError> {
+        let mut updated = *self;
+        for change in changes {
+            updated.set(change.field, change.new.clone())?;
+        }
+        *self = updated;
+        Ok(())
+    }
+
+    /// Hot-reload from the JSON text of a tuning file
+    ///
+    /// `player_tick` reads the config every tick, so replacing the shared config
+    /// a server passes to its players takes effect for all of them on the next
+    /// tick. On a parse error the config is left untouched.
+    ///
+    /// # Returns
+    /// The list of fields that changed
+    pub fn reload_json(&mut self, text: &str) -> Result<Vec<ConfigChange>, ConfigError> {
+        let reloaded = MovementConfig::from_json(text)?;
+        let changes = self.diff(&reloaded);
+        *self = reloaded;
+        Ok(changes)
+    }
+
+    /// Hot-reload from the TOML text of a tuning file, see `reload_json`
+    pub fn reload_toml(&mut self, text: &str) -> Result<Vec<ConfigChange>, ConfigError> {
+        let reloaded = MovementConfig::from_toml(text)?;
+        let changes = self.diff(&reloaded);
+        *self = reloaded;
+        Ok(changes)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MovementConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> MovementConfig {
+        MovementConfig::default()
+    }
+
+    #[wasm_bindgen(js_name = toWorldUnits)]
+    pub fn to_world_units_wasm(&self, value: f32) -> f32 {
+        self.to_world_units(value)
+    }
+
+    #[wasm_bindgen(js_name = toConfigUnits)]
+    pub fn to_config_units_wasm(&self, value: f32) -> f32 {
+        self.to_config_units(value)
+    }
+
+    #[wasm_bindgen(js_name = forMoveType)]
+    pub fn for_move_type_wasm(&self, move_type: MoveType) -> MovementConfig {
+        self.for_move_type(move_type)
+    }
+
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json_wasm(text: &str) -> Result<MovementConfig, JsValue> {
+        MovementConfig::from_json(text).map_err(|e| AirStrafeError::from(e).into())
+    }
+
+    #[wasm_bindgen(js_name = fromToml)]
+    pub fn from_toml_wasm(text: &str) -> Result<MovementConfig, JsValue> {
+        MovementConfig::from_toml(text).map_err(|e| AirStrafeError::from(e).into())
+    }
+
+    #[wasm_bindgen(js_name = builder)]
+    pub fn builder_wasm() -> MovementConfigBuilder {
+        MovementConfig::builder()
+    }
+
+    #[wasm_bindgen(js_name = fromPreset)]
+    pub fn preset_wasm(preset: ConfigPreset) -> MovementConfig {
+        MovementConfig::preset(preset)
+    }
+
+    #[wasm_bindgen(js_name = jsonSchema)]
+    pub fn json_schema_wasm() -> String {
+        MovementConfig::json_schema()
+    }
+
+    /// Hot-reload from JSON, returning the names of the changed fields
+    #[wasm_bindgen(js_name = reloadJson)]
+    pub fn reload_json_wasm(&mut self, text: &str) -> Result<Vec<String>, JsValue> {
+        self.reload_json(text)
+            .map(|changes| changes.iter().map(|c| c.field.to_string()).collect())
+            .map_err(|e| AirStrafeError::from(e).into())
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MovementConfigBuilder {
+    #[wasm_bindgen(js_name = preset)]
+    pub fn preset_wasm(self, preset: ConfigPreset) -> MovementConfigBuilder {
+        self.preset(preset)
+    }
+
+    #[wasm_bindgen(js_name = setNumber)]
+    pub fn set_number_wasm(self, name: &str, value: f32) -> Result<MovementConfigBuilder, JsValue> {
+        self.set(name, ConfigValue::Number(value)).map_err(JsValue::from)
+    }
+
+    #[wasm_bindgen(js_name = setBool)]
+    pub fn set_bool_wasm(self, name: &str, value: bool) -> Result<MovementConfigBuilder, JsValue> {
+        self.set(name, ConfigValue::Bool(value)).map_err(JsValue::from)
+    }
+
+    #[wasm_bindgen(js_name = setText)]
+    pub fn set_text_wasm(self, name: &str, value: &str) -> Result<MovementConfigBuilder, JsValue> {
+        self.set(name, ConfigValue::Text(value.to_string())).map_err(JsValue::from)
+    }
+
+    #[wasm_bindgen(js_name = build)]
+    pub fn build_wasm(self) -> Result<MovementConfig, JsValue> {
+        self.build().map_err(JsValue::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `config` with the decisions fixed by this build's features, so it passes validation
+    fn for_this_build(config: MovementConfig) -> MovementConfig {
+        MovementConfig {
+            up_axis: FIXED_UP_AXIS.unwrap_or(config.up_axis),
+            legacy_gravity: FIXED_LEGACY_GRAVITY.unwrap_or(config.legacy_gravity),
+            ..config
+        }
+    }
+
+    #[test]
+    fn test_from_json_and_toml_match() {
+        let json = r#"{ "max_speed": 250, "air_accelerate": 100.0, "auto_bhop": true }"#;
+        let toml = "# tuning\n[movement]\nmax_speed = 250\nair_accelerate = 1_00.0 # comment\nauto_bhop = true\n";
+
+        let from_json = MovementConfig::from_json(json).unwrap();
+        let from_toml = MovementConfig::from_toml(toml).unwrap();
+        assert_eq!(from_json, from_toml);
+        assert_eq!(from_json.max_speed, 250.0);
+        assert!(from_json.auto_bhop);
+        assert_eq!(from_json.friction, MovementConfig::default().friction);
+    }
+
+    #[test]
+    fn test_unknown_and_mistyped_fields_are_rejected() {
+        assert!(matches!(
+            MovementConfig::from_json(r#"{"max_sped": 1}"#),
+            Err(ConfigError::UnknownField(_))
+        ));
+        assert!(matches!(
+            MovementConfig::from_toml("auto_bhop = 1"),
+            Err(ConfigError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            MovementConfig::from_json(r#"{"max_speed": }"#),
+            Err(ConfigError::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_landing_impact_thresholds() {
+        let config = MovementConfig::default();
+        assert_eq!(config.landing_impact(0.0), 0.0);
+        assert!((config.landing_impact(config.safe_fall_speed) - 0.5).abs() < 0.0001);
+        assert!((config.landing_impact(config.fatal_fall_speed) - 1.0).abs() < 0.0001);
+        assert_eq!(config.landing_impact(5000.0), 1.0);
+    }
+
+    #[test]
+    fn test_air_accelerate_per_input_direction() {
+        let config = MovementConfig {
+            air_accelerate: 1.0,
+            air_strafe_accelerate: 70.0,
+            air_backpedal_factor: 0.5,
+            ..MovementConfig::default()
+        };
+        assert_eq!(config.air_accelerate_for(1.0, 0.0), 1.0);
+        assert_eq!(config.air_accelerate_for(1.0, 1.0), 1.0);
+        assert_eq!(config.air_accelerate_for(0.0, -1.0), 70.0);
+        assert_eq!(config.air_accelerate_for(-1.0, 0.0), 0.5);
+        assert_eq!(config.effective_air_accelerate(0.0, 1.0, 30.0), 2100.0);
+    }
+
+    #[test]
+    fn test_builder_setters_override_preset() {
+        let config = MovementConfig::builder()
+            .air_accelerate(10.0)
+            .preset(ConfigPreset::Cpm)
+            .auto_bhop(true)
+            .build()
+            .unwrap();
+        assert_eq!(config.air_accelerate, 10.0);
+        assert_eq!(config.air_strafe_accelerate, 70.0);
+        assert!(config.auto_bhop);
+        assert_eq!(MovementConfig::builder().build().unwrap(), MovementConfig::default());
+    }
+
+    #[test]
+    fn test_builder_validates_ranges() {
+        assert!(matches!(
+            MovementConfig::builder().gravity(-800.0).build(),
+            Err(AirStrafeError::ConfigOutOfRange { .. })
+        ));
+        assert!(MovementConfig::builder().max_speed(f32::NAN).build().is_err());
+        assert!(MovementConfig::builder().min_ground_normal_up(1.5).build().is_err());
+        assert!(MovementConfig::builder().set("max_sped", ConfigValue::Number(1.0)).is_err());
+        // The defaults follow the decisions fixed by this build's features
+        assert!(MovementConfig::default().validate().is_ok());
+        assert!(MovementConfig::builder().build().is_ok());
+        for preset in [ConfigPreset::Source, ConfigPreset::Csgo, ConfigPreset::Quake, ConfigPreset::Cpm] {
+            assert!(for_this_build(MovementConfig::preset(preset)).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_json_schema_lists_every_field() {
+        let schema: serde_json::Value = serde_json::from_str(&MovementConfig::json_schema()).unwrap();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema.get("required").is_none());
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(properties.len(), MovementConfig::FIELD_NAMES.len());
+        for field in MovementConfig::FIELD_NAMES {
+            assert!(properties[*field]["description"].is_string(), "{field} missing from schema");
+            assert!(properties[*field].get("default").is_some(), "{field} has no default");
+        }
+        let max_speed = &properties["max_speed"];
+        assert_eq!(max_speed["description"], "Ground wish speed at full input");
+        assert_eq!(max_speed["type"], "number");
+        assert_eq!((&max_speed["minimum"], &max_speed["default"]), (&0.into(), &320.into()));
+        // Enum fields list their values, lowercase like in tuning files
+        let up_axis = &properties["up_axis"];
+        let values: Vec<&serde_json::Value> = match up_axis.get("enum") {
+            Some(values) => values.as_array().unwrap().iter().collect(),
+            None => up_axis["oneOf"].as_array().unwrap().iter().map(|value| &value["const"]).collect(),
+        };
+        assert_eq!(values, [&serde_json::Value::from("y"), &"z".into()]);
+        let default_axis = if MovementConfig::default().up_axis == UpAxis::Z { "z" } else { "y" };
+        assert_eq!(up_axis["default"], default_axis);
+        // Bounds come from each field, so signed fields have no minimum
+        let normal = &properties["min_ground_normal_up"];
+        assert_eq!((&normal["minimum"], &normal["maximum"], &normal["default"]), (&0.into(), &1.into(), &0.7.into()));
+        assert_eq!(properties["unit_scale"]["exclusiveMinimum"], 0);
+        assert!(properties["unit_scale"].get("minimum").is_none());
+        assert!(properties["lean_angle"].get("minimum").is_none());
+        assert!(for_this_build(MovementConfig { lean_angle: -0.2, ..MovementConfig::default() }).validate().is_ok());
+    }
+
+    #[test]
+    fn test_up_axis_field() {
+        let config = MovementConfig::from_json(r#"{"up_axis": "Z"}"#).unwrap();
+        assert_eq!(config.up_axis, UpAxis::Z);
+        assert_eq!(MovementConfig::from_toml("up_axis = \"y\"").unwrap().up_axis, UpAxis::Y);
+        assert!(MovementConfig::from_json(r#"{"up_axis": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn test_unit_scale_conversion() {
+        let config = MovementConfig { unit_scale: HAMMER_UNITS_TO_METERS, ..MovementConfig::default() };
+        let world = config.in_world_units();
+
+        assert!((world.max_speed - 8.128).abs() < 0.0001);
+        assert!((world.gravity - 20.32).abs() < 0.0001);
+        assert_eq!(world.air_accelerate, config.air_accelerate);
+        assert_eq!(world.friction, config.friction);
+        assert_eq!(world.in_world_units(), world);
+        assert!((config.to_config_units(config.to_world_units(250.0)) - 250.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_limits_resolve_per_move_type() {
+        let config = MovementConfig { swim_max_speed: 20.0, swim_accelerate: 4.0, fly_max_speed: 600.0, ..MovementConfig::default() };
+        let swim = config.for_move_type(MoveType::Swim);
+        assert_eq!((swim.max_speed, swim.air_accelerate, swim.max_fall_speed), (20.0, 4.0, 300.0));
+        let fly = config.for_move_type(MoveType::Fly);
+        assert_eq!((fly.max_speed, fly.accelerate, fly.max_fall_speed), (600.0, config.fly_accelerate, 0.0));
+        assert_eq!(config.for_move_type(MoveType::Noclip).max_speed, config.noclip_speed);
+        assert_eq!(config.for_move_type(MoveType::Walk), config);
+
+        // Swimming players are held to the swim speed
+        use crate::{player_tick, PlayerInput, PlayerState};
+        let mut state = PlayerState { move_type: MoveType::Swim, water_level: 3, ..PlayerState::default() };
+        for _ in 0..200 {
+            player_tick(&mut state, &PlayerInput { forward_move: 1.0, ..PlayerInput::new() }, &config, 1.0 / 64.0, None);
+        }
+        assert!((config.up_axis.horizontal(&state.velocity).magnitude() - 20.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_hot_reload_reports_changes() {
+        let mut config = MovementConfig::default();
+        let changes = config.reload_json(r#"{"gravity": 600, "friction": 4}"#).unwrap();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "gravity");
+        assert_eq!(changes[0].new, ConfigValue::Number(600.0));
+        assert_eq!(config.gravity, 600.0);
+
+        // A broken file leaves the running config alone
+        assert!(config.reload_json("{ gravity: 1 }").is_err());
+        assert_eq!(config.gravity, 600.0);
+
+        let mut other = MovementConfig::default();
+        other.apply_changes(&changes).unwrap();
+        assert_eq!(other, config);
+    }
+}
@@ -0,0 +1,761 @@
+//! Flat JSON/TOML config file support for `MovementConfig`
+//!
+//! Tuning files are flat `key: value` maps of numbers, booleans and short
+//! strings, so a small purpose-built reader is used instead of pulling a
+//! serialization framework into the WASM bundle. The JSON reader also handles
+//! nested documents for scenario files.
+
+use std::fmt;
+
+use crate::air_dash::{AirDashDirection, AirDashMomentum};
+use crate::delta_time::DeltaTimePolicy;
+use crate::dodge::DodgeChain;
+use crate::UpAxis;
+
+/// A single config field value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Number(f32),
+    Bool(bool),
+    Text(String),
+}
+
+impl From<f32> for ConfigValue {
+    fn from(value: f32) -> Self {
+        ConfigValue::Number(value)
+    }
+}
+
+impl From<bool> for ConfigValue {
+    fn from(value: bool) -> Self {
+        ConfigValue::Bool(value)
+    }
+}
+
+impl From<u32> for ConfigValue {
+    fn from(value: u32) -> Self {
+        ConfigValue::Number(value as f32)
+    }
+}
+
+impl From<UpAxis> for ConfigValue {
+    fn from(value: UpAxis) -> Self {
+        ConfigValue::Text(match value {
+            UpAxis::Y => "y".to_string(),
+            UpAxis::Z => "z".to_string(),
+        })
+    }
+}
+
+impl From<DodgeChain> for ConfigValue {
+    fn from(value: DodgeChain) -> Self {
+        ConfigValue::Text(
+            match value {
+                DodgeChain::None => "none",
+                DodgeChain::DodgeJump => "dodge_jump",
+                DodgeChain::Unlimited => "unlimited",
+            }
+            .to_string(),
+        )
+    }
+}
+
+impl From<AirDashDirection> for ConfigValue {
+    fn from(value: AirDashDirection) -> Self {
+        ConfigValue::Text(
+            match value {
+                AirDashDirection::Input => "input",
+                AirDashDirection::Aim => "aim",
+            }
+            .to_string(),
+        )
+    }
+}
+
+impl From<AirDashMomentum> for ConfigValue {
+    fn from(value: AirDashMomentum) -> Self {
+        ConfigValue::Text(
+            match value {
+                AirDashMomentum::Replace => "replace",
+                AirDashMomentum::Add => "add",
+                AirDashMomentum::Clamp => "clamp",
+            }
+            .to_string(),
+        )
+    }
+}
+
+impl From<DeltaTimePolicy> for ConfigValue {
+    fn from(value: DeltaTimePolicy) -> Self {
+        ConfigValue::Text(
+            match value {
+                DeltaTimePolicy::Clamp => "clamp",
+                DeltaTimePolicy::Drop => "drop",
+                DeltaTimePolicy::SubStep => "sub_step",
+            }
+            .to_string(),
+        )
+    }
+}
+
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigValue::Number(n) => write!(f, "{n}"),
+            ConfigValue::Bool(b) => write!(f, "{b}"),
+            ConfigValue::Text(t) => write!(f, "\"{t}\""),
+        }
+    }
+}
+
+/// Conversion from a `ConfigValue` into a concrete field type
+pub trait FieldValue: Sized {
+    /// JSON Schema keywords describing the field type
+    const JSON_SCHEMA: &'static str;
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError>;
+}
+
+impl FieldValue for f32 {
+    const JSON_SCHEMA: &'static str = "\"type\": \"number\"";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Number(n) => Ok(n),
+            _ => Err(ConfigError::TypeMismatch { field: field.to_string(), expected: "number" }),
+        }
+    }
+}
+
+impl FieldValue for u32 {
+    const JSON_SCHEMA: &'static str = "\"type\": \"integer\", \"minimum\": 0";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Number(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as u32),
+            _ => Err(ConfigError::TypeMismatch { field: field.to_string(), expected: "non-negative integer" }),
+        }
+    }
+}
+
+impl FieldValue for bool {
+    const JSON_SCHEMA: &'static str = "\"type\": \"boolean\"";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Bool(b) => Ok(b),
+            _ => Err(ConfigError::TypeMismatch { field: field.to_string(), expected: "boolean" }),
+        }
+    }
+}
+
+impl FieldValue for UpAxis {
+    const JSON_SCHEMA: &'static str = "\"type\": \"string\", \"enum\": [\"y\", \"z\"]";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("y") => Ok(UpAxis::Y),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("z") => Ok(UpAxis::Z),
+            _ => Err(ConfigError::TypeMismatch { field: field.to_string(), expected: "\"y\" or \"z\"" }),
+        }
+    }
+}
+
+impl FieldValue for DodgeChain {
+    const JSON_SCHEMA: &'static str = "\"type\": \"string\", \"enum\": [\"none\", \"dodge_jump\", \"unlimited\"]";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("none") => Ok(DodgeChain::None),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("dodge_jump") => Ok(DodgeChain::DodgeJump),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("unlimited") => Ok(DodgeChain::Unlimited),
+            _ => Err(ConfigError::TypeMismatch {
+                field: field.to_string(),
+                expected: "\"none\", \"dodge_jump\" or \"unlimited\"",
+            }),
+        }
+    }
+}
+
+impl FieldValue for AirDashDirection {
+    const JSON_SCHEMA: &'static str = "\"type\": \"string\", \"enum\": [\"input\", \"aim\"]";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("input") => Ok(AirDashDirection::Input),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("aim") => Ok(AirDashDirection::Aim),
+            _ => Err(ConfigError::TypeMismatch { field: field.to_string(), expected: "\"input\" or \"aim\"" }),
+        }
+    }
+}
+
+impl FieldValue for AirDashMomentum {
+    const JSON_SCHEMA: &'static str = "\"type\": \"string\", \"enum\": [\"replace\", \"add\", \"clamp\"]";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("replace") => Ok(AirDashMomentum::Replace),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("add") => Ok(AirDashMomentum::Add),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("clamp") => Ok(AirDashMomentum::Clamp),
+            _ => Err(ConfigError::TypeMismatch {
+                field: field.to_string(),
+                expected: "\"replace\", \"add\" or \"clamp\"",
+            }),
+        }
+    }
+}
+
+impl FieldValue for DeltaTimePolicy {
+    const JSON_SCHEMA: &'static str = "\"type\": \"string\", \"enum\": [\"clamp\", \"drop\", \"sub_step\"]";
+
+    fn from_config_value(field: &str, value: ConfigValue) -> Result<Self, ConfigError> {
+        match value {
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("clamp") => Ok(DeltaTimePolicy::Clamp),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("drop") => Ok(DeltaTimePolicy::Drop),
+            ConfigValue::Text(t) if t.eq_ignore_ascii_case("sub_step") => Ok(DeltaTimePolicy::SubStep),
+            _ => Err(ConfigError::TypeMismatch {
+                field: field.to_string(),
+                expected: "\"clamp\", \"drop\" or \"sub_step\"",
+            }),
+        }
+    }
+}
+
+/// A changed field between two configs, produced by `MovementConfig::diff`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    pub field: &'static str,
+    pub old: ConfigValue,
+    pub new: ConfigValue,
+}
+
+/// Errors from reading or writing config fields
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The text is not valid for the format
+    Syntax { line: usize, message: String },
+    /// The field does not exist on `MovementConfig`
+    UnknownField(String),
+    /// The field exists but the value has the wrong type
+    TypeMismatch { field: String, expected: &'static str },
+    /// A config patch was made against another version than the receiver holds, see `config_sync`
+    StalePatch { version: u32, base_version: u32 },
+    /// Config patch bytes are truncated or were encoded by an incompatible build
+    MalformedPatch(String),
+    /// Replay bytes are truncated or in an unknown format, see `replay`
+    MalformedReplay(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Syntax { line, message } => write!(f, "syntax error on line {line}: {message}"),
+            ConfigError::UnknownField(field) => write!(f, "unknown config field `{field}`"),
+            ConfigError::TypeMismatch { field, expected } => write!(f, "config field `{field}` expects a {expected}"),
+            ConfigError::StalePatch { version, base_version } => {
+                write!(f, "config patch for version {base_version} does not apply to version {version}")
+            }
+            ConfigError::MalformedPatch(message) => write!(f, "malformed config patch: {message}"),
+            ConfigError::MalformedReplay(message) => write!(f, "malformed replay: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Parse a flat JSON object of numbers, booleans and strings
+pub(crate) fn parse_json(text: &str) -> Result<Vec<(String, ConfigValue)>, ConfigError> {
+    match parse_json_value(text)? {
+        JsonValue::Object(entries) => entries
+            .into_iter()
+            .map(|(key, value)| {
+                let value = value.into_config_value().ok_or_else(|| ConfigError::TypeMismatch {
+                    field: key.clone(),
+                    expected: "number, boolean or string",
+                })?;
+                Ok((key, value))
+            })
+            .collect(),
+        _ => Err(ConfigError::Syntax { line: 1, message: "expected an object".to_string() }),
+    }
+}
+
+/// A parsed JSON document
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f32),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// The value as a config field value, if it is a scalar
+    pub(crate) fn into_config_value(self) -> Option<ConfigValue> {
+        match self {
+            JsonValue::Bool(b) => Some(ConfigValue::Bool(b)),
+            JsonValue::Number(n) => Some(ConfigValue::Number(n)),
+            JsonValue::String(t) => Some(ConfigValue::Text(t)),
+            _ => None,
+        }
+    }
+
+    /// Look up a key of an object
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Parse any JSON document
+pub(crate) fn parse_json_value(text: &str) -> Result<JsonValue, ConfigError> {
+    let mut parser = JsonParser { text, bytes: text.as_bytes(), pos: 0, line: 1 };
+    let value = parser.value()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected text after the document"));
+    }
+    Ok(value)
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, message: &str) -> ConfigError {
+        ConfigError::Syntax { line: self.line, message: message.to_string() }
+    }
+
+    /// Next non-whitespace byte without consuming it
+    fn peek(&mut self) -> Option<u8> {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            match b {
+                b'\n' => self.line += 1,
+                b' ' | b'\t' | b'\r' => {}
+                _ => return Some(b),
+            }
+            self.pos += 1;
+        }
+        None
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ConfigError> {
+        match self.next() {
+            Some(b) if b == expected => Ok(()),
+            _ => Err(self.error(&format!("expected `{}`", expected as char))),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ConfigError> {
+        self.expect(b'"')?;
+        let mut string = String::new();
+        let mut start = self.pos;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            match b {
+                b'"' => {
+                    string.push_str(&self.text[start..self.pos]);
+                    self.pos += 1;
+                    return Ok(string);
+                }
+                b'\\' => {
+                    string.push_str(&self.text[start..self.pos]);
+                    let (c, length) = unescape(&self.text[self.pos + 1..], false).map_err(|m| self.error(m))?;
+                    string.push(c);
+                    self.pos += 1 + length;
+                    start = self.pos;
+                }
+                0..=0x1f => return Err(self.error("control characters in strings must be escaped")),
+                _ => self.pos += 1,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn value(&mut self) -> Result<JsonValue, ConfigError> {
+        match self.peek() {
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            _ => self.scalar(),
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, ConfigError> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            entries.push((key, self.value()?));
+            match self.next() {
+                Some(b',') => continue,
+                Some(b'}') => return Ok(JsonValue::Object(entries)),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, ConfigError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            match self.next() {
+                Some(b',') => continue,
+                Some(b']') => return Ok(JsonValue::Array(items)),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn scalar(&mut self) -> Result<JsonValue, ConfigError> {
+        let start = self.pos;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if matches!(b, b',' | b'}' | b']' | b' ' | b'\t' | b'\r' | b'\n') {
+                break;
+            }
+            self.pos += 1;
+        }
+        let token = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or("");
+        if token == "null" {
+            return Ok(JsonValue::Null);
+        }
+        match parse_scalar(token) {
+            Some(ConfigValue::Bool(b)) => Ok(JsonValue::Bool(b)),
+            Some(ConfigValue::Number(n)) => Ok(JsonValue::Number(n)),
+            _ => Err(self.error(&format!("expected a value, found `{token}`"))),
+        }
+    }
+}
+
+/// Decode the escape after a backslash into its character and the number of bytes it spans
+///
+/// JSON and TOML basic strings share most escapes; JSON adds `\/` and writes
+/// characters outside the BMP as surrogate pairs, TOML adds `\UXXXXXXXX`.
+fn unescape(text: &str, toml: bool) -> Result<(char, usize), &'static str> {
+    let hex = |digits: &str| {
+        digits.bytes().all(|b| b.is_ascii_hexdigit()).then(|| u32::from_str_radix(digits, 16).ok()).flatten()
+    };
+    let c = match text.as_bytes().first() {
+        Some(b'"') => '"',
+        Some(b'\\') => '\\',
+        Some(b'/') if !toml => '/',
+        Some(b'b') => '\u{8}',
+        Some(b'f') => '\u{c}',
+        Some(b'n') => '\n',
+        Some(b'r') => '\r',
+        Some(b't') => '\t',
+        Some(b'u') => {
+            let code = text.get(1..5).and_then(hex).ok_or("expected four hex digits after `\\u`")?;
+            if !toml && (0xd800..0xdc00).contains(&code) {
+                // A high surrogate has to be followed by the low half of the pair
+                let low = text
+                    .get(5..11)
+                    .and_then(|pair| pair.strip_prefix("\\u"))
+                    .and_then(hex)
+                    .filter(|low| (0xdc00..0xe000).contains(low))
+                    .ok_or("unpaired surrogate in `\\u` escape")?;
+                let c = char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00));
+                return c.map(|c| (c, 11)).ok_or("invalid `\\u` escape");
+            }
+            return char::from_u32(code).map(|c| (c, 5)).ok_or("invalid `\\u` escape");
+        }
+        Some(b'U') if toml => {
+            let code = text.get(1..9).and_then(hex).ok_or("expected eight hex digits after `\\U`")?;
+            return char::from_u32(code).map(|c| (c, 9)).ok_or("invalid `\\U` escape");
+        }
+        _ => return Err("unknown escape sequence"),
+    };
+    Ok((c, 1))
+}
+
+/// Quote and escape a string for JSON output
+pub(crate) fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Parse TOML `key = value` lines, at the top level or under a `[movement]` table
+///
+/// Only what a flat tuning file needs is read: bare and quoted keys, numbers,
+/// booleans and single-line strings. Other tables, dotted keys, arrays,
+/// inline tables and multi-line strings are rejected rather than misread.
+pub(crate) fn parse_toml(text: &str) -> Result<Vec<(String, ConfigValue)>, ConfigError> {
+    let mut entries: Vec<(String, ConfigValue)> = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let syntax = |message: &str| ConfigError::Syntax { line: index + 1, message: message.to_string() };
+        if is_blank(raw) {
+            continue;
+        }
+        let line = raw.trim_start();
+        if let Some(header) = line.strip_prefix('[') {
+            if header.starts_with('[') {
+                return Err(syntax("arrays of tables are not supported"));
+            }
+            let (name, rest) = header.split_once(']').ok_or_else(|| syntax("expected `]`"))?;
+            if name.trim() != "movement" {
+                return Err(syntax(&format!("unsupported table `[{name}]`, put the fields under `[movement]`")));
+            }
+            if !is_blank(rest) {
+                return Err(syntax("expected the end of the line after the table header"));
+            }
+            continue;
+        }
+        let (key, rest) = toml_key(line).map_err(syntax)?;
+        let rest = rest.trim_start().strip_prefix('=').ok_or_else(|| syntax("expected `key = value`"))?;
+        let (value, rest) = toml_value(rest.trim_start()).map_err(|message| syntax(&message))?;
+        if !is_blank(rest) {
+            return Err(syntax("expected the end of the line after the value"));
+        }
+        if entries.iter().any(|(k, _)| *k == key) {
+            return Err(syntax(&format!("`{key}` is set twice")));
+        }
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+/// Whether the rest of a TOML line is empty or a comment
+fn is_blank(rest: &str) -> bool {
+    let rest = rest.trim();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+/// Split a bare or quoted key off the start of a line
+fn toml_key(line: &str) -> Result<(String, &str), &'static str> {
+    if line.starts_with(['"', '\'']) {
+        return toml_string(line);
+    }
+    let end = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-')).unwrap_or(line.len());
+    if end == 0 {
+        return Err("expected a key");
+    }
+    Ok((line[..end].to_string(), &line[end..]))
+}
+
+/// Split a value off the start of the text after `=`
+fn toml_value(text: &str) -> Result<(ConfigValue, &str), String> {
+    if text.starts_with("\"\"\"") || text.starts_with("'''") {
+        return Err("multi-line strings are not supported".to_string());
+    }
+    if text.starts_with(['"', '\'']) {
+        let (string, rest) = toml_string(text)?;
+        return Ok((ConfigValue::Text(string), rest));
+    }
+    if text.starts_with(['[', '{']) {
+        return Err("arrays and inline tables are not supported".to_string());
+    }
+    let end = text.find(|c: char| c.is_whitespace() || c == '#').unwrap_or(text.len());
+    let token = &text[..end];
+    let value = match token {
+        "true" => ConfigValue::Bool(true),
+        "false" => ConfigValue::Bool(false),
+        _ => toml_number(token)
+            .map(ConfigValue::Number)
+            .ok_or_else(|| format!("expected a number, boolean or string, found `{token}`"))?,
+    };
+    Ok((value, &text[end..]))
+}
+
+/// Split a basic (`"..."`, with escapes) or literal (`'...'`) string off the start of `text`
+fn toml_string(text: &str) -> Result<(String, &str), &'static str> {
+    let quote = text.as_bytes()[0];
+    let mut string = String::new();
+    let mut start = 1;
+    let mut pos = 1;
+    while let Some(&b) = text.as_bytes().get(pos) {
+        match b {
+            _ if b == quote => {
+                string.push_str(&text[start..pos]);
+                return Ok((string, &text[pos + 1..]));
+            }
+            b'\\' if quote == b'"' => {
+                string.push_str(&text[start..pos]);
+                let (c, length) = unescape(&text[pos + 1..], true)?;
+                string.push(c);
+                pos += 1 + length;
+                start = pos;
+            }
+            0..=0x08 | 0x0a..=0x1f | 0x7f => return Err("control characters in strings must be escaped"),
+            _ => pos += 1,
+        }
+    }
+    Err("unterminated string")
+}
+
+/// A number in TOML syntax: a JSON number that may start with `+` and have `_` between digits
+fn toml_number(token: &str) -> Option<f32> {
+    let unsigned = token.strip_prefix('+').unwrap_or(token);
+    if unsigned.len() != token.len() && unsigned.starts_with('-') {
+        return None;
+    }
+    let bytes = unsigned.as_bytes();
+    let is_digit = |i: Option<usize>| i.and_then(|i| bytes.get(i)).is_some_and(u8::is_ascii_digit);
+    let separated =
+        (0..bytes.len()).filter(|&i| bytes[i] == b'_').all(|i| is_digit(i.checked_sub(1)) && is_digit(Some(i + 1)));
+    if !separated {
+        return None;
+    }
+    json_number(&unsigned.replace('_', ""))
+}
+
+/// A number in JSON syntax: no `+` or leading zeros, digits on both sides of a `.`
+fn json_number(token: &str) -> Option<f32> {
+    let bytes = token.as_bytes();
+    let digits_from =
+        |pos: usize| pos + bytes[pos.min(bytes.len())..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let integer_start = usize::from(bytes.first() == Some(&b'-'));
+    let mut pos = digits_from(integer_start);
+    let integer = &bytes[integer_start..pos];
+    if integer.is_empty() || (integer.len() > 1 && integer[0] == b'0') {
+        return None;
+    }
+    if bytes.get(pos) == Some(&b'.') {
+        let end = digits_from(pos + 1);
+        if end == pos + 1 {
+            return None;
+        }
+        pos = end;
+    }
+    if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(pos + 1), Some(b'+' | b'-')));
+        let end = digits_from(pos + 1 + sign);
+        if end == pos + 1 + sign {
+            return None;
+        }
+        pos = end;
+    }
+    if pos != bytes.len() {
+        return None;
+    }
+    token.parse::<f32>().ok().filter(|n| n.is_finite())
+}
+
+fn parse_scalar(token: &str) -> Option<ConfigValue> {
+    match token {
+        "true" => Some(ConfigValue::Bool(true)),
+        "false" => Some(ConfigValue::Bool(false)),
+        _ => json_number(token).map(ConfigValue::Number),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_reports_line() {
+        assert_eq!(parse_json("{}").unwrap(), vec![]);
+        let err = parse_json("{\n  \"a\": 1,\n  \"b\": nope\n}").unwrap_err();
+        assert!(matches!(err, ConfigError::Syntax { line: 3, .. }));
+    }
+
+    #[test]
+    fn test_parse_nested_json() {
+        let value = parse_json_value(r#"{"a": [1, true, null], "b": {"c": "d"}}"#).unwrap();
+        assert_eq!(
+            value.get("a"),
+            Some(&JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Bool(true), JsonValue::Null]))
+        );
+        assert_eq!(value.get("b").and_then(|b| b.get("c")), Some(&JsonValue::String("d".to_string())));
+        assert!(matches!(parse_json(r#"{"a": [1]}"#), Err(ConfigError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        let value = parse_json_value(r#""a\"b\\c\/d\n\t\u00e9\ud83d\ude00""#).unwrap();
+        let text = "a\"b\\c/d\n\t\u{e9}\u{1f600}";
+        assert_eq!(value, JsonValue::String(text.to_string()));
+        assert_eq!(parse_json_value(&json_string(text)).unwrap(), value);
+        assert_eq!(parse_json_value(&json_string("\u{1}\r")).unwrap(), JsonValue::String("\u{1}\r".to_string()));
+        for bad in [r#""\x""#, r#""\u12""#, r#""\ud83d""#, "\"a\nb\""] {
+            assert!(matches!(parse_json_value(bad), Err(ConfigError::Syntax { .. })), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_json_numbers_are_strict() {
+        for good in ["0", "-0.5", "250", "1.5e3", "2E-2"] {
+            assert_eq!(parse_json_value(good).unwrap(), JsonValue::Number(good.parse().unwrap()), "{good}");
+        }
+        for bad in ["+5", ".5", "5.", "05", "-", "1e", "1_0", "inf", "NaN", "1e39", "0x10"] {
+            assert!(matches!(parse_json_value(bad), Err(ConfigError::Syntax { .. })), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_toml_strings_and_comments() {
+        let toml = "a = \"x # not a comment\" # comment\n\"b\" = 'C:\\path#1'\n\
+                    c = \"tab\\there\\u00e9\"\nd = +1_000.5\n";
+        assert_eq!(
+            parse_toml(toml).unwrap(),
+            vec![
+                ("a".to_string(), ConfigValue::Text("x # not a comment".to_string())),
+                ("b".to_string(), ConfigValue::Text("C:\\path#1".to_string())),
+                ("c".to_string(), ConfigValue::Text("tab\there\u{e9}".to_string())),
+                ("d".to_string(), ConfigValue::Number(1000.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_toml_is_rejected() {
+        let entries = parse_toml("[ movement ] # tuning\na = 1").unwrap();
+        assert_eq!(entries, vec![("a".to_string(), ConfigValue::Number(1.0))]);
+        for (bad, line) in [
+            ("a = 1\n[physics]\nb = 2", 2),
+            ("[movement.air]", 1),
+            ("[[movement]]", 1),
+            ("a.b = 1", 1),
+            ("a = [1, 2]", 1),
+            ("a = { b = 1 }", 1),
+            ("a = \"\"\"text\"\"\"", 1),
+            ("a = \"open", 1),
+            ("a = \"\\q\"", 1),
+            ("a = 1 2", 1),
+            ("a = 1_", 1),
+            ("a = 1__0", 1),
+            ("a = _1", 1),
+            ("a = 01", 1),
+            ("a = .5", 1),
+            ("a = +-1", 1),
+            ("a = 1\na = 2", 2),
+        ] {
+            assert!(matches!(parse_toml(bad), Err(ConfigError::Syntax { line: l, .. }) if l == line), "{bad}");
+        }
+    }
+}
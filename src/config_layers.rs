@@ -0,0 +1,213 @@
+//! Layered per-player config overrides
+//!
+//! A player's effective config is the shared base `MovementConfig` with that
+//! player's override layers applied on top, in the order they were pushed.
+//! Layers can expire on their own, so temporary modifiers such as a speed
+//! powerup or a handicap never need to mutate the shared config.
+
+use crate::config_format::{ConfigError, ConfigValue};
+use crate::MovementConfig;
+
+/// How a layer changes a single field
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigOp {
+    /// Replace the value
+    Set(ConfigValue),
+    /// Multiply a numeric value
+    Multiply(f32),
+    /// Add to a numeric value
+    Add(f32),
+}
+
+/// A named set of field overrides, optionally expiring after some time
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigLayer {
+    name: String,
+    ops: Vec<(&'static str, ConfigOp)>,
+    remaining: Option<f32>,
+}
+
+impl ConfigLayer {
+    /// Create an empty, permanent layer
+    pub fn new(name: &str) -> ConfigLayer {
+        ConfigLayer {
+            name: name.to_string(),
+            ops: Vec::new(),
+            remaining: None,
+        }
+    }
+
+    /// Make the layer expire after `seconds` of ticking
+    pub fn lasting(mut self, seconds: f32) -> ConfigLayer {
+        self.remaining = Some(seconds);
+        self
+    }
+
+    /// Override a field with a fixed value
+    pub fn set(self, field: &str, value: impl Into<ConfigValue>) -> Result<ConfigLayer, ConfigError> {
+        self.push(field, ConfigOp::Set(value.into()))
+    }
+
+    /// Multiply a numeric field
+    pub fn multiply(self, field: &str, factor: f32) -> Result<ConfigLayer, ConfigError> {
+        self.push(field, ConfigOp::Multiply(factor))
+    }
+
+    /// Add to a numeric field
+    pub fn add(self, field: &str, amount: f32) -> Result<ConfigLayer, ConfigError> {
+        self.push(field, ConfigOp::Add(amount))
+    }
+
+    /// The layer's name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Seconds until the layer expires, `None` for permanent layers
+    pub fn remaining(&self) -> Option<f32> {
+        self.remaining
+    }
+
+    fn push(mut self, field: &str, op: ConfigOp) -> Result<ConfigLayer, ConfigError> {
+        let field = MovementConfig::FIELD_NAMES
+            .iter()
+            .copied()
+            .find(|name| *name == field)
+            .ok_or_else(|| ConfigError::UnknownField(field.to_string()))?;
+        // Validate the operation against the default config's field type up front
+        apply_op(&mut MovementConfig::default(), field, &op)?;
+        self.ops.push((field, op));
+        Ok(self)
+    }
+
+    fn apply(&self, config: &mut MovementConfig) {
+        for (field, op) in &self.ops {
+            // Ops were validated in `push`, so this can not fail
+            let _ = apply_op(config, field, op);
+        }
+    }
+}
+
+fn apply_op(config: &mut MovementConfig, field: &str, op: &ConfigOp) -> Result<(), ConfigError> {
+    let value = match (op, config.get(field)) {
+        (ConfigOp::Set(value), _) => value.clone(),
+        (ConfigOp::Multiply(factor), Some(ConfigValue::Number(n))) => ConfigValue::Number(n * factor),
+        (ConfigOp::Add(amount), Some(ConfigValue::Number(n))) => ConfigValue::Number(n + amount),
+        _ => {
+            return Err(ConfigError::TypeMismatch {
+                field: field.to_string(),
+                expected: "number",
+            })
+        }
+    };
+    config.set(field, value)
+}
+
+/// The override layers of a single player
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConfigLayers {
+    layers: Vec<ConfigLayer>,
+}
+
+impl ConfigLayers {
+    /// Create an empty layer stack
+    pub fn new() -> ConfigLayers {
+        ConfigLayers::default()
+    }
+
+    /// Add a layer on top, replacing any existing layer with the same name
+    pub fn push(&mut self, layer: ConfigLayer) {
+        self.remove(&layer.name);
+        self.layers.push(layer);
+    }
+
+    /// Remove a layer by name, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.layers.len();
+        self.layers.retain(|layer| layer.name != name);
+        self.layers.len() != before
+    }
+
+    /// Whether a layer with this name is active
+    pub fn contains(&self, name: &str) -> bool {
+        self.layers.iter().any(|layer| layer.name == name)
+    }
+
+    /// Number of active layers
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Whether no layer is active
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Count down timed layers and drop the expired ones
+    pub fn advance(&mut self, delta_time: f32) {
+        for layer in &mut self.layers {
+            if let Some(remaining) = layer.remaining.as_mut() {
+                *remaining -= delta_time;
+            }
+        }
+        self.layers.retain(|layer| layer.remaining.is_none_or(|r| r > 0.0));
+    }
+
+    /// The effective config for this tick: `base` with all layers applied in order
+    pub fn resolve(&self, base: &MovementConfig) -> MovementConfig {
+        let mut config = *base;
+        for layer in &self.layers {
+            layer.apply(&mut config);
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_layers_apply_in_order() {
+        let base = y_up_config();
+        let mut layers = ConfigLayers::new();
+        layers.push(ConfigLayer::new("handicap").set("max_speed", 200.0).unwrap());
+        layers.push(
+            ConfigLayer::new("powerup")
+                .multiply("max_speed", 1.5)
+                .unwrap()
+                .add("jump_speed", 50.0)
+                .unwrap()
+                .set("auto_bhop", true)
+                .unwrap(),
+        );
+
+        let resolved = layers.resolve(&base);
+        assert_eq!(resolved.max_speed, 300.0);
+        assert_eq!(resolved.jump_speed, base.jump_speed + 50.0);
+        assert!(resolved.auto_bhop);
+        // The base config is never touched
+        assert_eq!(base, y_up_config());
+    }
+
+    #[test]
+    fn test_timed_layers_expire() {
+        let mut layers = ConfigLayers::new();
+        layers.push(ConfigLayer::new("boost").lasting(1.0).multiply("max_speed", 2.0).unwrap());
+        layers.push(ConfigLayer::new("slow").multiply("max_speed", 0.5).unwrap());
+
+        layers.advance(0.5);
+        assert_eq!(layers.resolve(&y_up_config()).max_speed, 320.0);
+        layers.advance(0.5);
+        assert!(!layers.contains("boost"));
+        assert_eq!(layers.resolve(&y_up_config()).max_speed, 160.0);
+    }
+
+    #[test]
+    fn test_invalid_layers_are_rejected() {
+        assert!(ConfigLayer::new("x").multiply("max_sped", 2.0).is_err());
+        assert!(ConfigLayer::new("x").multiply("auto_bhop", 2.0).is_err());
+        assert!(ConfigLayer::new("x").set("auto_bhop", 1.0).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! Config patches for replicating live tuning changes
+//!
+//! A server that retunes its `MovementConfig` while players are connected
+//! has to send the change to every client so prediction keeps matching
+//! ("cvar replication"). Sending the whole config each time is wasteful;
+//! a `ConfigPatch` only carries the fields that changed, addressed by their
+//! index in `MovementConfig::FIELD_NAMES`, and is encoded in a few bytes.
+//!
+//! Every patch moves a `ReplicatedConfig` from one version to the next. A
+//! client only applies a patch made against the version it holds, and either
+//! applies every change or none, so calling `apply` between two ticks swaps
+//! the whole tuning change in at once. A `StalePatch` error means a patch
+//! was missed and the client needs the full config again.
+//!
+//! Encoding, all little-endian: a format byte, the base and new version as
+//! `u32`, the number of config fields of the encoding build as `u16` (so
+//! builds with different fields reject each other's patches), the change
+//! count as `u16`, then per change the field index as `u16` and the value as
+//! a tag byte followed by an `f32`, a bool byte or a length-prefixed string.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::config_format::{ConfigError, ConfigValue};
+use crate::MovementConfig;
+
+const PATCH_FORMAT: u8 = 1;
+const TAG_NUMBER: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_TEXT: u8 = 2;
+
+/// The changed fields between two versions of a config
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigPatch {
+    base_version: u32,
+    version: u32,
+    changes: Vec<(u16, ConfigValue)>,
+}
+
+impl ConfigPatch {
+    /// Patch turning `base` at `base_version` into `target` at the next version
+    pub fn between(base: &MovementConfig, base_version: u32, target: &MovementConfig) -> ConfigPatch {
+        let changes = MovementConfig::FIELD_NAMES
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &field)| {
+                let new = target.get(field)?;
+                (base.get(field)? != new).then_some((index as u16, new))
+            })
+            .collect();
+        ConfigPatch { base_version, version: base_version.wrapping_add(1), changes }
+    }
+
+    /// Version the patch applies to
+    pub fn base_version(&self) -> u32 {
+        self.base_version
+    }
+
+    /// Version after applying the patch
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether the patch changes no field
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Names and new values of the changed fields
+    pub fn changes(&self) -> impl Iterator<Item = (&'static str, &ConfigValue)> {
+        self.changes.iter().map(|(index, value)| (MovementConfig::FIELD_NAMES[*index as usize], value))
+    }
+
+    /// Apply the patch to `config`, all changes or none
+    pub fn apply_to(&self, config: &mut MovementConfig) -> Result<(), ConfigError> {
+        let mut updated = *config;
+        for (field, value) in self.changes() {
+            updated.set(field, value.clone())?;
+        }
+        *config = updated;
+        Ok(())
+    }
+
+    /// Encode the patch for sending
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![PATCH_FORMAT];
+        bytes.extend_from_slice(&self.base_version.to_le_bytes());
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&(MovementConfig::FIELD_NAMES.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.changes.len() as u16).to_le_bytes());
+        for (index, value) in &self.changes {
+            bytes.extend_from_slice(&index.to_le_bytes());
+            match value {
+                ConfigValue::Number(n) => {
+                    bytes.push(TAG_NUMBER);
+                    bytes.extend_from_slice(&n.to_le_bytes());
+                }
+                ConfigValue::Bool(b) => bytes.extend_from_slice(&[TAG_BOOL, *b as u8]),
+                ConfigValue::Text(t) => {
+                    // Text values are short enum names, well below 256 bytes
+                    bytes.extend_from_slice(&[TAG_TEXT, t.len().min(255) as u8]);
+                    bytes.extend_from_slice(&t.as_bytes()[..t.len().min(255)]);
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decode a patch encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<ConfigPatch, ConfigError> {
+        let mut reader = Reader { bytes };
+        if reader.take(1)?[0] != PATCH_FORMAT {
+            return Err(malformed("unknown format"));
+        }
+        let base_version = reader.u32()?;
+        let version = reader.u32()?;
+        if reader.u16()? as usize != MovementConfig::FIELD_NAMES.len() {
+            return Err(malformed("encoded by a build with different config fields"));
+        }
+        let count = reader.u16()?;
+        let mut changes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let index = reader.u16()?;
+            if index as usize >= MovementConfig::FIELD_NAMES.len() {
+                return Err(malformed("field index out of range"));
+            }
+            let value = match reader.take(1)?[0] {
+                TAG_NUMBER => ConfigValue::Number(f32::from_le_bytes(reader.array()?)),
+                TAG_BOOL => ConfigValue::Bool(reader.take(1)?[0] != 0),
+                TAG_TEXT => {
+                    let len = reader.take(1)?[0] as usize;
+                    let text = std::str::from_utf8(reader.take(len)?).map_err(|_| malformed("text is not UTF-8"))?;
+                    ConfigValue::Text(text.to_string())
+                }
+                _ => return Err(malformed("unknown value tag")),
+            };
+            changes.push((index, value));
+        }
+        if !reader.bytes.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(ConfigPatch { base_version, version, changes })
+    }
+}
+
+/// A config together with the version replicated so far
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ReplicatedConfig {
+    config: MovementConfig,
+    version: u32,
+}
+
+impl ReplicatedConfig {
+    /// Start replicating `config` at `version` (e.g. the version sent with the full config on connect)
+    pub fn new(config: MovementConfig, version: u32) -> ReplicatedConfig {
+        ReplicatedConfig { config, version }
+    }
+
+    /// The current config
+    pub fn config(&self) -> &MovementConfig {
+        &self.config
+    }
+
+    /// The current version
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Server side: switch to `config`, returning the patch to broadcast, if anything changed
+    pub fn update(&mut self, config: MovementConfig) -> Option<ConfigPatch> {
+        let patch = ConfigPatch::between(&self.config, self.version, &config);
+        if patch.is_empty() {
+            return None;
+        }
+        self.config = config;
+        self.version = patch.version;
+        Some(patch)
+    }
+
+    /// Client side: apply a patch received from the server, all changes or none
+    pub fn apply(&mut self, patch: &ConfigPatch) -> Result<(), ConfigError> {
+        if patch.base_version != self.version {
+            return Err(ConfigError::StalePatch { version: self.version, base_version: patch.base_version });
+        }
+        patch.apply_to(&mut self.config)?;
+        self.version = patch.version;
+        Ok(())
+    }
+}
+
+fn malformed(message: &str) -> ConfigError {
+    ConfigError::MalformedPatch(message.to_string())
+}
+
+/// Cursor over the bytes of an encoded patch
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ConfigError> {
+        if self.bytes.len() < len {
+            return Err(malformed("truncated"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ConfigError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn u16(&mut self) -> Result<u16, ConfigError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, ConfigError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl ReplicatedConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(config: &MovementConfig, version: u32) -> ReplicatedConfig {
+        ReplicatedConfig::new(*config, version)
+    }
+
+    #[wasm_bindgen(getter = config)]
+    pub fn config_wasm(&self) -> MovementConfig {
+        self.config
+    }
+
+    #[wasm_bindgen(getter = version)]
+    pub fn version_wasm(&self) -> u32 {
+        self.version
+    }
+
+    /// Encoded patch to broadcast, or `undefined` when nothing changed
+    #[wasm_bindgen(js_name = update)]
+    pub fn update_wasm(&mut self, config: &MovementConfig) -> Option<Vec<u8>> {
+        self.update(*config).map(|patch| patch.to_bytes())
+    }
+
+    /// Apply an encoded patch received from the server
+    #[wasm_bindgen(js_name = applyPatch)]
+    pub fn apply_patch_wasm(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        let patch = ConfigPatch::from_bytes(bytes).map_err(crate::AirStrafeError::from)?;
+        self.apply(&patch).map_err(|error| crate::AirStrafeError::from(error).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UpAxis;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_patches_replicate_live_changes() {
+        let mut server = ReplicatedConfig::new(y_up_config(), 7);
+        let mut client = server;
+        assert_eq!(server.update(y_up_config()), None);
+
+        let tuned = MovementConfig { gravity: 600.0, auto_bhop: true, up_axis: UpAxis::Z, ..y_up_config() };
+        let bytes = server.update(tuned).unwrap().to_bytes();
+        // Three fields fit in a few dozen bytes
+        assert!(bytes.len() < 40);
+        let patch = ConfigPatch::from_bytes(&bytes).unwrap();
+        assert_eq!(patch.changes().map(|(field, _)| field).collect::<Vec<_>>(), ["gravity", "auto_bhop", "up_axis"]);
+
+        client.apply(&patch).unwrap();
+        assert_eq!(client, server);
+        assert_eq!(client.version(), 8);
+
+        // Replaying the same patch is stale, and the client keeps its config
+        assert_eq!(client.apply(&patch), Err(ConfigError::StalePatch { version: 8, base_version: 7 }));
+        assert_eq!(client.config().gravity, 600.0);
+    }
+
+    #[test]
+    fn test_rejects_malformed_bytes() {
+        let tuned = MovementConfig { friction: 5.0, ..y_up_config() };
+        let patch = ConfigPatch::between(&y_up_config(), 0, &tuned);
+        let bytes = patch.to_bytes();
+        assert!(matches!(ConfigPatch::from_bytes(&bytes[..bytes.len() - 1]), Err(ConfigError::MalformedPatch(_))));
+        let mut other_build = bytes.clone();
+        other_build[9] ^= 1;
+        assert!(matches!(ConfigPatch::from_bytes(&other_build), Err(ConfigError::MalformedPatch(_))));
+        assert_eq!(ConfigPatch::from_bytes(&bytes), Ok(patch));
+    }
+}
@@ -0,0 +1,454 @@
+//! Course timer
+//!
+//! KZ and surf maps time runs between a start zone and an end zone, with
+//! checkpoint zones in between that have to be passed in order and that the
+//! player can teleport back to. A `CourseTimer` checks the segment the
+//! player's origin moved along each tick against the zones of a `Course`, so
+//! the run starts at the moment the origin leaves the start zone and ends at
+//! the moment it touches the end zone, down to a fraction of a tick, instead
+//! of on whole ticks.
+//!
+//! Maps limit the speed a run can start with, since the start zone would
+//! otherwise be a place to build up speed: `StartSpeedPolicy::Cap` cuts the
+//! horizontal speed down when the player leaves the start zone, `Reject` does
+//! not start the run at all.
+//!
+//! A finished run is reported as a `RunResult`. Its ticks count the timer's
+//! `update` calls from 0, the same way `Replay` counts the ticks it records,
+//! so a result recorded next to a replay points at the run's inputs in it.
+//! Results are encoded like replays. Zones are `TriggerShape`s, so like
+//! triggers the timer is not available through WASM.
+
+use crate::config_format::ConfigError;
+use crate::determinism::{fnv1a, FNV_OFFSET};
+use crate::replay::{malformed, write_varint, Reader};
+use crate::{PlayerInput, PlayerState, Replay, TriggerShape, UpAxis, Vector3};
+
+const RUN_RESULT_FORMAT: u8 = 1;
+
+/// How the speed the player leaves the start zone with is limited
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StartSpeedPolicy {
+    /// Any speed is allowed
+    #[default]
+    Unlimited,
+    /// Horizontal speed above this many world units per second is cut down
+    Cap(f32),
+    /// Leaving faster than this many world units per second does not start the run
+    Reject(f32),
+}
+
+/// The zones of a timed course, in world units
+#[derive(Debug, Clone, PartialEq)]
+pub struct Course {
+    start: TriggerShape,
+    end: TriggerShape,
+    checkpoints: Vec<TriggerShape>,
+    start_speed: StartSpeedPolicy,
+}
+
+impl Course {
+    /// A course without checkpoints and without a start speed limit
+    pub fn new(start: TriggerShape, end: TriggerShape) -> Course {
+        Course { start, end, checkpoints: Vec::new(), start_speed: StartSpeedPolicy::Unlimited }
+    }
+
+    /// Add the next checkpoint; checkpoints have to be passed in the order they were added
+    pub fn with_checkpoint(mut self, zone: TriggerShape) -> Course {
+        self.checkpoints.push(zone);
+        self
+    }
+
+    /// Limit the speed a run can start with
+    pub fn with_start_speed(mut self, policy: StartSpeedPolicy) -> Course {
+        self.start_speed = policy;
+        self
+    }
+
+    /// The zone runs start from
+    pub fn start(&self) -> &TriggerShape {
+        &self.start
+    }
+
+    /// Number of checkpoints
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Hash of the exact zones and start speed policy, to tell courses apart
+    pub fn hash(&self) -> u64 {
+        let mut words = vec![self.checkpoints.len() as f32];
+        for zone in [&self.start, &self.end].into_iter().chain(&self.checkpoints) {
+            match zone {
+                TriggerShape::Aabb { min, max } => words.extend([0.0, min.x, min.y, min.z, max.x, max.y, max.z]),
+                TriggerShape::Sphere { center, radius } => words.extend([1.0, center.x, center.y, center.z, *radius]),
+                TriggerShape::Convex { planes } => {
+                    words.extend([2.0, planes.len() as f32]);
+                    let plane_words = |&(normal, distance): &(Vector3, f32)| [normal.x, normal.y, normal.z, distance];
+                    words.extend(planes.iter().flat_map(plane_words));
+                }
+            }
+        }
+        words.extend(match self.start_speed {
+            StartSpeedPolicy::Unlimited => [0.0, 0.0],
+            StartSpeedPolicy::Cap(max) => [1.0, max],
+            StartSpeedPolicy::Reject(max) => [2.0, max],
+        });
+        words.iter().fold(FNV_OFFSET, |hash, word| fnv1a(hash, &word.to_bits().to_le_bytes()))
+    }
+}
+
+/// A finished run
+///
+/// Results come from `CourseTimer::update` or `from_bytes`, so the run always
+/// ends at or after the tick it started on.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunResult {
+    pub(crate) start_tick: u32,
+    pub(crate) end_tick: u32,
+    pub(crate) time: f32,
+    pub(crate) splits: Vec<f32>,
+    pub(crate) teleport_ticks: Vec<u32>,
+    pub(crate) start_speed: f32,
+}
+
+impl RunResult {
+    /// Tick the run started on
+    pub fn start_tick(&self) -> u32 {
+        self.start_tick
+    }
+
+    /// Tick the run finished on
+    pub fn end_tick(&self) -> u32 {
+        self.end_tick
+    }
+
+    /// Seconds from leaving the start zone to touching the end zone
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Seconds from the start to each checkpoint
+    pub fn splits(&self) -> &[f32] {
+        &self.splits
+    }
+
+    /// Number of teleports back to a checkpoint
+    pub fn teleports(&self) -> u32 {
+        self.teleport_ticks.len() as u32
+    }
+
+    /// Ticks before which the player teleported back to a checkpoint, in order
+    pub fn teleport_ticks(&self) -> &[u32] {
+        &self.teleport_ticks
+    }
+
+    /// Horizontal speed the run started with, after the start speed policy
+    pub fn start_speed(&self) -> f32 {
+        self.start_speed
+    }
+
+    /// The inputs of the run in a replay recorded alongside the timer
+    ///
+    /// Empty if the replay does not cover the run.
+    pub fn inputs<'a>(&self, replay: &'a Replay) -> &'a [PlayerInput] {
+        replay.inputs().get(self.start_tick as usize..=self.end_tick as usize).unwrap_or_default()
+    }
+
+    /// Encode the result
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![RUN_RESULT_FORMAT];
+        write_varint(&mut bytes, self.start_tick as u64);
+        write_varint(&mut bytes, (self.end_tick - self.start_tick) as u64);
+        bytes.extend_from_slice(&self.time.to_le_bytes());
+        write_varint(&mut bytes, self.teleport_ticks.len() as u64);
+        let mut previous = self.start_tick;
+        for &tick in &self.teleport_ticks {
+            write_varint(&mut bytes, (tick - previous) as u64);
+            previous = tick;
+        }
+        bytes.extend_from_slice(&self.start_speed.to_le_bytes());
+        write_varint(&mut bytes, self.splits.len() as u64);
+        for split in &self.splits {
+            bytes.extend_from_slice(&split.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a result encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<RunResult, ConfigError> {
+        let mut reader = Reader { bytes };
+        if reader.byte()? != RUN_RESULT_FORMAT {
+            return Err(malformed("unknown format"));
+        }
+        let start_tick = u32::try_from(reader.varint()?).map_err(|_| malformed("start tick"))?;
+        let end_tick = u32::try_from(reader.varint()?)
+            .ok()
+            .and_then(|ticks| start_tick.checked_add(ticks))
+            .ok_or_else(|| malformed("end tick"))?;
+        let time = reader.float()?;
+        let teleports = reader.varint()?;
+        if teleports > reader.bytes.len() as u64 {
+            return Err(malformed("truncated"));
+        }
+        let mut teleport_ticks = Vec::with_capacity(teleports as usize);
+        let mut previous = start_tick;
+        for _ in 0..teleports {
+            previous = u32::try_from(reader.varint()?)
+                .ok()
+                .and_then(|ticks| previous.checked_add(ticks))
+                .filter(|&tick| tick <= end_tick)
+                .ok_or_else(|| malformed("teleport tick"))?;
+            teleport_ticks.push(previous);
+        }
+        let start_speed = reader.float()?;
+        let count = reader.varint()?;
+        if count > reader.bytes.len() as u64 / 4 {
+            return Err(malformed("truncated"));
+        }
+        let splits = (0..count).map(|_| reader.float()).collect::<Result<Vec<_>, _>>()?;
+        if !reader.bytes.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(RunResult { start_tick, end_tick, time, splits, teleport_ticks, start_speed })
+    }
+}
+
+/// The run in progress
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    start_tick: u32,
+    start_time: f64,
+    start_speed: f32,
+    splits: Vec<f32>,
+    /// Where the origin touched the last checkpoint passed
+    checkpoint_origin: Option<Vector3>,
+    teleport_ticks: Vec<u32>,
+}
+
+/// Times one player's runs on a course
+#[derive(Debug, Clone, PartialEq)]
+pub struct CourseTimer {
+    course: Course,
+    up_axis: UpAxis,
+    tick: u32,
+    /// Seconds passed in all updates so far
+    elapsed: f64,
+    previous_origin: Option<Vector3>,
+    in_start: bool,
+    run: Option<Run>,
+}
+
+impl CourseTimer {
+    /// Create a timer for `course`; no run is in progress until the player leaves the start zone
+    pub fn new(course: Course, up_axis: UpAxis) -> CourseTimer {
+        CourseTimer { course, up_axis, tick: 0, elapsed: 0.0, previous_origin: None, in_start: false, run: None }
+    }
+
+    /// The course being timed
+    pub fn course(&self) -> &Course {
+        &self.course
+    }
+
+    /// Whether a run is in progress
+    pub fn running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Whether the player is waiting in the start zone
+    pub fn in_start(&self) -> bool {
+        self.in_start
+    }
+
+    /// Seconds since the current run started, 0.0 without a run
+    pub fn time(&self) -> f32 {
+        self.run.as_ref().map_or(0.0, |run| (self.elapsed - run.start_time) as f32)
+    }
+
+    /// Split times of the current run so far
+    pub fn splits(&self) -> &[f32] {
+        self.run.as_ref().map_or(&[], |run| &run.splits)
+    }
+
+    /// Tell the timer where the player is without timing a tick, e.g. after
+    /// spawning or a teleport by the host
+    pub fn place(&mut self, origin: Vector3) {
+        self.previous_origin = Some(origin);
+    }
+
+    /// Stop the current run without a result
+    pub fn reset(&mut self) {
+        self.run = None;
+    }
+
+    /// Time one tick
+    ///
+    /// Call this once per tick with the state after the tick. The move is
+    /// timed from where the last update or `place` left the player. A run
+    /// starting this tick may cut the player's speed, see `StartSpeedPolicy`.
+    ///
+    /// # Returns
+    /// The result of the run if it finished this tick
+    pub fn update(&mut self, state: &mut PlayerState, delta_time: f32) -> Option<RunResult> {
+        let (from, to) = (self.previous_origin.unwrap_or(state.origin), state.origin);
+        self.previous_origin = Some(to);
+        let tick = self.tick;
+        self.tick += 1;
+        let tick_start = self.elapsed;
+        self.elapsed += delta_time as f64;
+        let time_at = |fraction: f32| tick_start + fraction as f64 * delta_time as f64;
+
+        // Fraction of the move after which the zones count for the run
+        let mut after = 0.0;
+        self.in_start = false;
+        if let Some((_, exit)) = self.course.start.segment_overlap(&from, &to) {
+            self.run = None;
+            if exit >= 1.0 {
+                self.in_start = true;
+                return None;
+            }
+            after = exit;
+            self.start_run(state, tick, time_at(exit));
+        }
+        let run = self.run.as_mut()?;
+        let touched = |zone: &TriggerShape, after: f32| {
+            zone.segment_overlap(&from, &to).filter(|&(_, exit)| exit >= after).map(|(enter, _)| enter.max(after))
+        };
+
+        while let Some(zone) = self.course.checkpoints.get(run.splits.len()) {
+            let Some(enter) = touched(zone, after) else {
+                break;
+            };
+            run.splits.push((time_at(enter) - run.start_time) as f32);
+            run.checkpoint_origin = Some(from + (to - from) * enter);
+            after = enter;
+        }
+        if run.splits.len() < self.course.checkpoints.len() {
+            return None;
+        }
+        let enter = touched(&self.course.end, after)?;
+        let run = self.run.take()?;
+        Some(RunResult {
+            start_tick: run.start_tick,
+            end_tick: tick,
+            time: (time_at(enter) - run.start_time) as f32,
+            splits: run.splits,
+            teleport_ticks: run.teleport_ticks,
+            start_speed: run.start_speed,
+        })
+    }
+
+    /// Move the player back to the last checkpoint passed, standing still
+    ///
+    /// Call this between ticks. The run records the tick the teleport came
+    /// before, so `RunBundle::verify` can repeat it.
+    ///
+    /// # Returns
+    /// Whether the player was teleported; not without a run or before the first checkpoint
+    pub fn teleport_to_checkpoint(&mut self, state: &mut PlayerState) -> bool {
+        let Some(run) = self.run.as_mut() else {
+            return false;
+        };
+        let Some(origin) = run.checkpoint_origin else {
+            return false;
+        };
+        run.teleport_ticks.push(self.tick);
+        state.origin = origin;
+        state.velocity = Vector3::new(0.0, 0.0, 0.0);
+        self.previous_origin = Some(origin);
+        true
+    }
+
+    /// Start a run at `start_time`, applying the start speed policy
+    fn start_run(&mut self, state: &mut PlayerState, tick: u32, start_time: f64) {
+        let horizontal = self.up_axis.horizontal(&state.velocity);
+        let mut start_speed = horizontal.magnitude();
+        match self.course.start_speed {
+            StartSpeedPolicy::Unlimited => {}
+            StartSpeedPolicy::Cap(max) => {
+                if start_speed > max {
+                    state.velocity = state.velocity - horizontal * (1.0 - max / start_speed);
+                    start_speed = max;
+                }
+            }
+            StartSpeedPolicy::Reject(max) => {
+                if start_speed > max {
+                    return;
+                }
+            }
+        }
+        let (splits, checkpoint_origin, teleport_ticks) = (Vec::new(), None, Vec::new());
+        self.run = Some(Run { start_tick: tick, start_time, start_speed, splits, checkpoint_origin, teleport_ticks });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MovementConfig};
+    use crate::tick::tests::y_up_config;
+
+    fn zone(from_x: f32, to_x: f32) -> TriggerShape {
+        TriggerShape::aabb(Vector3::new(from_x, -1000.0, -1000.0), Vector3::new(to_x, 1000.0, 1000.0))
+    }
+
+    /// Fly along x at 20 units per tick until the run finishes
+    fn fly(timer: &mut CourseTimer, replay: &mut Replay, teleport_at: Option<u32>) -> (PlayerState, Option<RunResult>) {
+        let config = MovementConfig { gravity: 0.0, ..y_up_config() };
+        let mut state = PlayerState::new(Vector3::new(0.0, 100.0, 0.0));
+        state.velocity.x = 1280.0;
+        timer.place(state.origin);
+        for tick in 0..100 {
+            let input = PlayerInput::new();
+            replay.record(&state, &input);
+            player_tick(&mut state, &input, &config, 1.0 / 64.0, None);
+            if let Some(result) = timer.update(&mut state, 1.0 / 64.0) {
+                return (state, Some(result));
+            }
+            if teleport_at == Some(tick) {
+                assert!(timer.teleport_to_checkpoint(&mut state));
+                state.velocity.x = 1280.0;
+            }
+        }
+        (state, None)
+    }
+
+    #[test]
+    fn test_runs_are_timed_between_zones() {
+        let course = Course::new(zone(-50.0, 10.0), zone(600.0, 700.0)).with_checkpoint(zone(300.0, 320.0));
+        let mut timer = CourseTimer::new(course.clone(), UpAxis::Y);
+        let mut replay = Replay::new(64.0, 64);
+        let (_, result) = fly(&mut timer, &mut replay, None);
+        let result = result.unwrap();
+
+        // Leaves the start zone halfway through tick 0 and touches the end zone at the end of tick 29
+        assert_eq!((result.start_tick, result.end_tick), (0, 29));
+        assert!((result.time - 590.0 / 1280.0).abs() < 1e-6);
+        assert!((result.splits[0] - 290.0 / 1280.0).abs() < 1e-6);
+        assert_eq!(result.inputs(&replay).len(), 30);
+        assert_eq!(RunResult::from_bytes(&result.to_bytes()), Ok(result.clone()));
+        assert!(RunResult::from_bytes(&result.to_bytes()[..10]).is_err());
+
+        // Teleporting back to the checkpoint costs the time flown since
+        let mut timer = CourseTimer::new(course.clone(), UpAxis::Y);
+        let (_, teleported) = fly(&mut timer, &mut Replay::new(64.0, 64), Some(20));
+        let teleported = teleported.unwrap();
+        assert_eq!(teleported.teleport_ticks, [21]);
+        assert!((teleported.time - (result.time + 120.0 / 1280.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_start_speed_policies() {
+        let course = Course::new(zone(-50.0, 10.0), zone(600.0, 700.0));
+        let mut capped = CourseTimer::new(course.clone().with_start_speed(StartSpeedPolicy::Cap(640.0)), UpAxis::Y);
+        let (state, result) = fly(&mut capped, &mut Replay::new(64.0, 64), None);
+        let result = result.unwrap();
+        assert!((state.velocity.x - 640.0).abs() < 1e-3 && result.start_speed == 640.0);
+        // Half a tick at full speed, then 580 units at the capped speed
+        assert!((result.time - (0.5 / 64.0 + 580.0 / 640.0)).abs() < 1e-5);
+
+        let mut rejected = CourseTimer::new(course.with_start_speed(StartSpeedPolicy::Reject(640.0)), UpAxis::Y);
+        let (_, result) = fly(&mut rejected, &mut Replay::new(64.0, 64), None);
+        assert!(result.is_none() && !rejected.running());
+    }
+}
@@ -0,0 +1,161 @@
+//! Crouch sliding and ceiling clearance
+//!
+//! Ducking on the ground faster than `crouch_slide_min_speed` starts a crouch
+//! slide: the player switches to the ducked hull at once, keeps their speed
+//! with the low `crouch_slide_friction` and cannot accelerate. The slide ends
+//! when duck is released or the player slows to ducked walking speed.
+//!
+//! The host casts a ray straight up from the origin each tick and passes it to
+//! `update_ceiling_clearance`. While a ceiling is closer than `hull_height`
+//! the player cannot stand up, so a ducked player stays ducked, and a slide
+//! under a low gap keeps going at no less than `crouch_slide_stuck_speed`
+//! until there is room to stand (0.0 lets the slide stop under the gap).
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::hull::standing_hull;
+use crate::{MovementConfig, PlayerInput, PlayerState, RayCollisionHit, UpAxis};
+
+/// Check whether the player has room to stand up
+///
+/// # Arguments
+/// * `state` - The player, its `ceiling_blocked` is updated
+/// * `config` - Supplies the standing hull, see `hull`
+/// * `up_ray_hit` - Result of a ray cast straight up from the player origin
+pub fn update_ceiling_clearance(state: &mut PlayerState, config: &MovementConfig, up_ray_hit: Option<RayCollisionHit>) {
+    let standing = standing_hull(config);
+    state.ceiling_blocked = up_ray_hit.is_some_and(|hit| hit.distance_native() < standing.height);
+}
+
+/// Whether the player has to keep the ducked hull regardless of the duck button
+pub(crate) fn must_stay_ducked(state: &PlayerState) -> bool {
+    state.crouch_sliding || (state.ceiling_blocked && state.duck_amount > 0.0)
+}
+
+/// Start or end a crouch slide
+pub(crate) fn update_crouch_slide<const Z_UP: bool>(state: &mut PlayerState, input: &PlayerInput, config: &MovementConfig) {
+    if !state.on_ground || config.crouch_slide_min_speed <= 0.0 {
+        state.crouch_sliding = false;
+        return;
+    }
+    let speed = UpAxis::from_z_up(Z_UP).horizontal(&state.velocity).magnitude();
+    if state.crouch_sliding {
+        let trapped = state.ceiling_blocked && config.crouch_slide_stuck_speed > 0.0;
+        let walking = speed < config.max_speed * config.duck_multiplier;
+        if !trapped && (!input.duck || walking) {
+            state.crouch_sliding = false;
+        }
+    } else if input.duck && speed >= config.crouch_slide_min_speed {
+        // The hull switches at once instead of following the duck transition
+        state.crouch_sliding = true;
+        state.duck_amount = 1.0;
+        state.ducked = true;
+    }
+}
+
+/// Keep a slide under a low ceiling moving at `crouch_slide_stuck_speed`
+pub(crate) fn keep_sliding_under_ceiling<const Z_UP: bool>(state: &mut PlayerState, config: &MovementConfig) {
+    if !state.crouch_sliding || !state.ceiling_blocked {
+        return;
+    }
+    let axis = UpAxis::from_z_up(Z_UP);
+    let horizontal = axis.horizontal(&state.velocity);
+    let speed = horizontal.magnitude();
+    if speed > 0.1 && speed < config.crouch_slide_stuck_speed {
+        let mut velocity = horizontal.multiply_scalar(config.crouch_slide_stuck_speed / speed);
+        axis.set_height(&mut velocity, axis.height(&state.velocity));
+        state.velocity = velocity;
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = updateCeilingClearance)]
+pub fn update_ceiling_clearance_wasm(state: &mut PlayerState, config: &MovementConfig, up_ray_hit: Option<RayCollisionHit>) {
+    update_ceiling_clearance(state, config, up_ray_hit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_slide_under_low_gap_until_there_is_room_to_stand() {
+        let config = MovementConfig { crouch_slide_min_speed: 300.0, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let low_ceiling = Some(RayCollisionHit::new_native(Vector3::new(0.0, -1.0, 0.0), 40.0));
+        let dt = 1.0 / 64.0;
+        let mut state = PlayerState { on_ground: true, velocity: Vector3::new(0.0, 0.0, -500.0), ..PlayerState::default() };
+
+        player_tick(&mut state, &PlayerInput { duck: true, ..PlayerInput::new() }, &config, dt, ground);
+        assert!(state.crouch_sliding && state.ducked);
+
+        // Releasing duck under the gap keeps the slide going
+        for _ in 0..256 {
+            update_ceiling_clearance(&mut state, &config, low_ceiling);
+            player_tick(&mut state, &PlayerInput::new(), &config, dt, ground);
+        }
+        assert!(state.crouch_sliding && state.ducked);
+        assert!((state.velocity.z + config.crouch_slide_stuck_speed).abs() < 0.01);
+
+        update_ceiling_clearance(&mut state, &config, None);
+        for _ in 0..32 {
+            player_tick(&mut state, &PlayerInput::new(), &config, dt, ground);
+        }
+        assert!(!state.crouch_sliding && !state.ducked);
+    }
+
+    #[test]
+    fn test_slide_start_and_end_conditions() {
+        let config = MovementConfig { crouch_slide_min_speed: 300.0, ..y_up_config() };
+        let duck = PlayerInput { duck: true, ..PlayerInput::new() };
+        let slides = |config: &MovementConfig, on_ground: bool, speed: f32| {
+            let velocity = Vector3::new(speed, -50.0, 0.0);
+            let mut state = PlayerState { on_ground, velocity, ..PlayerState::default() };
+            update_crouch_slide::<false>(&mut state, &duck, config);
+            state.crouch_sliding
+        };
+        // Only the horizontal speed counts, from exactly the minimum on
+        assert!(slides(&config, true, 300.0));
+        assert!(!slides(&config, true, 299.9));
+        assert!(!slides(&config, false, 500.0));
+        assert!(!slides(&MovementConfig { crouch_slide_min_speed: 0.0, ..config }, true, 500.0));
+
+        // Releasing duck ends the slide, and so does slowing to ducked walking speed
+        let velocity = Vector3::new(400.0, 0.0, 0.0);
+        let sliding = PlayerState { on_ground: true, crouch_sliding: true, velocity, ..PlayerState::default() };
+        let mut state = sliding;
+        update_crouch_slide::<false>(&mut state, &PlayerInput::new(), &config);
+        assert!(!state.crouch_sliding);
+        let walking = config.max_speed * config.duck_multiplier - 1.0;
+        let mut state = PlayerState { velocity: Vector3::new(walking, 0.0, 0.0), ..sliding };
+        update_crouch_slide::<false>(&mut state, &duck, &config);
+        assert!(!state.crouch_sliding);
+        // Leaving the ground ends it too
+        let mut state = PlayerState { on_ground: false, ..sliding };
+        update_crouch_slide::<false>(&mut state, &duck, &config);
+        assert!(!state.crouch_sliding);
+    }
+
+    #[test]
+    fn test_ceiling_clearance_edges() {
+        let config = y_up_config();
+        let ceiling = |distance: f32| Some(RayCollisionHit::new_native(Vector3::new(0.0, -1.0, 0.0), distance));
+        let mut state = PlayerState::default();
+        update_ceiling_clearance(&mut state, &config, ceiling(config.hull_height));
+        assert!(!state.ceiling_blocked);
+        update_ceiling_clearance(&mut state, &config, ceiling(config.hull_height - 0.1));
+        assert!(state.ceiling_blocked);
+        // A standing player under a low ceiling does not have to duck, a ducking one stays ducked
+        assert!(!must_stay_ducked(&state));
+        state.duck_amount = 0.1;
+        assert!(must_stay_ducked(&state));
+
+        // A slide that has stopped under the ceiling is not pushed in a made-up direction
+        let mut stopped = PlayerState { crouch_sliding: true, ceiling_blocked: true, ..PlayerState::default() };
+        keep_sliding_under_ceiling::<false>(&mut stopped, &config);
+        assert_eq!(stopped.velocity, Vector3::new(0.0, 0.0, 0.0));
+    }
+}
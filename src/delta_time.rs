@@ -0,0 +1,84 @@
+//! Limits on the time step of a single tick
+//!
+//! Fixed tick loops never pass more than a tick's worth of time, but not every
+//! host runs one: a variable-rate host that hitches for two seconds would
+//! hand `player_tick` a two second step, which integrates gravity and
+//! velocity far past any ground or wall the host traced for. `player_tick`
+//! therefore enforces `max_delta_time` itself, whatever the host does, with
+//! the `delta_time_policy` deciding what happens to a longer step:
+//!
+//! - `Clamp` simulates a single tick of `max_delta_time`; the rest of the
+//!   time is lost, so the game briefly runs slow instead of jumping ahead.
+//! - `Drop` skips the tick entirely, as if it never happened.
+//! - `SubStep` splits the step into equal ticks of at most `max_delta_time`,
+//!   up to `MAX_SUB_STEPS` of them; time beyond that is lost as with `Clamp`.
+//!   Every sub-step counts as a tick of its own (`PlayerState::tick`), and the
+//!   ground hit is moved along with the player between sub-steps.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::MovementConfig;
+
+/// Most ticks a `SubStep` policy splits one step into
+pub const MAX_SUB_STEPS: u32 = 8;
+
+/// What `player_tick` does with a step longer than `max_delta_time`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema), schemars(rename_all = "snake_case"))]
+pub enum DeltaTimePolicy {
+    /// Simulate one tick of `max_delta_time`
+    #[default]
+    Clamp,
+    /// Skip the tick
+    Drop,
+    /// Simulate several shorter ticks
+    SubStep,
+}
+
+/// Number of ticks and tick length to simulate a step of `delta_time` with
+pub(crate) fn split_delta_time(delta_time: f32, config: &MovementConfig) -> (u32, f32) {
+    let max = config.max_delta_time;
+    if max <= 0.0 || delta_time <= max {
+        return (1, delta_time);
+    }
+    match config.delta_time_policy {
+        DeltaTimePolicy::Clamp => (1, max),
+        DeltaTimePolicy::Drop => (0, 0.0),
+        DeltaTimePolicy::SubStep => {
+            let steps = ((delta_time / max).ceil() as u32).min(MAX_SUB_STEPS);
+            (steps, (delta_time / steps as f32).min(max))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, PlayerState, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_hitches_never_fling_the_player() {
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 100.0));
+        let falling = PlayerState { origin: Vector3::new(0.0, 100.0, 0.0), ..PlayerState::default() };
+        let run = |policy| {
+            let config = MovementConfig { delta_time_policy: policy, max_delta_time: 0.1, ..y_up_config() };
+            let mut state = falling;
+            player_tick(&mut state, &PlayerInput::new(), &config, 2.0, ground);
+            state
+        };
+
+        // One clamped tick falls as far as a 0.1 second tick
+        let mut expected = falling;
+        player_tick(&mut expected, &PlayerInput::new(), &y_up_config(), 0.1, ground);
+        assert_eq!(run(DeltaTimePolicy::Clamp), expected);
+        assert_eq!(run(DeltaTimePolicy::Drop), falling);
+
+        // Sub-steps fall for at most 0.8 seconds and land on the ground instead of passing it
+        let sub_stepped = run(DeltaTimePolicy::SubStep);
+        assert_eq!(sub_stepped.tick, MAX_SUB_STEPS as u64);
+        assert!(sub_stepped.on_ground && sub_stepped.origin.y == 0.0);
+    }
+}
@@ -0,0 +1,225 @@
+//! Cross-run determinism checks
+//!
+//! Lockstep and rollback netcode need two machines fed the same inputs to end
+//! up with bit-identical players. A `DeterminismAuditor` records a hash of
+//! every field of every player after each tick. Record one run per machine,
+//! ship one side's `records` to the other (they are plain `u64`s) and
+//! `compare` them: the result names the first tick, player and field whose
+//! bits differ, which usually points straight at the math that drifted.
+//!
+//! Hashes cover the exact bit patterns (FNV-1a), so `0.0` and `-0.0` or two
+//! different NaNs count as different. They do not depend on the platform, the
+//! Rust version or the process.
+
+use std::fmt;
+
+use crate::components::{push_state_field_hashes, STATE_FIELD_NAMES};
+use crate::dodge::DodgeDirection;
+use crate::{MoveType, PlayerState, Vector3};
+
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// A `PlayerState` field whose exact bits can be hashed
+pub(crate) trait FieldBits {
+    fn bits_hash(&self) -> u64;
+}
+
+impl FieldBits for f32 {
+    fn bits_hash(&self) -> u64 {
+        fnv1a(FNV_OFFSET, &self.to_bits().to_le_bytes())
+    }
+}
+
+impl FieldBits for Vector3 {
+    fn bits_hash(&self) -> u64 {
+        [self.x, self.y, self.z].iter().fold(FNV_OFFSET, |hash, v| fnv1a(hash, &v.to_bits().to_le_bytes()))
+    }
+}
+
+impl FieldBits for bool {
+    fn bits_hash(&self) -> u64 {
+        fnv1a(FNV_OFFSET, &[*self as u8])
+    }
+}
+
+impl FieldBits for u8 {
+    fn bits_hash(&self) -> u64 {
+        fnv1a(FNV_OFFSET, &[*self])
+    }
+}
+
+impl FieldBits for u32 {
+    fn bits_hash(&self) -> u64 {
+        fnv1a(FNV_OFFSET, &self.to_le_bytes())
+    }
+}
+
+impl FieldBits for u64 {
+    fn bits_hash(&self) -> u64 {
+        fnv1a(FNV_OFFSET, &self.to_le_bytes())
+    }
+}
+
+impl FieldBits for MoveType {
+    fn bits_hash(&self) -> u64 {
+        (*self as u8).bits_hash()
+    }
+}
+
+impl FieldBits for DodgeDirection {
+    fn bits_hash(&self) -> u64 {
+        (*self as u8).bits_hash()
+    }
+}
+
+/// Hash of the exact bits of a whole player state
+pub fn state_hash(state: &PlayerState) -> u64 {
+    let mut fields = Vec::with_capacity(STATE_FIELD_NAMES.len());
+    push_state_field_hashes(state, &mut fields);
+    fields.iter().fold(FNV_OFFSET, |hash, field| fnv1a(hash, &field.to_le_bytes()))
+}
+
+/// The first difference between two recorded runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeterminismMismatch {
+    /// The runs had a different number of players on a tick
+    PlayerCount { tick: usize, expected: usize, actual: usize },
+    /// A player field had different bits on a tick
+    Field { tick: usize, player: usize, field: &'static str },
+}
+
+impl fmt::Display for DeterminismMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeterminismMismatch::PlayerCount { tick, expected, actual } => {
+                write!(f, "tick {tick}: {actual} players, expected {expected}")
+            }
+            DeterminismMismatch::Field { tick, player, field } => {
+                write!(f, "tick {tick}: player {player} diverged in `{field}`")
+            }
+        }
+    }
+}
+
+/// Per-tick field hashes of one run
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DeterminismAuditor {
+    records: Vec<Vec<u64>>,
+}
+
+impl DeterminismAuditor {
+    /// Start an empty recording
+    pub fn new() -> DeterminismAuditor {
+        DeterminismAuditor::default()
+    }
+
+    /// Continue from `records` of another run, e.g. received from another machine
+    pub fn from_records(records: Vec<Vec<u64>>) -> DeterminismAuditor {
+        DeterminismAuditor { records }
+    }
+
+    /// Hash every player after a tick; call once per tick with the players in a fixed order
+    pub fn record(&mut self, players: &[PlayerState]) {
+        let mut hashes = Vec::with_capacity(players.len() * STATE_FIELD_NAMES.len());
+        for state in players {
+            push_state_field_hashes(state, &mut hashes);
+        }
+        self.records.push(hashes);
+    }
+
+    /// Number of recorded ticks
+    pub fn ticks(&self) -> usize {
+        self.records.len()
+    }
+
+    /// The field hashes of every recorded tick, players one after another
+    pub fn records(&self) -> &[Vec<u64>] {
+        &self.records
+    }
+
+    /// The first difference from an `expected` run
+    ///
+    /// Only the ticks both runs recorded are compared.
+    pub fn compare(&self, expected: &DeterminismAuditor) -> Option<DeterminismMismatch> {
+        let fields = STATE_FIELD_NAMES.len();
+        for (tick, (actual, expected)) in self.records.iter().zip(&expected.records).enumerate() {
+            if actual.len() != expected.len() {
+                return Some(DeterminismMismatch::PlayerCount {
+                    tick,
+                    expected: expected.len() / fields,
+                    actual: actual.len() / fields,
+                });
+            }
+            if let Some(index) = actual.iter().zip(expected).position(|(a, b)| a != b) {
+                let (player, field) = (index / fields, STATE_FIELD_NAMES[index % fields]);
+                return Some(DeterminismMismatch::Field { tick, player, field });
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MovementConfig, PlayerInput, World};
+    use crate::tick::tests::y_up_config;
+
+    fn run(config: MovementConfig) -> DeterminismAuditor {
+        let mut world = World::new(config);
+        world.spawn(PlayerState::default());
+        world.spawn(PlayerState { on_ground: true, ..PlayerState::default() });
+        let inputs = [PlayerInput { forward_move: 1.0, yaw: 0.3, ..PlayerInput::new() }; 2];
+        let mut auditor = DeterminismAuditor::new();
+        for _ in 0..64 {
+            world.advance_all(&inputs, &[], 1.0 / 64.0);
+            auditor.record(world.snapshot().players());
+        }
+        auditor
+    }
+
+    #[test]
+    fn test_reports_first_diverging_tick_and_field() {
+        let reference = run(y_up_config());
+        let replay = DeterminismAuditor::from_records(reference.records().to_vec());
+        assert_eq!(replay.compare(&reference), None);
+
+        let drifted = run(MovementConfig { gravity: y_up_config().gravity + 1.0, ..y_up_config() });
+        let mismatch = drifted.compare(&reference);
+        assert_eq!(mismatch, Some(DeterminismMismatch::Field { tick: 0, player: 0, field: "origin" }));
+        assert_ne!(state_hash(&PlayerState::default()), state_hash(&PlayerState { lean: -0.0, ..PlayerState::default() }));
+    }
+    #[test]
+    fn test_compare_edges() {
+        let empty = DeterminismAuditor::new();
+        assert_eq!(empty.compare(&empty), None);
+        let reference = run(y_up_config());
+        assert_eq!(empty.compare(&reference), None);
+
+        // A prefix of the run matches, a player more does not
+        let prefix = DeterminismAuditor::from_records(reference.records()[..10].to_vec());
+        assert_eq!((prefix.ticks(), prefix.compare(&reference)), (10, None));
+        let mut crowded = prefix.clone();
+        crowded.record(&[PlayerState::default(); 3]);
+        let mismatch = crowded.compare(&reference).unwrap();
+        assert_eq!(mismatch, DeterminismMismatch::PlayerCount { tick: 10, expected: 2, actual: 3 });
+        assert_eq!(mismatch.to_string(), "tick 10: 3 players, expected 2");
+
+        // Only the second player drifts
+        let mut second = DeterminismAuditor::new();
+        let mut other = DeterminismAuditor::new();
+        second.record(&[PlayerState::default(), PlayerState::default()]);
+        other.record(&[PlayerState::default(), PlayerState { ducked: true, ..PlayerState::default() }]);
+        let mismatch = second.compare(&other).unwrap();
+        assert_eq!(mismatch, DeterminismMismatch::Field { tick: 0, player: 1, field: "ducked" });
+        assert_eq!(mismatch.to_string(), "tick 0: player 1 diverged in `ducked`");
+
+        let nan = |bits: u32| PlayerState { lean: f32::from_bits(bits), ..PlayerState::default() };
+        assert_ne!(state_hash(&nan(0x7fc0_0000)), state_hash(&nan(0x7fc0_0001)));
+    }
+}
@@ -0,0 +1,328 @@
+//! Unreal Tournament style dodging
+//!
+//! With `dodge_enabled`, double-tapping a movement direction within
+//! `dodge_tap_window` (or pressing the dodge button while moving) launches the
+//! player sideways off the ground with a fixed horizontal and vertical impulse.
+//! Landing from a dodge starts a recovery during which the player can neither
+//! dodge nor jump again.
+//!
+//! With `dodge_wall_enabled`, an airborne player can also dodge away from a
+//! wall reported by `update_dodge_wall`. `dodge_chain` selects what may follow
+//! a dodge before landing, to reproduce the different UT games.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::events::MovementEvent;
+use crate::fixed::with_fixed;
+use crate::{MovementConfig, PlayerInput, PlayerState, RayCollisionHit, UpAxis, Vector3};
+
+/// Direction of a dodge relative to the view
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DodgeDirection {
+    #[default]
+    None,
+    Forward,
+    Back,
+    Left,
+    Right,
+}
+
+impl DodgeDirection {
+    const ALL: [DodgeDirection; 4] =
+        [DodgeDirection::Forward, DodgeDirection::Back, DodgeDirection::Left, DodgeDirection::Right];
+
+    fn bit(self) -> u8 {
+        match self {
+            DodgeDirection::None => 0,
+            DodgeDirection::Forward => 1 << 0,
+            DodgeDirection::Back => 1 << 1,
+            DodgeDirection::Left => 1 << 2,
+            DodgeDirection::Right => 1 << 3,
+        }
+    }
+
+    /// Horizontal unit vector of the direction for a view yaw
+    pub fn vector(self, yaw: f32, up_axis: UpAxis) -> Vector3 {
+        match self {
+            DodgeDirection::None => Vector3::new(0.0, 0.0, 0.0),
+            DodgeDirection::Forward => up_axis.forward(yaw),
+            DodgeDirection::Back => up_axis.forward(yaw).multiply_scalar(-1.0),
+            DodgeDirection::Left => up_axis.right(yaw).multiply_scalar(-1.0),
+            DodgeDirection::Right => up_axis.right(yaw),
+        }
+    }
+}
+
+/// What a dodge can be chained with before landing
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema), schemars(rename_all = "snake_case"))]
+pub enum DodgeChain {
+    /// UT99: nothing; wall dodges only from regular jumps and falls
+    #[default]
+    None,
+    /// UT2004: one jump in the air during a dodge, and one wall dodge per airtime
+    DodgeJump,
+    /// Wall dodges chain without limit and landing needs no recovery
+    Unlimited,
+}
+
+/// Held bit of the explicit dodge button in `PlayerState::dodge_keys_held`
+const DODGE_BUTTON_BIT: u8 = 1 << 4;
+
+/// The movement directions held in an input
+fn held_directions(input: &PlayerInput) -> u8 {
+    let mut held = 0;
+    for (pressed, direction) in [
+        (input.forward_move > 0.0, DodgeDirection::Forward),
+        (input.forward_move < 0.0, DodgeDirection::Back),
+        (input.side_move < 0.0, DodgeDirection::Left),
+        (input.side_move > 0.0, DodgeDirection::Right),
+    ] {
+        if pressed {
+            held |= direction.bit();
+        }
+    }
+    held
+}
+
+/// Track direction taps and return the dodge requested on this tick
+///
+/// Runs every tick so double taps are seen wherever they happen; whether the
+/// dodge can be performed is up to the caller.
+pub(crate) fn dodge_request(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+) -> DodgeDirection {
+    state.dodge_tap_time += delta_time;
+    state.dodge_recovery = (state.dodge_recovery - delta_time).max(0.0);
+
+    let held = held_directions(input) | if input.dodge { DODGE_BUTTON_BIT } else { 0 };
+    let pressed = held & !state.dodge_keys_held;
+    state.dodge_keys_held = held;
+
+    let mut request = DodgeDirection::None;
+    if pressed & DODGE_BUTTON_BIT != 0 {
+        // The dodge button dodges along the held direction, forward by default
+        request = DodgeDirection::ALL.into_iter().find(|d| held & d.bit() != 0).unwrap_or(DodgeDirection::Forward);
+    }
+    for direction in DodgeDirection::ALL {
+        if pressed & direction.bit() == 0 {
+            continue;
+        }
+        if config.dodge_double_tap && state.dodge_tap == direction && state.dodge_tap_time <= config.dodge_tap_window {
+            request = direction;
+            state.dodge_tap = DodgeDirection::None;
+        } else {
+            state.dodge_tap = direction;
+            state.dodge_tap_time = 0.0;
+        }
+    }
+    request
+}
+
+/// Velocity right after dodging in a direction
+///
+/// The horizontal velocity is replaced by `dodge_speed` along the direction;
+/// the vertical velocity becomes `dodge_up_speed`.
+pub fn dodge_velocity(direction: DodgeDirection, yaw: f32, config: &MovementConfig) -> Vector3 {
+    with_fixed!(config.up_axis, Z_UP => dodge_velocity_fixed::<Z_UP>(direction, yaw, config))
+}
+
+/// `dodge_velocity` for the up axis of a `fixed` instantiation
+pub(crate) fn dodge_velocity_fixed<const Z_UP: bool>(direction: DodgeDirection, yaw: f32, config: &MovementConfig) -> Vector3 {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let mut velocity = direction.vector(yaw, axis).multiply_scalar(config.dodge_speed);
+    axis.set_height(&mut velocity, config.dodge_up_speed);
+    velocity
+}
+
+/// Remember the wall next to the player for wall dodges
+///
+/// Call before `player_tick` with a horizontal ray cast from the player
+/// towards the closest wall (for example the nearer hit of a left and a right
+/// ray). Hits beyond `dodge_wall_reach` and walkable surfaces are ignored.
+pub fn update_dodge_wall(state: &mut PlayerState, config: &MovementConfig, wall_ray_hit: Option<RayCollisionHit>) {
+    let config = &config.in_world_units();
+    let axis = config.up_axis;
+    state.dodge_wall_normal = wall_ray_hit
+        .filter(|hit| {
+            hit.distance_native() <= config.dodge_wall_reach
+                && axis.height(hit.normal_native()).abs() < config.min_ground_normal_up
+        })
+        .map_or(Vector3::new(0.0, 0.0, 0.0), |hit| axis.horizontal(hit.normal_native()).normalized());
+}
+
+/// End the dodge on landing
+pub(crate) fn dodge_landing(state: &mut PlayerState, config: &MovementConfig) {
+    if state.dodging && config.dodge_chain != DodgeChain::Unlimited {
+        state.dodge_recovery = config.dodge_recovery_time;
+    }
+    state.dodging = false;
+    state.dodge_jumped = false;
+    state.wall_dodge_count = 0;
+}
+
+/// Wall dodges and dodge jumps while airborne
+pub(crate) fn air_dodge<const Z_UP: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    dodge: DodgeDirection,
+    events: &mut Vec<MovementEvent>,
+) {
+    if !config.dodge_enabled {
+        return;
+    }
+    let axis = UpAxis::from_z_up(Z_UP);
+    if dodge != DodgeDirection::None && config.dodge_wall_enabled && state.dodge_recovery <= 0.0 {
+        let away_from_wall = dodge.vector(input.yaw, axis).dot(&state.dodge_wall_normal) > 0.5;
+        let allowed = match config.dodge_chain {
+            DodgeChain::None => !state.dodging && state.wall_dodge_count == 0,
+            DodgeChain::DodgeJump => state.wall_dodge_count == 0,
+            DodgeChain::Unlimited => true,
+        };
+        if away_from_wall && allowed {
+            state.velocity = dodge_velocity_fixed::<Z_UP>(dodge, input.yaw, config);
+            state.dodging = true;
+            state.wall_dodge_count += 1;
+            events.push(MovementEvent::Dodged {
+                tick: state.tick,
+                direction: dodge,
+                wall: true,
+                speed: axis.horizontal(&state.velocity).magnitude(),
+            });
+            return;
+        }
+    }
+
+    let jump_pressed = input.jump && !state.jump_held;
+    if jump_pressed && state.dodging && !state.dodge_jumped && config.dodge_chain != DodgeChain::None {
+        axis.set_height(&mut state.velocity, config.jump_speed);
+        state.dodge_jumped = true;
+        state.jump_count += 1;
+        events.push(MovementEvent::Jumped {
+            tick: state.tick,
+            jump_number: state.jump_count,
+            speed: axis.horizontal(&state.velocity).magnitude(),
+        });
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = updateDodgeWall)]
+pub fn update_dodge_wall_wasm(state: &mut PlayerState, config: &MovementConfig, wall_ray_hit: Option<RayCollisionHit>) {
+    update_dodge_wall(state, config, wall_ray_hit);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = dodgeVelocity)]
+pub fn dodge_velocity_wasm(direction: DodgeDirection, yaw: f32, config: &MovementConfig) -> Vector3 {
+    dodge_velocity(direction, yaw, &config.in_world_units())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_tick;
+    use crate::tick::tests::y_up_config;
+
+    fn ground(state: &PlayerState) -> Option<RayCollisionHit> {
+        Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), state.origin.y))
+    }
+
+    #[test]
+    fn test_double_tap_dodges_and_recovers() {
+        let config = MovementConfig { dodge_enabled: true, ..y_up_config() };
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+        let right = PlayerInput { side_move: 1.0, ..PlayerInput::new() };
+        let idle = PlayerInput::new();
+        let dt = 1.0 / 64.0;
+        let tick = |state: &mut PlayerState, input: &PlayerInput| {
+            let hit = ground(state);
+            player_tick(state, input, &config, dt, hit)
+        };
+
+        tick(&mut state, &right);
+        tick(&mut state, &idle);
+        let events = tick(&mut state, &right);
+        assert!(matches!(events.as_slice(), [MovementEvent::Dodged { .. }]));
+        assert!(!state.on_ground);
+        assert!((state.velocity.x - config.dodge_speed).abs() < 0.001);
+
+        while !state.on_ground {
+            tick(&mut state, &idle);
+        }
+        assert!(state.dodge_recovery > 0.0);
+        // Neither jumping nor dodging during recovery
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        tick(&mut state, &jump);
+        assert!(state.on_ground);
+    }
+
+    #[test]
+    fn test_slow_taps_and_disabled_dodge_do_nothing() {
+        let right = PlayerInput { side_move: 1.0, ..PlayerInput::new() };
+        let idle = PlayerInput::new();
+        let dt = 1.0 / 64.0;
+
+        let enabled = MovementConfig { dodge_enabled: true, ..y_up_config() };
+        let mut state = PlayerState::default();
+        assert_eq!(dodge_request(&mut state, &right, &enabled, dt), DodgeDirection::None);
+        for _ in 0..32 {
+            assert_eq!(dodge_request(&mut state, &idle, &enabled, dt), DodgeDirection::None);
+        }
+        assert_eq!(dodge_request(&mut state, &right, &enabled, dt), DodgeDirection::None);
+
+        let config = y_up_config();
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+        for input in [right, idle, right] {
+            let hit = ground(&state);
+            assert!(player_tick(&mut state, &input, &config, dt, hit).is_empty());
+        }
+        assert!(state.on_ground);
+    }
+
+    #[test]
+    fn test_wall_dodge_chaining_rules() {
+        let base = MovementConfig { dodge_enabled: true, dodge_wall_enabled: true, ..y_up_config() };
+        // Wall on the player's left, facing right (+X)
+        let wall = Some(RayCollisionHit::new_native(Vector3::new(1.0, 0.0, 0.0), 16.0));
+        let dodge_right = PlayerInput { dodge: true, side_move: 1.0, ..PlayerInput::new() };
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        let dt = 1.0 / 64.0;
+
+        let wall_dodges = |config: &MovementConfig, dodging: bool| {
+            let mut state = PlayerState { dodging, ..PlayerState::new(Vector3::new(0.0, 100.0, 0.0)) };
+            let mut dodges = 0;
+            for _ in 0..3 {
+                update_dodge_wall(&mut state, config, wall);
+                let events = player_tick(&mut state, &dodge_right, config, dt, None);
+                dodges += events.iter().filter(|e| matches!(e, MovementEvent::Dodged { wall: true, .. })).count();
+                player_tick(&mut state, &PlayerInput::new(), config, dt, None);
+            }
+            dodges
+        };
+        assert_eq!(wall_dodges(&base, true), 0);
+        assert_eq!(wall_dodges(&base, false), 1);
+        let ut2004 = MovementConfig { dodge_chain: DodgeChain::DodgeJump, ..base };
+        assert_eq!(wall_dodges(&ut2004, true), 1);
+        let unlimited = MovementConfig { dodge_chain: DodgeChain::Unlimited, ..base };
+        assert_eq!(wall_dodges(&unlimited, true), 3);
+
+        // Only UT2004-style chaining allows a single jump during a dodge
+        let mut state = PlayerState { dodging: true, ..PlayerState::new(Vector3::new(0.0, 100.0, 0.0)) };
+        assert!(player_tick(&mut state, &jump, &base, dt, None).is_empty());
+        let mut state = PlayerState { dodging: true, ..PlayerState::new(Vector3::new(0.0, 100.0, 0.0)) };
+        assert_eq!(player_tick(&mut state, &jump, &ut2004, dt, None).len(), 1);
+        player_tick(&mut state, &PlayerInput::new(), &ut2004, dt, None);
+        assert!(player_tick(&mut state, &jump, &ut2004, dt, None).is_empty());
+
+        let parsed = MovementConfig::from_json(r#"{ "dodge_chain": "dodge_jump" }"#).unwrap();
+        assert_eq!(parsed.dodge_chain, DodgeChain::DodgeJump);
+    }
+}
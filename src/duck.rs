@@ -0,0 +1,155 @@
+//! Duck timing and hull
+//!
+//! On the ground a duck takes `duck_time` and standing up `unduck_time`. The
+//! eye follows `duck_amount` right away (see `eye`), while the hull keeps its
+//! old height until the transition completes and `PlayerState::ducked`
+//! flips, as in Source. In the air ducking is instant.
+//!
+//! `source_duck` fills in the rest of Source's duck state machine. The duck
+//! slowdown applies as soon as the transition starts instead of once it ends,
+//! and an instant duck in the air pulls the feet up by the difference between
+//! `hull_height` and `duck_hull_height` rather than lowering the head. Standing
+//! up in the air pushes the feet back down, but not through the ground below.
+//! This is what lets a duck jump clear ledges a plain jump cannot.
+//!
+//! `duck_spam_penalty` curbs crouch-spam peeking and duck-glitch boosts, like
+//! CS:GO's duck speed. Every duck press counts for `duck_spam_recovery_time`
+//! seconds, and each earlier press still counting slows ducking and standing
+//! up by the penalty. While any press counts, ducking in the air is no longer
+//! instant either. The count lives in `PlayerState::duck_spam`, so client
+//! prediction and the server simulate the same penalty tick for tick.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::hull::current_hull;
+use crate::{MovementConfig, PlayerState, RayCollisionHit, UpAxis};
+
+/// Height of the player's collision hull in world units
+pub fn current_hull_height(state: &PlayerState, config: &MovementConfig) -> f32 {
+    current_hull(state, config).height
+}
+
+/// Speed of the duck transition after the spam penalty (1.0 = unpenalized)
+pub fn duck_spam_factor(state: &PlayerState, config: &MovementConfig) -> f32 {
+    1.0 / (1.0 + config.duck_spam_penalty * state.duck_spam.max(0.0))
+}
+
+/// Count a new duck press and let old presses wear off
+pub(crate) fn update_duck_spam(state: &mut PlayerState, duck: bool, config: &MovementConfig, delta_time: f32) {
+    state.duck_spam = if config.duck_spam_recovery_time > 0.0 {
+        (state.duck_spam - delta_time / config.duck_spam_recovery_time).max(0.0)
+    } else {
+        0.0
+    };
+    if duck && !state.duck_held && config.duck_spam_penalty > 0.0 {
+        state.duck_spam += 1.0;
+    }
+    state.duck_held = duck;
+}
+
+/// Whether the ducked wish speed applies
+pub(crate) fn duck_slowed(state: &PlayerState, config: &MovementConfig) -> bool {
+    state.ducked || (config.source_duck && state.duck_amount > 0.0)
+}
+
+/// Move the feet after the hull switched in the air with `source_duck`
+pub(crate) fn shift_air_duck<const Z_UP: bool>(
+    state: &mut PlayerState,
+    config: &MovementConfig,
+    was_ducked: bool,
+    down_ray_hit: Option<RayCollisionHit>,
+) {
+    if !config.source_duck || state.on_ground || state.ducked == was_ducked {
+        return;
+    }
+    let difference = (config.hull_height - config.duck_hull_height).max(0.0);
+    let shift = if state.ducked {
+        difference
+    } else {
+        -down_ray_hit.map_or(difference, |hit| hit.distance_native().clamp(0.0, difference))
+    };
+    state.origin.add(&UpAxis::from_z_up(Z_UP).up().multiply_scalar(shift));
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = duckSpamFactor)]
+pub fn duck_spam_factor_wasm(state: &PlayerState, config: &MovementConfig) -> f32 {
+    duck_spam_factor(state, config)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = currentHullHeight)]
+pub fn current_hull_height_wasm(state: &PlayerState, config: &MovementConfig) -> f32 {
+    current_hull_height(state, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eye_position, player_tick, PlayerInput, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_source_duck_jump_lifts_the_feet() {
+        let config = MovementConfig { source_duck: true, ..y_up_config() };
+        let duck = PlayerInput { duck: true, ..PlayerInput::new() };
+        let mut plain = PlayerState::default();
+        let mut source = PlayerState::default();
+        let eye = eye_position(&source, &config);
+
+        player_tick(&mut plain, &duck, &y_up_config(), 1.0 / 64.0, None);
+        player_tick(&mut source, &duck, &config, 1.0 / 64.0, None);
+        assert!(plain.ducked && source.ducked);
+        assert_eq!(current_hull_height(&source, &config), 36.0);
+        // Both fall the same, but the Source duck raised the feet under a steady eye
+        assert!((source.origin.y() - plain.origin.y() - 36.0).abs() < 1e-4);
+        assert!((eye_position(&source, &config).y() - eye.y() - plain.origin.y()).abs() < 1e-4);
+
+        // Standing back up 10 units above the ground only lowers the feet 10 units
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 10.0));
+        let before = source.origin.y();
+        player_tick(&mut source, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+        assert!(!source.ducked);
+        assert!(source.origin.y() < before - 9.0 && source.origin.y() > before - 11.0);
+    }
+
+    #[test]
+    fn test_source_duck_slows_during_the_transition() {
+        let config = MovementConfig { source_duck: true, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let input = PlayerInput { forward_move: 1.0, duck: true, ..PlayerInput::new() };
+        let mut plain = PlayerState { on_ground: true, ..PlayerState::default() };
+        let mut source = plain;
+        for _ in 0..16 {
+            player_tick(&mut plain, &input, &y_up_config(), 1.0 / 64.0, ground);
+            player_tick(&mut source, &input, &config, 1.0 / 64.0, ground);
+        }
+        assert!(!plain.ducked && !source.ducked);
+        assert!(source.velocity.magnitude() < plain.velocity.magnitude());
+        assert!(source.velocity.magnitude() <= config.max_speed * config.duck_multiplier + 1e-3);
+    }
+
+    #[test]
+    fn test_duck_spam_slows_later_ducks() {
+        let config = MovementConfig { duck_spam_penalty: 0.5, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+        let dt = 1.0 / 64.0;
+        for duck in [true, false, true, false, true] {
+            player_tick(&mut state, &PlayerInput { duck, ..PlayerInput::new() }, &config, dt, ground);
+        }
+        // Three presses, minus four ticks of recovery
+        assert!((state.duck_spam - (3.0 - 4.0 * dt)).abs() < 1e-4);
+        let factor = duck_spam_factor(&state, &config);
+        assert!((factor - 1.0 / (1.0 + 0.5 * state.duck_spam)).abs() < 1e-6);
+
+        // In the air a spammed duck takes time instead of snapping
+        let mut airborne = PlayerState { on_ground: false, duck_amount: 0.0, duck_held: false, ..state };
+        player_tick(&mut airborne, &PlayerInput { duck: true, ..PlayerInput::new() }, &config, dt, None);
+        assert!(!airborne.ducked && airborne.duck_amount > 0.0 && airborne.duck_amount < 0.1);
+        let mut fresh = PlayerState::default();
+        player_tick(&mut fresh, &PlayerInput { duck: true, ..PlayerInput::new() }, &config, dt, None);
+        assert!(fresh.ducked);
+    }
+}
@@ -0,0 +1,77 @@
+//! Edgebugs
+//!
+//! In Source, a fall whose landing trace only clips the very edge of a ledge
+//! can end with the fall speed cleared but the player still in the air, so
+//! they slide off the edge at full speed without landing friction or fall
+//! damage. Movement maps build jumps around it. With `edgebug` set the same
+//! happens here: a falling player about to land on ground whose hit lies
+//! within `edgebug_tolerance` of the edge of its surface is moved down to the
+//! edge with the fall stopped, keeps its horizontal velocity, and an
+//! `Edgebugged` event is emitted instead of `Landed`.
+//!
+//! The down ray knows nothing of edges, so the host reports how far the hit is
+//! from one with `RayCollisionHit::with_edge_distance`; hits without it never
+//! edgebug. Players without horizontal speed land normally, so standing still
+//! on an edge cannot hover.
+
+use crate::{MovementConfig, MovementEvent, PlayerState, RayCollisionHit, UpAxis};
+
+/// Catch a landing on the edge of a surface, returning whether it did
+pub(crate) fn edgebug<const Z_UP: bool>(
+    state: &mut PlayerState,
+    config: &MovementConfig,
+    ground: &RayCollisionHit,
+    delta_time: f32,
+    events: &mut Vec<MovementEvent>,
+) -> bool {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let fall_speed = -axis.height(&state.velocity);
+    let distance = ground.distance_native();
+    let landing = distance <= config.ground_snap_distance.max(fall_speed * delta_time);
+    if !config.edgebug
+        || state.on_ground
+        || fall_speed <= 0.0
+        || !landing
+        || ground.edge_distance() > config.edgebug_tolerance
+        || axis.horizontal(&state.velocity).length_sq() == 0.0
+    {
+        return false;
+    }
+    state.origin.add(&axis.up().multiply_scalar(-distance));
+    axis.set_height(&mut state.velocity, 0.0);
+    events.push(MovementEvent::Edgebugged { tick: state.tick, landing_speed: fall_speed });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_edge_landing_keeps_speed() {
+        let config = MovementConfig { edgebug: true, ..y_up_config() };
+        let falling = PlayerState {
+            origin: Vector3::new(0.0, 1.0, 0.0),
+            velocity: Vector3::new(400.0, -600.0, 0.0),
+            ..PlayerState::default()
+        };
+        let edge = RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 1.0).with_edge_distance(0.5);
+
+        let mut state = falling;
+        let events = player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, Some(edge));
+        assert!(matches!(events.as_slice(), [MovementEvent::Edgebugged { landing_speed: 600.0, .. }]));
+        assert!(!state.on_ground);
+        assert_eq!(state.velocity.x, 400.0);
+        assert!(state.velocity.y <= 0.0 && state.velocity.y > -20.0);
+
+        // Away from the edge, or with the flag off, the fall lands
+        let mut inland = falling;
+        let events = player_tick(&mut inland, &PlayerInput::new(), &config, 1.0 / 64.0, Some(edge.with_edge_distance(5.0)));
+        assert!(matches!(events.as_slice(), [MovementEvent::Landed { .. }, ..]));
+        let mut plain = falling;
+        player_tick(&mut plain, &PlayerInput::new(), &y_up_config(), 1.0 / 64.0, Some(edge));
+        assert!(inland.on_ground && plain.on_ground);
+    }
+}
@@ -0,0 +1,138 @@
+//! Crate-wide error type for fallible operations
+//!
+//! The checked movement functions, the builders and config validation all
+//! return `AirStrafeError`. On the WASM side it is thrown as a typed
+//! `AirStrafeErrorInfo` object carrying a `kind` and a `message`, so JS code
+//! can branch on the kind instead of parsing strings.
+
+use std::fmt;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::checked::MovementError;
+use crate::config_format::ConfigError;
+
+/// Error returned by the crate's fallible operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum AirStrafeError {
+    /// An argument or state value is invalid (NaN, infinite, zero-length, ...)
+    InvalidInput(MovementError),
+    /// A config field holds a value outside its allowed range
+    ConfigOutOfRange { field: String, expected: &'static str },
+    /// Config text or a by-name config value could not be read
+    DeserializeFailed(ConfigError),
+    /// A host-provided collision trace failed or returned unusable data
+    TraceProviderError(String),
+    /// A movement script failed to compile
+    ScriptFailed(String),
+}
+
+impl fmt::Display for AirStrafeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AirStrafeError::InvalidInput(error) => write!(f, "invalid input: {error}"),
+            AirStrafeError::ConfigOutOfRange { field, expected } => {
+                write!(f, "config field `{field}` must be {expected}")
+            }
+            AirStrafeError::DeserializeFailed(error) => write!(f, "could not read config: {error}"),
+            AirStrafeError::TraceProviderError(message) => write!(f, "trace provider failed: {message}"),
+            AirStrafeError::ScriptFailed(message) => write!(f, "movement script failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AirStrafeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AirStrafeError::InvalidInput(error) => Some(error),
+            AirStrafeError::DeserializeFailed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<MovementError> for AirStrafeError {
+    fn from(error: MovementError) -> Self {
+        AirStrafeError::InvalidInput(error)
+    }
+}
+
+impl From<ConfigError> for AirStrafeError {
+    fn from(error: ConfigError) -> Self {
+        AirStrafeError::DeserializeFailed(error)
+    }
+}
+
+/// Kind of an `AirStrafeError`, as seen from JS
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AirStrafeErrorKind {
+    InvalidInput,
+    ConfigOutOfRange,
+    DeserializeFailed,
+    TraceProviderError,
+    ScriptFailed,
+}
+
+impl AirStrafeError {
+    /// The variant of this error without its details
+    pub fn kind(&self) -> AirStrafeErrorKind {
+        match self {
+            AirStrafeError::InvalidInput(_) => AirStrafeErrorKind::InvalidInput,
+            AirStrafeError::ConfigOutOfRange { .. } => AirStrafeErrorKind::ConfigOutOfRange,
+            AirStrafeError::DeserializeFailed(_) => AirStrafeErrorKind::DeserializeFailed,
+            AirStrafeError::TraceProviderError(_) => AirStrafeErrorKind::TraceProviderError,
+            AirStrafeError::ScriptFailed(_) => AirStrafeErrorKind::ScriptFailed,
+        }
+    }
+}
+
+/// Typed error object thrown to JS
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub struct AirStrafeErrorInfo {
+    kind: AirStrafeErrorKind,
+    message: String,
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl AirStrafeErrorInfo {
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> AirStrafeErrorKind {
+        self.kind
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<AirStrafeError> for JsValue {
+    fn from(error: AirStrafeError) -> Self {
+        AirStrafeErrorInfo {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions_pick_the_right_kind() {
+        let error = AirStrafeError::from(MovementError::ZeroWishDir);
+        assert_eq!(error.kind(), AirStrafeErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "invalid input: wish direction has zero length");
+
+        let error = AirStrafeError::from(ConfigError::UnknownField("max_sped".to_string()));
+        assert_eq!(error.kind(), AirStrafeErrorKind::DeserializeFailed);
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
@@ -0,0 +1,414 @@
+//! Movement events emitted by `player_tick`
+//!
+//! Games use these to trigger sounds (including footsteps) and particles without re-deriving state
+//! transitions from consecutive `PlayerState`s.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::dodge::DodgeDirection;
+
+/// A state transition that happened during a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovementEvent {
+    /// The player left the ground by jumping
+    Jumped {
+        tick: u64,
+        /// Number of jumps since the state was created, starting at 1
+        jump_number: u32,
+        /// Horizontal speed at takeoff
+        speed: f32,
+    },
+    /// The player touched walkable ground after being airborne
+    Landed {
+        tick: u64,
+        /// Downward speed just before touching the ground
+        landing_speed: f32,
+        /// Material id reported by the down ray hit
+        surface_material: u32,
+        /// Normalized impact intensity, see `MovementConfig::landing_impact`
+        impact: f32,
+        /// Whether the player landed from a ground pound, see `ground_pound`
+        ground_pound: bool,
+    },
+    /// The player started sliding down a surface too steep to stand on
+    SlideStarted { tick: u64, surface_material: u32 },
+    /// The player stopped sliding
+    SlideStopped { tick: u64 },
+    /// A footstep while moving on the ground, see `MovementConfig::footstep_interval`
+    Footstep {
+        tick: u64,
+        /// Material id reported by the down ray hit
+        surface_material: u32,
+        /// Horizontal speed when the step happened
+        speed: f32,
+    },
+    /// The player dodged, see `dodge`
+    Dodged {
+        tick: u64,
+        direction: DodgeDirection,
+        /// Whether the player dodged off a wall while airborne
+        wall: bool,
+        /// Horizontal speed at takeoff
+        speed: f32,
+    },
+    /// The player dashed in the air, see `air_dash`
+    AirDashed {
+        tick: u64,
+        /// Speed after the dash
+        speed: f32,
+    },
+    /// The player jumped on the landing tick without landing, see `jumpbug`
+    Jumpbugged {
+        tick: u64,
+        /// Downward speed the landing would have had
+        landing_speed: f32,
+    },
+    /// A fall clipped the edge of a surface and kept going instead of landing, see `edgebug`
+    Edgebugged {
+        tick: u64,
+        /// Downward speed the landing would have had
+        landing_speed: f32,
+    },
+    /// A fall bounced back up off the ground instead of landing, see `overbounce`
+    Overbounced {
+        tick: u64,
+        /// Vertical speed the player bounced back up with
+        speed: f32,
+    },
+    /// The player stepped up onto a ledge, see `MovementConfig::step_height` and `step_smooth`
+    ///
+    /// Lifts below `MovementConfig::min_step_event_height` are not reported.
+    SteppedUp {
+        tick: u64,
+        /// How far the origin was lifted
+        height: f32,
+    },
+    /// The player's origin entered a trigger volume, see `triggers`
+    TriggerEntered { tick: u64, trigger: u32 },
+    /// The player's origin stayed inside a trigger volume for another tick
+    TriggerStayed { tick: u64, trigger: u32 },
+    /// The player's origin left a trigger volume
+    TriggerExited { tick: u64, trigger: u32 },
+}
+
+impl MovementEvent {
+    /// The tick the event happened on
+    pub fn tick(&self) -> u64 {
+        match *self {
+            MovementEvent::Jumped { tick, .. }
+            | MovementEvent::Landed { tick, .. }
+            | MovementEvent::SlideStarted { tick, .. }
+            | MovementEvent::SlideStopped { tick }
+            | MovementEvent::Footstep { tick, .. }
+            | MovementEvent::Dodged { tick, .. }
+            | MovementEvent::AirDashed { tick, .. }
+            | MovementEvent::Jumpbugged { tick, .. }
+            | MovementEvent::Edgebugged { tick, .. }
+            | MovementEvent::Overbounced { tick, .. }
+            | MovementEvent::SteppedUp { tick, .. }
+            | MovementEvent::TriggerEntered { tick, .. }
+            | MovementEvent::TriggerStayed { tick, .. }
+            | MovementEvent::TriggerExited { tick, .. } => tick,
+        }
+    }
+}
+
+/// Kind of a `MovementEvent`, as seen from JS
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementEventKind {
+    Jumped,
+    Landed,
+    SlideStarted,
+    SlideStopped,
+    Footstep,
+    Dodged,
+    AirDashed,
+    Jumpbugged,
+    Edgebugged,
+    Overbounced,
+    SteppedUp,
+    TriggerEntered,
+    TriggerStayed,
+    TriggerExited,
+}
+
+impl MovementEventKind {
+    /// Snake case name of the kind, as used in exported files
+    pub fn name(&self) -> &'static str {
+        match self {
+            MovementEventKind::Jumped => "jumped",
+            MovementEventKind::Landed => "landed",
+            MovementEventKind::SlideStarted => "slide_started",
+            MovementEventKind::SlideStopped => "slide_stopped",
+            MovementEventKind::Footstep => "footstep",
+            MovementEventKind::Dodged => "dodged",
+            MovementEventKind::AirDashed => "air_dashed",
+            MovementEventKind::Jumpbugged => "jumpbugged",
+            MovementEventKind::Edgebugged => "edgebugged",
+            MovementEventKind::Overbounced => "overbounced",
+            MovementEventKind::SteppedUp => "stepped_up",
+            MovementEventKind::TriggerEntered => "trigger_entered",
+            MovementEventKind::TriggerStayed => "trigger_stayed",
+            MovementEventKind::TriggerExited => "trigger_exited",
+        }
+    }
+}
+
+/// Flattened `MovementEvent` for JS; fields that don't apply to the kind are 0
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovementEventInfo {
+    pub kind: MovementEventKind,
+    pub tick: u64,
+    /// Takeoff speed for `Jumped`, `Dodged` and `AirDashed`, landing speed for `Landed`, `Jumpbugged` and
+    /// `Edgebugged`, walking speed for `Footstep`, bounce speed for `Overbounced`
+    pub speed: f32,
+    pub jump_number: u32,
+    pub surface_material: u32,
+    /// Impact intensity for `Landed`
+    pub impact: f32,
+    /// Whether a `Landed` event ended a ground pound
+    pub ground_pound: bool,
+    /// Height stepped up for `SteppedUp`
+    pub height: f32,
+    /// Trigger id for `TriggerEntered`, `TriggerStayed` and `TriggerExited`
+    pub trigger: u32,
+}
+
+/// Reusable event list for `playerTickInto`
+///
+/// `playerTick` returns a new JS array of new event objects every tick. A
+/// buffer keeps the events in WASM memory instead: pass the same buffer to
+/// every tick, read the few events with `kind` and `get`, and `clear` it once
+/// they are handled. Once it has grown to the busiest tick, ticking into it
+/// allocates nothing.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MovementEventBuffer {
+    pub(crate) events: Vec<MovementEvent>,
+}
+
+impl MovementEventBuffer {
+    /// Create a buffer with room for `capacity` events
+    pub fn new(capacity: usize) -> MovementEventBuffer {
+        MovementEventBuffer { events: Vec::with_capacity(capacity) }
+    }
+
+    /// The buffered events, oldest first
+    pub fn events(&self) -> &[MovementEvent] {
+        &self.events
+    }
+
+    /// Number of buffered events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no event is buffered
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Drop every event, keeping the allocation
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Kind of the event at `index`
+    pub fn kind(&self, index: usize) -> Option<MovementEventKind> {
+        self.get(index).map(|info| info.kind)
+    }
+
+    /// The event at `index`, flattened
+    pub fn get(&self, index: usize) -> Option<MovementEventInfo> {
+        self.events.get(index).map(|&event| MovementEventInfo::from(event))
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MovementEventBuffer {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(capacity: usize) -> MovementEventBuffer {
+        MovementEventBuffer::new(capacity)
+    }
+
+    #[wasm_bindgen(getter = length)]
+    pub fn len_wasm(&self) -> usize {
+        self.len()
+    }
+
+    #[wasm_bindgen(js_name = clear)]
+    pub fn clear_wasm(&mut self) {
+        self.clear();
+    }
+
+    #[wasm_bindgen(js_name = kind)]
+    pub fn kind_wasm(&self, index: usize) -> Option<MovementEventKind> {
+        self.kind(index)
+    }
+
+    #[wasm_bindgen(js_name = get)]
+    pub fn get_wasm(&self, index: usize) -> Option<MovementEventInfo> {
+        self.get(index)
+    }
+}
+
+impl From<MovementEvent> for MovementEventInfo {
+    fn from(event: MovementEvent) -> Self {
+        let info = MovementEventInfo {
+            kind: MovementEventKind::SlideStopped,
+            tick: event.tick(),
+            speed: 0.0,
+            jump_number: 0,
+            surface_material: 0,
+            impact: 0.0,
+            ground_pound: false,
+            height: 0.0,
+            trigger: 0,
+        };
+        match event {
+            MovementEvent::Jumped { jump_number, speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::Jumped,
+                speed,
+                jump_number,
+                ..info
+            },
+            MovementEvent::Landed { landing_speed, surface_material, impact, ground_pound, .. } => MovementEventInfo {
+                kind: MovementEventKind::Landed,
+                speed: landing_speed,
+                surface_material,
+                impact,
+                ground_pound,
+                ..info
+            },
+            MovementEvent::SlideStarted { surface_material, .. } => MovementEventInfo {
+                kind: MovementEventKind::SlideStarted,
+                surface_material,
+                ..info
+            },
+            MovementEvent::SlideStopped { .. } => info,
+            MovementEvent::Footstep { surface_material, speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::Footstep,
+                speed,
+                surface_material,
+                ..info
+            },
+            MovementEvent::Dodged { speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::Dodged,
+                speed,
+                ..info
+            },
+            MovementEvent::AirDashed { speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::AirDashed,
+                speed,
+                ..info
+            },
+            MovementEvent::Jumpbugged { landing_speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::Jumpbugged,
+                speed: landing_speed,
+                ..info
+            },
+            MovementEvent::Edgebugged { landing_speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::Edgebugged,
+                speed: landing_speed,
+                ..info
+            },
+            MovementEvent::Overbounced { speed, .. } => MovementEventInfo {
+                kind: MovementEventKind::Overbounced,
+                speed,
+                ..info
+            },
+            MovementEvent::SteppedUp { height, .. } => MovementEventInfo {
+                kind: MovementEventKind::SteppedUp,
+                height,
+                ..info
+            },
+            MovementEvent::TriggerEntered { trigger, .. } => MovementEventInfo {
+                kind: MovementEventKind::TriggerEntered,
+                trigger,
+                ..info
+            },
+            MovementEvent::TriggerStayed { trigger, .. } => MovementEventInfo {
+                kind: MovementEventKind::TriggerStayed,
+                trigger,
+                ..info
+            },
+            MovementEvent::TriggerExited { trigger, .. } => MovementEventInfo {
+                kind: MovementEventKind::TriggerExited,
+                trigger,
+                ..info
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// One event of every kind, on ticks 1, 2, ...
+    pub(crate) fn every_event() -> Vec<MovementEvent> {
+        vec![
+            MovementEvent::Jumped { tick: 1, jump_number: 3, speed: 300.0 },
+            MovementEvent::Landed {
+                tick: 2,
+                landing_speed: 500.0,
+                surface_material: 4,
+                impact: 0.5,
+                ground_pound: true,
+            },
+            MovementEvent::SlideStarted { tick: 3, surface_material: 5 },
+            MovementEvent::SlideStopped { tick: 4 },
+            MovementEvent::Footstep { tick: 5, surface_material: 6, speed: 250.0 },
+            MovementEvent::Dodged { tick: 6, direction: DodgeDirection::Left, wall: true, speed: 600.0 },
+            MovementEvent::AirDashed { tick: 7, speed: 700.0 },
+            MovementEvent::Jumpbugged { tick: 8, landing_speed: 800.0 },
+            MovementEvent::Edgebugged { tick: 9, landing_speed: 900.0 },
+            MovementEvent::Overbounced { tick: 10, speed: 100.0 },
+            MovementEvent::SteppedUp { tick: 11, height: 18.0 },
+            MovementEvent::TriggerEntered { tick: 12, trigger: 7 },
+            MovementEvent::TriggerStayed { tick: 13, trigger: 8 },
+            MovementEvent::TriggerExited { tick: 14, trigger: 9 },
+        ]
+    }
+
+    #[test]
+    fn test_every_event_flattens_to_its_own_kind() {
+        let infos: Vec<MovementEventInfo> = every_event().into_iter().map(MovementEventInfo::from).collect();
+        for (index, info) in infos.iter().enumerate() {
+            assert_eq!(info.tick, index as u64 + 1);
+            assert_eq!(every_event()[index].tick(), info.tick);
+            // Kinds and their names are distinct
+            let distinct = |other: &MovementEventInfo| other.kind != info.kind && other.kind.name() != info.kind.name();
+            assert!(infos[..index].iter().all(distinct));
+        }
+        let landed = infos[1];
+        assert_eq!(landed.kind, MovementEventKind::Landed);
+        assert_eq!((landed.speed, landed.surface_material, landed.impact, landed.ground_pound), (500.0, 4, 0.5, true));
+        assert_eq!((infos[10].kind.name(), infos[10].height), ("stepped_up", 18.0));
+        assert_eq!((infos[13].kind, infos[13].trigger), (MovementEventKind::TriggerExited, 9));
+        // Fields that don't apply are 0
+        let stopped = infos[3];
+        assert_eq!(stopped.kind, MovementEventKind::SlideStopped);
+        assert_eq!((stopped.speed, stopped.jump_number, stopped.surface_material, stopped.height), (0.0, 0, 0, 0.0));
+        assert_eq!((infos[0].jump_number, infos[0].height, infos[0].trigger), (3, 0.0, 0));
+    }
+
+    #[test]
+    fn test_buffer_reads_events_by_index_and_keeps_its_allocation() {
+        let mut buffer = MovementEventBuffer::new(16);
+        assert!(buffer.is_empty() && buffer.get(0).is_none());
+        buffer.events.extend(every_event());
+        assert_eq!(buffer.len(), 14);
+        assert_eq!(buffer.events(), every_event().as_slice());
+        assert_eq!(buffer.kind(0), Some(MovementEventKind::Jumped));
+        assert_eq!(buffer.get(10).map(|info| info.height), Some(18.0));
+        assert_eq!(buffer.kind(14), None);
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert!(buffer.events.capacity() >= 16);
+    }
+}
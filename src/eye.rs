@@ -0,0 +1,82 @@
+//! Camera eye height
+//!
+//! The eye moves between `eye_height` and `duck_eye_height` following the
+//! simulated `duck_amount`, eased with Source's SimpleSpline curve. Reading the
+//! camera height from here keeps it in sync with the hull `player_tick` uses,
+//! including instant ducks while airborne.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerState, Vector3};
+
+/// Eye height above the origin in world units for the state's duck progress
+pub fn eye_height(state: &PlayerState, config: &MovementConfig) -> f32 {
+    let t = state.duck_amount.clamp(0.0, 1.0);
+    // SimpleSpline: smooth start and stop, matching Source's duck view offset
+    let eased = t * t * (3.0 - 2.0 * t);
+    let height = config.eye_height + (config.duck_eye_height - config.eye_height) * eased;
+    config.to_world_units(height)
+}
+
+/// World position of the camera eye
+pub fn eye_position(state: &PlayerState, config: &MovementConfig) -> Vector3 {
+    let mut eye = state.origin;
+    eye.add(&config.up_axis.up().multiply_scalar(eye_height(state, config)));
+    eye
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = eyeHeight)]
+pub fn eye_height_wasm(state: &PlayerState, config: &MovementConfig) -> f32 {
+    eye_height(state, config)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = eyePosition)]
+pub fn eye_position_wasm(state: &PlayerState, config: &MovementConfig) -> Vector3 {
+    eye_position(state, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_eye_follows_duck_on_ground() {
+        let config = y_up_config();
+        let mut state = PlayerState::default();
+        let input = PlayerInput { duck: true, ..PlayerInput::new() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        assert_eq!(eye_height(&state, &config), 64.0);
+
+        // Halfway through the duck the eye is halfway down and the hull still standing
+        let delta_time = config.duck_time / 32.0;
+        for _ in 0..16 {
+            player_tick(&mut state, &input, &config, delta_time, ground);
+        }
+        assert!((state.duck_amount - 0.5).abs() < 0.001);
+        assert!((eye_height(&state, &config) - 46.0).abs() < 0.01);
+        assert!(!state.ducked);
+
+        for _ in 0..16 {
+            player_tick(&mut state, &input, &config, delta_time, ground);
+        }
+        assert!(state.ducked);
+        assert_eq!(eye_position(&state, &config), Vector3::new(0.0, 28.0, 0.0));
+    }
+
+    #[test]
+    fn test_duck_is_instant_while_airborne() {
+        let config = y_up_config();
+        let mut state = PlayerState::new(Vector3::new(0.0, 100.0, 0.0));
+        player_tick(&mut state, &PlayerInput { duck: true, ..PlayerInput::new() }, &config, 1.0 / 64.0, None);
+        assert!(state.ducked);
+        assert_eq!(eye_height(&state, &config), 28.0);
+
+        let meters = MovementConfig { unit_scale: 0.0254, ..config };
+        assert!((eye_height(&state, &meters) - 28.0 * 0.0254).abs() < 0.0001);
+    }
+}
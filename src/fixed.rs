@@ -0,0 +1,127 @@
+//! Build-time specialization of hot config decisions
+//!
+//! Every tick asks which axis is up and which gravity model is in use, dozens
+//! of times per player. The tick code and the helpers it calls are generic
+//! over both as const parameters, `Z_UP` and `LEGACY_GRAVITY`, so each of
+//! the four instantiations has them as constants and no branches on them.
+//! `player_tick` picks the instantiation matching the config once per tick;
+//! `player_tick_fixed_into` names one directly, and falls back to
+//! `player_tick_into` for a config that disagrees with it.
+//!
+//! Servers that never change these can pin them with cargo features:
+//! `up-axis-y` or `up-axis-z`, and `standard-gravity` or `legacy-gravity`.
+//! `MovementConfig::default` starts from the pinned values and
+//! `MovementConfig::validate` rejects configs the build was not specialized
+//! for. The features are additive: enabling both of a pair allows both values
+//! again.
+
+use crate::{AirStrafeError, MovementConfig, UpAxis};
+
+/// Evaluate `$body` with the const `$z_up` (and `$legacy`) naming a runtime up axis (and gravity model)
+///
+/// Bridges a runtime config to the generic tick code: every arm is its own
+/// instantiation of `$body`.
+macro_rules! with_fixed {
+    ($up_axis:expr, $z_up:ident => $body:expr) => {
+        match $up_axis {
+            $crate::UpAxis::Y => {
+                const $z_up: bool = false;
+                $body
+            }
+            $crate::UpAxis::Z => {
+                const $z_up: bool = true;
+                $body
+            }
+        }
+    };
+    ($up_axis:expr, $legacy_gravity:expr, $z_up:ident, $legacy:ident => $body:expr) => {
+        if $legacy_gravity {
+            const $legacy: bool = true;
+            $crate::fixed::with_fixed!($up_axis, $z_up => $body)
+        } else {
+            const $legacy: bool = false;
+            $crate::fixed::with_fixed!($up_axis, $z_up => $body)
+        }
+    };
+}
+pub(crate) use with_fixed;
+
+/// The up axis fixed by a cargo feature, `None` when `up_axis` is read at runtime
+pub const FIXED_UP_AXIS: Option<UpAxis> = match (cfg!(feature = "up-axis-y"), cfg!(feature = "up-axis-z")) {
+    (true, false) => Some(UpAxis::Y),
+    (false, true) => Some(UpAxis::Z),
+    _ => None,
+};
+
+/// The `legacy_gravity` value fixed by a cargo feature, `None` when it is read at runtime
+pub const FIXED_LEGACY_GRAVITY: Option<bool> =
+    match (cfg!(feature = "standard-gravity"), cfg!(feature = "legacy-gravity")) {
+        (true, false) => Some(false),
+        (false, true) => Some(true),
+        _ => None,
+    };
+
+/// Reject configs that disagree with the decisions fixed at build time
+pub(crate) fn check_fixed(config: &MovementConfig) -> Result<(), AirStrafeError> {
+    let out_of_range = |field: &str| AirStrafeError::ConfigOutOfRange {
+        field: field.to_string(),
+        expected: "the value this build was compiled for",
+    };
+    if FIXED_UP_AXIS.is_some_and(|axis| axis != config.up_axis) {
+        return Err(out_of_range("up_axis"));
+    }
+    if FIXED_LEGACY_GRAVITY.is_some_and(|legacy| legacy != config.legacy_gravity) {
+        return Err(out_of_range("legacy_gravity"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick_fixed_into, player_tick_into, MovementEvent, PlayerInput, PlayerState, RayCollisionHit, Vector3};
+
+    #[test]
+    fn test_configs_must_match_the_fixed_decisions() {
+        for up_axis in [UpAxis::Y, UpAxis::Z] {
+            let legacy_gravity = FIXED_LEGACY_GRAVITY.unwrap_or_default();
+            let config = MovementConfig { up_axis, legacy_gravity, ..MovementConfig::default() };
+            assert_eq!(config.validate().is_ok(), FIXED_UP_AXIS.is_none_or(|axis| axis == up_axis));
+        }
+        for legacy_gravity in [false, true] {
+            let up_axis = FIXED_UP_AXIS.unwrap_or_default();
+            let config = MovementConfig { up_axis, legacy_gravity, ..MovementConfig::default() };
+            assert_eq!(config.validate().is_ok(), FIXED_LEGACY_GRAVITY.is_none_or(|legacy| legacy == legacy_gravity));
+        }
+    }
+
+    #[test]
+    fn test_fixed_instantiations_match_the_runtime_path() {
+        type Tick = fn(&mut PlayerState, &PlayerInput, &MovementConfig, f32, Option<RayCollisionHit>, &mut Vec<MovementEvent>);
+        let ticks: [Tick; 5] = [
+            player_tick_into,
+            player_tick_fixed_into::<false, false>,
+            player_tick_fixed_into::<false, true>,
+            player_tick_fixed_into::<true, false>,
+            player_tick_fixed_into::<true, true>,
+        ];
+        let input = PlayerInput { forward_move: 1.0, jump: true, yaw: 0.4, ..PlayerInput::new() };
+        for up_axis in [UpAxis::Y, UpAxis::Z] {
+            for legacy_gravity in [false, true] {
+                let config = MovementConfig { up_axis, legacy_gravity, ..MovementConfig::default() };
+                let ground = Some(RayCollisionHit::new_native(up_axis.up(), 0.0));
+                let run = |tick: &Tick| {
+                    let mut state = PlayerState::new(Vector3::new(0.0, 0.0, 0.0));
+                    let mut events = Vec::new();
+                    for _ in 0..32 {
+                        tick(&mut state, &input, &config, 1.0 / 64.0, ground, &mut events);
+                    }
+                    (state, events)
+                };
+                // Every instantiation gives the runtime result, the mismatched ones by falling back to it
+                let runtime = run(&ticks[0]);
+                assert!(ticks[1..].iter().all(|tick| run(tick) == runtime));
+            }
+        }
+    }
+}
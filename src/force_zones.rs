@@ -0,0 +1,184 @@
+//! Wind and force zones
+//!
+//! A `ForceZone` is an axis-aligned box that pushes players inside it, either
+//! with a continuous acceleration (wind, fans) or with a velocity bias
+//! (conveyors, currents). `ForceZones` is a `MovementModifier`: register it on
+//! a player with `MovementModifiers::push` and `player_tick_with_modifiers`
+//! applies the zones around the player's origin at the right stages.
+//! Acceleration goes into the velocity before friction and air control, so
+//! fan jumps carry the player and air strafing still works against the wind;
+//! a velocity bias only moves the player and never builds up.
+//!
+//! Zones are in world units. Like other modifiers they are not available
+//! through WASM.
+
+use crate::modifiers::{ModifierContext, MovementModifier};
+use crate::Vector3;
+
+/// How a zone's force acts on the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForceKind {
+    /// World units per second squared, added to the velocity every tick
+    Acceleration,
+    /// World units per second, added to the displacement but not the velocity
+    VelocityBias,
+}
+
+/// An axis-aligned box applying a force to players inside it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForceZone {
+    min: Vector3,
+    max: Vector3,
+    force: Vector3,
+    kind: ForceKind,
+    falloff: f32,
+}
+
+impl ForceZone {
+    /// A zone spanning the box between two corners with full force everywhere
+    pub fn new(corner_a: Vector3, corner_b: Vector3, force: Vector3, kind: ForceKind) -> ForceZone {
+        ForceZone {
+            min: Vector3::new(corner_a.x.min(corner_b.x), corner_a.y.min(corner_b.y), corner_a.z.min(corner_b.z)),
+            max: Vector3::new(corner_a.x.max(corner_b.x), corner_a.y.max(corner_b.y), corner_a.z.max(corner_b.z)),
+            force,
+            kind,
+            falloff: 0.0,
+        }
+    }
+
+    /// Fade the force out linearly over `distance` towards the zone's faces
+    pub fn with_falloff(mut self, distance: f32) -> ForceZone {
+        self.falloff = distance.max(0.0);
+        self
+    }
+
+    /// How a zone's force acts on the player
+    pub fn kind(&self) -> ForceKind {
+        self.kind
+    }
+
+    /// The fraction of the force applied at a point (0.0 outside the zone)
+    pub fn strength_at(&self, point: &Vector3) -> f32 {
+        let edge = (point.x - self.min.x)
+            .min(self.max.x - point.x)
+            .min(point.y - self.min.y)
+            .min(self.max.y - point.y)
+            .min(point.z - self.min.z)
+            .min(self.max.z - point.z);
+        if edge < 0.0 {
+            0.0
+        } else if self.falloff > 0.0 {
+            (edge / self.falloff).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// The force at a point, scaled by the falloff
+    pub fn force_at(&self, point: &Vector3) -> Vector3 {
+        self.force.multiply_scalar(self.strength_at(point))
+    }
+}
+
+/// The force zones of a level, applied as a movement modifier
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForceZones {
+    zones: Vec<ForceZone>,
+}
+
+impl ForceZones {
+    /// Create an empty zone list
+    pub fn new() -> ForceZones {
+        ForceZones::default()
+    }
+
+    /// Register a zone; overlapping zones add up
+    pub fn push(&mut self, zone: ForceZone) {
+        self.zones.push(zone);
+    }
+
+    /// Remove every zone
+    pub fn clear(&mut self) {
+        self.zones.clear();
+    }
+
+    /// Number of registered zones
+    pub fn len(&self) -> usize {
+        self.zones.len()
+    }
+
+    /// Whether no zone is registered
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// Sum of the forces of one kind at a point
+    pub fn force_at(&self, point: &Vector3, kind: ForceKind) -> Vector3 {
+        let mut force = Vector3::new(0.0, 0.0, 0.0);
+        for zone in self.zones.iter().filter(|zone| zone.kind == kind) {
+            force.add(&zone.force_at(point));
+        }
+        force
+    }
+}
+
+impl MovementModifier for ForceZones {
+    fn external_acceleration(&mut self, ctx: &ModifierContext) -> Vector3 {
+        self.force_at(&ctx.state.origin, ForceKind::Acceleration)
+    }
+
+    fn base_velocity(&mut self, ctx: &ModifierContext) -> Vector3 {
+        self.force_at(&ctx.state.origin, ForceKind::VelocityBias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick_with_modifiers, MovementModifiers, PlayerInput, PlayerState, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_fan_lifts_player_off_the_ground() {
+        let config = y_up_config();
+        let mut zones = ForceZones::new();
+        let lift = Vector3::new(0.0, config.gravity * 3.0, 0.0);
+        let (bottom, top) = (Vector3::new(-64.0, -64.0, -64.0), Vector3::new(64.0, 512.0, 64.0));
+        let fan = ForceZone::new(bottom, top, lift, ForceKind::Acceleration).with_falloff(64.0);
+        assert_eq!(fan.strength_at(&Vector3::new(0.0, 480.0, 0.0)), 0.5);
+        zones.push(fan);
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("zones", Box::new(zones));
+
+        let mut state = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).build().unwrap();
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let dt = 1.0 / 64.0;
+        for _ in 0..32 {
+            let hit = ground.filter(|_| state.origin.y < 1.0);
+            player_tick_with_modifiers(&mut state, &PlayerInput::new(), &config, dt, hit, &mut modifiers);
+        }
+        assert!(!state.on_ground);
+        // Twice gravity upwards for half a second
+        assert!((state.velocity.y - config.gravity).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_velocity_bias_moves_without_building_up() {
+        let config = y_up_config();
+        let mut zones = ForceZones::new();
+        let current = Vector3::new(100.0, 0.0, 0.0);
+        let big = Vector3::new(1000.0, 1000.0, 1000.0);
+        zones.push(ForceZone::new(-big, big, current, ForceKind::VelocityBias));
+        assert_eq!(zones.force_at(&Vector3::new(2000.0, 0.0, 0.0), ForceKind::VelocityBias), Vector3::new(0.0, 0.0, 0.0));
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("zones", Box::new(zones));
+
+        let mut state = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).build().unwrap();
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        for _ in 0..64 {
+            player_tick_with_modifiers(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, ground, &mut modifiers);
+        }
+        assert!((state.origin.x - 100.0).abs() < 0.01);
+        assert_eq!(state.velocity, Vector3::new(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,299 @@
+//! One WASM call per frame
+//!
+//! Ticking players one call at a time crosses the JS/WASM boundary several
+//! times per player and frame. A `FrameBuffer` owns a `World` instead: JS
+//! writes every player's input and ground hit into one `Float32Array`, calls
+//! `process` once, and reads the results back from the same array.
+//!
+//! The array holds `FRAME_STRIDE` floats per player, in player index order.
+//! The host fills the input slots before the call:
+//!
+//! | Offset | Input |
+//! |---|---|
+//! | `FRAME_FORWARD_MOVE` (0) | `PlayerInput::forward_move` |
+//! | `FRAME_SIDE_MOVE` (1) | `PlayerInput::side_move` |
+//! | `FRAME_UP_MOVE` (2) | `PlayerInput::up_move` |
+//! | `FRAME_YAW` (3) | `PlayerInput::yaw` |
+//! | `FRAME_PITCH` (4) | `PlayerInput::pitch` |
+//! | `FRAME_LEAN` (5) | `PlayerInput::lean` |
+//! | `FRAME_BUTTONS` (6) | `FRAME_BUTTON_*` bits of the held buttons, plus `jump_presses` from bit `FRAME_JUMP_PRESSES_SHIFT` |
+//! | `FRAME_GROUND_DISTANCE` (7) | Down ray hit distance, negative for no hit |
+//! | `FRAME_GROUND_NORMAL` (8-10) | Down ray hit normal |
+//! | `FRAME_GROUND_MATERIAL` (11) | Down ray hit surface material id |
+//!
+//! and `process` overwrites the output slots:
+//!
+//! | Offset | Output |
+//! |---|---|
+//! | `FRAME_ORIGIN` (12-14) | Position after the tick |
+//! | `FRAME_VELOCITY` (15-17) | Velocity after the tick |
+//! | `FRAME_FLAGS` (18) | `PLAYER_ON_GROUND` and `PLAYER_DUCKED` bits |
+//! | `FRAME_EVENTS` (19) | Bit `1 << MovementEventKind` for each kind of event this tick |
+//!
+//! Bit masks are stored as float values and are exact below 2^24.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::checked::{check_delta_time, MovementError};
+use crate::events::MovementEventInfo;
+#[cfg(feature = "wasm")]
+use crate::{PlayerId, PlayerState};
+use crate::{AirStrafeError, MovementConfig, PlayerInput, RayCollisionHit, Vector3, World};
+
+/// Floats per player in a frame buffer
+pub const FRAME_STRIDE: usize = 20;
+pub const FRAME_FORWARD_MOVE: usize = 0;
+pub const FRAME_SIDE_MOVE: usize = 1;
+pub const FRAME_UP_MOVE: usize = 2;
+pub const FRAME_YAW: usize = 3;
+pub const FRAME_PITCH: usize = 4;
+pub const FRAME_LEAN: usize = 5;
+pub const FRAME_BUTTONS: usize = 6;
+pub const FRAME_GROUND_DISTANCE: usize = 7;
+pub const FRAME_GROUND_NORMAL: usize = 8;
+pub const FRAME_GROUND_MATERIAL: usize = 11;
+pub const FRAME_ORIGIN: usize = 12;
+pub const FRAME_VELOCITY: usize = 15;
+pub const FRAME_FLAGS: usize = 18;
+pub const FRAME_EVENTS: usize = 19;
+
+pub const FRAME_BUTTON_JUMP: u32 = 1 << 0;
+pub const FRAME_BUTTON_DUCK: u32 = 1 << 1;
+pub const FRAME_BUTTON_SPRINT: u32 = 1 << 2;
+pub const FRAME_BUTTON_JET: u32 = 1 << 3;
+pub const FRAME_BUTTON_GLIDE: u32 = 1 << 4;
+pub const FRAME_BUTTON_SKI: u32 = 1 << 5;
+pub const FRAME_BUTTON_DODGE: u32 = 1 << 6;
+pub const FRAME_BUTTON_GROUND_POUND: u32 = 1 << 7;
+pub const FRAME_BUTTON_AIR_DASH: u32 = 1 << 8;
+pub const FRAME_BUTTON_FIRE: u32 = 1 << 9;
+pub const FRAME_BUTTON_DETACH: u32 = 1 << 10;
+/// First bit of the 8-bit `PlayerInput::jump_presses` count in `FRAME_BUTTONS`
+pub const FRAME_JUMP_PRESSES_SHIFT: u32 = 16;
+
+/// A `World` driven through one shared `f32` buffer per frame
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default)]
+pub struct FrameBuffer {
+    world: World,
+    inputs: Vec<PlayerInput>,
+    down_ray_hits: Vec<Option<RayCollisionHit>>,
+}
+
+impl FrameBuffer {
+    /// Create a frame buffer without players, simulating them with `config`
+    pub fn new(config: MovementConfig) -> FrameBuffer {
+        FrameBuffer { world: World::new(config), ..FrameBuffer::default() }
+    }
+
+    /// The players behind the buffer
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// The players behind the buffer, for spawning, removing and editing them
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Length of the buffer `process` expects for the current players
+    pub fn buffer_len(&self) -> usize {
+        self.world.len() * FRAME_STRIDE
+    }
+
+    /// Tick every player with the inputs in `buffer` and write the results back into it
+    ///
+    /// # Errors
+    /// `InvalidInput` if `buffer` is shorter than `buffer_len()` or `delta_time`
+    /// is not a positive number of seconds; the players are left untouched
+    pub fn process(&mut self, buffer: &mut [f32], delta_time: f32) -> Result<(), AirStrafeError> {
+        let len = self.buffer_len();
+        if buffer.len() < len {
+            return Err(MovementError::BufferTooShort { expected: len, actual: buffer.len() }.into());
+        }
+        check_delta_time(delta_time)?;
+        self.inputs.clear();
+        self.down_ray_hits.clear();
+        let slots = &mut buffer[..len];
+        for slot in slots.chunks_exact(FRAME_STRIDE) {
+            self.inputs.push(read_input(slot));
+            self.down_ray_hits.push(read_down_ray_hit(slot));
+        }
+
+        let events = self.world.advance_all(&self.inputs, &self.down_ray_hits, delta_time);
+        for slot in slots.chunks_exact_mut(FRAME_STRIDE) {
+            slot[FRAME_EVENTS] = 0.0;
+        }
+        for &(index, event) in events {
+            let slot = &mut slots[index * FRAME_STRIDE..(index + 1) * FRAME_STRIDE];
+            let bits = slot[FRAME_EVENTS] as u32 | 1 << MovementEventInfo::from(event).kind as u32;
+            slot[FRAME_EVENTS] = bits as f32;
+        }
+        for (index, slot) in slots.chunks_exact_mut(FRAME_STRIDE).enumerate() {
+            let origin = self.world.origin(index);
+            let velocity = self.world.velocity(index);
+            slot[FRAME_ORIGIN..FRAME_ORIGIN + 3].copy_from_slice(&[origin.x, origin.y, origin.z]);
+            slot[FRAME_VELOCITY..FRAME_VELOCITY + 3].copy_from_slice(&[velocity.x, velocity.y, velocity.z]);
+            slot[FRAME_FLAGS] = self.world.flags()[index] as f32;
+        }
+        Ok(())
+    }
+}
+
+fn read_input(slot: &[f32]) -> PlayerInput {
+    let buttons = slot[FRAME_BUTTONS] as u32;
+    let held = |button: u32| buttons & button != 0;
+    PlayerInput {
+        forward_move: slot[FRAME_FORWARD_MOVE],
+        side_move: slot[FRAME_SIDE_MOVE],
+        up_move: slot[FRAME_UP_MOVE],
+        yaw: slot[FRAME_YAW],
+        pitch: slot[FRAME_PITCH],
+        lean: slot[FRAME_LEAN],
+        jump: held(FRAME_BUTTON_JUMP),
+        duck: held(FRAME_BUTTON_DUCK),
+        sprint: held(FRAME_BUTTON_SPRINT),
+        jet: held(FRAME_BUTTON_JET),
+        glide: held(FRAME_BUTTON_GLIDE),
+        ski: held(FRAME_BUTTON_SKI),
+        dodge: held(FRAME_BUTTON_DODGE),
+        ground_pound: held(FRAME_BUTTON_GROUND_POUND),
+        air_dash: held(FRAME_BUTTON_AIR_DASH),
+        fire: held(FRAME_BUTTON_FIRE),
+        detach: held(FRAME_BUTTON_DETACH),
+        jump_presses: (buttons >> FRAME_JUMP_PRESSES_SHIFT) as u8,
+    }
+}
+
+fn read_down_ray_hit(slot: &[f32]) -> Option<RayCollisionHit> {
+    let distance = slot[FRAME_GROUND_DISTANCE];
+    if distance.is_nan() || distance < 0.0 {
+        return None;
+    }
+    let normal = &slot[FRAME_GROUND_NORMAL..FRAME_GROUND_NORMAL + 3];
+    let hit = RayCollisionHit::new_native(Vector3::new(normal[0], normal[1], normal[2]), distance);
+    Some(hit.with_material(slot[FRAME_GROUND_MATERIAL] as u32))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl FrameBuffer {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(config: MovementConfig) -> FrameBuffer {
+        FrameBuffer::new(config)
+    }
+
+    /// Floats per player in the buffer
+    #[wasm_bindgen(getter = stride)]
+    pub fn stride_wasm(&self) -> usize {
+        FRAME_STRIDE
+    }
+
+    #[wasm_bindgen(getter = bufferLength)]
+    pub fn buffer_len_wasm(&self) -> usize {
+        self.buffer_len()
+    }
+
+    /// Add a player and return its id; its slot is the last one in the buffer
+    #[wasm_bindgen(js_name = spawn)]
+    pub fn spawn_wasm(&mut self, state: PlayerState) -> PlayerId {
+        self.world.spawn(state)
+    }
+
+    /// Remove a player; the last player moves into its slot
+    #[wasm_bindgen(js_name = despawn)]
+    pub fn despawn_wasm(&mut self, id: PlayerId) -> Option<PlayerState> {
+        self.world.despawn(id)
+    }
+
+    /// Index of the player's slot in the buffer
+    #[wasm_bindgen(js_name = indexOf)]
+    pub fn index_of_wasm(&self, id: PlayerId) -> Option<usize> {
+        self.world.index_of(id)
+    }
+
+    #[wasm_bindgen(js_name = getState)]
+    pub fn get_state_wasm(&self, id: PlayerId) -> Option<PlayerState> {
+        self.world.get_state(id)
+    }
+
+    #[wasm_bindgen(js_name = process)]
+    pub fn process_wasm(&mut self, buffer: &mut [f32], delta_time: f32) -> Result<(), JsValue> {
+        self.process(buffer, delta_time).map_err(JsValue::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MovementEvent, MovementEventKind, PlayerState, PLAYER_ON_GROUND};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_process_matches_player_tick() {
+        let config = y_up_config();
+        let mut frame = FrameBuffer::new(config);
+        let start = PlayerState { on_ground: true, ..PlayerState::default() };
+        frame.world_mut().spawn(PlayerState::new(Vector3::new(0.0, 100.0, 0.0)));
+        frame.world_mut().spawn(start);
+        let mut buffer = vec![0.0; frame.buffer_len()];
+        buffer[FRAME_GROUND_DISTANCE] = -1.0;
+        let jumper = &mut buffer[FRAME_STRIDE..];
+        jumper[FRAME_FORWARD_MOVE] = 1.0;
+        jumper[FRAME_BUTTONS] = (FRAME_BUTTON_JUMP | FRAME_BUTTON_SPRINT) as f32;
+        jumper[FRAME_GROUND_NORMAL + 1] = 1.0;
+
+        frame.process(&mut buffer, 1.0 / 64.0).unwrap();
+
+        let mut expected = start;
+        let input = PlayerInput { forward_move: 1.0, jump: true, sprint: true, ..PlayerInput::new() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let events = player_tick(&mut expected, &input, &config, 1.0 / 64.0, ground);
+        assert!(events.iter().any(|event| matches!(event, MovementEvent::Jumped { .. })));
+        let jumper = &buffer[FRAME_STRIDE..];
+        assert_eq!(&jumper[FRAME_VELOCITY..FRAME_VELOCITY + 3], &[expected.velocity.x, expected.velocity.y, expected.velocity.z]);
+        assert_eq!(jumper[FRAME_EVENTS] as u32 & 1 << MovementEventKind::Jumped as u32, 1);
+        assert_eq!(buffer[FRAME_ORIGIN + 1], frame.world().origin(0).y);
+        assert_eq!(buffer[FRAME_FLAGS] as u8 & PLAYER_ON_GROUND, 0);
+        assert_eq!(buffer[FRAME_EVENTS], 0.0);
+
+        assert!(frame.process(&mut buffer[..FRAME_STRIDE], 1.0 / 64.0).is_err());
+    }
+    #[test]
+    fn test_slots_decode_buttons_presses_and_missing_ground() {
+        let mut slot = [0.0; FRAME_STRIDE];
+        slot[FRAME_BUTTONS] = (FRAME_BUTTON_DUCK | FRAME_BUTTON_DETACH | 3 << FRAME_JUMP_PRESSES_SHIFT) as f32;
+        let input = read_input(&slot);
+        assert!(input.duck && input.detach && !input.jump && !input.fire);
+        assert_eq!(input.jump_presses, 3);
+
+        slot[FRAME_GROUND_NORMAL + 1] = 1.0;
+        slot[FRAME_GROUND_MATERIAL] = 7.0;
+        assert_eq!(read_down_ray_hit(&slot).map(|hit| hit.material), Some(7));
+        for distance in [-0.5, f32::NAN] {
+            slot[FRAME_GROUND_DISTANCE] = distance;
+            assert!(read_down_ray_hit(&slot).is_none());
+        }
+    }
+
+    #[test]
+    fn test_process_rejects_bad_frames_and_ignores_the_tail() {
+        let mut frame = FrameBuffer::new(y_up_config());
+        assert!(frame.process(&mut [], 1.0 / 64.0).is_ok());
+
+        frame.world_mut().spawn(PlayerState::new(Vector3::new(0.0, 100.0, 0.0)));
+        let mut buffer = vec![0.0; frame.buffer_len() + 2];
+        buffer[FRAME_GROUND_DISTANCE] = -1.0;
+        buffer[FRAME_STRIDE..].fill(42.0);
+        for delta_time in [0.0, -1.0, f32::NAN] {
+            assert!(frame.process(&mut buffer, delta_time).is_err());
+        }
+        assert_eq!(frame.world().origin(0).y, 100.0);
+        assert_eq!(buffer[FRAME_ORIGIN + 1], 0.0);
+
+        frame.process(&mut buffer, 1.0 / 64.0).unwrap();
+        assert!(buffer[FRAME_ORIGIN + 1] < 100.0 && buffer[FRAME_VELOCITY + 1] < 0.0);
+        assert_eq!(&buffer[FRAME_STRIDE..], &[42.0, 42.0]);
+    }
+}
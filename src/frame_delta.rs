@@ -0,0 +1,165 @@
+//! Smoothing browser frame deltas
+//!
+//! The time between two `requestAnimationFrame` callbacks jitters by a
+//! millisecond or two every frame, doubles when a frame is dropped and jumps
+//! to seconds when the tab was in the background. Fed straight into a fixed
+//! tick accumulator, the jitter makes the number of ticks per frame flicker
+//! and a background tab dumps seconds of catch-up ticks on the player.
+//!
+//! A `FrameDeltaSmoother` sits in front of the accumulator. Deltas of at
+//! least `pause_threshold` count as a pause and are dropped entirely, along
+//! with the history from before the pause. Every other delta is replaced by
+//! the median of the last `window` deltas, which hides single spikes, and
+//! clamped to `max_delta`. Smoothed time does not add up to wall time exactly;
+//! like any frame pacing it trades that for steady motion.
+
+use std::collections::VecDeque;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// How a `FrameDeltaSmoother` treats frame deltas
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameDeltaPolicy {
+    /// Number of recent deltas the median is taken over (0 is treated as 1)
+    pub window: usize,
+    /// Largest delta handed out, in seconds
+    pub max_delta: f32,
+    /// Deltas of at least this many seconds are pauses and dropped
+    pub pause_threshold: f32,
+}
+
+impl Default for FrameDeltaPolicy {
+    fn default() -> Self {
+        FrameDeltaPolicy { window: 5, max_delta: 0.1, pause_threshold: 0.5 }
+    }
+}
+
+/// Median-of-N frame delta filter with clamping and pause detection
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default)]
+pub struct FrameDeltaSmoother {
+    policy: FrameDeltaPolicy,
+    history: VecDeque<f32>,
+    sorted: Vec<f32>,
+    paused: bool,
+    pauses: u32,
+}
+
+impl FrameDeltaSmoother {
+    /// Create a smoother without history
+    pub fn new(policy: FrameDeltaPolicy) -> FrameDeltaSmoother {
+        FrameDeltaSmoother { policy, ..FrameDeltaSmoother::default() }
+    }
+
+    /// The smoothed delta for a raw frame delta in seconds
+    ///
+    /// Returns 0.0 for pauses and for negative or non-finite deltas.
+    pub fn smooth(&mut self, raw_delta: f32) -> f32 {
+        self.paused = raw_delta >= self.policy.pause_threshold;
+        if self.paused {
+            self.pauses += 1;
+            self.history.clear();
+            return 0.0;
+        }
+        if !raw_delta.is_finite() || raw_delta < 0.0 {
+            return 0.0;
+        }
+        if self.history.len() >= self.policy.window.max(1) {
+            self.history.pop_front();
+        }
+        self.history.push_back(raw_delta);
+
+        self.sorted.clear();
+        self.sorted.extend(&self.history);
+        self.sorted.sort_by(f32::total_cmp);
+        let middle = self.sorted.len() / 2;
+        let median = if self.sorted.len().is_multiple_of(2) {
+            (self.sorted[middle - 1] + self.sorted[middle]) / 2.0
+        } else {
+            self.sorted[middle]
+        };
+        median.min(self.policy.max_delta)
+    }
+
+    /// Whether the last delta passed to `smooth` was a pause
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Number of pauses detected so far
+    pub fn pauses(&self) -> u32 {
+        self.pauses
+    }
+
+    /// Forget the recent deltas, e.g. after the game was suspended on purpose
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.paused = false;
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl FrameDeltaPolicy {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> FrameDeltaPolicy {
+        FrameDeltaPolicy::default()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl FrameDeltaSmoother {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(policy: &FrameDeltaPolicy) -> FrameDeltaSmoother {
+        FrameDeltaSmoother::new(*policy)
+    }
+
+    /// Smooth a `requestAnimationFrame` delta in seconds
+    #[wasm_bindgen(js_name = smooth)]
+    pub fn smooth_wasm(&mut self, raw_delta: f32) -> f32 {
+        self.smooth(raw_delta)
+    }
+
+    #[wasm_bindgen(getter = paused)]
+    pub fn paused_wasm(&self) -> bool {
+        self.paused
+    }
+
+    #[wasm_bindgen(getter = pauses)]
+    pub fn pauses_wasm(&self) -> u32 {
+        self.pauses
+    }
+
+    #[wasm_bindgen(js_name = reset)]
+    pub fn reset_wasm(&mut self) {
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spikes_are_hidden_and_pauses_dropped() {
+        let mut smoother = FrameDeltaSmoother::new(FrameDeltaPolicy::default());
+        let frame = 1.0 / 60.0;
+        let jittered = [frame, frame + 0.002, frame - 0.002, frame, 2.0 * frame, frame + 0.001, frame];
+        let smoothed: Vec<f32> = jittered.iter().map(|&delta| smoother.smooth(delta)).collect();
+        // The dropped frame does not show up once the window has filled
+        assert!(smoothed[4..].iter().all(|&delta| (delta - frame).abs() <= 0.0011));
+
+        // A background tab resumes at normal speed instead of catching up
+        assert_eq!(smoother.smooth(3.0), 0.0);
+        assert!(smoother.paused() && smoother.pauses() == 1);
+        assert_eq!(smoother.smooth(frame), frame);
+        assert!(!smoother.paused());
+
+        let mut clamped = FrameDeltaSmoother::new(FrameDeltaPolicy { window: 1, ..FrameDeltaPolicy::default() });
+        assert_eq!(clamped.smooth(0.3), 0.1);
+        assert_eq!(clamped.smooth(f32::NAN), 0.0);
+    }
+}
@@ -0,0 +1,107 @@
+//! Glider / parachute aerodynamics
+//!
+//! Holding the glide button while airborne switches a walking player to
+//! `MoveType::Glide`; landing or releasing the button switches back. While
+//! gliding, air acceleration is replaced by `glide_velocity`: descent speed is
+//! turned into forward speed (`glide_lift`), forward speed bleeds off
+//! (`glide_drag`) and the heading follows the view at `glide_turn_rate`.
+//! Gravity, ground detection and landing events stay those of `player_tick`.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::fixed::with_fixed;
+use crate::{MovementConfig, UpAxis, Vector3};
+
+/// Velocity after one tick of gliding, before gravity
+///
+/// # Arguments
+/// * `velocity` - The current velocity in world units
+/// * `yaw` - The view yaw the glider turns towards
+/// * `config` - Supplies the glide coefficients and the up axis
+/// * `delta_time` - Time step in seconds
+pub fn glide_velocity(velocity: &Vector3, yaw: f32, config: &MovementConfig, delta_time: f32) -> Vector3 {
+    with_fixed!(config.up_axis, Z_UP => glide_velocity_fixed::<Z_UP>(velocity, yaw, config, delta_time))
+}
+
+/// `glide_velocity` for the up axis of a `fixed` instantiation
+pub(crate) fn glide_velocity_fixed<const Z_UP: bool>(
+    velocity: &Vector3,
+    yaw: f32,
+    config: &MovementConfig,
+    delta_time: f32,
+) -> Vector3 {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let horizontal = axis.horizontal(velocity);
+    let mut speed = horizontal.magnitude();
+    let mut vertical = axis.height(velocity);
+
+    // Turn the heading towards the view, taking the short way around
+    let heading = if speed > 0.0 { axis.yaw_from_direction(&horizontal) } else { yaw };
+    let offset = (yaw - heading + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+    let max_turn = config.glide_turn_rate * delta_time;
+    let forward = axis.forward(heading + offset.clamp(-max_turn, max_turn));
+
+    // Lift converts part of the descent into forward speed
+    if vertical < 0.0 {
+        let converted = (-vertical * config.glide_lift * delta_time).min(-vertical);
+        vertical += converted;
+        speed += converted;
+    }
+    speed *= (-config.glide_drag * delta_time).exp();
+
+    let mut glided = forward.multiply_scalar(speed);
+    axis.set_height(&mut glided, vertical);
+    glided
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = glideVelocity)]
+pub fn glide_velocity_wasm(velocity: &Vector3, yaw: f32, config: &MovementConfig, delta_time: f32) -> Vector3 {
+    glide_velocity(velocity, yaw, &config.in_world_units(), delta_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MoveType, PlayerInput, PlayerState, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_glide_trades_height_for_speed() {
+        let config = y_up_config();
+        let mut state = PlayerState::new(Vector3::new(0.0, 2000.0, 0.0));
+        let input = PlayerInput { glide: true, ..PlayerInput::new() };
+        let dt = 1.0 / 64.0;
+
+        player_tick(&mut state, &input, &config, dt, None);
+        assert_eq!(state.move_type, MoveType::Glide);
+        for _ in 0..255 {
+            player_tick(&mut state, &input, &config, dt, None);
+        }
+        let forward = config.up_axis.forward(0.0);
+        assert!(state.velocity.dot(&forward) > 200.0);
+        assert!(-state.velocity.y <= config.glide_max_fall_speed + 0.01);
+
+        // Landing ends the glide
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 1.0));
+        player_tick(&mut state, &input, &config, dt, ground);
+        assert_eq!(state.move_type, MoveType::Walk);
+        assert!(state.on_ground);
+    }
+
+    #[test]
+    fn test_glide_turns_towards_view_at_turn_rate() {
+        let config = MovementConfig { glide_lift: 0.0, glide_drag: 0.0, ..y_up_config() };
+        let velocity = config.up_axis.forward(0.0).multiply_scalar(500.0);
+
+        let turned = glide_velocity(&velocity, 3.0, &config, 0.1);
+        let heading = config.up_axis.yaw_from_direction(&turned);
+        assert!((heading - config.glide_turn_rate * 0.1).abs() < 0.0001);
+        assert!((turned.magnitude() - 500.0).abs() < 0.01);
+
+        // Wraps around instead of turning the long way
+        let turned = glide_velocity(&velocity, -6.0, &config, 0.1);
+        assert!(config.up_axis.yaw_from_direction(&turned) > 0.0);
+    }
+}
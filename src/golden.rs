@@ -0,0 +1,223 @@
+//! Golden-trace regression checks
+//!
+//! Record the trajectory of a `Scenario` once, store it next to the game's
+//! tests, and compare fresh simulations against it later. Any physics change
+//! between crate versions then shows up as the first tick where the new
+//! simulation leaves the recorded one.
+//!
+//! Traces are stored as JSON: `{"version": 1, "ticks": [[x, y, z, vx, vy, vz, on_ground], ...]}`
+//! with one entry per state of the trajectory. Floats are written with their
+//! shortest exact representation, so a trace survives a save/load round trip
+//! bit for bit.
+
+use std::fmt;
+
+use crate::config_format::{self, ConfigError, JsonValue};
+use crate::error::AirStrafeError;
+use crate::{Scenario, Trajectory, Vector3};
+
+const TRACE_VERSION: f32 = 1.0;
+
+/// One recorded state of a golden trace
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceSample {
+    pub origin: Vector3,
+    pub velocity: Vector3,
+    pub on_ground: bool,
+}
+
+/// Allowed differences between a recorded and a fresh trace
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceTolerance {
+    /// Largest allowed origin distance in world units
+    pub position: f32,
+    /// Largest allowed velocity difference in world units per second
+    pub velocity: f32,
+}
+
+impl Default for TraceTolerance {
+    fn default() -> Self {
+        TraceTolerance { position: 0.001, velocity: 0.01 }
+    }
+}
+
+/// The first place a fresh trace differs from the recorded one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceDivergence {
+    /// The traces have a different number of ticks
+    Length { expected: usize, actual: usize },
+    /// The origins are further apart than the tolerance
+    Position { tick: usize, expected: Vector3, actual: Vector3 },
+    /// The velocities differ by more than the tolerance
+    Velocity { tick: usize, expected: Vector3, actual: Vector3 },
+    /// One trace is on the ground and the other is not
+    OnGround { tick: usize, expected: bool },
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceDivergence::Length { expected, actual } => {
+                write!(f, "trace has {actual} ticks, expected {expected}")
+            }
+            TraceDivergence::Position { tick, expected, actual } => {
+                write!(f, "tick {tick}: origin {actual:?} diverged from {expected:?}")
+            }
+            TraceDivergence::Velocity { tick, expected, actual } => {
+                write!(f, "tick {tick}: velocity {actual:?} diverged from {expected:?}")
+            }
+            TraceDivergence::OnGround { tick, expected } => {
+                write!(f, "tick {tick}: on_ground is {}, expected {expected}", !expected)
+            }
+        }
+    }
+}
+
+/// A canonical trajectory to compare future simulations against
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GoldenTrace {
+    pub samples: Vec<TraceSample>,
+}
+
+impl GoldenTrace {
+    /// Capture the states of a trajectory
+    pub fn from_trajectory(trajectory: &Trajectory) -> GoldenTrace {
+        GoldenTrace {
+            samples: trajectory
+                .states
+                .iter()
+                .map(|state| TraceSample {
+                    origin: state.origin,
+                    velocity: state.velocity,
+                    on_ground: state.on_ground,
+                })
+                .collect(),
+        }
+    }
+
+    /// Run a scenario and capture its trajectory
+    pub fn record(scenario: &Scenario) -> GoldenTrace {
+        GoldenTrace::from_trajectory(&scenario.run())
+    }
+
+    /// Compare against another trace, reporting the first divergent tick
+    pub fn compare(&self, actual: &GoldenTrace, tolerance: &TraceTolerance) -> Result<(), TraceDivergence> {
+        for (tick, (expected, actual)) in self.samples.iter().zip(&actual.samples).enumerate() {
+            if (actual.origin - expected.origin).magnitude() > tolerance.position {
+                return Err(TraceDivergence::Position { tick, expected: expected.origin, actual: actual.origin });
+            }
+            if (actual.velocity - expected.velocity).magnitude() > tolerance.velocity {
+                return Err(TraceDivergence::Velocity { tick, expected: expected.velocity, actual: actual.velocity });
+            }
+            if actual.on_ground != expected.on_ground {
+                return Err(TraceDivergence::OnGround { tick, expected: expected.on_ground });
+            }
+        }
+        if self.samples.len() != actual.samples.len() {
+            return Err(TraceDivergence::Length { expected: self.samples.len(), actual: actual.samples.len() });
+        }
+        Ok(())
+    }
+
+    /// Run the scenario again and compare the result against this trace
+    pub fn verify(&self, scenario: &Scenario, tolerance: &TraceTolerance) -> Result<(), TraceDivergence> {
+        self.compare(&GoldenTrace::record(scenario), tolerance)
+    }
+
+    /// Serialize the trace to JSON
+    pub fn to_json(&self) -> String {
+        let ticks: Vec<String> = self
+            .samples
+            .iter()
+            .map(|s| {
+                let (o, v) = (s.origin, s.velocity);
+                format!(
+                    "    [{}, {}, {}, {}, {}, {}, {}]",
+                    o.x, o.y, o.z, v.x, v.y, v.z, s.on_ground as u8
+                )
+            })
+            .collect();
+        format!("{{\n  \"version\": {TRACE_VERSION},\n  \"ticks\": [\n{}\n  ]\n}}\n", ticks.join(",\n"))
+    }
+
+    /// Load a trace written by `to_json`
+    pub fn from_json(text: &str) -> Result<GoldenTrace, AirStrafeError> {
+        let root = config_format::parse_json_value(text)?;
+        let mismatch = |expected| ConfigError::TypeMismatch { field: "ticks".to_string(), expected };
+        if root.get("version") != Some(&JsonValue::Number(TRACE_VERSION)) {
+            return Err(ConfigError::TypeMismatch { field: "version".to_string(), expected: "trace version 1" }.into());
+        }
+        let Some(JsonValue::Array(ticks)) = root.get("ticks") else {
+            return Err(mismatch("array").into());
+        };
+        let samples = ticks
+            .iter()
+            .map(|tick| match tick {
+                JsonValue::Array(values) => {
+                    let numbers: Vec<f32> = values
+                        .iter()
+                        .filter_map(|value| match value {
+                            JsonValue::Number(n) => Some(*n),
+                            _ => None,
+                        })
+                        .collect();
+                    match numbers.as_slice() {
+                        &[x, y, z, vx, vy, vz, on_ground] if values.len() == 7 => Ok(TraceSample {
+                            origin: Vector3::new(x, y, z),
+                            velocity: Vector3::new(vx, vy, vz),
+                            on_ground: on_ground != 0.0,
+                        }),
+                        _ => Err(mismatch("array of 7 numbers per tick")),
+                    }
+                }
+                _ => Err(mismatch("array of 7 numbers per tick")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(GoldenTrace { samples })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Plane, PlayerInput, PlayerState};
+    use crate::tick::tests::y_up_config;
+
+    fn scenario() -> Scenario {
+        let floor = Plane::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        Scenario::new("strafe", y_up_config(), PlayerState::default())
+            .with_plane(floor)
+            .hold(32, PlayerInput { forward_move: 1.0, ..PlayerInput::new() })
+            .hold(64, PlayerInput { side_move: 1.0, yaw: 0.3, jump: true, ..PlayerInput::new() })
+    }
+
+    #[test]
+    fn test_trace_round_trips_exactly() {
+        let trace = GoldenTrace::record(&scenario());
+        let loaded = GoldenTrace::from_json(&trace.to_json()).unwrap();
+        assert_eq!(loaded, trace);
+        assert!(loaded.verify(&scenario(), &TraceTolerance::default()).is_ok());
+    }
+
+    #[test]
+    fn test_first_divergent_tick_is_reported() {
+        let trace = GoldenTrace::record(&scenario());
+        let mut changed = scenario();
+        changed.config.jump_speed = 300.0;
+
+        match trace.verify(&changed, &TraceTolerance::default()) {
+            Err(TraceDivergence::Position { tick, .. } | TraceDivergence::Velocity { tick, .. }) => {
+                // The jump speed only matters once the player has jumped
+                assert!(tick > 32);
+            }
+            other => panic!("expected a divergence, got {other:?}"),
+        }
+
+        let mut shorter = trace.clone();
+        shorter.samples.pop();
+        assert!(matches!(
+            trace.compare(&shorter, &TraceTolerance::default()),
+            Err(TraceDivergence::Length { .. })
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+//! Asymmetric gravity for platformer-style jumps
+//!
+//! Many platformers pull the player down harder than they push them up, so a
+//! jump rises floaty and falls snappy, and soften gravity around the apex to
+//! give the player a moment of hang time to line up a landing. While walking
+//! in the air, gravity is scaled by `rise_gravity_multiplier` on the way up
+//! and `fall_gravity_multiplier` on the way down. Within `apex_hang_speed` of
+//! standing still vertically, `apex_gravity_multiplier` applies instead.
+//!
+//! The multiplier only depends on the velocity at the start of the gravity
+//! stage, so prediction and replays see the same curve. Other move types
+//! (swimming, gliding, flying) keep their own fall models.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::fixed::with_fixed;
+use crate::{MoveType, MovementConfig, PlayerState, UpAxis};
+
+/// Gravity multiplier for the player's current vertical speed
+///
+/// `config` is expected in world units, as inside `player_tick`.
+pub fn gravity_curve_multiplier(state: &PlayerState, config: &MovementConfig) -> f32 {
+    with_fixed!(config.up_axis, Z_UP => gravity_curve_multiplier_fixed::<Z_UP>(state, config))
+}
+
+/// `gravity_curve_multiplier` for the up axis of a `fixed` instantiation
+pub(crate) fn gravity_curve_multiplier_fixed<const Z_UP: bool>(state: &PlayerState, config: &MovementConfig) -> f32 {
+    if state.move_type != MoveType::Walk {
+        return 1.0;
+    }
+    let vertical_speed = UpAxis::from_z_up(Z_UP).height(&state.velocity);
+    if vertical_speed.abs() < config.apex_hang_speed {
+        config.apex_gravity_multiplier
+    } else if vertical_speed > 0.0 {
+        config.rise_gravity_multiplier
+    } else {
+        config.fall_gravity_multiplier
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = gravityCurveMultiplier)]
+pub fn gravity_curve_multiplier_wasm(state: &PlayerState, config: &MovementConfig) -> f32 {
+    gravity_curve_multiplier(state, &config.in_world_units())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    /// Ticks in the air and apex height of a jump at `jump_speed` from y = 0
+    fn airtime(config: &MovementConfig) -> (u32, f32) {
+        let mut state = PlayerState { velocity: Vector3::new(0.0, config.jump_speed, 0.0), ..PlayerState::default() };
+        let mut apex: f32 = 0.0;
+        let mut ticks = 0;
+        while state.origin.y >= 0.0 {
+            player_tick(&mut state, &PlayerInput::new(), config, 1.0 / 64.0, None);
+            apex = apex.max(state.origin.y);
+            ticks += 1;
+        }
+        (ticks, apex)
+    }
+
+    #[test]
+    fn test_rise_fall_and_apex_multipliers() {
+        let (ticks, apex) = airtime(&y_up_config());
+
+        // Falling twice as hard keeps the apex but shortens the way down
+        let snappy = MovementConfig { fall_gravity_multiplier: 2.0, ..y_up_config() };
+        let (snappy_ticks, snappy_apex) = airtime(&snappy);
+        assert!((snappy_apex - apex).abs() < 1e-3);
+        assert!(snappy_ticks < ticks);
+
+        // Hang time keeps the player in the air longer without a higher jump
+        let hang = MovementConfig { apex_hang_speed: 50.0, apex_gravity_multiplier: 0.25, ..y_up_config() };
+        let (hang_ticks, hang_apex) = airtime(&hang);
+        assert!(hang_ticks > ticks);
+        assert!(hang_apex < apex + 5.0);
+
+        let rising = PlayerState { velocity: Vector3::new(0.0, 200.0, 0.0), ..PlayerState::default() };
+        let floaty = MovementConfig { rise_gravity_multiplier: 0.5, ..y_up_config() };
+        assert_eq!(gravity_curve_multiplier(&rising, &floaty), 0.5);
+        let swimming = PlayerState { move_type: MoveType::Swim, ..rising };
+        assert_eq!(gravity_curve_multiplier(&swimming, &floaty), 1.0);
+    }
+}
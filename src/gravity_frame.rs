@@ -0,0 +1,215 @@
+//! Arbitrary gravity directions
+//!
+//! The controller is written against `MovementConfig::up_axis`. To let gravity
+//! point anywhere (wall walking, spherical planets), `player_tick_with_gravity`
+//! rotates the player into a frame where gravity points down the configured up
+//! axis, runs the regular tick and rotates the result back. Ground detection,
+//! jumping, ducking and every other stage then treat any surface opposing
+//! gravity as ground.
+//!
+//! The rotation is the shortest arc between the two directions, so view yaw
+//! stays continuous while gravity turns gradually (e.g. walking around a
+//! planet). Hosts cast the down ray along the gravity direction and can use
+//! `GravityFrame` to convert view and eye vectors the same way.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::events::MovementEventInfo;
+use crate::events::MovementEvent;
+use crate::{player_tick_into, MovementConfig, PlayerInput, PlayerState, RayCollisionHit, UpAxis, Vector3};
+
+/// Rotation between world space and the frame where gravity points down `up_axis`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GravityFrame {
+    /// Rows of the world-to-local rotation matrix
+    rows: [Vector3; 3],
+}
+
+impl GravityFrame {
+    /// The frame for gravity pulling along `gravity_direction`
+    ///
+    /// A zero direction gives the identity frame (regular world-down gravity).
+    pub fn new(gravity_direction: &Vector3, up_axis: UpAxis) -> GravityFrame {
+        let from = gravity_direction.normalized().multiply_scalar(-1.0);
+        let to = up_axis.up();
+        let cos = from.dot(&to);
+        if from.length_sq() == 0.0 || cos > 0.999_999 {
+            return GravityFrame::identity();
+        }
+        if cos < -0.999_999 {
+            // Upside down: half turn around any horizontal axis
+            let axis = up_axis.forward(0.0);
+            return GravityFrame::from_axis_angle(&axis, -1.0, 0.0);
+        }
+        let axis = from.cross(&to);
+        let sin = axis.magnitude();
+        GravityFrame::from_axis_angle(&axis.multiply_scalar(1.0 / sin), cos, sin)
+    }
+
+    fn identity() -> GravityFrame {
+        GravityFrame {
+            rows: [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)],
+        }
+    }
+
+    /// Rodrigues rotation around a unit axis
+    fn from_axis_angle(axis: &Vector3, cos: f32, sin: f32) -> GravityFrame {
+        let (x, y, z) = (axis.x, axis.y, axis.z);
+        let t = 1.0 - cos;
+        GravityFrame {
+            rows: [
+                Vector3::new(t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y),
+                Vector3::new(t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x),
+                Vector3::new(t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos),
+            ],
+        }
+    }
+
+    /// Rotate a world-space vector into the gravity frame
+    pub fn to_local(&self, v: &Vector3) -> Vector3 {
+        Vector3::new(self.rows[0].dot(v), self.rows[1].dot(v), self.rows[2].dot(v))
+    }
+
+    /// Rotate a gravity-frame vector back into world space
+    pub fn to_world(&self, v: &Vector3) -> Vector3 {
+        let mut world = self.rows[0].multiply_scalar(v.x);
+        world.add(&self.rows[1].multiply_scalar(v.y));
+        world.add(&self.rows[2].multiply_scalar(v.z));
+        world
+    }
+
+    fn transform_state(state: &mut PlayerState, rotate: impl Fn(&Vector3) -> Vector3) {
+        state.origin = rotate(&state.origin);
+        state.velocity = rotate(&state.velocity);
+        state.ground_normal = rotate(&state.ground_normal);
+        state.climb_normal = rotate(&state.climb_normal);
+        state.water_current = rotate(&state.water_current);
+        state.water_jump_velocity = rotate(&state.water_jump_velocity);
+        state.dodge_wall_normal = rotate(&state.dodge_wall_normal);
+        state.stick_normal = rotate(&state.stick_normal);
+    }
+}
+
+/// `player_tick` with gravity pulling along `gravity_direction`
+///
+/// # Arguments
+/// * `state` - The player in world space
+/// * `input` - View yaw and pitch are relative to the gravity frame
+/// * `config` - `gravity` sets the strength, `up_axis` the frame's convention
+/// * `delta_time` - Time step in seconds
+/// * `down_ray_hit` - Result of a ray cast from the origin along `gravity_direction`
+/// * `gravity_direction` - Direction gravity pulls in (any length)
+pub fn player_tick_with_gravity(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    gravity_direction: &Vector3,
+) -> Vec<MovementEvent> {
+    let mut events = Vec::new();
+    player_tick_with_gravity_into(state, input, config, delta_time, down_ray_hit, gravity_direction, &mut events);
+    events
+}
+
+/// `player_tick_with_gravity` appending the events to a caller-owned buffer
+pub fn player_tick_with_gravity_into(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    gravity_direction: &Vector3,
+    events: &mut Vec<MovementEvent>,
+) {
+    let frame = GravityFrame::new(gravity_direction, config.up_axis);
+    let down_ray_hit = down_ray_hit.map(|hit| {
+        RayCollisionHit::new_native(frame.to_local(hit.normal_native()), hit.distance_native())
+            .with_material(hit.material())
+            .with_edge_distance(hit.edge_distance())
+    });
+    GravityFrame::transform_state(state, |v| frame.to_local(v));
+    player_tick_into(state, input, config, delta_time, down_ray_hit, events);
+    GravityFrame::transform_state(state, |v| frame.to_world(v));
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl GravityFrame {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(gravity_direction: &Vector3, up_axis: UpAxis) -> GravityFrame {
+        GravityFrame::new(gravity_direction, up_axis)
+    }
+
+    #[wasm_bindgen(js_name = toLocal)]
+    pub fn to_local_wasm(&self, v: &Vector3) -> Vector3 {
+        self.to_local(v)
+    }
+
+    #[wasm_bindgen(js_name = toWorld)]
+    pub fn to_world_wasm(&self, v: &Vector3) -> Vector3 {
+        self.to_world(v)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = playerTickWithGravity)]
+pub fn player_tick_with_gravity_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    gravity_direction: &Vector3,
+) -> Vec<MovementEventInfo> {
+    player_tick_with_gravity(state, input, config, delta_time, down_ray_hit, gravity_direction)
+        .into_iter()
+        .map(MovementEventInfo::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_frame_maps_gravity_to_down() {
+        for gravity in [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 3.0, 0.0), Vector3::new(-1.0, -2.0, 0.5)] {
+            for axis in [UpAxis::Y, UpAxis::Z] {
+                let frame = GravityFrame::new(&gravity, axis);
+                let down = frame.to_local(&gravity.normalized());
+                assert!((down + axis.up()).magnitude() < 0.0001);
+                let v = Vector3::new(0.3, -4.0, 2.0);
+                assert!((frame.to_world(&frame.to_local(&v)) - v).magnitude() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_walk_and_jump_on_a_wall() {
+        let config = y_up_config();
+        // Gravity pulls towards +X, so the wall at the player's feet faces -X
+        let gravity = Vector3::new(1.0, 0.0, 0.0);
+        let wall = Some(RayCollisionHit::new_native(Vector3::new(-1.0, 0.0, 0.0), 0.0));
+        let mut state = PlayerState::default();
+        let dt = 1.0 / 64.0;
+
+        let forward = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        for _ in 0..64 {
+            let hit = wall.filter(|_| state.origin.x >= -0.001);
+            player_tick_with_gravity(&mut state, &forward, &config, dt, hit, &gravity);
+        }
+        assert!(state.on_ground);
+        assert!(state.origin.x.abs() < 0.001);
+        assert!(state.velocity.magnitude() > config.max_speed * 0.9);
+
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        player_tick_with_gravity(&mut state, &jump, &config, dt, wall, &gravity);
+        assert!(!state.on_ground);
+        assert!(state.velocity.x < -config.jump_speed * 0.9);
+    }
+}
@@ -0,0 +1,62 @@
+//! Ground pound (fast-fall)
+//!
+//! Pressing `PlayerInput::ground_pound` in the air slams the player down at
+//! `ground_pound_speed`, cancelling `ground_pound_horizontal_cancel` of their
+//! horizontal speed. The player keeps falling at least that fast until they
+//! land, and the landing event is flagged as a ground pound so games can add
+//! shockwaves or damage on top of the regular impact.
+
+use crate::{MoveType, MovementConfig, PlayerInput, PlayerState, UpAxis};
+
+/// Start a ground pound and keep the fall fast while it lasts
+pub(crate) fn ground_pound<const Z_UP: bool>(state: &mut PlayerState, input: &PlayerInput, config: &MovementConfig) {
+    let axis = UpAxis::from_z_up(Z_UP);
+    if !state.ground_pounding {
+        if !config.ground_pound_enabled || !input.ground_pound {
+            return;
+        }
+        state.ground_pounding = true;
+        if state.move_type == MoveType::Glide {
+            state.move_type = MoveType::Walk;
+        }
+        let height = axis.height(&state.velocity);
+        state.velocity = axis.horizontal(&state.velocity).multiply_scalar(1.0 - config.ground_pound_horizontal_cancel);
+        axis.set_height(&mut state.velocity, height);
+    }
+    if axis.height(&state.velocity) > -config.ground_pound_speed {
+        axis.set_height(&mut state.velocity, -config.ground_pound_speed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MovementEvent, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_ground_pound_slams_down_and_flags_the_landing() {
+        let config =
+            MovementConfig { ground_pound_enabled: true, ground_pound_horizontal_cancel: 0.5, ..y_up_config() };
+        let pound = PlayerInput { ground_pound: true, ..PlayerInput::new() };
+        let mut state = PlayerState { velocity: Vector3::new(0.0, 200.0, -300.0), ..PlayerState::default() };
+
+        player_tick(&mut state, &pound, &config, 1.0 / 64.0, None);
+        assert!(state.ground_pounding);
+        assert_eq!(state.velocity.z, -150.0);
+        assert!(state.velocity.y <= -config.ground_pound_speed);
+
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 8.0));
+        let events = player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+        match events.as_slice() {
+            [MovementEvent::Landed { ground_pound, landing_speed, .. }] => {
+                assert!(*ground_pound);
+                assert!(*landing_speed >= config.ground_pound_speed);
+            }
+            other => panic!("expected a landing, got {other:?}"),
+        }
+        let standing = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, standing);
+        assert!(!state.ground_pounding);
+    }
+}
@@ -0,0 +1,455 @@
+//! Scenario-based simulation harness for movement regression tests
+//!
+//! A `Scenario` bundles an initial state, a config, scripted inputs and simple
+//! world geometry (infinite planes). Running it produces a `Trajectory` with
+//! every tick's state and event, which can be checked against `Expectations`.
+//! Scenarios can be built in code or loaded from JSON:
+//!
+//! ```json
+//! {
+//!   "name": "bhop",
+//!   "tick_rate": 64,
+//!   "config": { "auto_bhop": true },
+//!   "state": { "origin": [0, 0, 0], "velocity": [0, 0, -250] },
+//!   "planes": [{ "point": [0, 0, 0], "normal": [0, 1, 0] }],
+//!   "inputs": [{ "ticks": 128, "side_move": 1, "jump": true }],
+//!   "expect": { "min_final_speed": 250, "max_height": 60, "no_clipping": true }
+//! }
+//! ```
+//!
+//! Trajectories export to CSV (one row per state) and JSON (states plus the
+//! `JumpStats` of every completed jump). The `airstrafe-sim` binary, built with
+//! the `cli` feature, runs scenario files through these without writing Rust.
+
+use crate::config_format::{self, ConfigError, JsonValue};
+use crate::error::AirStrafeError;
+use crate::{
+    player_tick, JumpStats, LongJumpMeter, MovementConfig, MovementEvent, PlayerInput, PlayerState, RayCollisionHit,
+    UpAxis, Vector3,
+};
+
+/// Small tolerance for floating point drift when checking for clipping
+const CLIP_TOLERANCE: f32 = 0.01;
+
+/// An infinite plane; the side the normal points to is open space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub point: Vector3,
+    pub normal: Vector3,
+}
+
+impl Plane {
+    /// Create a plane through `point`; `normal` is normalized
+    pub fn new(point: Vector3, normal: Vector3) -> Plane {
+        Plane { point, normal: normal.normalized() }
+    }
+
+    /// Distance of `position` above the plane (negative when behind it)
+    pub fn signed_distance(&self, position: &Vector3) -> f32 {
+        (*position - self.point).dot(&self.normal)
+    }
+
+    /// Where a ray cast straight down from `origin` hits the plane, if it does
+    pub fn ray_hit_down(&self, origin: &Vector3, up_axis: UpAxis) -> Option<RayCollisionHit> {
+        let facing = up_axis.height(&self.normal);
+        if facing <= 0.0 {
+            return None;
+        }
+        let distance = self.signed_distance(origin) / facing;
+        // Allow for the feet resting a hair below the surface
+        (distance >= -CLIP_TOLERANCE).then(|| RayCollisionHit::new_native(self.normal, distance.max(0.0)))
+    }
+}
+
+/// An input held for a number of ticks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScriptedInput {
+    pub ticks: u32,
+    pub input: PlayerInput,
+}
+
+/// Checks run against a finished `Trajectory`; unset checks are skipped
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Expectations {
+    /// Lowest allowed horizontal speed at the end
+    pub min_final_speed: Option<f32>,
+    /// Highest allowed horizontal speed at the end
+    pub max_final_speed: Option<f32>,
+    /// Highest allowed height above the initial origin
+    pub max_height: Option<f32>,
+    /// Lowest required peak height above the initial origin
+    pub min_height: Option<f32>,
+    /// The player must never end a tick behind any world plane
+    pub no_clipping: bool,
+}
+
+/// A self-contained movement simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub config: MovementConfig,
+    pub initial_state: PlayerState,
+    /// Ticks per second
+    pub tick_rate: f32,
+    pub inputs: Vec<ScriptedInput>,
+    pub planes: Vec<Plane>,
+    pub expect: Expectations,
+}
+
+/// Result of running a `Scenario`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    /// The state before the first tick followed by the state after every tick
+    pub states: Vec<PlayerState>,
+    /// Every event emitted while running
+    pub events: Vec<MovementEvent>,
+    up_axis: UpAxis,
+}
+
+impl Scenario {
+    /// Create a scenario at 64 ticks per second without inputs or geometry
+    pub fn new(name: &str, config: MovementConfig, initial_state: PlayerState) -> Scenario {
+        Scenario {
+            name: name.to_string(),
+            config,
+            initial_state,
+            tick_rate: 64.0,
+            inputs: Vec::new(),
+            planes: Vec::new(),
+            expect: Expectations::default(),
+        }
+    }
+
+    /// Add a plane to the world
+    pub fn with_plane(mut self, plane: Plane) -> Scenario {
+        self.planes.push(plane);
+        self
+    }
+
+    /// Append an input held for `ticks` ticks
+    pub fn hold(mut self, ticks: u32, input: PlayerInput) -> Scenario {
+        self.inputs.push(ScriptedInput { ticks, input });
+        self
+    }
+
+    /// The same scenario simulated at another tick rate
+    ///
+    /// Scripted inputs are rescaled so each is held for the same time as before
+    /// (rounded to whole ticks, at least one).
+    pub fn at_tick_rate(mut self, tick_rate: f32) -> Scenario {
+        let scale = tick_rate / self.tick_rate;
+        for step in &mut self.inputs {
+            if step.ticks > 0 {
+                step.ticks = ((step.ticks as f32 * scale).round() as u32).max(1);
+            }
+        }
+        self.tick_rate = tick_rate;
+        self
+    }
+
+    /// Set the checks used by `run_and_check`
+    pub fn expecting(mut self, expect: Expectations) -> Scenario {
+        self.expect = expect;
+        self
+    }
+
+    /// Load a scenario from JSON (see the module docs for the format)
+    pub fn from_json(text: &str) -> Result<Scenario, AirStrafeError> {
+        let root = config_format::parse_json_value(text)?;
+        let mut config = MovementConfig::default();
+        if let Some(JsonValue::Object(fields)) = root.get("config") {
+            for (key, value) in fields {
+                let value = value.clone().into_config_value().ok_or_else(|| mismatch(key, "scalar"))?;
+                config.set(key, value)?;
+            }
+        }
+
+        let mut state = PlayerState::default();
+        if let Some(json) = root.get("state") {
+            state.origin = vector(json.get("origin"), "state.origin")?.unwrap_or(state.origin);
+            state.velocity = vector(json.get("velocity"), "state.velocity")?.unwrap_or(state.velocity);
+        }
+
+        let name = match root.get("name") {
+            Some(JsonValue::String(name)) => name.as_str(),
+            _ => "",
+        };
+        let mut scenario = Scenario::new(name, config, state);
+        if let Some(tick_rate) = number(root.get("tick_rate"), "tick_rate")? {
+            scenario.tick_rate = tick_rate;
+        }
+        for plane in array(root.get("planes"), "planes")? {
+            let point = vector(plane.get("point"), "planes.point")?.ok_or_else(|| mismatch("planes.point", "[x, y, z]"))?;
+            let normal = vector(plane.get("normal"), "planes.normal")?.ok_or_else(|| mismatch("planes.normal", "[x, y, z]"))?;
+            scenario.planes.push(Plane::new(point, normal));
+        }
+        for step in array(root.get("inputs"), "inputs")? {
+            let axis = |key: &'static str| number(step.get(key), key).map(|n| n.unwrap_or(0.0));
+            let button = |key: &'static str| flag(step.get(key), key);
+            let input = PlayerInput {
+                forward_move: axis("forward_move")?,
+                side_move: axis("side_move")?,
+                up_move: axis("up_move")?,
+                yaw: axis("yaw")?,
+                pitch: axis("pitch")?,
+                jump: button("jump")?,
+                duck: button("duck")?,
+                sprint: button("sprint")?,
+                jet: button("jet")?,
+                glide: button("glide")?,
+                ski: button("ski")?,
+                dodge: button("dodge")?,
+                ground_pound: button("ground_pound")?,
+                air_dash: button("air_dash")?,
+                fire: button("fire")?,
+                detach: button("detach")?,
+                lean: axis("lean")?,
+                jump_presses: axis("jump_presses")?.clamp(0.0, u8::MAX as f32) as u8,
+            };
+            let ticks = number(step.get("ticks"), "ticks")?.unwrap_or(1.0).max(0.0) as u32;
+            scenario.inputs.push(ScriptedInput { ticks, input });
+        }
+        if let Some(expect) = root.get("expect") {
+            scenario.expect = Expectations {
+                min_final_speed: number(expect.get("min_final_speed"), "min_final_speed")?,
+                max_final_speed: number(expect.get("max_final_speed"), "max_final_speed")?,
+                max_height: number(expect.get("max_height"), "max_height")?,
+                min_height: number(expect.get("min_height"), "min_height")?,
+                no_clipping: flag(expect.get("no_clipping"), "no_clipping")?,
+            };
+        }
+        Ok(scenario)
+    }
+
+    /// Simulate every scripted input, using the closest plane below the player as ground
+    pub fn run(&self) -> Trajectory {
+        let up_axis = self.config.up_axis;
+        let delta_time = 1.0 / self.tick_rate;
+        let mut state = self.initial_state;
+        let mut trajectory = Trajectory { states: vec![state], events: Vec::new(), up_axis };
+
+        for step in &self.inputs {
+            for _ in 0..step.ticks {
+                let hit = self
+                    .planes
+                    .iter()
+                    .filter_map(|plane| plane.ray_hit_down(&state.origin, up_axis))
+                    .min_by(|a, b| a.distance_native().total_cmp(&b.distance_native()));
+                let events = player_tick(&mut state, &step.input, &self.config, delta_time, hit);
+                trajectory.events.extend(events);
+                trajectory.states.push(state);
+            }
+        }
+        trajectory
+    }
+
+    /// Run the scenario and check its expectations
+    ///
+    /// # Returns
+    /// The trajectory, or a description of every failed check
+    pub fn run_and_check(&self) -> Result<Trajectory, Vec<String>> {
+        let trajectory = self.run();
+        let failures = self.check(&trajectory);
+        if failures.is_empty() {
+            Ok(trajectory)
+        } else {
+            Err(failures)
+        }
+    }
+
+    /// Describe every expectation the trajectory does not meet
+    pub fn check(&self, trajectory: &Trajectory) -> Vec<String> {
+        let mut failures = Vec::new();
+        let expect = &self.expect;
+        let speed = trajectory.final_speed();
+        if let Some(min) = expect.min_final_speed.filter(|&min| speed < min) {
+            failures.push(format!("{}: final speed {speed:.2} is below {min}", self.name));
+        }
+        if let Some(max) = expect.max_final_speed.filter(|&max| speed > max) {
+            failures.push(format!("{}: final speed {speed:.2} is above {max}", self.name));
+        }
+        let height = trajectory.max_height();
+        if let Some(max) = expect.max_height.filter(|&max| height > max) {
+            failures.push(format!("{}: max height {height:.2} is above {max}", self.name));
+        }
+        if let Some(min) = expect.min_height.filter(|&min| height < min) {
+            failures.push(format!("{}: max height {height:.2} is below {min}", self.name));
+        }
+        if expect.no_clipping {
+            for (index, plane) in self.planes.iter().enumerate() {
+                if let Some(tick) = trajectory.first_clip(plane) {
+                    failures.push(format!("{}: clipped through plane {index} on tick {tick}", self.name));
+                }
+            }
+        }
+        failures
+    }
+}
+
+impl Trajectory {
+    /// The state after the last tick
+    pub fn final_state(&self) -> &PlayerState {
+        self.states.last().expect("a trajectory always holds the initial state")
+    }
+
+    /// Horizontal speed after the last tick
+    pub fn final_speed(&self) -> f32 {
+        self.up_axis.horizontal(&self.final_state().velocity).magnitude()
+    }
+
+    /// Highest point reached, relative to the initial origin
+    pub fn max_height(&self) -> f32 {
+        let start = self.up_axis.height(&self.states[0].origin);
+        self.states
+            .iter()
+            .map(|state| self.up_axis.height(&state.origin) - start)
+            .fold(0.0, f32::max)
+    }
+
+    /// Statistics of every jump completed during the trajectory
+    pub fn jump_stats(&self) -> Vec<JumpStats> {
+        let mut meter = LongJumpMeter::new().with_up_axis(self.up_axis);
+        self.states
+            .iter()
+            .filter_map(|state| meter.observe(&state.origin, &state.velocity, state.on_ground))
+            .collect()
+    }
+
+    /// Every state as CSV, one row per tick starting with the initial state
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("tick,x,y,z,vx,vy,vz,on_ground,speed\n");
+        for (tick, state) in self.states.iter().enumerate() {
+            let (o, v) = (state.origin, state.velocity);
+            let speed = self.up_axis.horizontal(&v).magnitude();
+            csv += &format!("{tick},{},{},{},{},{},{},{},{speed}\n", o.x, o.y, o.z, v.x, v.y, v.z, state.on_ground as u8);
+        }
+        csv
+    }
+
+    /// The states and jump statistics as JSON
+    ///
+    /// `{"ticks": [[x, y, z, vx, vy, vz, on_ground], ...], "jumps": [{"distance": ..., ...}, ...]}`
+    pub fn to_json(&self) -> String {
+        let ticks: Vec<String> = self
+            .states
+            .iter()
+            .map(|state| {
+                let (o, v) = (state.origin, state.velocity);
+                format!("    [{}, {}, {}, {}, {}, {}, {}]", o.x, o.y, o.z, v.x, v.y, v.z, state.on_ground as u8)
+            })
+            .collect();
+        let jumps: Vec<String> = self
+            .jump_stats()
+            .iter()
+            .map(|jump| {
+                format!(
+                    "    {{\"distance\": {}, \"raw_distance\": {}, \"height_difference\": {}, \"max_height\": {}, \"pre_speed\": {}, \"max_speed\": {}, \"air_ticks\": {}}}",
+                    jump.distance,
+                    jump.raw_distance,
+                    jump.height_difference,
+                    jump.max_height,
+                    jump.pre_speed,
+                    jump.max_speed,
+                    jump.air_ticks
+                )
+            })
+            .collect();
+        format!("{{\n  \"ticks\": [\n{}\n  ],\n  \"jumps\": [\n{}\n  ]\n}}\n", ticks.join(",\n"), jumps.join(",\n"))
+    }
+
+    /// Number of ticks after which the player first ended up behind `plane`
+    pub fn first_clip(&self, plane: &Plane) -> Option<usize> {
+        self.states
+            .iter()
+            .position(|state| plane.signed_distance(&state.origin) < -CLIP_TOLERANCE)
+    }
+}
+
+fn mismatch(field: &str, expected: &'static str) -> AirStrafeError {
+    ConfigError::TypeMismatch { field: field.to_string(), expected }.into()
+}
+
+fn number(value: Option<&JsonValue>, field: &'static str) -> Result<Option<f32>, AirStrafeError> {
+    match value {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::Number(n)) => Ok(Some(*n)),
+        Some(_) => Err(mismatch(field, "number")),
+    }
+}
+
+fn flag(value: Option<&JsonValue>, field: &'static str) -> Result<bool, AirStrafeError> {
+    match value {
+        None | Some(JsonValue::Null) => Ok(false),
+        Some(JsonValue::Bool(b)) => Ok(*b),
+        Some(_) => Err(mismatch(field, "boolean")),
+    }
+}
+
+fn array<'a>(value: Option<&'a JsonValue>, field: &'static str) -> Result<&'a [JsonValue], AirStrafeError> {
+    match value {
+        None | Some(JsonValue::Null) => Ok(&[]),
+        Some(JsonValue::Array(items)) => Ok(items),
+        Some(_) => Err(mismatch(field, "array")),
+    }
+}
+
+fn vector(value: Option<&JsonValue>, field: &'static str) -> Result<Option<Vector3>, AirStrafeError> {
+    match value {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::Array(items)) => match items.as_slice() {
+            [JsonValue::Number(x), JsonValue::Number(y), JsonValue::Number(z)] => Ok(Some(Vector3::new(*x, *y, *z))),
+            _ => Err(mismatch(field, "[x, y, z]")),
+        },
+        Some(_) => Err(mismatch(field, "[x, y, z]")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    const BHOP: &str = r#"{
+        "name": "bhop",
+        "config": { "auto_bhop": true, "up_axis": "y", "legacy_gravity": false },
+        "state": { "velocity": [0, 0, -250] },
+        "planes": [{ "point": [0, 0, 0], "normal": [0, 1, 0] }],
+        "inputs": [{ "ticks": 256, "side_move": 1, "jump": true }],
+        "expect": { "max_height": 60, "min_height": 40, "no_clipping": true }
+    }"#;
+
+    #[test]
+    fn test_json_scenario_runs_and_checks() {
+        let scenario = Scenario::from_json(BHOP).unwrap();
+        assert!(scenario.config.auto_bhop);
+        assert_eq!(scenario.inputs[0].ticks, 256);
+
+        let trajectory = scenario.run_and_check().unwrap();
+        assert_eq!(trajectory.states.len(), 257);
+        assert!(trajectory.events.iter().any(|e| matches!(e, MovementEvent::Jumped { .. })));
+        assert!(!trajectory.jump_stats().is_empty());
+        assert_eq!(trajectory.to_csv().lines().count(), 258);
+        assert!(config_format::parse_json_value(&trajectory.to_json()).is_ok());
+
+        let fast = scenario.at_tick_rate(128.0);
+        assert_eq!(fast.inputs[0].ticks, 512);
+        assert_eq!(fast.run().states.len(), 513);
+    }
+
+    #[test]
+    fn test_failed_expectations_are_reported() {
+        let floor = Plane::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let scenario = Scenario::new("walk", y_up_config(), PlayerState::default())
+            .with_plane(floor)
+            .hold(128, PlayerInput { forward_move: 1.0, ..PlayerInput::new() })
+            .expecting(Expectations { min_final_speed: Some(400.0), no_clipping: true, ..Expectations::default() });
+
+        let failures = scenario.run_and_check().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].contains("final speed"));
+
+        // Without geometry the player falls through where the floor would be
+        let trajectory = Scenario { planes: Vec::new(), ..scenario }.run();
+        assert!(trajectory.first_clip(&floor).is_some());
+    }
+}
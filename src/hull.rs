@@ -0,0 +1,124 @@
+//! Collision hull dimensions per stance
+//!
+//! The crate does not trace the world itself, but the host's traces, the
+//! ceiling check of `update_ceiling_clearance` and any hitbox history kept for
+//! lag compensation all need the same hull the movement assumes. Standing and
+//! ducked hulls are configured on `MovementConfig` as a height above the
+//! origin (the feet) and a horizontal half-width, either as axis-aligned boxes
+//! or as upright capsules with `hull_capsule`. `current_hull` picks the hull
+//! from `PlayerState::ducked`, so a recorded state is all a history needs to
+//! rebuild the hull the player had at that tick.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerState, UpAxis, Vector3};
+
+/// Dimensions of a collision hull in world units, standing on the origin
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hull {
+    /// Horizontal half-width: the box half-extent or the capsule radius
+    pub radius: f32,
+    /// Height from the feet to the top of the hull
+    pub height: f32,
+    /// Whether the hull is an upright capsule instead of an axis-aligned box
+    pub capsule: bool,
+}
+
+impl Hull {
+    /// Lowest corner of the hull's bounds relative to the origin
+    pub fn mins(&self, up_axis: UpAxis) -> Vector3 {
+        let mut mins = Vector3::new(-self.radius, -self.radius, -self.radius);
+        up_axis.set_height(&mut mins, 0.0);
+        mins
+    }
+
+    /// Highest corner of the hull's bounds relative to the origin
+    pub fn maxs(&self, up_axis: UpAxis) -> Vector3 {
+        let mut maxs = Vector3::new(self.radius, self.radius, self.radius);
+        up_axis.set_height(&mut maxs, self.height);
+        maxs
+    }
+}
+
+/// The standing hull of `config`
+pub fn standing_hull(config: &MovementConfig) -> Hull {
+    Hull {
+        radius: config.to_world_units(config.hull_radius),
+        height: config.to_world_units(config.hull_height),
+        capsule: config.hull_capsule,
+    }
+}
+
+/// The ducked hull of `config`
+pub fn ducked_hull(config: &MovementConfig) -> Hull {
+    Hull {
+        radius: config.to_world_units(config.duck_hull_radius),
+        height: config.to_world_units(config.duck_hull_height),
+        capsule: config.hull_capsule,
+    }
+}
+
+/// The hull the player currently collides with
+pub fn current_hull(state: &PlayerState, config: &MovementConfig) -> Hull {
+    if state.ducked {
+        ducked_hull(config)
+    } else {
+        standing_hull(config)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Hull {
+    #[wasm_bindgen(js_name = mins)]
+    pub fn mins_wasm(&self, up_axis: UpAxis) -> Vector3 {
+        self.mins(up_axis)
+    }
+
+    #[wasm_bindgen(js_name = maxs)]
+    pub fn maxs_wasm(&self, up_axis: UpAxis) -> Vector3 {
+        self.maxs(up_axis)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = currentHull)]
+pub fn current_hull_wasm(state: &PlayerState, config: &MovementConfig) -> Hull {
+    current_hull(state, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HAMMER_UNITS_TO_METERS;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_hull_follows_the_stance_and_unit_scale() {
+        let config = MovementConfig { duck_hull_radius: 12.0, unit_scale: HAMMER_UNITS_TO_METERS, ..y_up_config() };
+        let standing = current_hull(&PlayerState::default(), &config);
+        let ducked = current_hull(&PlayerState { ducked: true, ..PlayerState::default() }, &config);
+        assert!((standing.height - 72.0 * HAMMER_UNITS_TO_METERS).abs() < 1e-6);
+        assert!((ducked.radius - 12.0 * HAMMER_UNITS_TO_METERS).abs() < 1e-6);
+
+        let hull = standing_hull(&y_up_config());
+        assert_eq!(hull.mins(UpAxis::Z), Vector3::new(-16.0, -16.0, 0.0));
+        assert_eq!(hull.maxs(UpAxis::Z), Vector3::new(16.0, 16.0, 72.0));
+        assert_eq!(hull.maxs(UpAxis::Y), Vector3::new(16.0, 72.0, 16.0));
+    }
+    #[test]
+    fn test_capsule_flag_and_y_up_bounds_cover_both_stances() {
+        let config = MovementConfig { hull_capsule: true, ..y_up_config() };
+        assert!(standing_hull(&config).capsule && ducked_hull(&config).capsule);
+        assert!(!current_hull(&PlayerState::default(), &y_up_config()).capsule);
+
+        let ducked = current_hull(&PlayerState { ducked: true, ..PlayerState::default() }, &config);
+        assert_eq!(ducked.mins(UpAxis::Y), Vector3::new(-16.0, 0.0, -16.0));
+        assert_eq!(ducked.maxs(UpAxis::Y), Vector3::new(16.0, 36.0, 16.0));
+
+        let point = Hull { radius: 0.0, height: 0.0, capsule: false };
+        assert_eq!(point.mins(UpAxis::Z), point.maxs(UpAxis::Z));
+    }
+}
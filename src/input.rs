@@ -0,0 +1,104 @@
+//! Per-tick player input
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{UpAxis, Vector3};
+
+/// Input for a single simulation tick
+///
+/// View angles are in radians and positive yaw turns left. Where yaw 0.0 looks
+/// depends on the `UpAxis` in use.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayerInput {
+    /// Forward (+1.0) / backward (-1.0) movement
+    pub forward_move: f32,
+    /// Right (+1.0) / left (-1.0) movement
+    pub side_move: f32,
+    /// Up (+1.0) / down (-1.0) movement, for move types that use it
+    pub up_move: f32,
+    /// View yaw in radians
+    pub yaw: f32,
+    /// View pitch in radians (positive looks up)
+    pub pitch: f32,
+    /// Jump button
+    pub jump: bool,
+    /// Duck button
+    pub duck: bool,
+    /// Sprint button
+    pub sprint: bool,
+    /// Jetpack button (only with `PlayerState::jetpack_enabled`)
+    pub jet: bool,
+    /// Glide button, opens the glider while airborne
+    pub glide: bool,
+    /// Ski button, disables ground friction and slides down slopes
+    pub ski: bool,
+    /// Dodge button, dodges along the held direction (see `dodge`)
+    pub dodge: bool,
+    /// Ground pound button, fast-falls while airborne (see `ground_pound`)
+    pub ground_pound: bool,
+    /// Air dash button, dashes while airborne (see `air_dash`)
+    pub air_dash: bool,
+    /// Fire button, cancels a ramped sprint (see `sprint`)
+    pub fire: bool,
+    /// Detach button, lets go of sticky surfaces (see `sticky`)
+    pub detach: bool,
+    /// Lean right (+1.0) / left (-1.0)
+    pub lean: f32,
+    /// Jump presses made within this tick, e.g. by a scroll wheel bind
+    ///
+    /// Any press counts as pressing jump for the tick, even if the button was
+    /// released again before the tick ended. As in Source, the next tick sees
+    /// jump as held, so scrolling only jumps on ticks after one without a press.
+    pub jump_presses: u8,
+}
+
+impl PlayerInput {
+    /// Create an empty input (no buttons, looking down -Z)
+    pub fn new() -> PlayerInput {
+        PlayerInput::default()
+    }
+
+    /// Horizontal forward direction for the input's yaw
+    pub fn forward(&self, up_axis: UpAxis) -> Vector3 {
+        up_axis.forward(self.yaw)
+    }
+
+    /// Horizontal right direction for the input's yaw
+    pub fn right(&self, up_axis: UpAxis) -> Vector3 {
+        up_axis.right(self.yaw)
+    }
+
+    /// Full 3D view direction including pitch
+    pub fn view_forward(&self, up_axis: UpAxis) -> Vector3 {
+        up_axis.view_forward(self.yaw, self.pitch)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl PlayerInput {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> PlayerInput {
+        PlayerInput::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basis_and_yaw_roundtrip() {
+        let input = PlayerInput::new();
+        assert!((input.forward(UpAxis::Y).z + 1.0).abs() < 0.0001);
+        assert!((input.right(UpAxis::Y).x - 1.0).abs() < 0.0001);
+        assert!((input.forward(UpAxis::Z).x - 1.0).abs() < 0.0001);
+
+        let dir = Vector3::new(0.6, 0.0, 0.8);
+        let input = PlayerInput { yaw: UpAxis::Y.yaw_from_direction(&dir), ..PlayerInput::new() };
+        assert!((input.forward(UpAxis::Y).dot(&dir) - 1.0).abs() < 0.0001);
+        assert!(input.right(UpAxis::Y).dot(&dir).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,150 @@
+//! Jetpack thrust (Tribes/Starsiege style)
+//!
+//! While `PlayerState::jetpack_enabled` is set and the jet button is held,
+//! `player_tick` lifts the player off the ground and pushes them up, tilted
+//! towards the movement input, until the tank runs dry. Thrust goes through
+//! the same integration as gravity (`gravity_velocity_change_native`), so it
+//! balances `gravity` the same way with the legacy `gravity_influence` model as
+//! with the standard one.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::fixed::with_fixed;
+use crate::{gravity_velocity_change_native, MovementConfig, PlayerState, UpAxis, Vector3};
+
+/// Unit direction of the jetpack thrust
+///
+/// Straight up, tilted towards the horizontal `wish_dir` by
+/// `jetpack_directional_factor` (1.0 = 45 degrees at full input).
+pub fn jetpack_direction(wish_dir: &Vector3, config: &MovementConfig) -> Vector3 {
+    with_fixed!(config.up_axis, Z_UP => jetpack_direction_fixed::<Z_UP>(wish_dir, config))
+}
+
+/// `jetpack_direction` for the up axis of a `fixed` instantiation
+fn jetpack_direction_fixed<const Z_UP: bool>(wish_dir: &Vector3, config: &MovementConfig) -> Vector3 {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let mut direction = axis.up();
+    direction.add(&axis.horizontal(wish_dir).multiply_scalar(config.jetpack_directional_factor));
+    direction.normalized()
+}
+
+/// Velocity change from one tick of jetpack thrust
+///
+/// No speed is added along the thrust direction beyond `jetpack_max_speed`.
+/// Fuel is not checked or used; `player_tick` takes care of that.
+///
+/// # Arguments
+/// * `velocity` - The current velocity in world units
+/// * `wish_dir` - The horizontal wish direction built from the player's input
+/// * `config` - The config in world units
+/// * `delta_time` - Time step in seconds
+pub fn jetpack_thrust(velocity: &Vector3, wish_dir: &Vector3, config: &MovementConfig, delta_time: f32) -> Vector3 {
+    with_fixed!(config.up_axis, config.legacy_gravity, Z_UP, LEGACY_GRAVITY => {
+        jetpack_thrust_fixed::<Z_UP, LEGACY_GRAVITY>(velocity, wish_dir, config, delta_time)
+    })
+}
+
+/// `jetpack_thrust` for the up axis and gravity model of a `fixed` instantiation
+pub(crate) fn jetpack_thrust_fixed<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+    velocity: &Vector3,
+    wish_dir: &Vector3,
+    config: &MovementConfig,
+    delta_time: f32,
+) -> Vector3 {
+    let direction = jetpack_direction_fixed::<Z_UP>(wish_dir, config);
+    let thrust = direction.multiply_scalar(config.jetpack_thrust);
+    let legacy_scale = LEGACY_GRAVITY.then_some(config.legacy_gravity_scale);
+    let change = gravity_velocity_change_native(&thrust, delta_time, legacy_scale);
+
+    let room = config.jetpack_max_speed - velocity.dot(&direction);
+    if room <= 0.0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+    if change.magnitude() > room {
+        direction.multiply_scalar(room)
+    } else {
+        change
+    }
+}
+
+/// Use fuel while thrusting and refill it otherwise
+///
+/// # Returns
+/// Whether there was fuel for thrust on this tick
+pub fn update_jetpack_fuel(state: &mut PlayerState, thrusting: bool, config: &MovementConfig, delta_time: f32) -> bool {
+    let had_fuel = state.jetpack_fuel > 0.0;
+    let (time, sign) = if thrusting && had_fuel {
+        (config.jetpack_fuel_time, -1.0)
+    } else {
+        (config.jetpack_regen_time, 1.0)
+    };
+    state.jetpack_fuel = if time > 0.0 {
+        (state.jetpack_fuel + sign * delta_time / time).clamp(0.0, 1.0)
+    } else if sign > 0.0 {
+        1.0
+    } else {
+        state.jetpack_fuel
+    };
+    thrusting && had_fuel
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = jetpackThrust)]
+pub fn jetpack_thrust_wasm(velocity: &Vector3, wish_dir: &Vector3, config: &MovementConfig, delta_time: f32) -> Vector3 {
+    jetpack_thrust(velocity, wish_dir, &config.in_world_units(), delta_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_jetpack_lifts_off_and_drains_fuel() {
+        let config = y_up_config();
+        let mut state = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).jetpack(true).build().unwrap();
+        let input = PlayerInput { jet: true, forward_move: 1.0, ..PlayerInput::new() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let dt = 1.0 / 64.0;
+
+        player_tick(&mut state, &input, &config, dt, ground);
+        assert!(!state.on_ground);
+        for _ in 0..63 {
+            player_tick(&mut state, &input, &config, dt, None);
+        }
+        assert!(state.origin.y > 100.0);
+        assert!(state.velocity.dot(&jetpack_direction(&input.forward(config.up_axis), &config)) <= config.jetpack_max_speed + 0.01);
+        assert!((state.jetpack_fuel - (1.0 - 1.0 / config.jetpack_fuel_time)).abs() < 0.001);
+
+        // An empty tank gives no thrust and refills once the button is released
+        state.jetpack_fuel = 0.0;
+        let falling = state.velocity.y;
+        player_tick(&mut state, &input, &config, dt, None);
+        assert!(state.velocity.y < falling);
+        player_tick(&mut state, &PlayerInput::new(), &config, dt, None);
+        assert!(state.jetpack_fuel > 0.0);
+    }
+
+    #[test]
+    fn test_jetpack_is_off_unless_enabled() {
+        let config = y_up_config();
+        let mut state = PlayerState::new(Vector3::new(0.0, 100.0, 0.0));
+        player_tick(&mut state, &PlayerInput { jet: true, ..PlayerInput::new() }, &config, 1.0 / 64.0, None);
+        assert!(state.velocity.y < 0.0);
+        assert_eq!(state.jetpack_fuel, 1.0);
+    }
+
+    #[test]
+    fn test_thrust_uses_gravity_model() {
+        let config = y_up_config();
+        let no_input = Vector3::new(0.0, 0.0, 0.0);
+        let standard = jetpack_thrust(&Vector3::new(0.0, 0.0, 0.0), &no_input, &config, 0.1);
+        assert!((standard.y - config.jetpack_thrust * 0.1).abs() < 0.001);
+
+        let legacy = MovementConfig { legacy_gravity: true, ..config };
+        let thrust = jetpack_thrust(&Vector3::new(0.0, 0.0, 0.0), &no_input, &legacy, 0.1);
+        assert!((thrust.y - config.jetpack_thrust * 0.01 * legacy.legacy_gravity_scale).abs() < 0.001);
+    }
+}
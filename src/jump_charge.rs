@@ -0,0 +1,79 @@
+//! Charged jumps
+//!
+//! With a non-zero `jump_charge_time`, holding jump on the ground charges the
+//! jump instead of jumping. `PlayerState::jump_charge` fills to 1.0 over
+//! `jump_charge_time` while the player moves at `jump_charge_move_multiplier`
+//! of their speed, and releasing jumps with the jump speed scaled from 1.0 up
+//! to `jump_charge_max_multiplier`. Leaving the ground loses the charge. All
+//! of it lives in `PlayerState`, so charged jumps predict and rewind like
+//! everything else.
+
+use crate::{MovementConfig, PlayerInput, PlayerState};
+
+/// Jump speed multiplier for a charge
+pub fn jump_charge_multiplier(charge: f32, config: &MovementConfig) -> f32 {
+    1.0 + (config.jump_charge_max_multiplier - 1.0) * charge.clamp(0.0, 1.0)
+}
+
+/// Build charge while jump is held on the ground
+///
+/// # Returns
+/// The jump speed multiplier when the jump is released this tick
+pub(crate) fn update_jump_charge(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+) -> Option<f32> {
+    if config.jump_charge_time <= 0.0 || !state.on_ground {
+        state.jump_charging = false;
+        state.jump_charge = 0.0;
+        return None;
+    }
+    if input.jump {
+        // A jump held since before landing has to be pressed again
+        if state.jump_charging || !state.jump_held {
+            state.jump_charging = true;
+            state.jump_charge = (state.jump_charge + delta_time / config.jump_charge_time).min(1.0);
+        }
+        return None;
+    }
+    if !state.jump_charging {
+        return None;
+    }
+    let multiplier = jump_charge_multiplier(state.jump_charge, config);
+    state.jump_charging = false;
+    state.jump_charge = 0.0;
+    Some(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MovementEvent, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_holding_jump_charges_a_higher_jump() {
+        let config = MovementConfig { jump_charge_time: 0.5, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let dt = 1.0 / 64.0;
+        let hold = PlayerInput { jump: true, forward_move: 1.0, ..PlayerInput::new() };
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+
+        for _ in 0..64 {
+            let events = player_tick(&mut state, &hold, &config, dt, ground);
+            assert!(!events.iter().any(|event| matches!(event, MovementEvent::Jumped { .. })));
+        }
+        assert!(state.on_ground && state.jump_charging);
+        assert_eq!(state.jump_charge, 1.0);
+        let slowed = config.max_speed * config.jump_charge_move_multiplier;
+        assert!((state.velocity.magnitude() - slowed).abs() < 1.0);
+
+        let events = player_tick(&mut state, &PlayerInput::new(), &config, dt, ground);
+        assert!(events.iter().any(|event| matches!(event, MovementEvent::Jumped { .. })));
+        let jump = config.jump_speed * config.jump_charge_max_multiplier;
+        assert!((state.velocity.y + config.gravity * dt - jump).abs() < 0.01);
+        assert!(!state.jump_charging && state.jump_charge == 0.0);
+    }
+}
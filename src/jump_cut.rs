@@ -0,0 +1,55 @@
+//! Variable jump height
+//!
+//! Platformers let a quick tap of jump make a short hop: releasing the button
+//! while still rising scales the remaining upward speed by
+//! `jump_cut_multiplier`, once per jump. Whether the current jump can still
+//! be cut lives in `PlayerState::jump_cut_ready`, so client prediction and
+//! replays cut the same jumps on the same ticks. The window closes at the
+//! apex, on landing and after the cut.
+
+use crate::{MovementConfig, PlayerState, UpAxis};
+
+/// Cut the current jump short if jump was released on the way up
+pub(crate) fn update_jump_cut<const Z_UP: bool>(state: &mut PlayerState, jump: bool, config: &MovementConfig) {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let up_speed = axis.height(&state.velocity);
+    if state.on_ground || up_speed <= 0.0 {
+        state.jump_cut_ready = false;
+        return;
+    }
+    if state.jump_cut_ready && !jump {
+        axis.set_height(&mut state.velocity, up_speed * config.jump_cut_multiplier);
+        state.jump_cut_ready = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::{player_tick, PlayerInput};
+
+    /// Apex of a jump with the button held for `held_ticks`
+    fn apex(config: &MovementConfig, held_ticks: usize) -> f32 {
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+        let mut apex: f32 = 0.0;
+        for tick in 0..200 {
+            let input = PlayerInput { jump: tick < held_ticks, ..PlayerInput::new() };
+            let ground = flat_ground(&state);
+            player_tick(&mut state, &input, config, 1.0 / 64.0, ground);
+            apex = apex.max(state.origin.y);
+        }
+        apex
+    }
+
+    #[test]
+    fn test_releasing_early_cuts_the_jump() {
+        let config = MovementConfig { jump_cut_multiplier: 0.5, ..y_up_config() };
+        let full = apex(&y_up_config(), 1);
+        assert!((apex(&config, 200) - full).abs() < 1e-3);
+        // A tap cuts right away; holding past the apex is a full jump
+        assert!(apex(&config, 1) < 0.5 * full);
+        assert!(apex(&config, 10) > apex(&config, 1) && apex(&config, 10) < full);
+        assert!((apex(&config, 40) - full).abs() < 1e-3);
+    }
+}
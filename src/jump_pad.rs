@@ -0,0 +1,168 @@
+//! Jump pads and bounce pads
+//!
+//! A `JumpPad` launches the player along the pad normal, either at a fixed
+//! speed or hard enough to reach a given apex height. Like Quake's
+//! `trigger_push`, the launch replaces the player's velocity by default; pads
+//! can instead keep the horizontal velocity the player came in with and only
+//! add the launch on top. The host detects touching the pad and calls
+//! `launch`, usually once when the player enters the trigger.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerState, Vector3};
+
+/// What happens to the player's horizontal velocity on launch
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PadHorizontal {
+    /// The launch velocity replaces the whole velocity (Quake `trigger_push`)
+    #[default]
+    Replace,
+    /// The horizontal velocity is kept and the launch velocity is added to it
+    Preserve,
+}
+
+/// How hard a pad launches the player
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LaunchTarget {
+    Speed(f32),
+    ApexHeight(f32),
+}
+
+/// A launch pad; speeds and heights are in world units
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpPad {
+    normal: Vector3,
+    target: LaunchTarget,
+    horizontal: PadHorizontal,
+}
+
+impl JumpPad {
+    /// A pad that launches along `normal` at `speed`
+    pub fn with_speed(normal: Vector3, speed: f32) -> JumpPad {
+        JumpPad {
+            normal: normal.normalized(),
+            target: LaunchTarget::Speed(speed.max(0.0)),
+            horizontal: PadHorizontal::Replace,
+        }
+    }
+
+    /// A pad that launches along `normal` hard enough to rise `height` above it
+    ///
+    /// The height is exact for the standard gravity model. Pads whose normal
+    /// has no upward part can't reach any height and don't launch.
+    pub fn with_apex_height(normal: Vector3, height: f32) -> JumpPad {
+        JumpPad {
+            normal: normal.normalized(),
+            target: LaunchTarget::ApexHeight(height.max(0.0)),
+            horizontal: PadHorizontal::Replace,
+        }
+    }
+
+    /// Set what happens to the horizontal velocity on launch
+    pub fn horizontal(mut self, horizontal: PadHorizontal) -> JumpPad {
+        self.horizontal = horizontal;
+        self
+    }
+
+    /// The velocity the pad adds along its normal
+    pub fn launch_impulse(&self, config: &MovementConfig) -> Vector3 {
+        let config = config.in_world_units();
+        let speed = match self.target {
+            LaunchTarget::Speed(speed) => speed,
+            LaunchTarget::ApexHeight(height) => {
+                let up = config.up_axis.height(&self.normal);
+                if up <= 0.0 {
+                    return Vector3::new(0.0, 0.0, 0.0);
+                }
+                (2.0 * config.gravity * height).sqrt() / up
+            }
+        };
+        self.normal.multiply_scalar(speed)
+    }
+
+    /// The player's velocity right after launching from the pad
+    pub fn launch_velocity(&self, velocity: &Vector3, config: &MovementConfig) -> Vector3 {
+        let impulse = self.launch_impulse(config);
+        match self.horizontal {
+            PadHorizontal::Replace => impulse,
+            PadHorizontal::Preserve => config.up_axis.horizontal(velocity) + impulse,
+        }
+    }
+
+    /// Launch the player off the pad
+    pub fn launch(&self, state: &mut PlayerState, config: &MovementConfig) {
+        state.velocity = self.launch_velocity(&state.velocity, config);
+        state.on_ground = false;
+        state.time_on_ground = 0.0;
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl JumpPad {
+    #[wasm_bindgen(js_name = withSpeed)]
+    pub fn with_speed_wasm(normal: &Vector3, speed: f32) -> JumpPad {
+        JumpPad::with_speed(*normal, speed)
+    }
+
+    #[wasm_bindgen(js_name = withApexHeight)]
+    pub fn with_apex_height_wasm(normal: &Vector3, height: f32) -> JumpPad {
+        JumpPad::with_apex_height(*normal, height)
+    }
+
+    #[wasm_bindgen(js_name = horizontal)]
+    pub fn horizontal_wasm(self, horizontal: PadHorizontal) -> JumpPad {
+        self.horizontal(horizontal)
+    }
+
+    #[wasm_bindgen(js_name = launchVelocity)]
+    pub fn launch_velocity_wasm(&self, velocity: &Vector3, config: &MovementConfig) -> Vector3 {
+        self.launch_velocity(velocity, config)
+    }
+
+    #[wasm_bindgen(js_name = launch)]
+    pub fn launch_wasm(&self, state: &mut PlayerState, config: &MovementConfig) {
+        self.launch(state, config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_apex_height_pad_reaches_height() {
+        let config = y_up_config();
+        let pad = JumpPad::with_apex_height(Vector3::new(0.0, 1.0, 0.0), 256.0);
+        let mut state = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).build().unwrap();
+        pad.launch(&mut state, &config);
+        assert!(!state.on_ground);
+
+        let mut apex: f32 = 0.0;
+        for _ in 0..256 {
+            player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 128.0, None);
+            apex = apex.max(state.origin.y);
+        }
+        assert!((apex - 256.0).abs() < 256.0 * 0.02);
+    }
+
+    #[test]
+    fn test_replace_or_preserve_horizontal_velocity() {
+        let config = y_up_config();
+        let incoming = Vector3::new(300.0, -200.0, 0.0);
+        let pad = JumpPad::with_speed(Vector3::new(0.0, 1.0, 0.0), 500.0);
+        assert_eq!(pad.launch_velocity(&incoming, &config), Vector3::new(0.0, 500.0, 0.0));
+
+        let pad = pad.horizontal(PadHorizontal::Preserve);
+        assert_eq!(pad.launch_velocity(&incoming, &config), Vector3::new(300.0, 500.0, 0.0));
+
+        // Walls can't reach an apex height
+        let wall = JumpPad::with_apex_height(Vector3::new(1.0, 0.0, 0.0), 100.0);
+        assert_eq!(wall.launch_impulse(&config), Vector3::new(0.0, 0.0, 0.0));
+    }
+}
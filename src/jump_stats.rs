@@ -0,0 +1,271 @@
+//! Jump statistics and long-jump (LJ) measurement
+//!
+//! Tracks ground/air transitions of a player and produces a `JumpStats` record
+//! for every completed jump, using the KZ convention of adding the player hull
+//! width (32 units) to the horizontal distance between takeoff and landing.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{UpAxis, Vector3};
+
+/// Standard distance offset added to measured jumps (player hull width in units)
+pub const DEFAULT_JUMP_DISTANCE_OFFSET: f32 = 32.0;
+
+/// Statistics for a single completed jump
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpStats {
+    /// Player origin on the last grounded tick before leaving the ground
+    pub takeoff: Vector3,
+    /// Player origin on the first grounded tick after the jump
+    pub landing: Vector3,
+    /// Horizontal takeoff-to-landing distance without the offset
+    pub raw_distance: f32,
+    /// Reported jump distance (`raw_distance` + distance offset)
+    pub distance: f32,
+    /// Landing height minus takeoff height (0.0 for a flat long jump)
+    pub height_difference: f32,
+    /// Highest point reached relative to the takeoff height
+    pub max_height: f32,
+    /// Horizontal speed at takeoff
+    pub pre_speed: f32,
+    /// Highest horizontal speed reached while airborne
+    pub max_speed: f32,
+    /// Number of ticks spent in the air
+    pub air_ticks: u32,
+}
+
+impl JumpStats {
+    /// Whether takeoff and landing happened at the same height (within tolerance),
+    /// which is required for the jump to count as a long jump
+    pub fn is_flat(&self, tolerance: f32) -> bool {
+        self.height_difference.abs() <= tolerance
+    }
+}
+
+/// Jump in progress, started on the takeoff edge
+#[derive(Debug, Clone, Copy)]
+struct AirborneJump {
+    takeoff: Vector3,
+    pre_speed: f32,
+    max_height: f32,
+    max_speed: f32,
+    air_ticks: u32,
+}
+
+/// Detects takeoff and landing edges from per-tick samples and measures jumps
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct LongJumpMeter {
+    distance_offset: f32,
+    up_axis: UpAxis,
+    last_origin: Option<Vector3>,
+    last_speed: f32,
+    was_on_ground: bool,
+    airborne: Option<AirborneJump>,
+    last_jump: Option<JumpStats>,
+}
+
+impl Default for LongJumpMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LongJumpMeter {
+    /// Create a meter using the standard +32 unit distance offset
+    pub fn new() -> LongJumpMeter {
+        Self::with_distance_offset(DEFAULT_JUMP_DISTANCE_OFFSET)
+    }
+
+    /// Create a meter with a custom distance offset (e.g. 0.0 for raw distances)
+    pub fn with_distance_offset(distance_offset: f32) -> LongJumpMeter {
+        LongJumpMeter {
+            distance_offset,
+            up_axis: UpAxis::Y,
+            last_origin: None,
+            last_speed: 0.0,
+            was_on_ground: false,
+            airborne: None,
+            last_jump: None,
+        }
+    }
+
+    /// The distance offset added to every measured jump
+    pub fn distance_offset(&self) -> f32 {
+        self.distance_offset
+    }
+
+    /// Measure heights along `up_axis` and distances in the plane orthogonal to it
+    pub fn with_up_axis(mut self, up_axis: UpAxis) -> LongJumpMeter {
+        self.up_axis = up_axis;
+        self
+    }
+
+    /// The axis heights are measured along
+    pub fn up_axis(&self) -> UpAxis {
+        self.up_axis
+    }
+
+    /// The most recently completed jump, if any
+    pub fn last_jump(&self) -> Option<JumpStats> {
+        self.last_jump
+    }
+
+    /// Whether a jump is currently being measured
+    pub fn is_airborne(&self) -> bool {
+        self.airborne.is_some()
+    }
+
+    /// Forget any jump in progress (e.g. after a teleport or rewind)
+    pub fn reset(&mut self) {
+        self.last_origin = None;
+        self.airborne = None;
+        self.was_on_ground = false;
+    }
+
+    /// Feed one tick of player data into the meter
+    ///
+    /// # Arguments
+    /// * `origin` - The player origin after this tick
+    /// * `velocity` - The player velocity after this tick
+    /// * `on_ground` - Whether the player is on the ground after this tick
+    ///
+    /// # Returns
+    /// The stats of the jump that was completed on this tick, if any
+    pub fn observe(&mut self, origin: &Vector3, velocity: &Vector3, on_ground: bool) -> Option<JumpStats> {
+        let axis = self.up_axis;
+        let speed = axis.horizontal(velocity).magnitude();
+        let height = axis.height(origin);
+        let mut completed = None;
+
+        match (self.was_on_ground, on_ground) {
+            // Takeoff edge: the jump starts from the last grounded origin
+            (true, false) => {
+                if let Some(takeoff) = self.last_origin {
+                    self.airborne = Some(AirborneJump {
+                        takeoff,
+                        pre_speed: self.last_speed,
+                        max_height: height.max(axis.height(&takeoff)),
+                        max_speed: speed.max(self.last_speed),
+                        air_ticks: 1,
+                    });
+                }
+            }
+            (false, false) => {
+                if let Some(jump) = self.airborne.as_mut() {
+                    jump.max_height = jump.max_height.max(height);
+                    jump.max_speed = jump.max_speed.max(speed);
+                    jump.air_ticks += 1;
+                }
+            }
+            // Landing edge: close the jump at the first grounded origin
+            (false, true) => {
+                if let Some(jump) = self.airborne.take() {
+                    let takeoff_height = axis.height(&jump.takeoff);
+                    let raw_distance = axis.horizontal(&(*origin - jump.takeoff)).magnitude();
+                    let stats = JumpStats {
+                        takeoff: jump.takeoff,
+                        landing: *origin,
+                        raw_distance,
+                        distance: raw_distance + self.distance_offset,
+                        height_difference: height - takeoff_height,
+                        max_height: jump.max_height - takeoff_height,
+                        pre_speed: jump.pre_speed,
+                        max_speed: jump.max_speed,
+                        air_ticks: jump.air_ticks,
+                    };
+                    self.last_jump = Some(stats);
+                    completed = Some(stats);
+                }
+            }
+            (true, true) => {}
+        }
+
+        self.was_on_ground = on_ground;
+        self.last_origin = Some(*origin);
+        self.last_speed = speed;
+        completed
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl LongJumpMeter {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(distance_offset: f32) -> LongJumpMeter {
+        LongJumpMeter::with_distance_offset(distance_offset)
+    }
+
+    #[wasm_bindgen(js_name = observe)]
+    pub fn observe_wasm(&mut self, origin: &Vector3, velocity: &Vector3, on_ground: bool) -> Option<JumpStats> {
+        self.observe(origin, velocity, on_ground)
+    }
+
+    #[wasm_bindgen(getter = lastJump)]
+    pub fn last_jump_wasm(&self) -> Option<JumpStats> {
+        self.last_jump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_jump_distance_with_offset() {
+        let mut meter = LongJumpMeter::new();
+        let vel = Vector3::new(250.0, 0.0, 0.0);
+
+        assert!(meter.observe(&Vector3::new(0.0, 0.0, 0.0), &vel, true).is_none());
+        assert!(meter.observe(&Vector3::new(4.0, 3.0, 0.0), &vel, false).is_none());
+        assert!(meter.observe(&Vector3::new(120.0, 50.0, 0.0), &vel, false).is_none());
+        let stats = meter.observe(&Vector3::new(220.0, 0.0, 0.0), &vel, true).unwrap();
+
+        assert!((stats.raw_distance - 220.0).abs() < 0.001);
+        assert!((stats.distance - 252.0).abs() < 0.001);
+        assert!((stats.max_height - 50.0).abs() < 0.001);
+        assert_eq!(stats.air_ticks, 2);
+        assert!(stats.is_flat(0.1));
+        assert_eq!(meter.last_jump(), Some(stats));
+    }
+
+    #[test]
+    fn test_custom_offset_and_height_difference() {
+        let mut meter = LongJumpMeter::with_distance_offset(0.0);
+        let vel = Vector3::new(0.0, 0.0, 300.0);
+
+        meter.observe(&Vector3::new(0.0, 0.0, 0.0), &vel, true);
+        meter.observe(&Vector3::new(0.0, 10.0, 50.0), &vel, false);
+        let stats = meter.observe(&Vector3::new(0.0, 16.0, 100.0), &vel, true).unwrap();
+
+        assert!((stats.distance - 100.0).abs() < 0.001);
+        assert!(!stats.is_flat(1.0));
+        assert!((stats.pre_speed - 300.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_z_up_measures_xy_distance() {
+        let mut meter = LongJumpMeter::new().with_up_axis(UpAxis::Z);
+        let vel = Vector3::new(0.0, 250.0, 0.0);
+
+        meter.observe(&Vector3::new(0.0, 0.0, 0.0), &vel, true);
+        meter.observe(&Vector3::new(0.0, 100.0, 40.0), &vel, false);
+        let stats = meter.observe(&Vector3::new(0.0, 220.0, 0.0), &vel, true).unwrap();
+
+        assert!((stats.raw_distance - 220.0).abs() < 0.001);
+        assert!((stats.max_height - 40.0).abs() < 0.001);
+        assert!((stats.pre_speed - 250.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_no_jump_without_takeoff_sample() {
+        // Starting in the air (e.g. spawned mid-air) must not produce a jump
+        let mut meter = LongJumpMeter::new();
+        let vel = Vector3::new(0.0, 0.0, 0.0);
+
+        meter.observe(&Vector3::new(0.0, 50.0, 0.0), &vel, false);
+        assert!(meter.observe(&Vector3::new(0.0, 0.0, 0.0), &vel, true).is_none());
+    }
+}
@@ -0,0 +1,58 @@
+//! Jumpbugs
+//!
+//! In Source, a player falling ducked who stands up and jumps on the exact
+//! tick they reach the ground jumps without ever landing: there is no fall
+//! damage and no landing friction, and the jump keeps the horizontal speed.
+//! With `jumpbug` set the same happens here. A landing snap with the duck
+//! released and a fresh jump press on a player who was ducked emits
+//! `Jumpbugged` instead of `Landed`, and the jump follows on the same tick.
+//!
+//! Only landings caught by the ground snap at the start of a tick can be
+//! jumpbugged; a fall that reaches the ground mid-tick lands before the jump
+//! can happen. `analyze_strafes` counts jumpbugs in recorded inputs.
+
+use crate::{MovementConfig, PlayerInput, PlayerState};
+
+/// Whether a landing at the start of this tick is a jumpbug
+pub(crate) fn jumpbug(state: &PlayerState, input: &PlayerInput, config: &MovementConfig, delta_time: f32) -> bool {
+    // Charged jumps need time on the ground, so they can not leave on the landing tick
+    config.jumpbug
+        && config.jump_charge_time <= 0.0
+        && state.ducked
+        && !input.duck
+        && input.jump
+        && (config.auto_bhop || !state.jump_held)
+        && state.jump_cooldown <= delta_time
+        && state.dodge_recovery <= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MovementEvent, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_unduck_jump_on_landing_skips_the_landing() {
+        let config = MovementConfig { jumpbug: true, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 1.0));
+        let falling = PlayerState { ducked: true, velocity: Vector3::new(500.0, -700.0, 0.0), ..PlayerState::default() };
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+
+        let mut state = falling;
+        let events = player_tick(&mut state, &jump, &config, 1.0 / 64.0, ground);
+        assert!(matches!(
+            events.as_slice(),
+            [MovementEvent::Jumpbugged { landing_speed: 700.0, .. }, MovementEvent::Jumped { .. }]
+        ));
+        assert!(!state.on_ground && state.velocity.y > 0.0);
+        assert_eq!(state.velocity.x, 500.0);
+
+        // Still holding duck, or not ducked at all, is a plain landing and jump
+        for (ducked, duck) in [(true, true), (false, false)] {
+            let mut state = PlayerState { ducked, ..falling };
+            let events = player_tick(&mut state, &PlayerInput { duck, ..jump }, &config, 1.0 / 64.0, ground);
+            assert!(matches!(events.as_slice(), [MovementEvent::Landed { .. }, MovementEvent::Jumped { .. }]));
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Leaning / peeking around corners (Q/E)
+//!
+//! `PlayerState::lean` moves towards `PlayerInput::lean` over `lean_time`
+//! seconds and is turned into a sideways camera offset and roll. The lean is
+//! limited by a ray the host casts from the eye along `lean_trace_direction`,
+//! the same way `player_tick` is fed a down ray, so the camera never leans
+//! into a wall.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerInput, PlayerState, RayCollisionHit, UpAxis, Vector3};
+
+/// Space kept between the leaning camera and a wall, in Hammer units
+const LEAN_WALL_CLEARANCE: f32 = 4.0;
+
+/// Camera offset and roll for the current lean
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeanOffset {
+    /// Sideways eye offset in world units
+    pub offset: Vector3,
+    /// Roll in radians, positive when leaning right
+    pub roll: f32,
+}
+
+/// Horizontal direction to cast the lean ray in (zero when not leaning)
+///
+/// Points to the side of the wished lean, or of the current lean while
+/// returning upright.
+pub fn lean_trace_direction(state: &PlayerState, input: &PlayerInput, up_axis: UpAxis) -> Vector3 {
+    let side = lean_side(state, input);
+    if side == 0.0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+    up_axis.right(input.yaw).multiply_scalar(side)
+}
+
+/// -1.0, 1.0 or 0.0 for the side `lean_trace_direction` points to
+fn lean_side(state: &PlayerState, input: &PlayerInput) -> f32 {
+    let lean = if input.lean != 0.0 && input.lean.is_finite() { input.lean } else { state.lean };
+    if lean == 0.0 {
+        0.0
+    } else {
+        lean.signum()
+    }
+}
+
+/// Largest lean fraction (0–1) that keeps the camera clear of a wall `hit`
+pub fn max_lean_for_hit(hit: Option<RayCollisionHit>, config: &MovementConfig) -> f32 {
+    let (Some(hit), true) = (hit, config.lean_distance > 0.0) else {
+        return 1.0;
+    };
+    let clearance = config.to_world_units(LEAN_WALL_CLEARANCE);
+    let room = hit.distance_native() - clearance;
+    (room / config.to_world_units(config.lean_distance)).clamp(0.0, 1.0)
+}
+
+/// Move the lean towards the input and limit it by the wall hit
+///
+/// # Arguments
+/// * `state` - The player whose `lean` is updated
+/// * `input` - Supplies the wished lean (-1.0 = left, 1.0 = right)
+/// * `config` - Supplies `lean_time` and `lean_distance`
+/// * `delta_time` - Time step in seconds
+/// * `side_ray_hit` - Result of a ray cast from the eye along `lean_trace_direction`
+pub fn update_lean(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    side_ray_hit: Option<RayCollisionHit>,
+) {
+    let target = if input.lean.is_finite() { input.lean.clamp(-1.0, 1.0) } else { 0.0 };
+    let lean = if config.lean_time > 0.0 {
+        let max_change = delta_time.max(0.0) / config.lean_time;
+        state.lean + (target - state.lean).clamp(-max_change, max_change)
+    } else {
+        target
+    };
+    // The wall only limits leaning towards the side the ray was cast to
+    let limit = max_lean_for_hit(side_ray_hit, config);
+    state.lean = match lean_side(state, input) {
+        side if side > 0.0 => lean.min(limit),
+        side if side < 0.0 => lean.max(-limit),
+        _ => lean,
+    };
+}
+
+/// Camera offset and roll for the state's current lean
+pub fn lean_offset(state: &PlayerState, yaw: f32, config: &MovementConfig) -> LeanOffset {
+    let distance = config.to_world_units(config.lean_distance) * state.lean;
+    LeanOffset {
+        offset: config.up_axis.right(yaw).multiply_scalar(distance),
+        roll: config.lean_angle * state.lean,
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = updateLean)]
+pub fn update_lean_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    side_ray_hit: Option<RayCollisionHit>,
+) {
+    update_lean(state, input, config, delta_time, side_ray_hit);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = leanOffset)]
+pub fn lean_offset_wasm(state: &PlayerState, yaw: f32, config: &MovementConfig) -> LeanOffset {
+    lean_offset(state, yaw, config)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = leanTraceDirection)]
+pub fn lean_trace_direction_wasm(state: &PlayerState, input: &PlayerInput, up_axis: UpAxis) -> Vector3 {
+    lean_trace_direction(state, input, up_axis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_lean_eases_in_and_offsets_camera() {
+        let config = y_up_config();
+        let mut state = PlayerState::default();
+        let input = PlayerInput { lean: 1.0, ..PlayerInput::new() };
+
+        update_lean(&mut state, &input, &config, config.lean_time / 2.0, None);
+        assert!((state.lean - 0.5).abs() < 0.0001);
+        update_lean(&mut state, &input, &config, config.lean_time, None);
+        assert_eq!(state.lean, 1.0);
+
+        let lean = lean_offset(&state, 0.0, &config);
+        assert!((lean.offset.dot(&config.up_axis.right(0.0)) - config.lean_distance).abs() < 0.0001);
+        assert_eq!(lean.roll, config.lean_angle);
+    }
+
+    #[test]
+    fn test_wall_limits_lean_towards_it() {
+        let config = MovementConfig { lean_time: 0.0, ..y_up_config() };
+        let mut state = PlayerState::default();
+        let wall = Some(RayCollisionHit::new_native(Vector3::new(-1.0, 0.0, 0.0), 12.0));
+
+        update_lean(&mut state, &PlayerInput { lean: 1.0, ..PlayerInput::new() }, &config, 0.1, wall);
+        assert!((state.lean - 0.5).abs() < 0.0001);
+
+        // Leaning to the open side casts the ray there and is not limited
+        let left = PlayerInput { lean: -1.0, ..PlayerInput::new() };
+        assert_eq!(lean_trace_direction(&state, &left, config.up_axis), -config.up_axis.right(0.0));
+        update_lean(&mut state, &left, &config, 0.1, None);
+        assert_eq!(state.lean, -1.0);
+
+        // A wall flush against the side pushes the lean back upright
+        let flush = Some(RayCollisionHit::new_native(Vector3::new(1.0, 0.0, 0.0), 0.0));
+        update_lean(&mut state, &left, &config, 0.1, flush);
+        assert_eq!(state.lean, 0.0);
+    }
+}
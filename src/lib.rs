@@ -10,6 +10,166 @@ extern "C" {
     fn log(s: &str);
 }
 
+pub mod air_dash;
+pub mod air_drag;
+pub mod analog;
+pub mod analysis;
+pub mod axis;
+pub mod bhop_penalty;
+pub mod checked;
+pub mod bot;
+pub mod climb;
+pub mod cmd_rate;
+pub mod components;
+pub mod config;
+pub mod config_format;
+pub mod config_layers;
+pub mod config_sync;
+pub mod course;
+pub mod crouch_slide;
+pub mod delta_time;
+pub mod determinism;
+pub mod dodge;
+pub mod duck;
+pub mod edgebug;
+pub mod error;
+pub mod events;
+pub mod eye;
+pub mod fixed;
+pub mod force_zones;
+pub mod frame_buffer;
+pub mod frame_delta;
+pub mod glide;
+pub mod golden;
+pub mod gravity_curve;
+pub mod gravity_frame;
+pub mod ground_pound;
+pub mod harness;
+pub mod hull;
+pub mod input;
+pub mod jetpack;
+pub mod jump_charge;
+pub mod jump_cut;
+pub mod jump_pad;
+pub mod jump_stats;
+pub mod jumpbug;
+pub mod lean;
+pub mod materials;
+pub mod max_gain;
+pub mod metrics;
+pub mod modifiers;
+pub mod momentum;
+pub mod mouse;
+pub mod netsim;
+pub mod noclip;
+pub mod overbounce;
+pub mod player;
+pub mod replay;
+pub mod remote;
+pub mod rewind;
+pub mod rope;
+pub mod run_bundle;
+#[cfg(feature = "rhai")]
+pub mod scripting;
+pub mod ski;
+pub mod speed_units;
+pub mod step_smooth;
+pub mod sprint;
+pub mod sticky;
+pub mod strafe;
+pub mod surf;
+pub mod tick;
+pub mod timeline;
+pub mod triggers;
+pub mod vector_pool;
+pub mod view_bob;
+pub mod view_punch;
+pub mod wall_run;
+pub mod water;
+pub mod wishdir;
+pub mod world;
+
+pub use air_dash::{air_dash_direction, AirDashDirection, AirDashMomentum};
+pub use air_drag::air_drag_terminal_speed;
+pub use analog::analog_stick_amount;
+pub use analysis::{analyze_strafes, AnalysisThresholds, InputFrame, StrafeAnalysisReport};
+pub use axis::UpAxis;
+pub use bhop_penalty::bhop_stamina_jump_factor;
+pub use bot::StrafeBot;
+pub use checked::{
+    air_accelerate_checked, gravity_velocity_change_checked, player_move_checked, MovementError,
+};
+pub use climb::update_climb_contact;
+pub use cmd_rate::{merge_slices, CommandResampler, InputSlice};
+pub use components::{
+    player_tick_components, AbilityState, AbilityTimers, DuckState, GroundInfo, Kinematics, PlayerComponents,
+    PlayerComponentsMut,
+};
+pub use config::{ConfigPreset, MovementConfig, MovementConfigBuilder, HAMMER_UNITS_TO_METERS};
+pub use config_format::{ConfigChange, ConfigError, ConfigValue};
+pub use config_layers::{ConfigLayer, ConfigLayers, ConfigOp};
+pub use config_sync::{ConfigPatch, ReplicatedConfig};
+pub use course::{Course, CourseTimer, RunResult, StartSpeedPolicy};
+pub use crouch_slide::update_ceiling_clearance;
+pub use delta_time::{DeltaTimePolicy, MAX_SUB_STEPS};
+pub use determinism::{state_hash, DeterminismAuditor, DeterminismMismatch};
+pub use dodge::{dodge_velocity, update_dodge_wall, DodgeChain, DodgeDirection};
+pub use duck::{current_hull_height, duck_spam_factor};
+pub use error::{AirStrafeError, AirStrafeErrorKind};
+pub use events::{MovementEvent, MovementEventBuffer, MovementEventInfo, MovementEventKind};
+pub use eye::{eye_height, eye_position};
+pub use fixed::{FIXED_LEGACY_GRAVITY, FIXED_UP_AXIS};
+pub use force_zones::{ForceKind, ForceZone, ForceZones};
+pub use frame_buffer::{FrameBuffer, FRAME_STRIDE};
+pub use frame_delta::{FrameDeltaPolicy, FrameDeltaSmoother};
+pub use glide::glide_velocity;
+pub use golden::{GoldenTrace, TraceDivergence, TraceSample, TraceTolerance};
+pub use gravity_curve::gravity_curve_multiplier;
+pub use gravity_frame::{player_tick_with_gravity, player_tick_with_gravity_into, GravityFrame};
+pub use harness::{Expectations, Plane, Scenario, ScriptedInput, Trajectory};
+pub use hull::{current_hull, ducked_hull, standing_hull, Hull};
+pub use input::PlayerInput;
+pub use jetpack::{jetpack_direction, jetpack_thrust, update_jetpack_fuel};
+pub use jump_charge::jump_charge_multiplier;
+pub use jump_pad::{JumpPad, PadHorizontal};
+pub use jump_stats::{JumpStats, LongJumpMeter, DEFAULT_JUMP_DISTANCE_OFFSET};
+pub use lean::{lean_offset, lean_trace_direction, max_lean_for_hit, update_lean, LeanOffset};
+pub use materials::{MaterialTable, SURFACE_CLIMBABLE, SURFACE_STICKY};
+pub use max_gain::{max_gain, GainProfile};
+pub use metrics::{MetricsPublisher, MetricsSink, TickMetrics};
+pub use modifiers::{ModifierContext, MovementModifier, MovementModifiers};
+pub use mouse::{MouseAggregator, MouseLook, MouseSettings, DEFAULT_DEGREES_PER_COUNT, DEFAULT_MAX_PITCH};
+pub use netsim::{NetConditions, NetSim};
+pub use noclip::noclip_speed_multiplier;
+pub use overbounce::{predict_overbounce, Overbounce};
+pub use player::{MoveType, PlayerState, PlayerStateBuilder};
+pub use replay::{quantize_input, Replay, ReplayKeyframe};
+pub use remote::{AdaptiveDelay, RemotePlayer, DEFAULT_INTERPOLATION_DELAY, DEFAULT_MAX_EXTRAPOLATION};
+pub use rewind::{RewindBuffer, MAX_REWIND_SECONDS, MIN_REWIND_SECONDS};
+pub use rope::RopeSwing;
+pub use run_bundle::{config_hash, RunBundle, RunRejection};
+#[cfg(feature = "rhai")]
+pub use scripting::ScriptModifier;
+pub use ski::ski_slope_velocity_change;
+pub use speed_units::{convert_speed, format_speed, speed_to_world_units, SpeedUnit};
+pub use step_smooth::StepSmoothing;
+pub use sprint::sprint_multiplier;
+pub use sticky::{player_tick_sticky, player_tick_sticky_into, sticky_gravity_direction, update_sticky_contact};
+pub use strafe::{optimal_strafe_angle, optimal_strafe_wish_dir, strafe_assist_wish_dir};
+pub use timeline::{Timeline, TimelineEntry};
+pub use triggers::{TriggerId, TriggerShape, TriggerVolumes};
+pub use tick::{
+    player_tick, player_tick_checked, player_tick_fixed_into, player_tick_into, player_tick_with_modifiers,
+    player_tick_with_modifiers_into,
+};
+pub use vector_pool::Vector3Pool;
+pub use view_bob::{view_bob, view_bob_height, view_roll, ViewBob};
+pub use view_punch::{ViewPunch, DEFAULT_PUNCH_DECAY_RATE, LANDING_PUNCH_THRESHOLD};
+pub use wall_run::{wall_run_chain_bonus, wall_run_chain_jump};
+pub use water::{buoyancy, check_water_jump, water_current_velocity, MAX_WATER_LEVEL};
+pub use wishdir::compute_wishdir;
+pub use world::{PastState, PlayerId, World, WorldSnapshot, DEFAULT_HISTORY_LENGTH, PLAYER_DUCKED, PLAYER_ON_GROUND};
+
 /// A 3D vector struct that can be used both in Rust and exported to JavaScript/WASM
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -56,6 +216,12 @@ impl Vector3 {
     pub fn set_z(&mut self, z: f32) {
         self.z = z;
     }
+
+    /// Overwrite all three components, for reusing a preallocated vector
+    #[wasm_bindgen(js_name = set)]
+    pub fn set_wasm(&mut self, x: f32, y: f32, z: f32) {
+        *self = Vector3 { x, y, z };
+    }
 }
 
 /// Represents a ray collision intersection (simplified version for WASM compatibility)
@@ -66,6 +232,10 @@ pub struct RayCollisionHit {
     normal: Vector3,
     /// Distance from ray origin to intersection point
     distance: f32,
+    /// Game-defined surface material id (0 when unknown)
+    material: u32,
+    /// Horizontal distance from the intersection point to the edge of the surface (infinite when unknown)
+    edge_distance: f32,
 }
 
 #[cfg(feature = "wasm")]
@@ -76,6 +246,8 @@ impl RayCollisionHit {
         RayCollisionHit {
             normal: Vector3::new(normal_x, normal_y, normal_z),
             distance,
+            material: 0,
+            edge_distance: f32::INFINITY,
         }
     }
 
@@ -88,6 +260,26 @@ impl RayCollisionHit {
     pub fn distance(&self) -> f32 {
         self.distance
     }
+
+    #[wasm_bindgen(getter = material)]
+    pub fn material_wasm(&self) -> u32 {
+        self.material
+    }
+
+    #[wasm_bindgen(js_name = withMaterial)]
+    pub fn with_material_wasm(self, material: u32) -> RayCollisionHit {
+        self.with_material(material)
+    }
+
+    #[wasm_bindgen(getter = edgeDistance)]
+    pub fn edge_distance_wasm(&self) -> f32 {
+        self.edge_distance
+    }
+
+    #[wasm_bindgen(js_name = withEdgeDistance)]
+    pub fn with_edge_distance_wasm(self, edge_distance: f32) -> RayCollisionHit {
+        self.with_edge_distance(edge_distance)
+    }
 }
 
 /// Additional methods for RayCollisionHit (available for WASM too)
@@ -95,7 +287,7 @@ impl RayCollisionHit {
 impl RayCollisionHit {
     /// Create a new RayCollisionHit (Rust-native version)
     pub fn new_native(normal: Vector3, distance: f32) -> RayCollisionHit {
-        RayCollisionHit { normal, distance }
+        RayCollisionHit { normal, distance, material: 0, edge_distance: f32::INFINITY }
     }
     
     /// Get the normal vector (Rust-native)
@@ -117,12 +309,14 @@ impl RayCollisionHit {
         RayCollisionHit {
             normal: Vector3::new(normal_x, normal_y, normal_z),
             distance,
+            material: 0,
+            edge_distance: f32::INFINITY,
         }
     }
 
     /// Create a new RayCollisionHit (Rust-native version)
     pub fn new_native(normal: Vector3, distance: f32) -> RayCollisionHit {
-        RayCollisionHit { normal, distance }
+        RayCollisionHit { normal, distance, material: 0, edge_distance: f32::INFINITY }
     }
     
     /// Get the normal vector (Rust-native)
@@ -136,6 +330,30 @@ impl RayCollisionHit {
     }
 }
 
+impl RayCollisionHit {
+    /// The same hit tagged with a game-defined surface material id
+    pub fn with_material(mut self, material: u32) -> RayCollisionHit {
+        self.material = material;
+        self
+    }
+
+    /// The surface material id (0 when unknown)
+    pub fn material(&self) -> u32 {
+        self.material
+    }
+
+    /// The same hit `edge_distance` world units in from the edge of the surface, see `edgebug`
+    pub fn with_edge_distance(mut self, edge_distance: f32) -> RayCollisionHit {
+        self.edge_distance = edge_distance;
+        self
+    }
+
+    /// Horizontal distance to the edge of the surface (infinite when unknown)
+    pub fn edge_distance(&self) -> f32 {
+        self.edge_distance
+    }
+}
+
 /// Player movement function that handles ground projection and movement modifiers
 /// 
 /// This function processes player input direction, projects it onto the ground surface
@@ -187,6 +405,34 @@ pub fn player_move_core(
     final_direction.multiply_scalar(delta_time * speed_multiplier)
 }
 
+/// `playerMove` writing the movement into `out` instead of returning a new object
+///
+/// Every `Vector3` returned through WASM is a fresh object for the JS garbage
+/// collector. The `...Into` functions let JS callers preallocate their vectors
+/// once and reuse them every frame.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = playerMoveInto)]
+pub fn player_move_into_wasm(
+    direction: &Vector3,
+    delta_time: f32,
+    speed_multiplier: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    out: &mut Vector3,
+) {
+    player_move_into(direction, delta_time, speed_multiplier, down_ray_hit, out);
+}
+
+/// `player_move_core` writing the movement into `out`
+pub fn player_move_into(
+    direction: &Vector3,
+    delta_time: f32,
+    speed_multiplier: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    out: &mut Vector3,
+) {
+    *out = player_move_core(direction, delta_time, speed_multiplier, down_ray_hit);
+}
+
 /// Rust-native version of player_move for server use
 pub fn player_move_native(
     direction: &Vector3,
@@ -197,9 +443,55 @@ pub fn player_move_native(
     player_move_core(direction, delta_time, speed_multiplier, down_ray_hit)
 }
 
+/// Downscale factor of the legacy gravity model (`gravity * delta² * scale`)
+pub const LEGACY_GRAVITY_SCALE: f32 = 0.0875;
+
+/// Velocity change caused by gravity over one time step
+///
+/// By default gravity is an acceleration in units/s², so the velocity changes by
+/// `gravity * delta` and values like Source's `sv_gravity 800` behave as in the
+/// engine. Passing `legacy_scale` switches to the legacy model used by
+/// `gravity_influence_core` (`gravity * delta² * legacy_scale`).
+///
+/// # Arguments
+/// * `gravity` - The gravity acceleration vector
+/// * `delta` - Time step in seconds
+/// * `legacy_scale` - Downscale factor for the legacy model, `None` for standard gravity
+///
+/// # Returns
+/// The velocity change to add
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = gravityVelocityChange)]
+pub fn gravity_velocity_change(gravity: &Vector3, delta: f32, legacy_scale: Option<f32>) -> Vector3 {
+    gravity_velocity_change_native(gravity, delta, legacy_scale)
+}
+
+/// `gravityVelocityChange` writing the velocity change into `out` instead of returning a new object
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = gravityVelocityChangeInto)]
+pub fn gravity_velocity_change_into_wasm(gravity: &Vector3, delta: f32, legacy_scale: Option<f32>, out: &mut Vector3) {
+    gravity_velocity_change_into(gravity, delta, legacy_scale, out);
+}
+
+/// `gravity_velocity_change_native` writing the velocity change into `out`
+pub fn gravity_velocity_change_into(gravity: &Vector3, delta: f32, legacy_scale: Option<f32>, out: &mut Vector3) {
+    *out = gravity_velocity_change_native(gravity, delta, legacy_scale);
+}
+
+/// Native version for non-WASM targets
+pub fn gravity_velocity_change_native(gravity: &Vector3, delta: f32, legacy_scale: Option<f32>) -> Vector3 {
+    let scale_factor = match legacy_scale {
+        Some(scale) => delta * delta * scale,
+        None => delta,
+    };
+    gravity.multiply_scalar(scale_factor)
+}
+
 /// Apply gravity force to a gravity force vector
 /// 
-/// This function applies gravity acceleration using squared delta time and a downscale factor.
+/// This is the legacy gravity model: gravity is scaled by squared delta time and
+/// `LEGACY_GRAVITY_SCALE`, so it does not match engine gravity values. Prefer
+/// `gravity_velocity_change_native`, which integrates gravity as an acceleration.
 /// The gravity force vector is modified in place.
 /// 
 /// # Arguments
@@ -225,9 +517,7 @@ pub fn gravity_influence_core(
     gravity: &Vector3,
     delta: f32,
 ) -> Vector3 {
-    const GRAVITY_DOWN_SCALE: f32 = 0.0875;
-    let delta_sqrd = delta * delta;
-    let scale_factor = delta_sqrd * GRAVITY_DOWN_SCALE;
+    let scale_factor = delta * delta * LEGACY_GRAVITY_SCALE;
     
     gravity_influence.x += gravity.x * scale_factor;
     gravity_influence.y += gravity.y * scale_factor;
@@ -254,9 +544,7 @@ pub fn gravity_influence_acceleration(
     gravity: &Vector3,
     delta: f32,
 ) -> Vector3 {
-    const GRAVITY_DOWN_SCALE: f32 = 0.0875;
-    let delta_sqrd = delta * delta;
-    let scale_factor = delta_sqrd * GRAVITY_DOWN_SCALE;
+    let scale_factor = delta * delta * LEGACY_GRAVITY_SCALE;
     
     Vector3::new(
         gravity.x * scale_factor,
@@ -270,9 +558,7 @@ pub fn gravity_influence_acceleration_native(
     gravity: &Vector3,
     delta: f32,
 ) -> Vector3 {
-    const GRAVITY_DOWN_SCALE: f32 = 0.0875;
-    let delta_sqrd = delta * delta;
-    let scale_factor = delta_sqrd * GRAVITY_DOWN_SCALE;
+    let scale_factor = delta * delta * LEGACY_GRAVITY_SCALE;
     
     Vector3::new(
         gravity.x * scale_factor,
@@ -316,6 +602,41 @@ pub fn air_accelerate(
     )
 }
 
+/// `airAccelerate` writing the acceleration into `out` instead of returning a new object
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = airAccelerateInto)]
+pub fn air_accelerate_into_wasm(
+    current_vel: &Vector3,
+    wish_dir: &Vector3,
+    wish_speed: f32,
+    air_accelerate: f32,
+    max_air_wish_speed: f32,
+    delta_time: f32,
+    out: &mut Vector3,
+) {
+    air_accelerate_into(current_vel, wish_dir, wish_speed, air_accelerate, max_air_wish_speed, delta_time, out);
+}
+
+/// `air_accelerate_get_acceleration` writing the acceleration into `out`
+pub fn air_accelerate_into(
+    current_vel: &Vector3,
+    wish_dir: &Vector3,
+    wish_speed: f32,
+    air_accelerate: f32,
+    max_air_wish_speed: f32,
+    delta_time: f32,
+    out: &mut Vector3,
+) {
+    *out = air_accelerate_get_acceleration(
+        current_vel,
+        wish_dir,
+        wish_speed,
+        air_accelerate,
+        max_air_wish_speed,
+        delta_time,
+    );
+}
+
 /// Rust-native version that can either mutate velocity or return acceleration
 /// This provides both the old mutating API for server compatibility and
 /// the new return-value API for consistency
@@ -352,56 +673,35 @@ pub fn air_accelerate_get_acceleration(
     max_air_wish_speed: f32,
     delta_time: f32,
 ) -> Vector3 {
-    // Project the current velocity onto the movement direction
-    let proj_vel = project_vector_onto_vector(current_vel, wish_dir);
-    
-    // Check if the movement direction is moving towards or away from the projected velocity
-    let is_away = wish_dir.dot(&proj_vel) <= 0.0;
-    
-    // Only apply force if moving away from velocity or velocity is below max air speed
-    if proj_vel.magnitude() < max_air_wish_speed || is_away {
-        // Calculate the ideal movement force
-        let wish_dir_normalized = wish_dir.normalized();
-        let mut velocity_change = wish_dir_normalized.multiply_scalar(air_accelerate * delta_time);
-        
-        // Cap it based on whether we're moving towards or away from current velocity
-        if !is_away {
-            // Moving towards current velocity - cap to not exceed max air speed
-            let max_change = max_air_wish_speed - proj_vel.magnitude();
-            velocity_change = clamp_vector_magnitude(velocity_change, max_change);
-        } else {
-            // Moving away from current velocity - allow more acceleration
-            let max_change = max_air_wish_speed + proj_vel.magnitude();
-            velocity_change = clamp_vector_magnitude(velocity_change, max_change);
-        }
-        
-        velocity_change
-    } else {
-        // No acceleration when at max speed and not moving away
-        Vector3::new(0.0, 0.0, 0.0)
+    let wish_len_sq = wish_dir.length_sq();
+    if wish_len_sq == 0.0 {
+        return Vector3::new(0.0, 0.0, 0.0);
     }
-}
+    // The only square root: everything below is measured along the wish direction
+    let wish_len = wish_len_sq.sqrt();
 
-/// Project vector A onto vector B: proj_B(A) = (A · B / |B|²) * B
-fn project_vector_onto_vector(a: &Vector3, b: &Vector3) -> Vector3 {
-    let b_magnitude_sq = b.length_sq();
-    if b_magnitude_sq == 0.0 {
+    // Signed speed of the current velocity projected onto the movement direction
+    let proj_speed = current_vel.dot(wish_dir) / wish_len;
+
+    // Check if the movement direction is moving towards or away from the projected velocity
+    let is_away = proj_speed <= 0.0;
+
+    // No acceleration when at max speed and not moving away
+    if !is_away && proj_speed >= max_air_wish_speed {
         return Vector3::new(0.0, 0.0, 0.0);
     }
-    
-    let dot_product = a.dot(b);
-    let scalar = dot_product / b_magnitude_sq;
-    b.multiply_scalar(scalar)
-}
 
-/// Clamp a vector's magnitude to a maximum value
-fn clamp_vector_magnitude(vector: Vector3, max_magnitude: f32) -> Vector3 {
-    let current_magnitude = vector.magnitude();
-    if current_magnitude <= max_magnitude || max_magnitude <= 0.0 {
-        vector
-    } else {
-        vector.multiply_scalar(max_magnitude / current_magnitude)
+    // Cap the change based on whether we're moving towards or away from current velocity:
+    // towards it may not exceed max air speed, away from it allows more acceleration
+    // (with a negative projected speed this is max_air_wish_speed + |proj_speed|)
+    let max_change = max_air_wish_speed - proj_speed;
+    let mut change = air_accelerate * delta_time;
+    if max_change > 0.0 && change.abs() > max_change {
+        change = max_change.copysign(change);
     }
+
+    // Scaling the raw wish direction once replaces normalizing it and re-measuring the result
+    wish_dir.multiply_scalar(change / wish_len)
 }
 
 /// Rust-native implementation (not exported to WASM)
@@ -426,6 +726,15 @@ impl Vector3 {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
 
+    /// Calculate the cross product of this vector with another
+    pub fn cross(&self, other: &Vector3) -> Vector3 {
+        Vector3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
     /// Add another vector to this vector (mutating)
     pub fn add(&mut self, other: &Vector3) {
         self.x += other.x;
@@ -466,6 +775,11 @@ impl Vector3 {
         self.x * self.x + self.y * self.y + self.z * self.z
     }
 
+    /// Whether every component is finite (neither NaN nor infinite)
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
     /// Set the length of this vector to a specific value (mutating)
     pub fn set_length(&mut self, length: f32) {
         let current_mag = self.magnitude();
@@ -485,6 +799,7 @@ impl Vector3 {
     }
 
     /// Clone this vector
+    #[allow(clippy::should_implement_trait)]
     pub fn clone(&self) -> Vector3 {
         Vector3 {
             x: self.x,
@@ -546,6 +861,39 @@ impl Vector3 {
     }
 }
 
+/// Operator overloads for concise vector math in Rust code
+impl std::ops::Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl std::ops::Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl std::ops::Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, scalar: f32) -> Vector3 {
+        self.multiply_scalar(scalar)
+    }
+}
+
+impl std::ops::Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        Vector3::new(-self.x, -self.y, -self.z)
+    }
+}
+
 // Conditional compilation for different target architectures
 #[cfg(feature = "wasm")]
 #[wasm_bindgen(start)]
@@ -680,6 +1028,36 @@ mod tests {
         assert!((acceleration.x - 10.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_air_accelerate_unnormalized_wish_dir() {
+        // Only the direction of wish_dir matters, the projection and cap use its unit length
+        let vel = Vector3::new(10.0, 0.0, 0.0);
+        let wish_dir = Vector3::new(6.0, 8.0, 0.0);
+
+        let acceleration = air_accelerate_get_acceleration(&vel, &wish_dir, 100.0, 1000.0, 30.0, 0.1);
+
+        // Projected speed = 60 / 10 = 6, so the change is capped to 30 - 6 = 24 along [0.6, 0.8, 0]
+        assert!((acceleration.x - 14.4).abs() < 0.001);
+        assert!((acceleration.y - 19.2).abs() < 0.001);
+        assert!((acceleration.magnitude() - 24.0).abs() < 0.001);
+        assert_eq!(air_accelerate_get_acceleration(&vel, &Vector3::new(0.0, 0.0, 0.0), 100.0, 10.0, 30.0, 0.1).length_sq(), 0.0);
+    }
+
+    #[test]
+    fn test_into_variants_overwrite_out() {
+        let vel = Vector3::new(5.0, 0.0, 0.0);
+        let wish_dir = Vector3::new(0.0, 0.0, 1.0);
+        let mut out = Vector3::new(9.0, 9.0, 9.0);
+
+        air_accelerate_into(&vel, &wish_dir, 100.0, 10.0, 30.0, 0.1, &mut out);
+        assert_eq!(out, air_accelerate_get_acceleration(&vel, &wish_dir, 100.0, 10.0, 30.0, 0.1));
+        gravity_velocity_change_into(&Vector3::new(0.0, -800.0, 0.0), 0.5, None, &mut out);
+        assert_eq!(out, Vector3::new(0.0, -400.0, 0.0));
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 1.0));
+        player_move_into(&wish_dir, 0.5, 2.0, ground, &mut out);
+        assert_eq!(out, player_move_core(&wish_dir, 0.5, 2.0, ground));
+    }
+
     #[test]
     fn test_air_accelerate_wasm_api() {
         let vel = Vector3::new(0.0, 0.0, 0.0);
@@ -823,6 +1201,21 @@ mod tests {
         assert!((v.y - 8.0).abs() < 0.001); // Should be 4 * (10/5) = 8
     }
 
+    #[test]
+    fn test_gravity_velocity_change_models() {
+        let gravity = Vector3::new(0.0, -800.0, 0.0);
+        let delta = 0.1;
+
+        // Standard model: one second of ticks adds the full gravity to the velocity
+        let standard = gravity_velocity_change_native(&gravity, delta, None);
+        assert!((standard.y + 80.0).abs() < 0.0001);
+
+        // Legacy model matches gravity_influence_acceleration_native
+        let legacy = gravity_velocity_change_native(&gravity, delta, Some(LEGACY_GRAVITY_SCALE));
+        let expected = gravity_influence_acceleration_native(&gravity, delta);
+        assert!((legacy.y - expected.y).abs() < 0.0001);
+    }
+
     #[test]
     fn test_gravity_influence_basic() {
         let mut gravity_influence_vec = Vector3::new(0.0, 0.0, 0.0);
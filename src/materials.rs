@@ -0,0 +1,100 @@
+//! Surface material table
+//!
+//! Ray hits carry a game-defined material id. A `MaterialTable` maps those ids
+//! to movement flags, so abilities like wall climbing can ask whether a
+//! surface allows them without the crate knowing the game's materials.
+//! Unknown ids have no flags.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::RayCollisionHit;
+
+/// The surface can be climbed, see `climb`
+pub const SURFACE_CLIMBABLE: u32 = 1 << 0;
+/// The surface holds players against it, see `sticky`
+pub const SURFACE_STICKY: u32 = 1 << 1;
+
+/// Movement flags per material id
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaterialTable {
+    flags: HashMap<u32, u32>,
+}
+
+impl MaterialTable {
+    /// Create a table where no material has any flags
+    pub fn new() -> MaterialTable {
+        MaterialTable::default()
+    }
+
+    /// Replace the flags of a material
+    pub fn set_flags(&mut self, material: u32, flags: u32) {
+        if flags == 0 {
+            self.flags.remove(&material);
+        } else {
+            self.flags.insert(material, flags);
+        }
+    }
+
+    /// Builder-style `set_flags`
+    pub fn with_flags(mut self, material: u32, flags: u32) -> MaterialTable {
+        self.set_flags(material, flags);
+        self
+    }
+
+    /// The flags of a material (0 when unknown)
+    pub fn flags(&self, material: u32) -> u32 {
+        self.flags.get(&material).copied().unwrap_or(0)
+    }
+
+    /// Whether the material has every flag in `flags`
+    pub fn has(&self, material: u32, flags: u32) -> bool {
+        self.flags(material) & flags == flags
+    }
+
+    /// Whether the surface a ray hit has every flag in `flags`
+    pub fn hit_has(&self, hit: &RayCollisionHit, flags: u32) -> bool {
+        self.has(hit.material(), flags)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MaterialTable {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> MaterialTable {
+        MaterialTable::new()
+    }
+
+    #[wasm_bindgen(js_name = setFlags)]
+    pub fn set_flags_wasm(&mut self, material: u32, flags: u32) {
+        self.set_flags(material, flags);
+    }
+
+    #[wasm_bindgen(js_name = flags)]
+    pub fn flags_wasm(&self, material: u32) -> u32 {
+        self.flags(material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vector3;
+
+    #[test]
+    fn test_flags_per_material() {
+        let mut table = MaterialTable::new().with_flags(4, SURFACE_CLIMBABLE);
+        assert!(table.has(4, SURFACE_CLIMBABLE));
+        assert!(!table.has(5, SURFACE_CLIMBABLE));
+        assert!(table.has(5, 0));
+
+        let hit = RayCollisionHit::new_native(Vector3::new(1.0, 0.0, 0.0), 10.0).with_material(4);
+        assert!(table.hit_has(&hit, SURFACE_CLIMBABLE));
+        table.set_flags(4, 0);
+        assert!(!table.hit_has(&hit, SURFACE_CLIMBABLE));
+    }
+}
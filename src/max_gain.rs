@@ -0,0 +1,205 @@
+//! Theoretical maximum air strafe gain of a config
+//!
+//! With the wish direction held at `optimal_strafe_angle` every tick, one tick
+//! of air acceleration `a = air_accelerate * wish_speed * delta_time` adds the
+//! same amount to the squared horizontal speed no matter how fast the player
+//! already is: `2 * a * m - a * a` when `a` stays below the air speed cap `m`
+//! (`max_air_wish_speed`), and `m * m` when it does not. Perfect strafing
+//! therefore follows `speed(t) = sqrt(start_speed² + gain * tick_rate * t)`:
+//! speed keeps growing without limit, but ever more slowly, at
+//! `gain * tick_rate / (2 * speed)` per second. `max_gain` works this curve
+//! out for a config and tick rate so presets can be compared before
+//! playtesting. Friction, gravity and collisions are left out; a
+//! `bhop_speed_cap` flattens the curve at the cap.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::MovementConfig;
+
+/// Perfect-strafe speed curve of a config at a tick rate, in config units
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainProfile {
+    tick_rate: f32,
+    start_speed: f32,
+    squared_speed_per_tick: f32,
+    speed_cap: f32,
+}
+
+/// Perfect-strafe gain of `config` at `tick_rate` ticks per second, starting from `max_speed`
+pub fn max_gain(config: &MovementConfig, tick_rate: f32) -> GainProfile {
+    let wish_speed = config.max_speed;
+    let delta_time = if tick_rate > 0.0 { 1.0 / tick_rate } else { 0.0 };
+    let cap = config.max_air_wish_speed.min(wish_speed);
+    // Pure side strafing and forward strafing may use different accelerations; take the better one
+    let squared_speed_per_tick = [(0.0, 1.0), (1.0, 1.0)]
+        .iter()
+        .map(|&(forward_move, side_move)| {
+            let change = config.effective_air_accelerate(forward_move, side_move, wish_speed) * delta_time;
+            if change < cap {
+                2.0 * change * cap - change * change
+            } else {
+                cap * cap
+            }
+        })
+        .fold(0.0, f32::max);
+    let speed_cap = if config.bhop_speed_cap > 0.0 { config.bhop_speed_cap * config.max_speed } else { 0.0 };
+    GainProfile { tick_rate: tick_rate.max(0.0), start_speed: wish_speed, squared_speed_per_tick, speed_cap }
+}
+
+impl GainProfile {
+    /// Ticks per second the profile was computed for
+    pub fn tick_rate(&self) -> f32 {
+        self.tick_rate
+    }
+
+    /// Horizontal speed at the start of the curve
+    pub fn start_speed(&self) -> f32 {
+        self.start_speed
+    }
+
+    /// Squared speed added by each perfect tick
+    pub fn squared_speed_per_tick(&self) -> f32 {
+        self.squared_speed_per_tick
+    }
+
+    /// Speed the curve flattens at (0.0 = no cap)
+    pub fn speed_cap(&self) -> f32 {
+        self.speed_cap
+    }
+
+    /// Speed after strafing perfectly for `seconds`
+    pub fn speed_at(&self, seconds: f32) -> f32 {
+        let speed = (self.start_speed.powi(2) + self.squared_speed_per_tick * self.tick_rate * seconds.max(0.0)).sqrt();
+        self.capped(speed)
+    }
+
+    /// Speed gained per second of perfect strafing while moving at `speed`
+    pub fn gain_per_second_at(&self, speed: f32) -> f32 {
+        if speed <= 0.0 || (self.speed_cap > 0.0 && speed >= self.speed_cap) {
+            return 0.0;
+        }
+        self.squared_speed_per_tick * self.tick_rate / (2.0 * speed)
+    }
+
+    /// Speed gained over the first second of perfect strafing from `start_speed`
+    pub fn gain_in_first_second(&self) -> f32 {
+        self.speed_at(1.0) - self.start_speed
+    }
+
+    /// Seconds of perfect strafing to reach `speed` (infinite when it is above the cap or there is no gain)
+    pub fn time_to_reach(&self, speed: f32) -> f32 {
+        if speed <= self.start_speed {
+            return 0.0;
+        }
+        if (self.speed_cap > 0.0 && speed > self.speed_cap) || self.squared_speed_per_tick * self.tick_rate <= 0.0 {
+            return f32::INFINITY;
+        }
+        (speed.powi(2) - self.start_speed.powi(2)) / (self.squared_speed_per_tick * self.tick_rate)
+    }
+
+    /// `(seconds, speed)` samples of the curve, `samples` evenly spaced points from 0 to `duration`
+    pub fn curve(&self, duration: f32, samples: usize) -> Vec<(f32, f32)> {
+        let steps = samples.saturating_sub(1).max(1) as f32;
+        (0..samples)
+            .map(|i| {
+                let seconds = duration * i as f32 / steps;
+                (seconds, self.speed_at(seconds))
+            })
+            .collect()
+    }
+
+    fn capped(&self, speed: f32) -> f32 {
+        if self.speed_cap > 0.0 {
+            speed.min(self.speed_cap.max(self.start_speed))
+        } else {
+            speed
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl GainProfile {
+    #[wasm_bindgen(getter = tickRate)]
+    pub fn tick_rate_wasm(&self) -> f32 {
+        self.tick_rate
+    }
+
+    #[wasm_bindgen(getter = startSpeed)]
+    pub fn start_speed_wasm(&self) -> f32 {
+        self.start_speed
+    }
+
+    #[wasm_bindgen(js_name = speedAt)]
+    pub fn speed_at_wasm(&self, seconds: f32) -> f32 {
+        self.speed_at(seconds)
+    }
+
+    #[wasm_bindgen(js_name = gainPerSecondAt)]
+    pub fn gain_per_second_at_wasm(&self, speed: f32) -> f32 {
+        self.gain_per_second_at(speed)
+    }
+
+    #[wasm_bindgen(js_name = timeToReach)]
+    pub fn time_to_reach_wasm(&self, speed: f32) -> f32 {
+        self.time_to_reach(speed)
+    }
+
+    /// Speeds of the curve at `samples` evenly spaced times from 0 to `duration`
+    #[wasm_bindgen(js_name = curveSpeeds)]
+    pub fn curve_speeds_wasm(&self, duration: f32, samples: usize) -> Vec<f32> {
+        self.curve(duration, samples).into_iter().map(|(_, speed)| speed).collect()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = maxGain)]
+pub fn max_gain_wasm(config: &MovementConfig, tick_rate: f32) -> GainProfile {
+    max_gain(config, tick_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{air_accelerate_get_acceleration, optimal_strafe_wish_dir, ConfigPreset, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_matches_a_perfectly_strafed_simulation() {
+        for preset in [ConfigPreset::Source, ConfigPreset::Csgo, ConfigPreset::Quake, ConfigPreset::Cpm] {
+            let config = MovementConfig::preset(preset);
+            let profile = max_gain(&config, 100.0);
+            assert!(profile.squared_speed_per_tick() > 0.0);
+
+            let accelerate = config.effective_air_accelerate(0.0, 1.0, config.max_speed)
+                .max(config.effective_air_accelerate(1.0, 1.0, config.max_speed));
+            let cap = config.max_air_wish_speed.min(config.max_speed);
+            let mut velocity = Vector3::new(config.max_speed, 0.0, 0.0);
+            for _ in 0..200 {
+                let wish = optimal_strafe_wish_dir(&velocity, &Vector3::new(0.0, 0.0, 1.0), accelerate, cap, 0.01);
+                velocity.add(&air_accelerate_get_acceleration(&velocity, &wish, config.max_speed, accelerate, cap, 0.01));
+            }
+            let expected = profile.speed_at(2.0);
+            assert!((velocity.magnitude() - expected).abs() < 1e-3 * expected, "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn test_curve_and_cap() {
+        let profile = max_gain(&y_up_config(), 64.0);
+        assert_eq!(profile.speed_at(0.0), 320.0);
+        assert!(profile.gain_in_first_second() > 0.0);
+        assert!(profile.gain_per_second_at(640.0) < profile.gain_per_second_at(320.0));
+        assert!((profile.speed_at(profile.time_to_reach(500.0)) - 500.0).abs() < 0.01);
+        let curve = profile.curve(10.0, 11);
+        assert_eq!(curve.len(), 11);
+        assert!(curve.windows(2).all(|pair| pair[1].1 > pair[0].1));
+
+        let capped = max_gain(&MovementConfig { bhop_speed_cap: 1.2, ..y_up_config() }, 64.0);
+        assert_eq!(capped.speed_at(60.0), 384.0);
+        assert_eq!(capped.gain_per_second_at(384.0), 0.0);
+        assert_eq!(capped.time_to_reach(400.0), f32::INFINITY);
+    }
+}
@@ -0,0 +1,225 @@
+//! Live per-tick metrics
+//!
+//! Strafe HUDs, dashboards and stream overlays show speed, gain and strafe
+//! sync as the player moves. A `MetricsPublisher` computes them from the state
+//! after every tick and hands each tick's `TickMetrics` to its subscribed
+//! `MetricsSink`s right away, so consumers get pushed every tick instead of
+//! reading back recorded states. From JS, `subscribe` takes a function that is
+//! called with the `TickMetrics`.
+//!
+//! Sync is counted like `analyze_strafes` does: an airborne tick that turns
+//! and strafes is synced when it turns towards the side it strafes to. The
+//! running `sync` ratio starts over at every takeoff, as on most strafe HUDs.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::analysis::wrap_angle;
+use crate::{PlayerInput, PlayerState, UpAxis};
+
+/// Metrics of a single tick
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TickMetrics {
+    pub tick: u64,
+    /// Horizontal speed after the tick
+    pub speed: f32,
+    /// Horizontal speed gained this tick, negative when speed was lost
+    pub gain: f32,
+    /// Whether this tick turned towards the strafe side while airborne
+    pub synced: bool,
+    /// Ratio of synced ticks among the airborne turning ticks since takeoff
+    pub sync: f32,
+    pub on_ground: bool,
+}
+
+/// Receives the metrics of every tick as soon as they are computed
+pub trait MetricsSink {
+    fn on_tick(&mut self, metrics: &TickMetrics);
+}
+
+impl<F: FnMut(&TickMetrics)> MetricsSink for F {
+    fn on_tick(&mut self, metrics: &TickMetrics) {
+        self(metrics)
+    }
+}
+
+/// Computes per-tick metrics of one player and pushes them to sinks
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct MetricsPublisher {
+    up_axis: UpAxis,
+    sinks: Vec<Box<dyn MetricsSink>>,
+    previous: Option<(f32, f32)>,
+    synced_ticks: u32,
+    turning_ticks: u32,
+}
+
+impl MetricsPublisher {
+    /// Create a publisher without sinks
+    pub fn new(up_axis: UpAxis) -> MetricsPublisher {
+        MetricsPublisher { up_axis, sinks: Vec::new(), previous: None, synced_ticks: 0, turning_ticks: 0 }
+    }
+
+    /// Add a sink; it receives every tick observed from now on
+    pub fn subscribe(&mut self, sink: Box<dyn MetricsSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Remove every sink
+    pub fn clear(&mut self) {
+        self.sinks.clear();
+    }
+
+    /// Compute the metrics of a tick and push them to every sink
+    ///
+    /// Call this once per tick with the state after the tick and the input it was simulated with.
+    pub fn observe(&mut self, state: &PlayerState, input: &PlayerInput) -> TickMetrics {
+        let speed = self.up_axis.horizontal(&state.velocity).magnitude();
+        let (previous_speed, previous_yaw) = self.previous.unwrap_or((speed, input.yaw));
+        self.previous = Some((speed, input.yaw));
+
+        let mut synced = false;
+        if state.on_ground {
+            self.synced_ticks = 0;
+            self.turning_ticks = 0;
+        } else {
+            // Positive yaw turns left, so a right turn has a negative yaw delta
+            let yaw_delta = wrap_angle(input.yaw - previous_yaw);
+            if yaw_delta != 0.0 && input.side_move != 0.0 {
+                synced = (yaw_delta < 0.0) == (input.side_move > 0.0);
+                self.turning_ticks += 1;
+                self.synced_ticks += synced as u32;
+            }
+        }
+
+        let metrics = TickMetrics {
+            tick: state.tick,
+            speed,
+            gain: speed - previous_speed,
+            synced,
+            sync: if self.turning_ticks > 0 { self.synced_ticks as f32 / self.turning_ticks as f32 } else { 0.0 },
+            on_ground: state.on_ground,
+        };
+        for sink in &mut self.sinks {
+            sink.on_tick(&metrics);
+        }
+        metrics
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+extern "C" {
+    /// A JS function taking a `TickMetrics`
+    pub type MetricsCallback;
+
+    #[wasm_bindgen(method, js_name = call)]
+    fn call(this: &MetricsCallback, this_arg: &JsValue, metrics: TickMetrics);
+}
+
+#[cfg(feature = "wasm")]
+impl MetricsSink for MetricsCallback {
+    fn on_tick(&mut self, metrics: &TickMetrics) {
+        self.call(&JsValue::NULL, *metrics);
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MetricsPublisher {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(up_axis: UpAxis) -> MetricsPublisher {
+        MetricsPublisher::new(up_axis)
+    }
+
+    /// Call `callback(metrics)` for every observed tick
+    #[wasm_bindgen(js_name = subscribe)]
+    pub fn subscribe_wasm(&mut self, callback: MetricsCallback) {
+        self.subscribe(Box::new(callback));
+    }
+
+    #[wasm_bindgen(js_name = clear)]
+    pub fn clear_wasm(&mut self) {
+        self.clear();
+    }
+
+    #[wasm_bindgen(js_name = observe)]
+    pub fn observe_wasm(&mut self, state: &PlayerState, input: &PlayerInput) -> TickMetrics {
+        self.observe(state, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::tick::tests::y_up_config;
+
+    use super::*;
+    use crate::{player_tick, Vector3};
+
+    #[test]
+    fn test_sinks_receive_every_tick() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut publisher = MetricsPublisher::new(UpAxis::Y);
+        let sink = Rc::clone(&received);
+        publisher.subscribe(Box::new(move |metrics: &TickMetrics| sink.borrow_mut().push(*metrics)));
+
+        // Strafe right while turning right through the air
+        let config = y_up_config();
+        let mut state = PlayerState { velocity: Vector3::new(0.0, 0.0, -300.0), ..PlayerState::default() };
+        state.origin.y = 100.0;
+        for tick in 0..32 {
+            let input = PlayerInput { side_move: 1.0, yaw: -0.02 * tick as f32, ..PlayerInput::new() };
+            player_tick(&mut state, &input, &config, 1.0 / 64.0, None);
+            publisher.observe(&state, &input);
+        }
+
+        let received = received.borrow();
+        assert_eq!(received.len(), 32);
+        let last = received[31];
+        assert_eq!(last.sync, 1.0);
+        assert!(last.synced && last.gain > 0.0);
+        assert!((received.iter().map(|m| m.gain).sum::<f32>() - (last.speed - received[0].speed)).abs() < 0.01);
+    }
+    #[test]
+    fn test_sync_edges() {
+        let mut publisher = MetricsPublisher::new(UpAxis::Y);
+        let airborne = PlayerState { velocity: Vector3::new(300.0, 0.0, 0.0), ..PlayerState::default() };
+        let strafe = |side_move: f32, yaw: f32| PlayerInput { side_move, yaw, ..PlayerInput::new() };
+
+        // The first tick has nothing to gain or turn from
+        let first = publisher.observe(&airborne, &strafe(1.0, 0.0));
+        assert_eq!((first.gain, first.synced, first.sync), (0.0, false, 0.0));
+        // Turning left while strafing right is out of sync, holding the yaw is not counted
+        assert!(!publisher.observe(&airborne, &strafe(1.0, 0.1)).synced);
+        assert_eq!(publisher.observe(&airborne, &strafe(1.0, 0.1)).sync, 0.0);
+        assert_eq!(publisher.observe(&airborne, &strafe(1.0, 0.0)).sync, 0.5);
+
+        // A right turn across the wrap from -PI to PI is still a small right turn
+        let pi = std::f32::consts::PI;
+        publisher.observe(&airborne, &strafe(1.0, -pi + 0.05));
+        let wrapped = publisher.observe(&airborne, &strafe(1.0, pi - 0.05));
+        assert!(wrapped.synced);
+
+        // Landing starts the ratio over
+        let landed = publisher.observe(&PlayerState { on_ground: true, ..airborne }, &strafe(1.0, 1.0));
+        assert_eq!((landed.synced, landed.sync), (false, 0.0));
+        assert_eq!(publisher.observe(&airborne, &strafe(1.0, 0.9)).sync, 1.0);
+    }
+
+    #[test]
+    fn test_cleared_sinks_stop_receiving() {
+        let received = Rc::new(RefCell::new(0));
+        let mut publisher = MetricsPublisher::new(UpAxis::Z);
+        for _ in 0..2 {
+            let sink = Rc::clone(&received);
+            publisher.subscribe(Box::new(move |_: &TickMetrics| *sink.borrow_mut() += 1));
+        }
+        let state = PlayerState { velocity: Vector3::new(3.0, 4.0, 100.0), ..PlayerState::default() };
+        assert_eq!(publisher.observe(&state, &PlayerInput::new()).speed, 5.0);
+        publisher.clear();
+        publisher.observe(&state, &PlayerInput::new());
+        assert_eq!(*received.borrow(), 2);
+    }
+}
@@ -0,0 +1,241 @@
+//! Per-player movement modifiers
+//!
+//! A `MovementModifier` hooks into fixed stages of `player_tick` (wish
+//! direction, external forces, acceleration, friction, final velocity, the
+//! finished move), so slow fields, wind zones, triggers, speed
+//! boosts or custom abilities can change movement without forking the tick
+//! function. Modifiers run in the order they were pushed. Noclip and
+//! spectator movement is never modified.
+//!
+//! Modifiers are Rust trait objects and are not available through WASM; use
+//! config layers for data-only tweaks from JS.
+//!
+//! With the `rhai` feature, server admins can script modifiers instead, see
+//! `scripting`.
+
+use crate::events::MovementEvent;
+use crate::{MovementConfig, PlayerInput, PlayerState, Vector3};
+
+/// Read-only view of the tick a modifier is running in
+#[derive(Debug, Clone, Copy)]
+pub struct ModifierContext<'a> {
+    /// The player state at the time the stage runs
+    pub state: PlayerState,
+    pub input: &'a PlayerInput,
+    /// The config in world units
+    pub config: &'a MovementConfig,
+    pub delta_time: f32,
+}
+
+/// Hooks into the stages of `player_tick`; every stage defaults to a no-op
+pub trait MovementModifier {
+    /// Adjust the wish direction and wish speed built from the input
+    fn modify_wish(&mut self, _ctx: &ModifierContext, _wish_dir: &mut Vector3, _wish_speed: &mut f32) {}
+
+    /// Continuous acceleration (e.g. wind) in world units per second squared
+    ///
+    /// Ground and air movement add it to the velocity before friction and air
+    /// control, so air strafing works against the pushed velocity. A push
+    /// upwards stronger than `gravity` lifts the player off the ground.
+    fn external_acceleration(&mut self, _ctx: &ModifierContext) -> Vector3 {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Velocity that moves the player without becoming part of their velocity
+    ///
+    /// Like a conveyor or current, it is added to the displacement of ground
+    /// and air movement only, so it neither builds up nor affects air control.
+    fn base_velocity(&mut self, _ctx: &ModifierContext) -> Vector3 {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Adjust the acceleration factor (ground, air or fly accelerate) before it
+    /// is applied
+    fn modify_accelerate(&mut self, _ctx: &ModifierContext, _accelerate: &mut f32) {}
+
+    /// Adjust the friction factor before it is applied
+    fn modify_friction(&mut self, _ctx: &ModifierContext, _friction: &mut f32) {}
+
+    /// Adjust the velocity after all accelerations and gravity, right before
+    /// the position is integrated
+    fn modify_velocity(&mut self, _ctx: &ModifierContext, _velocity: &mut Vector3) {}
+
+    /// Look at the finished move from `previous_origin` to `ctx.state.origin`
+    /// and report events of the tick, e.g. touched triggers
+    fn after_move(&mut self, _ctx: &ModifierContext, _previous_origin: &Vector3, _events: &mut Vec<MovementEvent>) {}
+}
+
+/// The named modifiers registered on a single player
+#[derive(Default)]
+pub struct MovementModifiers {
+    modifiers: Vec<(String, Box<dyn MovementModifier>)>,
+}
+
+impl MovementModifiers {
+    /// Create an empty modifier list
+    pub fn new() -> MovementModifiers {
+        MovementModifiers::default()
+    }
+
+    /// Register a modifier, replacing any existing modifier with the same name
+    pub fn push(&mut self, name: &str, modifier: Box<dyn MovementModifier>) {
+        self.remove(name);
+        self.modifiers.push((name.to_string(), modifier));
+    }
+
+    /// Remove a modifier by name, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.modifiers.len();
+        self.modifiers.retain(|(existing, _)| existing != name);
+        self.modifiers.len() != before
+    }
+
+    /// Whether a modifier with this name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.modifiers.iter().any(|(existing, _)| existing == name)
+    }
+
+    /// Number of registered modifiers
+    pub fn len(&self) -> usize {
+        self.modifiers.len()
+    }
+
+    /// Whether no modifier is registered
+    pub fn is_empty(&self) -> bool {
+        self.modifiers.is_empty()
+    }
+
+    pub(crate) fn wish(&mut self, ctx: &ModifierContext, wish_dir: &mut Vector3, wish_speed: &mut f32) {
+        for (_, modifier) in &mut self.modifiers {
+            modifier.modify_wish(ctx, wish_dir, wish_speed);
+        }
+    }
+
+    pub(crate) fn external_acceleration(&mut self, ctx: &ModifierContext) -> Vector3 {
+        let mut acceleration = Vector3::new(0.0, 0.0, 0.0);
+        for (_, modifier) in &mut self.modifiers {
+            acceleration.add(&modifier.external_acceleration(ctx));
+        }
+        acceleration
+    }
+
+    pub(crate) fn base_velocity(&mut self, ctx: &ModifierContext) -> Vector3 {
+        let mut base_velocity = Vector3::new(0.0, 0.0, 0.0);
+        for (_, modifier) in &mut self.modifiers {
+            base_velocity.add(&modifier.base_velocity(ctx));
+        }
+        base_velocity
+    }
+
+    pub(crate) fn accelerate(&mut self, ctx: &ModifierContext, accelerate: f32) -> f32 {
+        let mut accelerate = accelerate;
+        for (_, modifier) in &mut self.modifiers {
+            modifier.modify_accelerate(ctx, &mut accelerate);
+        }
+        accelerate
+    }
+
+    pub(crate) fn friction(&mut self, ctx: &ModifierContext, friction: f32) -> f32 {
+        let mut friction = friction;
+        for (_, modifier) in &mut self.modifiers {
+            modifier.modify_friction(ctx, &mut friction);
+        }
+        friction
+    }
+
+    pub(crate) fn velocity(&mut self, ctx: &ModifierContext, velocity: &mut Vector3) {
+        for (_, modifier) in &mut self.modifiers {
+            modifier.modify_velocity(ctx, velocity);
+        }
+    }
+
+    pub(crate) fn after_move(
+        &mut self,
+        ctx: &ModifierContext,
+        previous_origin: &Vector3,
+        events: &mut Vec<MovementEvent>,
+    ) {
+        for (_, modifier) in &mut self.modifiers {
+            modifier.after_move(ctx, previous_origin, events);
+        }
+    }
+}
+
+impl std::fmt::Debug for MovementModifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.modifiers.iter().map(|(name, _)| name)).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+    use crate::{player_tick, player_tick_with_modifiers, MoveType};
+
+    /// Appends its digit to the velocity's x (x * 10 + digit) and pushes along +x
+    struct Digit(f32);
+
+    impl MovementModifier for Digit {
+        fn modify_velocity(&mut self, _ctx: &ModifierContext, velocity: &mut Vector3) {
+            velocity.x = velocity.x * 10.0 + self.0;
+        }
+
+        fn external_acceleration(&mut self, _ctx: &ModifierContext) -> Vector3 {
+            Vector3::new(1000.0, 0.0, 0.0)
+        }
+    }
+
+    /// The digits of the modifiers in the order they ran
+    fn run_order(modifiers: &mut MovementModifiers) -> f32 {
+        let config = y_up_config();
+        let state = PlayerState::default();
+        let ctx = ModifierContext { state, input: &PlayerInput::new(), config: &config, delta_time: 0.0 };
+        let mut velocity = Vector3::new(0.0, 0.0, 0.0);
+        modifiers.velocity(&ctx, &mut velocity);
+        velocity.x
+    }
+
+    #[test]
+    fn test_modifiers_run_in_push_order() {
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("a", Box::new(Digit(1.0)));
+        modifiers.push("b", Box::new(Digit(2.0)));
+        modifiers.push("c", Box::new(Digit(3.0)));
+        assert_eq!(run_order(&mut modifiers), 123.0);
+        assert!(modifiers.remove("b"));
+        assert!(!modifiers.remove("b"));
+        assert_eq!(run_order(&mut modifiers), 13.0);
+    }
+
+    #[test]
+    fn test_push_replaces_a_modifier_with_the_same_name() {
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("a", Box::new(Digit(1.0)));
+        modifiers.push("b", Box::new(Digit(2.0)));
+        modifiers.push("a", Box::new(Digit(4.0)));
+        assert_eq!(modifiers.len(), 2);
+        assert!(modifiers.contains("a") && modifiers.contains("b"));
+        // The replacement runs last, like any newly pushed modifier
+        assert_eq!(run_order(&mut modifiers), 24.0);
+        assert_eq!(format!("{modifiers:?}"), r#"["b", "a"]"#);
+    }
+
+    #[test]
+    fn test_noclip_and_spectator_skip_modifiers() {
+        let config = y_up_config();
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        for move_type in [MoveType::Noclip, MoveType::Spectator, MoveType::Walk] {
+            let mut modifiers = MovementModifiers::new();
+            modifiers.push("digit", Box::new(Digit(1.0)));
+            let mut modified = PlayerState { move_type, ..PlayerState::default() };
+            let mut plain = modified;
+            for _ in 0..8 {
+                player_tick_with_modifiers(&mut modified, &input, &config, 1.0 / 64.0, None, &mut modifiers);
+                player_tick(&mut plain, &input, &config, 1.0 / 64.0, None);
+            }
+            assert_ne!(plain.origin, Vector3::new(0.0, 0.0, 0.0));
+            assert_eq!(modified == plain, move_type != MoveType::Walk, "{move_type:?}");
+        }
+    }
+}
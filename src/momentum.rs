@@ -0,0 +1,73 @@
+//! Grounded momentum and skidding
+//!
+//! Quake-style ground movement follows the wish direction instantly. With a
+//! non-zero `momentum_turn_rate`, players faster than `skid_min_speed` can
+//! only turn the ground wish direction away from their direction of travel at
+//! that rate (radians per second), so heavy characters carve turns instead of
+//! snapping. Reversing (wishing more than 90° away from the velocity) at such
+//! speeds skids: the player brakes with `skid_friction` and does not
+//! accelerate until slow enough to turn around freely.
+
+use crate::{MovementConfig, PlayerState, UpAxis, Vector3};
+
+/// The rate-limited ground wish direction and whether the player is skidding
+///
+/// Returns `wish_dir` unchanged while the model is off, below `skid_min_speed`
+/// or when nothing is wished.
+pub(crate) fn momentum_wish_dir<const Z_UP: bool>(
+    state: &PlayerState,
+    wish_dir: &Vector3,
+    wish_speed: f32,
+    config: &MovementConfig,
+    delta_time: f32,
+) -> (Vector3, bool) {
+    let horizontal = UpAxis::from_z_up(Z_UP).horizontal(&state.velocity);
+    let speed = horizontal.magnitude();
+    if config.momentum_turn_rate <= 0.0 || wish_speed <= 0.0 || speed <= config.skid_min_speed.max(0.1) {
+        return (*wish_dir, false);
+    }
+    let heading = horizontal.multiply_scalar(1.0 / speed);
+    let cos = heading.dot(wish_dir).clamp(-1.0, 1.0);
+    if cos < 0.0 {
+        return (*wish_dir, true);
+    }
+    let max_angle = config.momentum_turn_rate * delta_time;
+    if cos.acos() <= max_angle {
+        return (*wish_dir, false);
+    }
+    let side = (*wish_dir - heading.multiply_scalar(cos)).normalized();
+    let mut turned = heading.multiply_scalar(max_angle.cos());
+    turned.add(&side.multiply_scalar(max_angle.sin()));
+    (turned, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_turns_are_rate_limited_and_reversing_skids() {
+        let config = MovementConfig { momentum_turn_rate: std::f32::consts::PI, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let dt = 1.0 / 64.0;
+        let running = PlayerState { on_ground: true, velocity: Vector3::new(0.0, 0.0, -300.0), ..PlayerState::default() };
+
+        // Full right at yaw 0 is +X, 90° away from the velocity
+        let (turned, skidding) = momentum_wish_dir::<false>(&running, &Vector3::new(1.0, 0.0, 0.0), 300.0, &config, dt);
+        assert!(!skidding);
+        assert!((turned.dot(&Vector3::new(0.0, 0.0, -1.0)) - (std::f32::consts::PI * dt).cos()).abs() < 0.0001);
+
+        let mut state = running;
+        let back = PlayerInput { forward_move: -1.0, ..PlayerInput::new() };
+        player_tick(&mut state, &back, &config, dt, ground);
+        assert!(state.skidding);
+        assert!(state.velocity.z < 0.0 && state.velocity.z > -300.0);
+        for _ in 0..64 {
+            player_tick(&mut state, &back, &config, dt, ground);
+        }
+        assert!(!state.skidding);
+        assert!(state.velocity.z > 0.0);
+    }
+}
@@ -0,0 +1,405 @@
+//! Mouse look from raw mouse deltas
+//!
+//! Hosts usually hand `player_tick` finished view angles. Trainers and bots
+//! that want realistic human turn rates can instead feed raw mouse counts
+//! into a `MouseLook`: `add_delta` collects every mouse event between ticks
+//! and `tick` turns the total into yaw and pitch the way Source does
+//! (`sensitivity * m_yaw` degrees per count, optional `m_filter`-style
+//! smoothing and `m_customaccel`-style acceleration). `tick` returns the raw
+//! counts it consumed; store those in a replay and play them back through
+//! `turn` to get the same angles again.
+//!
+//! High polling rate mice report every millisecond, and browsers coalesce
+//! those reports into events with uneven timing. A `MouseAggregator` keeps
+//! the reports with their timestamps and hands out the counts that fell
+//! before a tick boundary, splitting a report that spans the boundary by
+//! time. Each tick then turns by exactly the motion made during it, instead of
+//! whichever reports happened to arrive before the tick ran.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use std::collections::VecDeque;
+
+use crate::PlayerInput;
+
+/// Source's default `m_yaw` and `m_pitch`, in degrees per count at sensitivity 1.0
+pub const DEFAULT_DEGREES_PER_COUNT: f32 = 0.022;
+
+/// Default pitch limit in degrees, as `cl_pitchup` / `cl_pitchdown`
+pub const DEFAULT_MAX_PITCH: f32 = 89.0;
+
+/// How raw mouse counts turn into view angles
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseSettings {
+    /// Multiplier on both axes
+    pub sensitivity: f32,
+    /// Degrees of yaw per count at sensitivity 1.0
+    pub yaw_per_count: f32,
+    /// Degrees of pitch per count at sensitivity 1.0
+    pub pitch_per_count: f32,
+    /// Share of the previous tick's delta mixed into this tick's (0.0 = off, 0.5 = `m_filter`)
+    pub smoothing: f32,
+    /// Extra sensitivity per count per second of mouse speed (0.0 = off)
+    pub acceleration: f32,
+    /// Largest sensitivity multiplier acceleration may reach (0.0 = no cap)
+    pub acceleration_cap: f32,
+    /// Pitch limit in degrees, both up and down
+    pub max_pitch: f32,
+    /// Moving the mouse forward looks down instead of up
+    pub invert_pitch: bool,
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        MouseSettings {
+            sensitivity: 1.0,
+            yaw_per_count: DEFAULT_DEGREES_PER_COUNT,
+            pitch_per_count: DEFAULT_DEGREES_PER_COUNT,
+            smoothing: 0.0,
+            acceleration: 0.0,
+            acceleration_cap: 0.0,
+            max_pitch: DEFAULT_MAX_PITCH,
+            invert_pitch: false,
+        }
+    }
+}
+
+/// View angles driven by raw mouse counts
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseLook {
+    settings: MouseSettings,
+    yaw: f32,
+    pitch: f32,
+    pending_x: f32,
+    pending_y: f32,
+    smoothed_x: f32,
+    smoothed_y: f32,
+}
+
+impl Default for MouseLook {
+    fn default() -> Self {
+        Self::new(MouseSettings::default())
+    }
+}
+
+impl MouseLook {
+    /// Create a mouse look facing yaw 0.0 and pitch 0.0
+    pub fn new(settings: MouseSettings) -> MouseLook {
+        MouseLook { settings, yaw: 0.0, pitch: 0.0, pending_x: 0.0, pending_y: 0.0, smoothed_x: 0.0, smoothed_y: 0.0 }
+    }
+
+    /// The settings in use
+    pub fn settings(&self) -> &MouseSettings {
+        &self.settings
+    }
+
+    /// Change the settings; the view angles are kept
+    pub fn set_settings(&mut self, settings: MouseSettings) {
+        self.settings = settings;
+    }
+
+    /// Current yaw in radians (positive turns left)
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    /// Current pitch in radians (positive looks up)
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// Jump to the given view angles (e.g. on respawn), dropping pending counts and smoothing
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        *self = MouseLook { yaw: wrap_yaw(yaw), pitch: self.clamp_pitch(pitch), ..MouseLook::new(self.settings) };
+    }
+
+    /// Collect a raw mouse event; `dx` is positive to the right, `dy` positive towards the user
+    pub fn add_delta(&mut self, dx: f32, dy: f32) {
+        self.pending_x += dx;
+        self.pending_y += dy;
+    }
+
+    /// Turn by the counts collected since the last tick and return them for replays
+    pub fn tick(&mut self, delta_time: f32) -> (f32, f32) {
+        let (dx, dy) = (self.pending_x, self.pending_y);
+        self.pending_x = 0.0;
+        self.pending_y = 0.0;
+        self.turn(dx, dy, delta_time);
+        (dx, dy)
+    }
+
+    /// Turn by one tick's worth of raw counts, e.g. read back from a replay
+    pub fn turn(&mut self, dx: f32, dy: f32, delta_time: f32) {
+        let smoothing = self.settings.smoothing.clamp(0.0, 1.0);
+        self.smoothed_x = dx + (self.smoothed_x - dx) * smoothing;
+        self.smoothed_y = dy + (self.smoothed_y - dy) * smoothing;
+
+        let mut sensitivity = self.settings.sensitivity;
+        if self.settings.acceleration > 0.0 && delta_time > 0.0 {
+            let rate = self.smoothed_x.hypot(self.smoothed_y) / delta_time;
+            let mut multiplier = 1.0 + self.settings.acceleration * rate;
+            if self.settings.acceleration_cap > 0.0 {
+                multiplier = multiplier.min(self.settings.acceleration_cap);
+            }
+            sensitivity *= multiplier;
+        }
+
+        let pitch_sign = if self.settings.invert_pitch { 1.0 } else { -1.0 };
+        let yaw = self.yaw - (self.smoothed_x * sensitivity * self.settings.yaw_per_count).to_radians();
+        let pitch = self.pitch + (pitch_sign * self.smoothed_y * sensitivity * self.settings.pitch_per_count).to_radians();
+        self.yaw = wrap_yaw(yaw);
+        self.pitch = self.clamp_pitch(pitch);
+    }
+
+    /// Write the view angles into a tick's input
+    pub fn apply_to(&self, input: &mut PlayerInput) {
+        input.yaw = self.yaw;
+        input.pitch = self.pitch;
+    }
+
+    fn clamp_pitch(&self, pitch: f32) -> f32 {
+        let max = self.settings.max_pitch.max(0.0).to_radians();
+        pitch.clamp(-max, max)
+    }
+}
+
+/// Raw mouse motion over a span of time
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MouseSample {
+    start: f64,
+    end: f64,
+    dx: f32,
+    dy: f32,
+}
+
+/// Timestamped raw mouse reports cut at tick boundaries
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default)]
+pub struct MouseAggregator {
+    samples: VecDeque<MouseSample>,
+    last_time: Option<f64>,
+}
+
+impl MouseAggregator {
+    /// Create an aggregator without reports
+    pub fn new() -> MouseAggregator {
+        MouseAggregator::default()
+    }
+
+    /// Collect a report of the counts moved since the previous report, taken at `time` seconds
+    ///
+    /// Any clock works as long as `take` uses the same one. The first report,
+    /// and reports older than the previous one, count as moved at an instant.
+    pub fn push(&mut self, time: f64, dx: f32, dy: f32) {
+        let start = self.last_time.unwrap_or(time).min(time);
+        let end = self.last_time.map_or(time, |last| last.max(time));
+        self.last_time = Some(end);
+        self.samples.push_back(MouseSample { start, end, dx, dy });
+    }
+
+    /// Counts moved up to `time`, splitting the report that spans it by time
+    pub fn take(&mut self, time: f64) -> (f32, f32) {
+        let (mut dx, mut dy) = (0.0, 0.0);
+        while let Some(sample) = self.samples.front_mut() {
+            if sample.end <= time {
+                dx += sample.dx;
+                dy += sample.dy;
+                self.samples.pop_front();
+            } else {
+                if sample.start < time {
+                    let share = ((time - sample.start) / (sample.end - sample.start)) as f32;
+                    dx += sample.dx * share;
+                    dy += sample.dy * share;
+                    sample.dx -= sample.dx * share;
+                    sample.dy -= sample.dy * share;
+                    sample.start = time;
+                }
+                break;
+            }
+        }
+        (dx, dy)
+    }
+
+    /// Collect the counts moved up to `time` into `look`, ready for `MouseLook::tick`
+    pub fn feed(&mut self, time: f64, look: &mut MouseLook) {
+        let (dx, dy) = self.take(time);
+        look.add_delta(dx, dy);
+    }
+
+    /// Drop every report, e.g. when the pointer lock is lost
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.last_time = None;
+    }
+}
+
+/// Wrap yaw into [-PI, PI) so it keeps its precision over long sessions
+fn wrap_yaw(yaw: f32) -> f32 {
+    use std::f32::consts::PI;
+    (yaw + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MouseSettings {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> MouseSettings {
+        MouseSettings::default()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MouseLook {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(settings: &MouseSettings) -> MouseLook {
+        MouseLook::new(*settings)
+    }
+
+    #[wasm_bindgen(getter = yaw)]
+    pub fn yaw_wasm(&self) -> f32 {
+        self.yaw
+    }
+
+    #[wasm_bindgen(getter = pitch)]
+    pub fn pitch_wasm(&self) -> f32 {
+        self.pitch
+    }
+
+    #[wasm_bindgen(js_name = setSettings)]
+    pub fn set_settings_wasm(&mut self, settings: &MouseSettings) {
+        self.set_settings(*settings);
+    }
+
+    #[wasm_bindgen(js_name = setAngles)]
+    pub fn set_angles_wasm(&mut self, yaw: f32, pitch: f32) {
+        self.set_angles(yaw, pitch);
+    }
+
+    #[wasm_bindgen(js_name = addDelta)]
+    pub fn add_delta_wasm(&mut self, dx: f32, dy: f32) {
+        self.add_delta(dx, dy);
+    }
+
+    /// Turn by the collected counts and return them as `[dx, dy]`
+    #[wasm_bindgen(js_name = tick)]
+    pub fn tick_wasm(&mut self, delta_time: f32) -> Vec<f32> {
+        let (dx, dy) = self.tick(delta_time);
+        vec![dx, dy]
+    }
+
+    #[wasm_bindgen(js_name = turn)]
+    pub fn turn_wasm(&mut self, dx: f32, dy: f32, delta_time: f32) {
+        self.turn(dx, dy, delta_time);
+    }
+
+    #[wasm_bindgen(js_name = applyTo)]
+    pub fn apply_to_wasm(&self, input: &mut PlayerInput) {
+        self.apply_to(input);
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MouseAggregator {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> MouseAggregator {
+        MouseAggregator::new()
+    }
+
+    /// Collect a report, e.g. `push(event.timeStamp / 1000, event.movementX, event.movementY)`
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_wasm(&mut self, time: f64, dx: f32, dy: f32) {
+        self.push(time, dx, dy);
+    }
+
+    /// Counts moved up to `time` as `[dx, dy]`
+    #[wasm_bindgen(js_name = take)]
+    pub fn take_wasm(&mut self, time: f64) -> Vec<f32> {
+        let (dx, dy) = self.take(time);
+        vec![dx, dy]
+    }
+
+    #[wasm_bindgen(js_name = feed)]
+    pub fn feed_wasm(&mut self, time: f64, look: &mut MouseLook) {
+        self.feed(time, look);
+    }
+
+    #[wasm_bindgen(js_name = clear)]
+    pub fn clear_wasm(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_turn_like_source_and_replay_exactly() {
+        let settings = MouseSettings { sensitivity: 2.0, ..MouseSettings::default() };
+        let mut look = MouseLook::new(settings);
+        look.add_delta(300.0, 0.0);
+        look.add_delta(700.0, 100.0);
+        assert_eq!(look.tick(1.0 / 64.0), (1000.0, 100.0));
+        // 1000 counts at sensitivity 2 and m_yaw 0.022 turn 44 degrees to the right
+        assert!((look.yaw().to_degrees() + 44.0).abs() < 1e-3);
+        assert!((look.pitch().to_degrees() + 4.4).abs() < 1e-3);
+
+        look.add_delta(0.0, -100_000.0);
+        let recorded = [(1000.0, 100.0), look.tick(1.0 / 64.0)];
+        assert!((look.pitch().to_degrees() - DEFAULT_MAX_PITCH).abs() < 1e-3);
+
+        let mut replay = MouseLook::new(settings);
+        let mut input = PlayerInput::new();
+        for (dx, dy) in recorded {
+            replay.turn(dx, dy, 1.0 / 64.0);
+        }
+        replay.apply_to(&mut input);
+        assert_eq!((input.yaw, input.pitch), (look.yaw(), look.pitch()));
+    }
+
+    #[test]
+    fn test_smoothing_and_acceleration() {
+        let mut smooth = MouseLook::new(MouseSettings { smoothing: 0.5, ..MouseSettings::default() });
+        smooth.turn(100.0, 0.0, 1.0 / 64.0);
+        smooth.turn(0.0, 0.0, 1.0 / 64.0);
+        // 50 counts on the first tick, 25 on the second
+        assert!((smooth.yaw().to_degrees() + 75.0 * DEFAULT_DEGREES_PER_COUNT).abs() < 1e-4);
+
+        let settings = MouseSettings { acceleration: 0.001, acceleration_cap: 3.0, ..MouseSettings::default() };
+        let (mut slow, mut fast) = (MouseLook::new(settings), MouseLook::new(settings));
+        for _ in 0..10 {
+            slow.turn(1.0, 0.0, 1.0 / 100.0);
+        }
+        fast.turn(10.0, 0.0, 1.0 / 100.0);
+        assert!(fast.yaw() < slow.yaw());
+        let mut flick = MouseLook::new(settings);
+        flick.turn(1000.0, 0.0, 1.0 / 100.0);
+        assert!((flick.yaw().to_degrees() + 3000.0 * DEFAULT_DEGREES_PER_COUNT).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_reports_are_split_at_tick_boundaries() {
+        let mut aggregator = MouseAggregator::new();
+        // A steady 1000 Hz flick of 10 counts per millisecond, reported 1.5 ms late
+        aggregator.push(0.0, 0.0, 0.0);
+        for ms in 1..=40 {
+            aggregator.push(ms as f64 / 1000.0 + 0.0015, 10.0, -2.0);
+        }
+        let tick = 1.0 / 64.0;
+        let (dx, dy) = aggregator.take(tick);
+        // 15.625 ms of motion, minus the 1.5 ms of latency
+        assert!((dx - 141.25).abs() < 1e-3 && (dy + 28.25).abs() < 1e-3);
+
+        let mut look = MouseLook::default();
+        aggregator.feed(2.0 * tick, &mut look);
+        aggregator.feed(1.0, &mut look);
+        let (rest, _) = look.tick(tick as f32);
+        assert!((dx + rest - 400.0).abs() < 1e-3);
+        assert_eq!(aggregator.take(2.0), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,174 @@
+//! Simulated network links for tests and examples
+//!
+//! Prediction, reconciliation and interpolation code only shows its problems
+//! on bad connections. A `NetSim` is a one-way link that delays, drops and
+//! reorders whatever is sent through it, so a client (inputs out, snapshots
+//! in) and a local authoritative `World` can be wired together inside a test
+//! with one `NetSim` per direction, and `RemotePlayer` or client-side
+//! prediction exercised against a connection that is reproducibly bad.
+//!
+//! Every message is delayed by `latency` plus a random share of `jitter`
+//! seconds, but never overtakes an earlier message, as on a single ordered
+//! route. A share `reorder` of messages is held back by one more `latency`
+//! instead, so the messages after it overtake it, and a share `loss` never
+//! arrives. The randomness comes from a seeded generator, so a seed always
+//! produces the same connection.
+//!
+//! `NetSim` is generic over the message type and is not available through WASM.
+
+/// Conditions of a simulated link
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NetConditions {
+    /// One-way delay in seconds
+    pub latency: f32,
+    /// Largest extra random delay in seconds
+    pub jitter: f32,
+    /// Share of messages dropped, from 0.0 to 1.0
+    pub loss: f32,
+    /// Share of messages held back so later ones overtake them, from 0.0 to 1.0
+    pub reorder: f32,
+}
+
+/// A one-way link delivering messages late, out of order or not at all
+#[derive(Debug, Clone)]
+pub struct NetSim<T> {
+    conditions: NetConditions,
+    rng: u64,
+    /// Messages with their delivery time and send order
+    in_flight: Vec<(f64, u64, T)>,
+    /// Delivery time of the newest message that was not reordered
+    ordered_until: f64,
+    sent: u64,
+    dropped: u64,
+}
+
+impl<T> NetSim<T> {
+    /// Create an empty link; the same `seed` always drops and delays the same messages
+    pub fn new(conditions: NetConditions, seed: u64) -> NetSim<T> {
+        NetSim { conditions, rng: seed, in_flight: Vec::new(), ordered_until: f64::NEG_INFINITY, sent: 0, dropped: 0 }
+    }
+
+    /// The conditions in use
+    pub fn conditions(&self) -> &NetConditions {
+        &self.conditions
+    }
+
+    /// Change the conditions for messages sent from now on
+    pub fn set_conditions(&mut self, conditions: NetConditions) {
+        self.conditions = conditions;
+    }
+
+    /// Send a message at local time `now`, in seconds
+    pub fn send(&mut self, now: f64, message: T) {
+        self.sent += 1;
+        if self.random() < self.conditions.loss {
+            self.dropped += 1;
+            return;
+        }
+        let latency = self.conditions.latency.max(0.0) as f64;
+        let delay = latency + (self.random() * self.conditions.jitter.max(0.0)) as f64;
+        let deliver_at = if self.random() < self.conditions.reorder {
+            now + delay + latency
+        } else {
+            self.ordered_until = self.ordered_until.max(now + delay);
+            self.ordered_until
+        };
+        self.in_flight.push((deliver_at, self.sent, message));
+    }
+
+    /// Messages that arrived by local time `now`, in arrival order
+    pub fn receive(&mut self, now: f64) -> Vec<T> {
+        let (mut arrived, in_flight): (Vec<_>, Vec<_>) =
+            self.in_flight.drain(..).partition(|(deliver_at, _, _)| *deliver_at <= now);
+        self.in_flight = in_flight;
+        arrived.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        arrived.into_iter().map(|(_, _, message)| message).collect()
+    }
+
+    /// Number of messages sent and not yet delivered or dropped
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Number of messages sent so far
+    pub fn sent(&self) -> u64 {
+        self.sent
+    }
+
+    /// Number of messages dropped so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Uniform random number in [0, 1) from a SplitMix64 sequence
+    fn random(&mut self) -> f32 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::{AdaptiveDelay, PlayerInput, PlayerState, RemotePlayer, World};
+
+    #[test]
+    fn test_link_delays_drops_and_reorders() {
+        let conditions = NetConditions { latency: 0.05, jitter: 0.02, loss: 0.1, reorder: 0.1 };
+        let mut link = NetSim::new(conditions, 7);
+        let mut received = Vec::new();
+        for i in 0..1000u32 {
+            let now = i as f64 / 100.0;
+            link.send(now, (i, now));
+            for (sent, sent_at) in link.receive(now) {
+                assert!(now - sent_at >= 0.05 - 1e-9);
+                received.push(sent);
+            }
+        }
+        received.extend(link.receive(f64::INFINITY).into_iter().map(|(sent, _)| sent));
+        assert_eq!(received.len() as u64 + link.dropped(), 1000);
+        assert!((70..130).contains(&link.dropped()));
+        let overtaken = received.windows(2).filter(|pair| pair[1] < pair[0]).count();
+        assert!(overtaken > 50);
+
+        // The same seed gives the same connection
+        let mut again = NetSim::new(conditions, 7);
+        (0..1000u32).for_each(|i| again.send(i as f64 / 100.0, i));
+        assert_eq!(again.dropped(), link.dropped());
+    }
+
+    #[test]
+    fn test_client_and_server_over_a_bad_link() {
+        let config = y_up_config();
+        let mut world = World::new(config);
+        let id = world.spawn(PlayerState { on_ground: true, ..PlayerState::default() });
+        let conditions = NetConditions { latency: 0.06, jitter: 0.03, loss: 0.05, reorder: 0.02 };
+        let (mut upstream, mut downstream) = (NetSim::new(conditions, 1), NetSim::new(conditions, 2));
+        let mut remote = RemotePlayer::new(config).with_adaptive_delay(AdaptiveDelay::default());
+
+        let tick = 1.0 / 64.0;
+        for i in 0..640 {
+            let now = i as f64 * tick as f64;
+            upstream.send(now, PlayerInput { forward_move: 1.0, ..PlayerInput::new() });
+            for input in upstream.receive(now) {
+                let ground = flat_ground(&world.get_state(id).unwrap());
+                world.apply_input(id, &input, ground, tick);
+            }
+            if i % 3 == 0 {
+                downstream.send(now, (now, world.get_state(id).unwrap()));
+            }
+            for (server_time, state) in downstream.receive(now) {
+                remote.receive(server_time, now, state);
+            }
+        }
+        // The client draws the player moving, with a delay covering the bad link
+        let drawn = remote.sample(10.0).unwrap();
+        assert!(drawn.origin.x.abs() + drawn.origin.z.abs() > 100.0);
+        assert!(remote.jitter() > 0.0 && remote.interpolation_delay() > AdaptiveDelay::default().min_delay);
+    }
+}
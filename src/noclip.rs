@@ -0,0 +1,66 @@
+//! Noclip flight
+//!
+//! Noclip is the developer's free-fly camera. Setting the velocity straight
+//! from the input makes it jerky, and every host engine smooths it a little
+//! differently, so noclip eases towards the wished velocity like the
+//! spectator camera: with a time constant of `noclip_acceleration_time` while
+//! speeding up and `noclip_deceleration_time` while slowing down, the same at
+//! any tick rate. Holding sprint multiplies the speed by
+//! `noclip_fast_multiplier` and holding duck by `noclip_slow_multiplier`, for
+//! crossing a map quickly or lining up a shot. `up_move` flies straight up or
+//! down as before.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerInput};
+
+/// Noclip speed multiplier of the held speed keys
+pub fn noclip_speed_multiplier(input: &PlayerInput, config: &MovementConfig) -> f32 {
+    let mut multiplier = 1.0;
+    if input.sprint {
+        multiplier *= config.noclip_fast_multiplier;
+    }
+    if input.duck {
+        multiplier *= config.noclip_slow_multiplier;
+    }
+    multiplier
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = noclipSpeedMultiplier)]
+pub fn noclip_speed_multiplier_wasm(input: &PlayerInput, config: &MovementConfig) -> f32 {
+    noclip_speed_multiplier(input, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MoveType, PlayerState};
+    use crate::tick::tests::y_up_config;
+
+    fn speed_after(input: &PlayerInput, config: &MovementConfig, ticks: usize, delta_time: f32) -> f32 {
+        let mut state = PlayerState { move_type: MoveType::Noclip, ..PlayerState::default() };
+        for _ in 0..ticks {
+            player_tick(&mut state, input, config, delta_time, None);
+        }
+        state.velocity.magnitude()
+    }
+
+    #[test]
+    fn test_noclip_eases_to_the_speed_of_the_held_keys() {
+        let config = y_up_config();
+        let forward = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        assert!((speed_after(&forward, &config, 128, 1.0 / 64.0) - 1000.0).abs() < 0.1);
+        assert!((speed_after(&PlayerInput { sprint: true, ..forward }, &config, 128, 1.0 / 64.0) - 3000.0).abs() < 0.1);
+        assert!((speed_after(&PlayerInput { duck: true, ..forward }, &config, 128, 1.0 / 64.0) - 250.0).abs() < 0.1);
+
+        // One acceleration time constant reaches ~63% of the speed at any tick rate
+        let fast = speed_after(&forward, &config, 32, 1.0 / 320.0);
+        let slow = speed_after(&forward, &config, 4, 1.0 / 40.0);
+        assert!((fast - 632.1).abs() < 0.5 && (fast - slow).abs() < 0.01);
+
+        let instant = MovementConfig { noclip_acceleration_time: 0.0, ..config };
+        assert_eq!(speed_after(&forward, &instant, 1, 1.0 / 64.0), 1000.0);
+    }
+}
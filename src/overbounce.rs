@@ -0,0 +1,155 @@
+//! Quake 3 overbounces
+//!
+//! Quake 3 traces for ground with a 0.25 unit tolerance. A fall that ends a
+//! frame just above the floor, inside that tolerance but not touching it, is
+//! clipped against the floor on the next frame while still falling, and the
+//! clip sends the player back up at the full fall speed instead of landing.
+//! Defrag maps are built around these overbounces at known heights. With
+//! `overbounce` set, a falling player that starts a tick less than
+//! `overbounce_window` above walkable ground bounces the same way, keeping
+//! its horizontal speed, and an `Overbounced` event is emitted.
+//!
+//! Whether a fall overbounces only depends on the height, the vertical speed,
+//! gravity and the tick rate, so `predict_overbounce` can tell ahead of time
+//! by simulating the fall over flat ground.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{player_tick, MovementConfig, MovementEvent, PlayerInput, PlayerState, RayCollisionHit, UpAxis};
+
+/// Ticks `predict_overbounce` simulates before giving up on a fall that never ends
+const MAX_PREDICTED_TICKS: u32 = 10_000;
+
+/// An overbounce found by `predict_overbounce`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overbounce {
+    /// Tick of the fall the bounce happens on, starting at 1
+    pub ticks: u32,
+    /// Height above the ground the bounce happens at, in world units
+    pub height: f32,
+    /// Vertical speed the player bounces back up with
+    pub speed: f32,
+}
+
+/// Bounce a falling player off ground just below, returning whether it did
+pub(crate) fn overbounce<const Z_UP: bool>(
+    state: &mut PlayerState,
+    config: &MovementConfig,
+    ground: &RayCollisionHit,
+    events: &mut Vec<MovementEvent>,
+) -> bool {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let fall_speed = -axis.height(&state.velocity);
+    let distance = ground.distance_native();
+    if !config.overbounce || state.on_ground || fall_speed <= 0.0 || distance <= 0.0 || distance > config.overbounce_window {
+        return false;
+    }
+    axis.set_height(&mut state.velocity, fall_speed);
+    events.push(MovementEvent::Overbounced { tick: state.tick, speed: fall_speed });
+    true
+}
+
+/// Whether falling from `height` above flat ground with `vertical_speed` (positive = up) overbounces
+///
+/// Heights are in world units. The prediction assumes `overbounce` is on,
+/// so it also works for configs that keep it off, and that nothing is
+/// pressed during the fall.
+pub fn predict_overbounce(height: f32, vertical_speed: f32, config: &MovementConfig, delta_time: f32) -> Option<Overbounce> {
+    let config = MovementConfig { overbounce: true, ..*config };
+    let axis = config.up_axis;
+    let mut state = PlayerState::default();
+    axis.set_height(&mut state.origin, height);
+    axis.set_height(&mut state.velocity, vertical_speed);
+    let input = PlayerInput::new();
+    for ticks in 1..=MAX_PREDICTED_TICKS {
+        let height = axis.height(&state.origin);
+        let ground = (height >= 0.0).then(|| RayCollisionHit::new_native(axis.up(), height));
+        let events = player_tick(&mut state, &input, &config, delta_time, ground);
+        if let Some(&MovementEvent::Overbounced { speed, .. }) = events.first() {
+            return Some(Overbounce { ticks, height, speed });
+        }
+        if state.on_ground || axis.height(&state.origin) < 0.0 {
+            return None;
+        }
+    }
+    None
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = predictOverbounce)]
+pub fn predict_overbounce_wasm(height: f32, vertical_speed: f32, config: &MovementConfig, delta_time: f32) -> Option<Overbounce> {
+    predict_overbounce(height, vertical_speed, config, delta_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::Vector3;
+
+    #[test]
+    fn test_prediction_matches_the_simulation() {
+        let dt = 1.0 / 125.0;
+        let heights: Vec<f32> = (0..400).map(|i| 16.0 + i as f32 * 0.5).collect();
+        let predictions: Vec<_> = heights.iter().map(|&h| predict_overbounce(h, 0.0, &y_up_config(), dt)).collect();
+        assert!(predictions.iter().any(Option::is_some) && predictions.iter().any(Option::is_none));
+
+        let (height, predicted) = heights.iter().zip(&predictions).find_map(|(&h, p)| p.map(|p| (h, p))).unwrap();
+        assert!(predicted.height > 0.0 && predicted.height <= 0.25 && predicted.speed > 0.0);
+        let config = MovementConfig { overbounce: true, ..y_up_config() };
+        let mut state = PlayerState { origin: Vector3::new(0.0, height, 0.0), ..PlayerState::default() };
+        state.velocity.x = 300.0;
+        for _ in 1..predicted.ticks {
+            let ground = flat_ground(&state);
+            assert!(player_tick(&mut state, &PlayerInput::new(), &config, dt, ground).is_empty());
+        }
+        let ground = flat_ground(&state);
+        let events = player_tick(&mut state, &PlayerInput::new(), &config, dt, ground);
+        assert!(matches!(events.as_slice(), [MovementEvent::Overbounced { .. }]));
+        assert!(!state.on_ground && state.velocity.y > 0.0);
+        assert_eq!(state.velocity.x, 300.0);
+
+        // Without the compat flag the same fall lands
+        let mut plain = PlayerState { origin: Vector3::new(0.0, height, 0.0), ..PlayerState::default() };
+        for _ in 0..predicted.ticks {
+            let ground = flat_ground(&plain);
+            player_tick(&mut plain, &PlayerInput::new(), &y_up_config(), dt, ground);
+        }
+        assert!(plain.on_ground);
+    }
+    #[test]
+    fn test_overbounce_window_edges() {
+        let config = MovementConfig { overbounce: true, ..y_up_config() };
+        let falling = PlayerState { velocity: Vector3::new(0.0, -400.0, 0.0), ..PlayerState::default() };
+        let bounces = |state: PlayerState, config: &MovementConfig, distance: f32| {
+            let mut state = state;
+            let mut events = Vec::new();
+            let hit = RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), distance);
+            let bounced = overbounce::<false>(&mut state, config, &hit, &mut events);
+            assert_eq!(bounced, events.len() == 1);
+            assert_eq!(state.velocity.y, if bounced { 400.0 } else { -400.0 });
+            bounced
+        };
+        assert!(bounces(falling, &config, 0.25));
+        assert!(bounces(falling, &config, 0.01));
+        assert!(!bounces(falling, &config, 0.0), "touching the floor lands");
+        assert!(!bounces(falling, &config, 0.26));
+        assert!(!bounces(falling, &y_up_config(), 0.1));
+        assert!(!bounces(PlayerState { on_ground: true, ..falling }, &config, 0.1));
+
+        let mut rising = PlayerState { velocity: Vector3::new(0.0, 10.0, 0.0), ..PlayerState::default() };
+        let hit = RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.1);
+        assert!(!overbounce::<false>(&mut rising, &config, &hit, &mut Vec::new()));
+    }
+
+    #[test]
+    fn test_prediction_gives_up_on_falls_that_cannot_bounce() {
+        let dt = 1.0 / 125.0;
+        assert_eq!(predict_overbounce(-1.0, 0.0, &y_up_config(), dt), None);
+        assert_eq!(predict_overbounce(0.0, 0.0, &y_up_config(), dt), None);
+        let gravityless = MovementConfig { gravity: 0.0, ..y_up_config() };
+        assert_eq!(predict_overbounce(100.0, 0.0, &gravityless, dt), None);
+    }
+}
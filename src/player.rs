@@ -0,0 +1,359 @@
+//! Per-player simulation state
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::checked::MovementError;
+use crate::dodge::DodgeDirection;
+use crate::error::AirStrafeError;
+use crate::Vector3;
+
+/// How the player is currently being moved, selected per tick by `player_tick`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MoveType {
+    /// Regular ground/air movement with gravity
+    #[default]
+    Walk,
+    /// Free 3D flight without gravity or collision
+    Fly,
+    /// Smoothed free flight for development, with speed keys (see `noclip`)
+    Noclip,
+    /// Ladder climbing (currently moved like `Walk`)
+    Ladder,
+    /// Swimming (moved like `Walk` along the view pitch unless `classic_swim`, with its own terminal velocity)
+    Swim,
+    /// Smoothed free camera for spectators and replays, without collision
+    Spectator,
+    /// Gliding through the air (entered and left by `player_tick`, see `glide`)
+    Glide,
+    /// Climbing a wall (entered and left by `update_climb_contact`, see `climb`)
+    Climb,
+}
+
+/// Complete movement state of a single player
+///
+/// Everything the movement simulation needs to continue from a given tick lives
+/// here, so copying a `PlayerState` is enough to snapshot and restore a player.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerState {
+    /// Movement mode used for the next tick
+    pub move_type: MoveType,
+    /// Player position (feet)
+    pub origin: Vector3,
+    /// Current velocity in units per second
+    pub velocity: Vector3,
+    /// Whether the player is standing on walkable ground
+    pub on_ground: bool,
+    /// Normal of the ground surface (only meaningful while `on_ground`)
+    pub ground_normal: Vector3,
+    /// Whether the player is fully ducked
+    pub ducked: bool,
+    /// Whether the player is crouch sliding, see `crouch_slide`
+    pub crouch_sliding: bool,
+    /// Whether a ceiling is too low to stand up, see `update_ceiling_clearance`
+    pub ceiling_blocked: bool,
+    /// Duck transition progress (0.0 = standing, 1.0 = fully ducked)
+    pub duck_amount: f32,
+    /// Whether duck was held on the previous tick
+    pub duck_held: bool,
+    /// Recent duck presses still slowing the duck (see `duck`)
+    pub duck_spam: f32,
+    /// Seconds remaining before another jump is allowed
+    pub jump_cooldown: f32,
+    /// Sprint progress (0.0 = walking, 1.0 = full `sprint_multiplier`), see `sprint`
+    pub sprint_amount: f32,
+    /// Whether jump is being held to charge a jump, see `jump_charge`
+    pub jump_charging: bool,
+    /// Charge of the held jump (0.0 to 1.0)
+    pub jump_charge: f32,
+    /// Seconds until the stamina cost of the last jump wore off, see `bhop_penalty`
+    pub bhop_stamina: f32,
+    /// Seconds spent on the ground since the last landing
+    pub time_on_ground: f32,
+    /// Seconds spent in the air since the last takeoff
+    pub time_in_air: f32,
+    /// Whether jump was held on the previous tick
+    pub jump_held: bool,
+    /// Whether releasing jump can still cut the current jump short, see `jump_cut`
+    pub jump_cut_ready: bool,
+    /// Whether the player is sliding down a surface too steep to stand on
+    pub sliding: bool,
+    /// Whether the player is skidding to reverse direction, see `momentum`
+    pub skidding: bool,
+    /// Number of ticks simulated so far
+    pub tick: u64,
+    /// Number of jumps performed so far
+    pub jump_count: u32,
+    /// Seconds until the next footstep while moving on the ground
+    pub footstep_time: f32,
+    /// Whether this player has a jetpack, see `jetpack`
+    pub jetpack_enabled: bool,
+    /// Jetpack fuel (0.0 = empty, 1.0 = full)
+    pub jetpack_fuel: f32,
+    /// Normal of the wall being climbed (only meaningful while climbing)
+    pub climb_normal: Vector3,
+    /// Climbing stamina (0.0 = exhausted, 1.0 = full)
+    pub climb_stamina: f32,
+    /// How deep the player is in water (0 = dry, 1 = feet, 2 = waist, 3 = eyes), set by the host
+    pub water_level: u8,
+    /// Flow velocity of the water volume the player is in, set by the host
+    pub water_current: Vector3,
+    /// Seconds left of the current water jump (0.0 = not water jumping)
+    pub water_jump_time: f32,
+    /// Velocity pushing the player over the ledge during a water jump
+    pub water_jump_velocity: Vector3,
+    /// Gravity multiplier of the zone the player is in (1.0 = normal, 0.0 = weightless), set by the host
+    pub gravity_scale: f32,
+    /// Last direction tapped, for double-tap dodges
+    pub dodge_tap: DodgeDirection,
+    /// Seconds since `dodge_tap` was tapped
+    pub dodge_tap_time: f32,
+    /// Movement directions and dodge button held on the previous tick (bit mask)
+    pub dodge_keys_held: u8,
+    /// Whether the player is in the air from a dodge
+    pub dodging: bool,
+    /// Seconds left of the landing recovery after a dodge
+    pub dodge_recovery: f32,
+    /// Horizontal normal of a wall within dodge reach (zero when there is none), see `update_dodge_wall`
+    pub dodge_wall_normal: Vector3,
+    /// Wall dodges since the player last stood on the ground
+    pub wall_dodge_count: u32,
+    /// Whether the player already jumped during the current dodge
+    pub dodge_jumped: bool,
+    /// Whether the player is slamming down in a ground pound, see `ground_pound`
+    pub ground_pounding: bool,
+    /// Air dashes used since the player last stood on the ground, see `air_dash`
+    pub air_dashes: u32,
+    /// Whether the air dash button was held on the previous tick
+    pub air_dash_held: bool,
+    /// Length of the current wall-run jump chain, decaying on the ground (see `wall_run`)
+    pub wall_run_chain: f32,
+    /// Normal of the sticky surface the player is stuck to (zero when free), see `sticky`
+    pub stick_normal: Vector3,
+    /// Lean progress (-1.0 = full left, 1.0 = full right), see `update_lean`
+    pub lean: f32,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        PlayerState {
+            move_type: MoveType::Walk,
+            origin: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
+            on_ground: false,
+            ground_normal: Vector3::new(0.0, 1.0, 0.0),
+            ducked: false,
+            crouch_sliding: false,
+            ceiling_blocked: false,
+            duck_amount: 0.0,
+            duck_held: false,
+            duck_spam: 0.0,
+            jump_cooldown: 0.0,
+            sprint_amount: 0.0,
+            jump_charging: false,
+            jump_charge: 0.0,
+            bhop_stamina: 0.0,
+            time_on_ground: 0.0,
+            time_in_air: 0.0,
+            jump_held: false,
+            jump_cut_ready: false,
+            sliding: false,
+            skidding: false,
+            tick: 0,
+            jump_count: 0,
+            footstep_time: 0.0,
+            jetpack_enabled: false,
+            jetpack_fuel: 1.0,
+            climb_normal: Vector3::new(0.0, 0.0, 1.0),
+            climb_stamina: 1.0,
+            water_level: 0,
+            water_current: Vector3::new(0.0, 0.0, 0.0),
+            water_jump_time: 0.0,
+            water_jump_velocity: Vector3::new(0.0, 0.0, 0.0),
+            gravity_scale: 1.0,
+            dodge_tap: DodgeDirection::None,
+            dodge_tap_time: 0.0,
+            dodge_keys_held: 0,
+            dodging: false,
+            dodge_recovery: 0.0,
+            dodge_wall_normal: Vector3::new(0.0, 0.0, 0.0),
+            wall_dodge_count: 0,
+            dodge_jumped: false,
+            ground_pounding: false,
+            air_dashes: 0,
+            air_dash_held: false,
+            wall_run_chain: 0.0,
+            stick_normal: Vector3::new(0.0, 0.0, 0.0),
+            lean: 0.0,
+        }
+    }
+}
+
+impl PlayerState {
+    /// Create a standing player at the given origin
+    pub fn new(origin: Vector3) -> PlayerState {
+        PlayerState {
+            origin,
+            ..PlayerState::default()
+        }
+    }
+
+    /// Start building a state from the defaults
+    pub fn builder() -> PlayerStateBuilder {
+        PlayerStateBuilder::default()
+    }
+}
+
+/// Fluent, validating builder for `PlayerState`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerStateBuilder {
+    state: PlayerState,
+}
+
+impl PlayerStateBuilder {
+    /// Movement mode used for the first tick
+    pub fn move_type(mut self, move_type: MoveType) -> PlayerStateBuilder {
+        self.state.move_type = move_type;
+        self
+    }
+
+    /// Player position (feet)
+    pub fn origin(mut self, origin: Vector3) -> PlayerStateBuilder {
+        self.state.origin = origin;
+        self
+    }
+
+    /// Initial velocity in units per second
+    pub fn velocity(mut self, velocity: Vector3) -> PlayerStateBuilder {
+        self.state.velocity = velocity;
+        self
+    }
+
+    /// Start on walkable ground with the given surface normal (normalized on build)
+    pub fn on_ground(mut self, ground_normal: Vector3) -> PlayerStateBuilder {
+        self.state.on_ground = true;
+        self.state.ground_normal = ground_normal;
+        self
+    }
+
+    /// Start fully ducked
+    pub fn ducked(mut self, ducked: bool) -> PlayerStateBuilder {
+        self.state.ducked = ducked;
+        self.state.duck_amount = if ducked { 1.0 } else { 0.0 };
+        self
+    }
+
+    /// Give the player a jetpack with a full tank
+    pub fn jetpack(mut self, enabled: bool) -> PlayerStateBuilder {
+        self.state.jetpack_enabled = enabled;
+        self.state.jetpack_fuel = 1.0;
+        self
+    }
+
+    /// Gravity multiplier of the zone the player starts in
+    pub fn gravity_scale(mut self, scale: f32) -> PlayerStateBuilder {
+        self.state.gravity_scale = scale;
+        self
+    }
+
+    /// Validate the vectors and create the state
+    pub fn build(self) -> Result<PlayerState, AirStrafeError> {
+        let mut state = self.state;
+        for (argument, value) in [("origin", state.origin), ("velocity", state.velocity), ("ground_normal", state.ground_normal)] {
+            if !value.is_finite() {
+                return Err(MovementError::NonFinite { argument }.into());
+            }
+        }
+        if !state.gravity_scale.is_finite() {
+            return Err(MovementError::NonFinite { argument: "gravity_scale" }.into());
+        }
+        if state.ground_normal.length_sq() == 0.0 {
+            return Err(MovementError::ZeroLength { argument: "ground_normal" }.into());
+        }
+        state.ground_normal = state.ground_normal.normalized();
+        Ok(state)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl PlayerState {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(x: f32, y: f32, z: f32) -> PlayerState {
+        PlayerState::new(Vector3::new(x, y, z))
+    }
+
+    #[wasm_bindgen(js_name = builder)]
+    pub fn builder_wasm() -> PlayerStateBuilder {
+        PlayerState::builder()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl PlayerStateBuilder {
+    #[wasm_bindgen(js_name = moveType)]
+    pub fn move_type_wasm(self, move_type: MoveType) -> PlayerStateBuilder {
+        self.move_type(move_type)
+    }
+
+    #[wasm_bindgen(js_name = origin)]
+    pub fn origin_wasm(self, origin: &Vector3) -> PlayerStateBuilder {
+        self.origin(*origin)
+    }
+
+    #[wasm_bindgen(js_name = velocity)]
+    pub fn velocity_wasm(self, velocity: &Vector3) -> PlayerStateBuilder {
+        self.velocity(*velocity)
+    }
+
+    #[wasm_bindgen(js_name = onGround)]
+    pub fn on_ground_wasm(self, ground_normal: &Vector3) -> PlayerStateBuilder {
+        self.on_ground(*ground_normal)
+    }
+
+    #[wasm_bindgen(js_name = ducked)]
+    pub fn ducked_wasm(self, ducked: bool) -> PlayerStateBuilder {
+        self.ducked(ducked)
+    }
+
+    #[wasm_bindgen(js_name = jetpack)]
+    pub fn jetpack_wasm(self, enabled: bool) -> PlayerStateBuilder {
+        self.jetpack(enabled)
+    }
+
+    #[wasm_bindgen(js_name = gravityScale)]
+    pub fn gravity_scale_wasm(self, scale: f32) -> PlayerStateBuilder {
+        self.gravity_scale(scale)
+    }
+
+    #[wasm_bindgen(js_name = build)]
+    pub fn build_wasm(self) -> Result<PlayerState, JsValue> {
+        self.build().map_err(JsValue::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_validates_and_normalizes() {
+        let state = PlayerState::builder()
+            .origin(Vector3::new(1.0, 2.0, 3.0))
+            .on_ground(Vector3::new(0.0, 2.0, 0.0))
+            .ducked(true)
+            .build()
+            .unwrap();
+        assert_eq!(state.origin, Vector3::new(1.0, 2.0, 3.0));
+        assert!(state.on_ground);
+        assert_eq!(state.ground_normal, Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(state.duck_amount, 1.0);
+
+        assert!(PlayerState::builder().velocity(Vector3::new(f32::NAN, 0.0, 0.0)).build().is_err());
+        assert!(PlayerState::builder().on_ground(Vector3::new(0.0, 0.0, 0.0)).build().is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Remote player interpolation with extrapolation fallback
+//!
+//! Clients draw other players from server snapshots, rendered a little in the
+//! past (`interpolation_delay`) so there is usually a snapshot on either side
+//! of the render time to interpolate between. When snapshots arrive late or
+//! get lost, the render time runs past the newest snapshot. Instead of
+//! freezing the player there, `RemotePlayer` extrapolates from the newest
+//! snapshot through `player_tick` without any input: airborne players keep
+//! their velocity and fall under gravity, players on the ground keep sliding
+//! at their last velocity. Extrapolation stops after `max_extrapolation`
+//! seconds, so a player who disconnects does not fly off forever, and the
+//! next snapshot takes over again as soon as it arrives.
+//!
+//! A fixed delay is either too long on a good connection or too short on a
+//! bad one. With `with_adaptive_delay`, snapshots handed to `receive` along
+//! with their local arrival time tune the delay: it covers one snapshot
+//! interval, one more interval per 10% of snapshots lost and a multiple of
+//! the arrival jitter (measured as in RFC 3550), kept between the configured
+//! bounds. The delay grows quickly when the connection gets worse and shrinks
+//! slowly when it recovers, so the render time never visibly jumps.
+
+use std::collections::VecDeque;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{player_tick, MovementConfig, PlayerInput, PlayerState};
+
+/// Default render delay behind the newest snapshot, in seconds
+pub const DEFAULT_INTERPOLATION_DELAY: f32 = 0.1;
+/// Default longest extrapolation past the newest snapshot, in seconds
+pub const DEFAULT_MAX_EXTRAPOLATION: f32 = 0.25;
+/// Tick length extrapolation simulates with, in seconds
+const EXTRAPOLATION_STEP: f32 = 1.0 / 64.0;
+/// Seconds of snapshots kept behind the newest one
+const SNAPSHOT_HISTORY: f64 = 1.0;
+/// Share of the distance to the target delay covered per snapshot when growing
+const DELAY_GROW_RATE: f32 = 0.25;
+/// Share of the distance to the target delay covered per snapshot when shrinking
+const DELAY_SHRINK_RATE: f32 = 0.02;
+/// Weight of a new measurement in the snapshot interval and loss averages
+const STATS_SMOOTHING: f32 = 0.05;
+
+/// Bounds and margins of an adaptive interpolation delay
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveDelay {
+    /// Shortest delay in seconds
+    pub min_delay: f32,
+    /// Longest delay in seconds
+    pub max_delay: f32,
+    /// Multiple of the measured jitter added to the delay
+    pub jitter_multiplier: f32,
+}
+
+impl Default for AdaptiveDelay {
+    fn default() -> Self {
+        AdaptiveDelay { min_delay: 0.05, max_delay: 0.5, jitter_multiplier: 2.5 }
+    }
+}
+
+/// Measured quality of the snapshot stream
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct SnapshotStats {
+    /// Server time and transit time (arrival minus server time) of the newest snapshot
+    last: Option<(f64, f64)>,
+    interval: f32,
+    jitter: f32,
+    loss: f32,
+}
+
+/// Buffered server snapshots of one remote player
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct RemotePlayer {
+    config: MovementConfig,
+    interpolation_delay: f32,
+    max_extrapolation: f32,
+    snapshots: VecDeque<(f64, PlayerState)>,
+    adaptive: Option<AdaptiveDelay>,
+    stats: SnapshotStats,
+}
+
+impl RemotePlayer {
+    /// Create a remote player without snapshots, extrapolating with `config`
+    pub fn new(config: MovementConfig) -> RemotePlayer {
+        RemotePlayer {
+            config,
+            interpolation_delay: DEFAULT_INTERPOLATION_DELAY,
+            max_extrapolation: DEFAULT_MAX_EXTRAPOLATION,
+            snapshots: VecDeque::new(),
+            adaptive: None,
+            stats: SnapshotStats::default(),
+        }
+    }
+
+    /// Render this many seconds behind the given time
+    pub fn with_interpolation_delay(mut self, seconds: f32) -> RemotePlayer {
+        self.interpolation_delay = seconds.max(0.0);
+        self
+    }
+
+    /// Extrapolate at most this many seconds past the newest snapshot (0.0 freezes instead)
+    pub fn with_max_extrapolation(mut self, seconds: f32) -> RemotePlayer {
+        self.max_extrapolation = seconds.max(0.0);
+        self
+    }
+
+    /// Tune the interpolation delay from the snapshots passed to `receive`
+    pub fn with_adaptive_delay(mut self, adaptive: AdaptiveDelay) -> RemotePlayer {
+        self.interpolation_delay = self.interpolation_delay.clamp(adaptive.min_delay, adaptive.max_delay);
+        self.adaptive = Some(adaptive);
+        self
+    }
+
+    /// Seconds the render time is behind the time passed to `sample`
+    pub fn interpolation_delay(&self) -> f32 {
+        self.interpolation_delay
+    }
+
+    /// Measured arrival jitter in seconds
+    pub fn jitter(&self) -> f32 {
+        self.stats.jitter
+    }
+
+    /// Measured share of snapshots lost, from 0.0 to 1.0
+    pub fn loss(&self) -> f32 {
+        self.stats.loss
+    }
+
+    /// Number of buffered snapshots
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Whether no snapshot has arrived yet
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Server time of the newest snapshot
+    pub fn newest_time(&self) -> Option<f64> {
+        self.snapshots.back().map(|(time, _)| *time)
+    }
+
+    /// Add a snapshot taken at server time `time`, in seconds
+    ///
+    /// Snapshots may arrive out of order; duplicates of a buffered time are ignored.
+    pub fn push(&mut self, time: f64, state: PlayerState) {
+        let index = self.snapshots.partition_point(|(t, _)| *t < time);
+        if self.snapshots.get(index).is_some_and(|(t, _)| *t == time) {
+            return;
+        }
+        self.snapshots.insert(index, (time, state));
+        let newest = self.snapshots.back().map_or(time, |(t, _)| *t);
+        while self.snapshots.len() > 2 && self.snapshots[1].0 < newest - SNAPSHOT_HISTORY {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Add a snapshot taken at server time `time` that arrived at local time `arrival_time`
+    ///
+    /// Updates the jitter and loss measurements and, with an adaptive delay,
+    /// the interpolation delay. The two clocks do not need to be in sync.
+    pub fn receive(&mut self, time: f64, arrival_time: f64, state: PlayerState) {
+        let transit = arrival_time - time;
+        let stats = &mut self.stats;
+        match stats.last {
+            Some((last_time, last_transit)) if time > last_time => {
+                stats.jitter += ((transit - last_transit).abs() as f32 - stats.jitter) / 16.0;
+                let gap = (time - last_time) as f32;
+                if stats.interval <= 0.0 || gap < stats.interval * 1.5 {
+                    stats.interval += (gap - stats.interval) * if stats.interval > 0.0 { STATS_SMOOTHING } else { 1.0 };
+                }
+                let lost = ((gap / stats.interval).round() - 1.0).max(0.0);
+                stats.loss += (lost / (lost + 1.0) - stats.loss) * STATS_SMOOTHING;
+                stats.last = Some((time, transit));
+            }
+            Some(_) => {}
+            None => stats.last = Some((time, transit)),
+        }
+        if let Some(adaptive) = self.adaptive {
+            let target = (stats.interval * (1.0 + 10.0 * stats.loss) + adaptive.jitter_multiplier * stats.jitter)
+                .clamp(adaptive.min_delay, adaptive.max_delay);
+            let rate = if target > self.interpolation_delay { DELAY_GROW_RATE } else { DELAY_SHRINK_RATE };
+            self.interpolation_delay += (target - self.interpolation_delay) * rate;
+        }
+        self.push(time, state);
+    }
+
+    /// Drop every snapshot, e.g. when the player respawns
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.stats = SnapshotStats::default();
+    }
+
+    /// Whether `sample(time)` lies past the newest snapshot
+    pub fn extrapolating(&self, time: f64) -> bool {
+        self.newest_time().is_some_and(|newest| self.render_time(time) > newest)
+    }
+
+    /// The state to draw at `time` (server clock), or `None` before the first snapshot
+    pub fn sample(&self, time: f64) -> Option<PlayerState> {
+        let render_time = self.render_time(time);
+        let (newest_time, newest) = *self.snapshots.back()?;
+        if render_time >= newest_time {
+            let seconds = ((render_time - newest_time) as f32).min(self.max_extrapolation);
+            return Some(self.extrapolate(newest, seconds));
+        }
+        let next = self.snapshots.partition_point(|(t, _)| *t <= render_time);
+        if next == 0 {
+            return Some(self.snapshots[0].1);
+        }
+        let ((from_time, from), (to_time, to)) = (self.snapshots[next - 1], self.snapshots[next]);
+        let t = ((render_time - from_time) / (to_time - from_time)) as f32;
+        Some(PlayerState {
+            origin: from.origin + (to.origin - from.origin) * t,
+            velocity: from.velocity + (to.velocity - from.velocity) * t,
+            ..from
+        })
+    }
+
+    fn render_time(&self, time: f64) -> f64 {
+        time - self.interpolation_delay as f64
+    }
+
+    /// Move `state` forward by `seconds` without input
+    fn extrapolate(&self, mut state: PlayerState, seconds: f32) -> PlayerState {
+        if state.on_ground {
+            // Without the ground under them, a grounded player would start to fall
+            state.origin = state.origin + state.velocity * seconds;
+            return state;
+        }
+        let input = PlayerInput::new();
+        let mut remaining = seconds;
+        while remaining > 0.0 {
+            let step = remaining.min(EXTRAPOLATION_STEP);
+            player_tick(&mut state, &input, &self.config, step, None);
+            remaining -= step;
+        }
+        state
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl AdaptiveDelay {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> AdaptiveDelay {
+        AdaptiveDelay::default()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl RemotePlayer {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(config: &MovementConfig) -> RemotePlayer {
+        RemotePlayer::new(*config)
+    }
+
+    #[wasm_bindgen(js_name = setInterpolationDelay)]
+    pub fn set_interpolation_delay_wasm(&mut self, seconds: f32) {
+        self.interpolation_delay = seconds.max(0.0);
+    }
+
+    #[wasm_bindgen(js_name = setMaxExtrapolation)]
+    pub fn set_max_extrapolation_wasm(&mut self, seconds: f32) {
+        self.max_extrapolation = seconds.max(0.0);
+    }
+
+    #[wasm_bindgen(js_name = setAdaptiveDelay)]
+    pub fn set_adaptive_delay_wasm(&mut self, adaptive: &AdaptiveDelay) {
+        *self = self.clone().with_adaptive_delay(*adaptive);
+    }
+
+    #[wasm_bindgen(getter = interpolationDelay)]
+    pub fn interpolation_delay_wasm(&self) -> f32 {
+        self.interpolation_delay
+    }
+
+    #[wasm_bindgen(getter = jitter)]
+    pub fn jitter_wasm(&self) -> f32 {
+        self.stats.jitter
+    }
+
+    #[wasm_bindgen(getter = loss)]
+    pub fn loss_wasm(&self) -> f32 {
+        self.stats.loss
+    }
+
+    #[wasm_bindgen(js_name = receive)]
+    pub fn receive_wasm(&mut self, time: f64, arrival_time: f64, state: &PlayerState) {
+        self.receive(time, arrival_time, *state);
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_wasm(&mut self, time: f64, state: &PlayerState) {
+        self.push(time, *state);
+    }
+
+    #[wasm_bindgen(js_name = sample)]
+    pub fn sample_wasm(&self, time: f64) -> Option<PlayerState> {
+        self.sample(time)
+    }
+
+    #[wasm_bindgen(js_name = extrapolating)]
+    pub fn extrapolating_wasm(&self, time: f64) -> bool {
+        self.extrapolating(time)
+    }
+
+    #[wasm_bindgen(js_name = clear)]
+    pub fn clear_wasm(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vector3;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_interpolates_then_extrapolates_a_bounded_fall() {
+        let airborne = |x: f32| PlayerState {
+            origin: Vector3::new(x, 100.0, 0.0),
+            velocity: Vector3::new(320.0, 0.0, 0.0),
+            ..PlayerState::default()
+        };
+        let mut remote = RemotePlayer::new(y_up_config());
+        assert_eq!(remote.sample(0.0), None);
+        remote.push(0.05, airborne(16.0));
+        remote.push(0.0, airborne(0.0));
+
+        let halfway = remote.sample(0.125).unwrap();
+        assert!((halfway.origin - Vector3::new(8.0, 100.0, 0.0)).magnitude() < 1e-4);
+        assert!(!remote.extrapolating(0.125));
+
+        // Snapshots stop: the player keeps moving and falls, up to the extrapolation limit
+        let late = remote.sample(0.25).unwrap();
+        assert!(remote.extrapolating(0.25));
+        assert!(late.origin.x > 40.0 && late.origin.y < 100.0);
+        let limit = remote.sample(0.05 + 0.1 + DEFAULT_MAX_EXTRAPOLATION as f64).unwrap();
+        assert_eq!(remote.sample(5.0), Some(limit));
+        assert!((limit.origin.x - (16.0 + 320.0 * DEFAULT_MAX_EXTRAPOLATION)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_delay_follows_connection_quality() {
+        let mut remote = RemotePlayer::new(y_up_config()).with_adaptive_delay(AdaptiveDelay::default());
+        // 20 snapshots per second over a clean connection
+        for i in 0..200 {
+            let time = i as f64 / 20.0;
+            remote.receive(time, time + 0.03, PlayerState::default());
+        }
+        let clean = remote.interpolation_delay();
+        assert!(remote.jitter() < 1e-4 && remote.loss() == 0.0);
+        assert!((clean - 0.05).abs() < 0.005, "{clean}");
+
+        // Up to 40 ms of jitter and every fifth snapshot lost
+        for i in 200..400 {
+            let time = i as f64 / 20.0;
+            if i % 5 != 0 {
+                remote.receive(time, time + 0.03 + 0.04 * ((i * 7 % 11) as f64 / 10.0), PlayerState::default());
+            }
+        }
+        let bad = remote.interpolation_delay();
+        assert!(remote.jitter() > 0.005 && remote.loss() > 0.1);
+        assert!(bad > 2.0 * clean && bad <= AdaptiveDelay::default().max_delay, "{bad}");
+    }
+}
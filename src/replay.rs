@@ -0,0 +1,507 @@
+//! Compact replay encoding
+//!
+//! A `Replay` records the input of every tick plus a keyframe of the player's
+//! origin, velocity and ground state every `keyframe_interval` ticks, and
+//! encodes them small enough for trainer sites to keep thousands of runs:
+//! typical play at 64 ticks per second takes well under 1 KB per second.
+//!
+//! Inputs are quantized as they are recorded, in the spirit of Source's
+//! usercmd compression: move axes and lean to 1/127, yaw and pitch to 1/65536
+//! of a turn, with yaw wrapped into [-PI, PI). Hosts that feed the simulation
+//! `quantize_input(&input)` instead of the raw input get a run the decoded
+//! replay reproduces exactly. Keyframes are quantized to 1/32 unit of origin
+//! and 1/8 unit per second of velocity; they are seek and resync points for
+//! playback, not full `PlayerState`s.
+//!
+//! Encoding: a format byte, the tick rate as a little-endian `f32`, then the
+//! keyframe interval and the tick count as varints. Each tick follows as a
+//! byte flagging which input fields changed since the previous tick and only
+//! the changed values (angles as zigzag varint deltas), then every keyframe
+//! as zigzag varint deltas from the previous one and a ground byte.
+
+use std::f32::consts::TAU;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::config_format::ConfigError;
+use crate::{PlayerInput, PlayerState, Vector3};
+
+const REPLAY_FORMAT: u8 = 1;
+
+/// Steps per unit of a move axis or lean
+const AXIS_STEPS: f32 = 127.0;
+/// Radians per angle step, 1/65536 of a turn
+const ANGLE_STEP: f32 = TAU / 65536.0;
+/// World units per keyframe origin step
+const ORIGIN_STEP: f32 = 1.0 / 32.0;
+/// World units per second per keyframe velocity step
+const VELOCITY_STEP: f32 = 1.0 / 8.0;
+
+const CHANGED_FORWARD: u8 = 1 << 0;
+const CHANGED_SIDE: u8 = 1 << 1;
+const CHANGED_UP: u8 = 1 << 2;
+const CHANGED_YAW: u8 = 1 << 3;
+const CHANGED_PITCH: u8 = 1 << 4;
+const CHANGED_LEAN: u8 = 1 << 5;
+const CHANGED_BUTTONS: u8 = 1 << 6;
+const CHANGED_JUMP_PRESSES: u8 = 1 << 7;
+
+/// Recorded position of the player at the start of a tick
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayKeyframe {
+    /// Tick the keyframe was taken before, counting from 0
+    pub tick: u32,
+    pub origin: Vector3,
+    pub velocity: Vector3,
+    pub on_ground: bool,
+}
+
+/// Recorded inputs and keyframes of a run
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    tick_rate: f32,
+    keyframe_interval: u32,
+    inputs: Vec<PlayerInput>,
+    keyframes: Vec<ReplayKeyframe>,
+}
+
+impl Replay {
+    /// Create an empty replay
+    ///
+    /// # Arguments
+    /// * `tick_rate` - Simulation ticks per second
+    /// * `keyframe_interval` - Take a keyframe every N ticks (0 is treated as 1)
+    pub fn new(tick_rate: f32, keyframe_interval: u32) -> Replay {
+        Replay { tick_rate, keyframe_interval: keyframe_interval.max(1), inputs: Vec::new(), keyframes: Vec::new() }
+    }
+
+    /// Record one tick: the state before the tick and the input it is simulated with
+    pub fn record(&mut self, state: &PlayerState, input: &PlayerInput) {
+        let tick = self.inputs.len() as u32;
+        if tick.is_multiple_of(self.keyframe_interval) {
+            self.keyframes.push(ReplayKeyframe {
+                tick,
+                origin: quantize_vector(&state.origin, ORIGIN_STEP),
+                velocity: quantize_vector(&state.velocity, VELOCITY_STEP),
+                on_ground: state.on_ground,
+            });
+        }
+        self.inputs.push(quantize_input(input));
+    }
+
+    /// Ticks per second
+    pub fn tick_rate(&self) -> f32 {
+        self.tick_rate
+    }
+
+    /// Number of recorded ticks
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Whether no tick has been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Recorded length in seconds
+    pub fn duration(&self) -> f32 {
+        self.inputs.len() as f32 / self.tick_rate
+    }
+
+    /// The quantized input of every tick
+    pub fn inputs(&self) -> &[PlayerInput] {
+        &self.inputs
+    }
+
+    /// Keyframes in tick order
+    pub fn keyframes(&self) -> &[ReplayKeyframe] {
+        &self.keyframes
+    }
+
+    /// The latest keyframe at or before `tick`, to start playback from when seeking
+    pub fn keyframe_before(&self, tick: u32) -> Option<&ReplayKeyframe> {
+        self.keyframes.get((tick / self.keyframe_interval) as usize)
+    }
+
+    /// Encode the replay
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![REPLAY_FORMAT];
+        bytes.extend_from_slice(&self.tick_rate.to_le_bytes());
+        write_varint(&mut bytes, self.keyframe_interval as u64);
+        write_varint(&mut bytes, self.inputs.len() as u64);
+
+        let mut previous = PlayerInput::new();
+        for input in &self.inputs {
+            encode_input(&mut bytes, &previous, input);
+            previous = *input;
+        }
+
+        let mut previous = [0i32; 6];
+        for keyframe in &self.keyframes {
+            let steps = keyframe_steps(keyframe);
+            for (step, previous) in steps.iter().zip(&previous) {
+                write_varint(&mut bytes, zigzag(step.wrapping_sub(*previous)));
+            }
+            bytes.push(keyframe.on_ground as u8);
+            previous = steps;
+        }
+        bytes
+    }
+
+    /// Decode a replay encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ConfigError> {
+        let mut reader = Reader { bytes };
+        if reader.byte()? != REPLAY_FORMAT {
+            return Err(malformed("unknown format"));
+        }
+        let tick_rate = reader.float()?;
+        let keyframe_interval = u32::try_from(reader.varint()?).map_err(|_| malformed("keyframe interval"))?;
+        let ticks = u32::try_from(reader.varint()?).map_err(|_| malformed("tick count"))?;
+        if keyframe_interval == 0 || tick_rate.is_nan() || tick_rate <= 0.0 {
+            return Err(malformed("invalid header"));
+        }
+        let mut replay = Replay::new(tick_rate, keyframe_interval);
+
+        let mut previous = PlayerInput::new();
+        for _ in 0..ticks {
+            previous = decode_input(&mut reader, &previous)?;
+            replay.inputs.push(previous);
+        }
+
+        let mut previous = [0i32; 6];
+        for tick in (0..ticks).step_by(keyframe_interval as usize) {
+            for step in &mut previous {
+                *step = step.wrapping_add(unzigzag(reader.varint()?));
+            }
+            let [x, y, z, vx, vy, vz] = previous.map(|step| step as f32);
+            replay.keyframes.push(ReplayKeyframe {
+                tick,
+                origin: Vector3::new(x, y, z).multiply_scalar(ORIGIN_STEP),
+                velocity: Vector3::new(vx, vy, vz).multiply_scalar(VELOCITY_STEP),
+                on_ground: reader.byte()? != 0,
+            });
+        }
+        if !reader.bytes.is_empty() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(replay)
+    }
+}
+
+/// The input as a replay stores it
+pub fn quantize_input(input: &PlayerInput) -> PlayerInput {
+    PlayerInput {
+        forward_move: axis_value(axis_steps(input.forward_move)),
+        side_move: axis_value(axis_steps(input.side_move)),
+        up_move: axis_value(axis_steps(input.up_move)),
+        yaw: angle_value(angle_steps(input.yaw)),
+        pitch: angle_value(angle_steps(input.pitch)),
+        lean: axis_value(axis_steps(input.lean)),
+        ..*input
+    }
+}
+
+fn axis_steps(value: f32) -> i8 {
+    (value.clamp(-1.0, 1.0) * AXIS_STEPS).round() as i8
+}
+
+fn axis_value(steps: i8) -> f32 {
+    steps as f32 / AXIS_STEPS
+}
+
+fn angle_steps(angle: f32) -> i16 {
+    // Whole turns wrap, so `as u16` turns a rounded 65536 into 0
+    ((angle / TAU).rem_euclid(1.0) * 65536.0).round() as u32 as u16 as i16
+}
+
+fn angle_value(steps: i16) -> f32 {
+    steps as f32 * ANGLE_STEP
+}
+
+fn quantize_vector(vector: &Vector3, step: f32) -> Vector3 {
+    Vector3::new((vector.x / step).round(), (vector.y / step).round(), (vector.z / step).round()).multiply_scalar(step)
+}
+
+fn keyframe_steps(keyframe: &ReplayKeyframe) -> [i32; 6] {
+    let (o, v) = (keyframe.origin, keyframe.velocity);
+    [o.x / ORIGIN_STEP, o.y / ORIGIN_STEP, o.z / ORIGIN_STEP, v.x / VELOCITY_STEP, v.y / VELOCITY_STEP, v.z / VELOCITY_STEP]
+        .map(|steps| steps.round() as i32)
+}
+
+fn buttons(input: &PlayerInput) -> u16 {
+    [
+        input.jump,
+        input.duck,
+        input.sprint,
+        input.jet,
+        input.glide,
+        input.ski,
+        input.dodge,
+        input.ground_pound,
+        input.air_dash,
+        input.fire,
+        input.detach,
+    ]
+    .iter()
+    .enumerate()
+    .fold(0, |bits, (i, &pressed)| bits | (pressed as u16) << i)
+}
+
+fn set_buttons(input: &mut PlayerInput, bits: u16) {
+    let pressed = |i: u16| bits & (1 << i) != 0;
+    input.jump = pressed(0);
+    input.duck = pressed(1);
+    input.sprint = pressed(2);
+    input.jet = pressed(3);
+    input.glide = pressed(4);
+    input.ski = pressed(5);
+    input.dodge = pressed(6);
+    input.ground_pound = pressed(7);
+    input.air_dash = pressed(8);
+    input.fire = pressed(9);
+    input.detach = pressed(10);
+}
+
+fn encode_input(bytes: &mut Vec<u8>, previous: &PlayerInput, input: &PlayerInput) {
+    let axes = [
+        (CHANGED_FORWARD, previous.forward_move, input.forward_move),
+        (CHANGED_SIDE, previous.side_move, input.side_move),
+        (CHANGED_UP, previous.up_move, input.up_move),
+    ];
+    let yaw_delta = angle_steps(input.yaw).wrapping_sub(angle_steps(previous.yaw));
+    let pitch_delta = angle_steps(input.pitch).wrapping_sub(angle_steps(previous.pitch));
+    let buttons = buttons(input);
+
+    let mut changed = 0;
+    for (flag, old, new) in axes {
+        if old != new {
+            changed |= flag;
+        }
+    }
+    let flags = [
+        (CHANGED_YAW, yaw_delta != 0),
+        (CHANGED_PITCH, pitch_delta != 0),
+        (CHANGED_LEAN, previous.lean != input.lean),
+        (CHANGED_BUTTONS, self::buttons(previous) != buttons),
+        (CHANGED_JUMP_PRESSES, previous.jump_presses != input.jump_presses),
+    ];
+    for (flag, set) in flags {
+        if set {
+            changed |= flag;
+        }
+    }
+
+    bytes.push(changed);
+    for (flag, _, new) in axes {
+        if changed & flag != 0 {
+            bytes.push(axis_steps(new) as u8);
+        }
+    }
+    if changed & CHANGED_YAW != 0 {
+        write_varint(bytes, zigzag(yaw_delta as i32));
+    }
+    if changed & CHANGED_PITCH != 0 {
+        write_varint(bytes, zigzag(pitch_delta as i32));
+    }
+    if changed & CHANGED_LEAN != 0 {
+        bytes.push(axis_steps(input.lean) as u8);
+    }
+    if changed & CHANGED_BUTTONS != 0 {
+        write_varint(bytes, buttons as u64);
+    }
+    if changed & CHANGED_JUMP_PRESSES != 0 {
+        bytes.push(input.jump_presses);
+    }
+}
+
+fn decode_input(reader: &mut Reader, previous: &PlayerInput) -> Result<PlayerInput, ConfigError> {
+    let mut input = *previous;
+    let changed = reader.byte()?;
+    for (flag, value) in [
+        (CHANGED_FORWARD, &mut input.forward_move),
+        (CHANGED_SIDE, &mut input.side_move),
+        (CHANGED_UP, &mut input.up_move),
+    ] {
+        if changed & flag != 0 {
+            *value = axis_value(reader.byte()? as i8);
+        }
+    }
+    if changed & CHANGED_YAW != 0 {
+        input.yaw = angle_value(angle_steps(input.yaw).wrapping_add(unzigzag(reader.varint()?) as i16));
+    }
+    if changed & CHANGED_PITCH != 0 {
+        input.pitch = angle_value(angle_steps(input.pitch).wrapping_add(unzigzag(reader.varint()?) as i16));
+    }
+    if changed & CHANGED_LEAN != 0 {
+        input.lean = axis_value(reader.byte()? as i8);
+    }
+    if changed & CHANGED_BUTTONS != 0 {
+        let bits = u16::try_from(reader.varint()?).map_err(|_| malformed("buttons"))?;
+        set_buttons(&mut input, bits);
+    }
+    if changed & CHANGED_JUMP_PRESSES != 0 {
+        input.jump_presses = reader.byte()?;
+    }
+    Ok(input)
+}
+
+fn zigzag(value: i32) -> u64 {
+    ((value << 1) ^ (value >> 31)) as u32 as u64
+}
+
+fn unzigzag(value: u64) -> i32 {
+    let value = value as u32;
+    (value >> 1) as i32 ^ -((value & 1) as i32)
+}
+
+pub(crate) fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+pub(crate) fn malformed(message: &str) -> ConfigError {
+    ConfigError::MalformedReplay(message.to_string())
+}
+
+/// Cursor over the bytes of an encoded replay
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    pub(crate) fn byte(&mut self) -> Result<u8, ConfigError> {
+        let (&byte, rest) = self.bytes.split_first().ok_or_else(|| malformed("truncated"))?;
+        self.bytes = rest;
+        Ok(byte)
+    }
+
+    pub(crate) fn float(&mut self) -> Result<f32, ConfigError> {
+        Ok(f32::from_le_bytes([self.byte()?, self.byte()?, self.byte()?, self.byte()?]))
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64, ConfigError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(malformed("varint too long"))
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Replay {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(tick_rate: f32, keyframe_interval: u32) -> Replay {
+        Replay::new(tick_rate, keyframe_interval)
+    }
+
+    #[wasm_bindgen(js_name = record)]
+    pub fn record_wasm(&mut self, state: &PlayerState, input: &PlayerInput) {
+        self.record(state, input);
+    }
+
+    #[wasm_bindgen(getter = length)]
+    pub fn len_wasm(&self) -> usize {
+        self.len()
+    }
+
+    #[wasm_bindgen(getter = duration)]
+    pub fn duration_wasm(&self) -> f32 {
+        self.duration()
+    }
+
+    /// The quantized input of a tick
+    #[wasm_bindgen(js_name = input)]
+    pub fn input_wasm(&self, tick: u32) -> Option<PlayerInput> {
+        self.inputs.get(tick as usize).copied()
+    }
+
+    #[wasm_bindgen(js_name = keyframeBefore)]
+    pub fn keyframe_before_wasm(&self, tick: u32) -> Option<ReplayKeyframe> {
+        self.keyframe_before(tick).copied()
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes_wasm(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes_wasm(bytes: &[u8]) -> Result<Replay, JsValue> {
+        Replay::from_bytes(bytes).map_err(|error| crate::AirStrafeError::from(error).into())
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = quantizeInput)]
+pub fn quantize_input_wasm(input: &PlayerInput) -> PlayerInput {
+    quantize_input(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::{player_tick, MovementConfig};
+
+    #[test]
+    fn test_typical_play_stays_under_1kb_per_second() {
+        let config = MovementConfig { auto_bhop: true, ..y_up_config() };
+        let mut state = PlayerState::default();
+        let mut replay = Replay::new(64.0, 64);
+        for tick in 0..64 * 30 {
+            // Strafe left and right every half second while turning with the mouse
+            let t = tick as f32 / 64.0;
+            let side = if (tick / 32) % 2 == 0 { 1.0 } else { -1.0 };
+            let input = quantize_input(&PlayerInput {
+                forward_move: if tick < 64 { 1.0 } else { 0.0 },
+                side_move: side,
+                yaw: 40.0 * t + 0.4 * (t * std::f32::consts::PI * 2.0).sin(),
+                pitch: -0.1 + 0.02 * (t * 3.0).sin(),
+                jump: tick >= 64,
+                ..PlayerInput::new()
+            });
+            replay.record(&state, &input);
+            let ground = flat_ground(&state);
+            player_tick(&mut state, &input, &config, 1.0 / 64.0, ground);
+        }
+
+        let bytes = replay.to_bytes();
+        assert!((bytes.len() as f32 / replay.duration()) < 1024.0, "{} bytes", bytes.len());
+        let decoded = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, replay);
+        assert_eq!(decoded.keyframe_before(100).unwrap().tick, 64);
+
+        // The decoded inputs reproduce the run exactly
+        let mut replayed = PlayerState::default();
+        for input in decoded.inputs() {
+            let ground = flat_ground(&replayed);
+            player_tick(&mut replayed, input, &config, 1.0 / 64.0, ground);
+        }
+        assert_eq!(replayed, state);
+    }
+
+    #[test]
+    fn test_rejects_malformed_bytes() {
+        let mut replay = Replay::new(64.0, 8);
+        for _ in 0..20 {
+            replay.record(&PlayerState::default(), &PlayerInput { jump: true, yaw: 1.0, ..PlayerInput::new() });
+        }
+        let bytes = replay.to_bytes();
+        assert!(matches!(Replay::from_bytes(&bytes[..bytes.len() - 1]), Err(ConfigError::MalformedReplay(_))));
+        assert!(matches!(Replay::from_bytes(&[bytes.as_slice(), &[0]].concat()), Err(ConfigError::MalformedReplay(_))));
+        assert!(matches!(Replay::from_bytes(&[2]), Err(ConfigError::MalformedReplay(_))));
+    }
+}
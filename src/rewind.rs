@@ -0,0 +1,220 @@
+//! Practice rewind buffer
+//!
+//! Periodically snapshots a player's full `PlayerState` so practice tools can
+//! jump the player back in time (e.g. to retry a surf ramp or a bhop section).
+
+use std::collections::VecDeque;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::checked::check_tick_rate;
+use crate::error::AirStrafeError;
+use crate::PlayerState;
+
+/// Shortest rewind the buffer accepts, in seconds
+pub const MIN_REWIND_SECONDS: f32 = 1.0;
+/// Longest rewind the buffer keeps history for, in seconds
+pub const MAX_REWIND_SECONDS: f32 = 30.0;
+
+/// Ring buffer of periodic `PlayerState` snapshots
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct RewindBuffer {
+    tick_rate: f32,
+    interval_ticks: u32,
+    capacity: usize,
+    current_tick: u64,
+    snapshots: VecDeque<(u64, PlayerState)>,
+}
+
+impl RewindBuffer {
+    /// Create a rewind buffer
+    ///
+    /// # Arguments
+    /// * `tick_rate` - Simulation ticks per second
+    /// * `interval_ticks` - Take a snapshot every N recorded ticks (0 is treated as 1)
+    ///
+    /// # Returns
+    /// The buffer, or `MovementError::InvalidTickRate` if `tick_rate` is not
+    /// positive and finite
+    pub fn new(tick_rate: f32, interval_ticks: u32) -> Result<RewindBuffer, AirStrafeError> {
+        check_tick_rate(tick_rate)?;
+        let interval_ticks = interval_ticks.max(1);
+        let history_ticks = (MAX_REWIND_SECONDS * tick_rate).ceil() as usize;
+        // One extra snapshot so a full 30 second rewind always has a target
+        let capacity = history_ticks / interval_ticks as usize + 1;
+
+        Ok(RewindBuffer {
+            tick_rate,
+            interval_ticks,
+            capacity,
+            current_tick: 0,
+            snapshots: VecDeque::with_capacity(capacity),
+        })
+    }
+
+    /// Number of ticks recorded since creation (rewinds move this backwards)
+    pub fn current_tick(&self) -> u64 {
+        self.current_tick
+    }
+
+    /// Number of snapshots currently held
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Whether no snapshot has been taken yet
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Seconds of history currently available to rewind into
+    pub fn available_seconds(&self) -> f32 {
+        match self.snapshots.front() {
+            Some((tick, _)) => (self.current_tick - tick) as f32 / self.tick_rate,
+            None => 0.0,
+        }
+    }
+
+    /// Drop all snapshots (e.g. when the player respawns)
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Record one simulated tick, snapshotting the state every `interval_ticks`
+    ///
+    /// Call this once per tick after the player has been simulated.
+    pub fn record(&mut self, state: &PlayerState) {
+        if self.current_tick.is_multiple_of(self.interval_ticks as u64) {
+            if self.snapshots.len() == self.capacity {
+                self.snapshots.pop_front();
+            }
+            self.snapshots.push_back((self.current_tick, *state));
+        }
+        self.current_tick += 1;
+    }
+
+    /// Move the player back in time
+    ///
+    /// Restores the newest snapshot that is at least `seconds` old (clamped to
+    /// 1–30 seconds). If the history is shorter than requested, the oldest
+    /// snapshot is used. Snapshots newer than the restored one are discarded so
+    /// recording continues seamlessly from the restored point.
+    ///
+    /// # Arguments
+    /// * `state` - The player state to overwrite
+    /// * `seconds` - How far to rewind
+    ///
+    /// # Returns
+    /// The number of seconds actually rewound, or `None` if there is no history
+    pub fn rewind(&mut self, state: &mut PlayerState, seconds: f32) -> Option<f32> {
+        let seconds = seconds.clamp(MIN_REWIND_SECONDS, MAX_REWIND_SECONDS);
+        let back_ticks = (seconds * self.tick_rate).round() as u64;
+        let target_tick = self.current_tick.saturating_sub(back_ticks);
+
+        let index = self
+            .snapshots
+            .iter()
+            .rposition(|(tick, _)| *tick <= target_tick)
+            .or(if self.snapshots.is_empty() { None } else { Some(0) })?;
+
+        let (tick, snapshot) = self.snapshots[index];
+        let rewound = (self.current_tick - tick) as f32 / self.tick_rate;
+
+        *state = snapshot;
+        // The restored snapshot itself is re-taken by the next `record` call
+        self.snapshots.truncate(index);
+        self.current_tick = tick;
+        Some(rewound)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl RewindBuffer {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(tick_rate: f32, interval_ticks: u32) -> Result<RewindBuffer, JsValue> {
+        RewindBuffer::new(tick_rate, interval_ticks).map_err(JsValue::from)
+    }
+
+    #[wasm_bindgen(js_name = record)]
+    pub fn record_wasm(&mut self, state: &PlayerState) {
+        self.record(state);
+    }
+
+    #[wasm_bindgen(js_name = rewind)]
+    pub fn rewind_wasm(&mut self, state: &mut PlayerState, seconds: f32) -> Option<f32> {
+        self.rewind(state, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MovementError, Vector3};
+
+    fn state_at(tick: u32) -> PlayerState {
+        let mut state = PlayerState::new(Vector3::new(tick as f32, 0.0, 0.0));
+        state.velocity = Vector3::new(0.0, 0.0, tick as f32);
+        state.ducked = tick.is_multiple_of(2);
+        state.time_in_air = tick as f32 * 0.01;
+        state
+    }
+
+    #[test]
+    fn test_rewind_restores_full_state() {
+        let mut buffer = RewindBuffer::new(100.0, 10).unwrap();
+        for tick in 0..500 {
+            buffer.record(&state_at(tick));
+        }
+
+        let mut state = state_at(499);
+        let rewound = buffer.rewind(&mut state, 2.0).unwrap();
+
+        // 2 seconds at 100 Hz from tick 500 is tick 300
+        assert!((rewound - 2.0).abs() < 0.001);
+        assert_eq!(state, state_at(300));
+        assert_eq!(buffer.current_tick(), 300);
+    }
+
+    #[test]
+    fn test_rewind_clamps_to_available_history() {
+        let mut buffer = RewindBuffer::new(10.0, 5).unwrap();
+        for tick in 0..20 {
+            buffer.record(&state_at(tick));
+        }
+
+        let mut state = state_at(19);
+        let rewound = buffer.rewind(&mut state, 30.0).unwrap();
+        assert_eq!(state, state_at(0));
+        assert!((rewound - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut buffer = RewindBuffer::new(64.0, 8).unwrap();
+        for tick in 0..64 * 60 {
+            buffer.record(&state_at(tick));
+        }
+        assert!(buffer.available_seconds() <= MAX_REWIND_SECONDS + 8.0 / 64.0 + 0.001);
+        assert!(buffer.available_seconds() >= MAX_REWIND_SECONDS);
+    }
+
+    #[test]
+    fn test_invalid_tick_rates_are_rejected() {
+        for tick_rate in [0.0, -64.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                RewindBuffer::new(tick_rate, 8),
+                Err(AirStrafeError::InvalidInput(MovementError::InvalidTickRate(_)))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rewind_without_history() {
+        let mut buffer = RewindBuffer::new(64.0, 1).unwrap();
+        let mut state = PlayerState::default();
+        assert!(buffer.rewind(&mut state, 5.0).is_none());
+    }
+}
@@ -0,0 +1,157 @@
+//! Rope swinging
+//!
+//! A `RopeSwing` is a fixed-length rope from an anchor to the player origin.
+//! The host runs `player_tick` as usual and then calls `constrain`: whenever
+//! the rope is taut, the origin is pulled back onto the rope's sphere and the
+//! outward part of the velocity is removed, leaving only the tangential part.
+//! Gravity and air strafing then swing the player like a pendulum, gaining
+//! speed on the way down. Unlike a grapple, the rope never reels in.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{PlayerState, Vector3};
+
+/// A fixed-length rope attached to the player origin
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RopeSwing {
+    anchor: Vector3,
+    length: f32,
+}
+
+impl RopeSwing {
+    /// Create a rope of `length` world units hanging from `anchor`
+    pub fn new(anchor: Vector3, length: f32) -> RopeSwing {
+        RopeSwing { anchor, length: length.max(0.0) }
+    }
+
+    /// Attach a rope that is exactly taut at the player's current origin
+    pub fn attach(anchor: Vector3, state: &PlayerState) -> RopeSwing {
+        RopeSwing::new(anchor, (state.origin - anchor).magnitude())
+    }
+
+    /// The point the rope hangs from
+    pub fn anchor(&self) -> Vector3 {
+        self.anchor
+    }
+
+    /// The rope length in world units
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Whether the player is at (or past) the end of the rope
+    pub fn is_taut(&self, state: &PlayerState) -> bool {
+        (state.origin - self.anchor).magnitude() >= self.length
+    }
+
+    /// Keep the player within the rope's reach
+    ///
+    /// # Returns
+    /// Whether the rope was taut and constrained the player
+    pub fn constrain(&self, state: &mut PlayerState) -> bool {
+        let offset = state.origin - self.anchor;
+        let distance = offset.magnitude();
+        if distance < self.length || distance == 0.0 {
+            return false;
+        }
+        let radial = offset.multiply_scalar(1.0 / distance);
+        state.origin = self.anchor;
+        state.origin.add(&radial.multiply_scalar(self.length));
+
+        let outward = state.velocity.dot(&radial);
+        if outward > 0.0 {
+            state.velocity = state.velocity - radial.multiply_scalar(outward);
+        }
+        state.on_ground = false;
+        true
+    }
+
+    /// Let go of the rope, keeping only the tangential velocity if it is taut
+    ///
+    /// The player leaves along the arc they were swinging on.
+    pub fn release(&self, state: &mut PlayerState) {
+        let offset = state.origin - self.anchor;
+        if self.is_taut(state) && offset.length_sq() > 0.0 {
+            state.velocity = state.velocity.project_on_plane(&offset.normalized());
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl RopeSwing {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(anchor: &Vector3, length: f32) -> RopeSwing {
+        RopeSwing::new(*anchor, length)
+    }
+
+    #[wasm_bindgen(js_name = attach)]
+    pub fn attach_wasm(anchor: &Vector3, state: &PlayerState) -> RopeSwing {
+        RopeSwing::attach(*anchor, state)
+    }
+
+    #[wasm_bindgen(getter = anchor)]
+    pub fn anchor_wasm(&self) -> Vector3 {
+        self.anchor
+    }
+
+    #[wasm_bindgen(getter = length)]
+    pub fn length_wasm(&self) -> f32 {
+        self.length
+    }
+
+    #[wasm_bindgen(js_name = constrain)]
+    pub fn constrain_wasm(&self, state: &mut PlayerState) -> bool {
+        self.constrain(state)
+    }
+
+    #[wasm_bindgen(js_name = release)]
+    pub fn release_wasm(&self, state: &mut PlayerState) {
+        self.release(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_pendulum_swings_through_the_bottom() {
+        let config = y_up_config();
+        let anchor = Vector3::new(0.0, 500.0, 0.0);
+        // Start level with the anchor, rope horizontal
+        let mut state = PlayerState::new(Vector3::new(-200.0, 500.0, 0.0));
+        let rope = RopeSwing::attach(anchor, &state);
+        let dt = 1.0 / 128.0;
+
+        let mut max_speed: f32 = 0.0;
+        for _ in 0..128 {
+            player_tick(&mut state, &PlayerInput::new(), &config, dt, None);
+            rope.constrain(&mut state);
+            assert!((state.origin - anchor).magnitude() <= rope.length() + 0.001);
+            max_speed = max_speed.max(state.velocity.magnitude());
+        }
+        // Energy conservation gives sqrt(2 g L) at the bottom, minus integration losses
+        let ideal = (2.0 * config.gravity * 200.0f32).sqrt();
+        assert!(max_speed > ideal * 0.9 && max_speed <= ideal * 1.01);
+        assert!(state.origin.x > 0.0);
+    }
+
+    #[test]
+    fn test_release_keeps_tangential_velocity() {
+        let rope = RopeSwing::new(Vector3::new(0.0, 100.0, 0.0), 100.0);
+        let mut state = PlayerState { velocity: Vector3::new(300.0, -50.0, 0.0), ..PlayerState::default() };
+        rope.release(&mut state);
+        assert_eq!(state.velocity, Vector3::new(300.0, 0.0, 0.0));
+
+        // A slack rope lets go without touching the velocity
+        let mut slack = PlayerState { velocity: Vector3::new(0.0, -50.0, 0.0), ..PlayerState::new(Vector3::new(0.0, 50.0, 0.0)) };
+        assert!(!rope.constrain(&mut slack));
+        rope.release(&mut slack);
+        assert_eq!(slack.velocity, Vector3::new(0.0, -50.0, 0.0));
+    }
+}
@@ -0,0 +1,321 @@
+//! Verifiable run submissions
+//!
+//! A leaderboard cannot trust a time a client reports. A `RunBundle` packs a
+//! finished run as everything needed to check it: a hash of the config and
+//! one of the course zones it was played with, the spawn point, the replay of
+//! every input from the spawn on, and the claimed `RunResult`. The server
+//! calls `verify` with its own config, course and tick rate; the run is
+//! simulated again from the spawn, which has to lie in the start zone, with
+//! the run's teleports to checkpoints repeated on the ticks they happened, and
+//! only accepted if it finishes with exactly the claimed result.
+//!
+//! The simulation is bit-for-bit deterministic, but only for the same
+//! inputs: the client has to simulate with `quantize_input(&input)`, as the
+//! replay stores it. The crate does not know the map, so `verify` takes the
+//! down ray hit for each tick from the server's own trace of its copy of it,
+//! which also keeps a client from bringing its own map.
+//!
+//! Encoding: a format byte, the config and zone hashes as little-endian
+//! `u64`s, the spawn as three `f32`s, the length of the encoded `RunResult`
+//! as a varint, the result and then the encoded `Replay`.
+
+use std::fmt;
+
+use crate::config_format::{ConfigError, ConfigValue};
+use crate::determinism::{fnv1a, FNV_OFFSET};
+use crate::replay::{malformed, write_varint, Reader};
+use crate::{
+    player_tick_into, Course, CourseTimer, MovementConfig, PlayerState, RayCollisionHit, Replay, RunResult, Vector3,
+};
+
+const RUN_BUNDLE_FORMAT: u8 = 1;
+
+/// Hash of every field of a config, to tell whether two configs are exactly the same
+pub fn config_hash(config: &MovementConfig) -> u64 {
+    MovementConfig::FIELD_NAMES.iter().fold(FNV_OFFSET, |hash, &field| {
+        let hash = fnv1a(hash, field.as_bytes());
+        match config.get(field) {
+            Some(ConfigValue::Number(number)) => fnv1a(hash, &number.to_bits().to_le_bytes()),
+            Some(ConfigValue::Bool(flag)) => fnv1a(hash, &[flag as u8]),
+            Some(ConfigValue::Text(text)) => fnv1a(hash, text.as_bytes()),
+            None => hash,
+        }
+    })
+}
+
+/// Why `RunBundle::verify` rejected a run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunRejection {
+    /// The run was played with a different config
+    ConfigMismatch,
+    /// The run was played on a course with different zones
+    ZoneMismatch,
+    /// The run was simulated at a different tick rate
+    TickRateMismatch,
+    /// The run did not spawn in the start zone
+    SpawnOutsideStart,
+    /// The inputs do not finish the course
+    NotFinished,
+    /// The inputs finish the course with a different result than claimed
+    ResultMismatch { claimed: f32, simulated: f32 },
+}
+
+impl fmt::Display for RunRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunRejection::ConfigMismatch => write!(f, "run was played with a different config"),
+            RunRejection::ZoneMismatch => write!(f, "run was played on different zones"),
+            RunRejection::TickRateMismatch => write!(f, "run was simulated at a different tick rate"),
+            RunRejection::SpawnOutsideStart => write!(f, "run did not spawn in the start zone"),
+            RunRejection::NotFinished => write!(f, "inputs do not finish the course"),
+            RunRejection::ResultMismatch { claimed, simulated } => {
+                write!(f, "inputs finish in {simulated} seconds, claimed {claimed}")
+            }
+        }
+    }
+}
+
+/// A finished run packaged for verification
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunBundle {
+    config_hash: u64,
+    zone_hash: u64,
+    spawn: Vector3,
+    result: RunResult,
+    replay: Replay,
+}
+
+impl RunBundle {
+    /// Package a run
+    ///
+    /// # Arguments
+    /// * `config` - The config the run was simulated with
+    /// * `course` - The course the run was timed on
+    /// * `spawn` - Origin of the `PlayerState::new` the replay starts from
+    /// * `replay` - Every tick from the spawn on, at least up to the run's end
+    /// * `result` - The result the `CourseTimer` reported
+    pub fn new(
+        config: &MovementConfig,
+        course: &Course,
+        spawn: Vector3,
+        replay: Replay,
+        result: RunResult,
+    ) -> RunBundle {
+        RunBundle { config_hash: config_hash(config), zone_hash: course.hash(), spawn, result, replay }
+    }
+
+    /// Hash of the config the run claims to be played with
+    pub fn config_hash(&self) -> u64 {
+        self.config_hash
+    }
+
+    /// Hash of the course the run claims to be played on
+    pub fn zone_hash(&self) -> u64 {
+        self.zone_hash
+    }
+
+    /// The claimed result
+    pub fn result(&self) -> &RunResult {
+        &self.result
+    }
+
+    /// The recorded inputs
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Simulate the run again and check it against the claimed result
+    ///
+    /// # Arguments
+    /// * `config` - The server's config
+    /// * `course` - The server's course
+    /// * `tick_rate` - The server's ticks per second
+    /// * `trace_down` - The down ray hit for the state before each tick
+    ///
+    /// # Returns
+    /// The verified result, or why the run was rejected
+    pub fn verify(
+        &self,
+        config: &MovementConfig,
+        course: &Course,
+        tick_rate: f32,
+        mut trace_down: impl FnMut(&PlayerState) -> Option<RayCollisionHit>,
+    ) -> Result<RunResult, RunRejection> {
+        if config_hash(config) != self.config_hash {
+            return Err(RunRejection::ConfigMismatch);
+        }
+        if course.hash() != self.zone_hash {
+            return Err(RunRejection::ZoneMismatch);
+        }
+        if self.replay.tick_rate() != tick_rate {
+            return Err(RunRejection::TickRateMismatch);
+        }
+        if !course.start().contains(&self.spawn) {
+            return Err(RunRejection::SpawnOutsideStart);
+        }
+        let delta_time = 1.0 / tick_rate;
+        let mut state = PlayerState::new(self.spawn);
+        let mut timer = CourseTimer::new(course.clone(), config.up_axis);
+        timer.place(state.origin);
+        let mut events = Vec::new();
+        let mut teleports = self.result.teleport_ticks().iter().peekable();
+        for (tick, input) in self.replay.inputs().iter().enumerate() {
+            while teleports.next_if(|&&teleport| teleport as usize == tick).is_some() {
+                timer.teleport_to_checkpoint(&mut state);
+            }
+            let down_ray_hit = trace_down(&state);
+            events.clear();
+            player_tick_into(&mut state, input, config, delta_time, down_ray_hit, &mut events);
+            if let Some(result) = timer.update(&mut state, delta_time) {
+                return if result == self.result {
+                    Ok(result)
+                } else {
+                    Err(RunRejection::ResultMismatch { claimed: self.result.time(), simulated: result.time() })
+                };
+            }
+        }
+        Err(RunRejection::NotFinished)
+    }
+
+    /// Encode the bundle
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![RUN_BUNDLE_FORMAT];
+        bytes.extend_from_slice(&self.config_hash.to_le_bytes());
+        bytes.extend_from_slice(&self.zone_hash.to_le_bytes());
+        for coordinate in [self.spawn.x, self.spawn.y, self.spawn.z] {
+            bytes.extend_from_slice(&coordinate.to_le_bytes());
+        }
+        let result = self.result.to_bytes();
+        write_varint(&mut bytes, result.len() as u64);
+        bytes.extend_from_slice(&result);
+        bytes.extend_from_slice(&self.replay.to_bytes());
+        bytes
+    }
+
+    /// Decode a bundle encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<RunBundle, ConfigError> {
+        let mut reader = Reader { bytes };
+        if reader.byte()? != RUN_BUNDLE_FORMAT {
+            return Err(malformed("unknown format"));
+        }
+        let config_hash = read_u64(&mut reader)?;
+        let zone_hash = read_u64(&mut reader)?;
+        let spawn = Vector3::new(reader.float()?, reader.float()?, reader.float()?);
+        let length = reader.varint()?;
+        if length > reader.bytes.len() as u64 {
+            return Err(malformed("truncated"));
+        }
+        let (result, replay) = reader.bytes.split_at(length as usize);
+        Ok(RunBundle {
+            config_hash,
+            zone_hash,
+            spawn,
+            result: RunResult::from_bytes(result)?,
+            replay: Replay::from_bytes(replay)?,
+        })
+    }
+}
+
+fn read_u64(reader: &mut Reader) -> Result<u64, ConfigError> {
+    let mut bytes = [0; 8];
+    for byte in &mut bytes {
+        *byte = reader.byte()?;
+    }
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::{player_tick, quantize_input, PlayerInput, TriggerShape, UpAxis};
+
+    #[test]
+    fn test_verifier_resimulates_runs() {
+        let zone = |from_x: f32, to_x: f32| {
+            TriggerShape::aabb(Vector3::new(from_x, -100.0, -1000.0), Vector3::new(to_x, 100.0, 1000.0))
+        };
+        let course = Course::new(zone(-50.0, 10.0), zone(400.0, 500.0)).with_checkpoint(zone(200.0, 220.0));
+        let config = y_up_config();
+
+        // Run along the ground, turning a little, as a client would record it
+        let record = |spawn: Vector3, teleport_at: Option<u32>| {
+            let mut state = PlayerState::new(spawn);
+            let mut timer = CourseTimer::new(course.clone(), UpAxis::Y);
+            timer.place(spawn);
+            let mut replay = Replay::new(64.0, 64);
+            for tick in 0..640 {
+                let yaw = -1.5 + tick as f32 * 0.001;
+                let input = quantize_input(&PlayerInput { forward_move: 1.0, yaw, ..PlayerInput::new() });
+                replay.record(&state, &input);
+                let ground = flat_ground(&state);
+                player_tick(&mut state, &input, &config, 1.0 / 64.0, ground);
+                if let Some(result) = timer.update(&mut state, 1.0 / 64.0) {
+                    return RunBundle::new(&config, &course, spawn, replay, result);
+                }
+                if teleport_at == Some(tick) {
+                    assert!(timer.teleport_to_checkpoint(&mut state));
+                }
+            }
+            panic!("the run did not finish");
+        };
+        let spawn = Vector3::new(0.0, 0.0, 0.0);
+        let bundle = RunBundle::from_bytes(&record(spawn, None).to_bytes()).unwrap();
+        let result = bundle.result().clone();
+        assert_eq!(bundle.verify(&config, &course, 64.0, flat_ground), Ok(result.clone()));
+
+        // Teleports back to the checkpoint are repeated
+        let teleported = record(spawn, Some(60));
+        assert_eq!(teleported.result().teleports(), 1);
+        assert!(teleported.result().time() > result.time());
+        assert_eq!(teleported.verify(&config, &course, 64.0, flat_ground), Ok(teleported.result().clone()));
+
+        // Tampered times, other configs, zones or tick rates, spawns past the start and cut inputs are rejected
+        let mut faster = bundle.clone();
+        faster.result.time -= 0.1;
+        assert!(matches!(faster.verify(&config, &course, 64.0, flat_ground), Err(RunRejection::ResultMismatch { .. })));
+        let tuned = MovementConfig { max_speed: config.max_speed + 1.0, ..config };
+        assert_eq!(bundle.verify(&tuned, &course, 64.0, flat_ground), Err(RunRejection::ConfigMismatch));
+        let moved = course.clone().with_checkpoint(zone(300.0, 310.0));
+        assert_eq!(bundle.verify(&config, &moved, 64.0, flat_ground), Err(RunRejection::ZoneMismatch));
+        assert_eq!(bundle.verify(&config, &course, 128.0, flat_ground), Err(RunRejection::TickRateMismatch));
+        let ahead = RunBundle { spawn: Vector3::new(150.0, 0.0, 0.0), ..bundle.clone() };
+        assert_eq!(ahead.verify(&config, &course, 64.0, flat_ground), Err(RunRejection::SpawnOutsideStart));
+        let mut cut = Replay::new(64.0, 64);
+        for input in &bundle.replay.inputs()[..result.end_tick() as usize] {
+            cut.record(&PlayerState::default(), input);
+        }
+        let cut = RunBundle { replay: cut, ..bundle };
+        assert_eq!(cut.verify(&config, &course, 64.0, flat_ground), Err(RunRejection::NotFinished));
+    }
+    #[test]
+    fn test_config_hash_covers_every_kind_of_field() {
+        let config = y_up_config();
+        assert_eq!(config_hash(&config), config_hash(&y_up_config()));
+        assert_ne!(config_hash(&config), config_hash(&MovementConfig { overbounce: !config.overbounce, ..config }));
+        assert_ne!(config_hash(&config), config_hash(&MovementConfig { up_axis: UpAxis::Z, ..config }));
+        let frictionless = MovementConfig { friction: 0.0, ..config };
+        assert_ne!(config_hash(&frictionless), config_hash(&MovementConfig { friction: -0.0, ..config }));
+    }
+
+    #[test]
+    fn test_malformed_bundles_are_rejected() {
+        let zone = |x: f32| {
+            TriggerShape::aabb(Vector3::new(x - 10.0, -10.0, -10.0), Vector3::new(x + 10.0, 10.0, 10.0))
+        };
+        let course = Course::new(zone(0.0), zone(100.0));
+        let mut replay = Replay::new(64.0, 64);
+        replay.record(&PlayerState::default(), &PlayerInput::new());
+        let result = RunResult::default();
+        let bytes = RunBundle::new(&y_up_config(), &course, Vector3::new(0.0, 0.0, 0.0), replay, result).to_bytes();
+        assert!(RunBundle::from_bytes(&bytes).is_ok());
+
+        assert!(RunBundle::from_bytes(&[]).is_err());
+        let mut unknown = bytes.clone();
+        unknown[0] = RUN_BUNDLE_FORMAT + 1;
+        assert!(RunBundle::from_bytes(&unknown).is_err());
+        for length in [1, 9, 17, 29, bytes.len() - 1] {
+            assert!(RunBundle::from_bytes(&bytes[..length]).is_err(), "{length}");
+        }
+    }
+}
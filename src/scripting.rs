@@ -0,0 +1,346 @@
+//! Scripted movement modifiers (`rhai` feature)
+//!
+//! Server admins often want small movement tweaks (a custom knockback
+//! formula, a zone effect) without rebuilding the server. A `ScriptModifier`
+//! compiles a rhai script and runs its functions as the matching
+//! `MovementModifier` stages, so it registers like any other modifier with
+//! `MovementModifiers::push`. A script defines any of these functions; each
+//! gets the tick's `ctx` and returns the new value:
+//!
+//! * `modify_wish(ctx, wish_velocity)` - wish direction times wish speed
+//! * `external_acceleration(ctx)` and `base_velocity(ctx)`
+//! * `modify_accelerate(ctx, accelerate)` and `modify_friction(ctx, friction)`
+//! * `modify_velocity(ctx, velocity)`
+//!
+//! `ctx` exposes the player state (`origin`, `velocity`, `on_ground`,
+//! `ducked`, `tick`), the input (`forward_move`, `side_move`, `up_move`,
+//! `yaw`, `pitch`, `jump`, `duck`), `delta_time` and config fields by name
+//! through `ctx.config("gravity")`. Vectors are built with `vec3(x, y, z)` and
+//! support `+`, `-`, `*` with a number, `length()`, `normalized()` and `dot()`.
+//!
+//! Scripts are sandboxed: they cannot import modules, read the clock or print,
+//! and every call is limited in operations, call depth and data size. Floats
+//! are `f32` like the rest of the simulation, so scripted ticks stay
+//! deterministic. A hook that fails (e.g. by running out of operations) leaves
+//! its value unchanged and keeps an `AirStrafeError::ScriptFailed` for
+//! `take_error`; the script still runs at its next hook.
+
+use std::any::Any;
+
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST};
+
+use crate::config_format::ConfigValue;
+use crate::error::AirStrafeError;
+use crate::modifiers::{ModifierContext, MovementModifier};
+use crate::{MovementConfig, PlayerInput, PlayerState, Vector3};
+
+/// Operations a single hook call may run before it is stopped
+pub const DEFAULT_MAX_OPERATIONS: u64 = 10_000;
+
+/// The hooks a script can define, with their parameter counts
+const HOOKS: [(&str, usize); 6] = [
+    ("modify_wish", 2),
+    ("external_acceleration", 1),
+    ("base_velocity", 1),
+    ("modify_accelerate", 2),
+    ("modify_friction", 2),
+    ("modify_velocity", 2),
+];
+
+/// The `ctx` argument of every hook
+#[derive(Debug, Clone)]
+struct ScriptContext {
+    state: PlayerState,
+    input: PlayerInput,
+    config: MovementConfig,
+    delta_time: f32,
+}
+
+impl ScriptContext {
+    fn new(ctx: &ModifierContext) -> ScriptContext {
+        ScriptContext { state: ctx.state, input: *ctx.input, config: *ctx.config, delta_time: ctx.delta_time }
+    }
+
+    fn config(&mut self, name: &str) -> Dynamic {
+        match self.config.get(name) {
+            Some(ConfigValue::Number(number)) => number.into(),
+            Some(ConfigValue::Bool(flag)) => flag.into(),
+            Some(ConfigValue::Text(text)) => text.into(),
+            None => Dynamic::UNIT,
+        }
+    }
+}
+
+/// A movement modifier running the hooks of a rhai script
+pub struct ScriptModifier {
+    engine: Engine,
+    ast: AST,
+    hooks: Vec<&'static str>,
+    error: Option<AirStrafeError>,
+}
+
+impl ScriptModifier {
+    /// Compile a script
+    ///
+    /// # Returns
+    /// The modifier, or `AirStrafeError::ScriptFailed` if the script does not
+    /// compile or defines a hook with the wrong number of parameters
+    pub fn new(source: &str) -> Result<ScriptModifier, AirStrafeError> {
+        let engine = sandboxed_engine();
+        let ast = engine.compile(source).map_err(|error| AirStrafeError::ScriptFailed(error.to_string()))?;
+        let mut hooks = Vec::new();
+        for function in ast.iter_functions() {
+            if let Some(&(hook, params)) = HOOKS.iter().find(|(hook, _)| *hook == function.name) {
+                if function.params.len() != params {
+                    let message = format!("`{hook}` must take {params} parameters");
+                    return Err(AirStrafeError::ScriptFailed(message));
+                }
+                hooks.push(hook);
+            }
+        }
+        Ok(ScriptModifier { engine, ast, hooks, error: None })
+    }
+
+    /// Limit the operations a single hook call may run
+    pub fn with_max_operations(mut self, max_operations: u64) -> ScriptModifier {
+        self.engine.set_max_operations(max_operations);
+        self
+    }
+
+    /// Whether the script defines a hook, e.g. `"modify_velocity"`
+    pub fn defines(&self, hook: &str) -> bool {
+        self.hooks.contains(&hook)
+    }
+
+    /// The `AirStrafeError::ScriptFailed` of the last hook that failed since the previous call
+    pub fn take_error(&mut self) -> Option<AirStrafeError> {
+        self.error.take()
+    }
+
+    /// Run a hook, or `None` if the script does not define it or it failed
+    fn call<T: Any + Clone>(&mut self, hook: &'static str, args: impl FuncArgs) -> Option<T> {
+        if !self.defines(hook) {
+            return None;
+        }
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, hook, args);
+        let failure = match result {
+            Ok(value) => match value.try_cast::<T>() {
+                Some(value) => return Some(value),
+                None => format!("`{hook}` returned a value of the wrong type"),
+            },
+            Err(error) => format!("`{hook}` failed: {error}"),
+        };
+        self.error = Some(AirStrafeError::ScriptFailed(failure));
+        None
+    }
+}
+
+impl MovementModifier for ScriptModifier {
+    fn modify_wish(&mut self, ctx: &ModifierContext, wish_dir: &mut Vector3, wish_speed: &mut f32) {
+        let wish_velocity = wish_dir.multiply_scalar(*wish_speed);
+        if let Some(wish_velocity) = self.call::<Vector3>("modify_wish", (ScriptContext::new(ctx), wish_velocity)) {
+            *wish_speed = wish_velocity.length();
+            if *wish_speed > 0.0 {
+                *wish_dir = wish_velocity.normalized();
+            }
+        }
+    }
+
+    fn external_acceleration(&mut self, ctx: &ModifierContext) -> Vector3 {
+        let acceleration = self.call("external_acceleration", (ScriptContext::new(ctx),));
+        acceleration.unwrap_or(Vector3::new(0.0, 0.0, 0.0))
+    }
+
+    fn base_velocity(&mut self, ctx: &ModifierContext) -> Vector3 {
+        let base_velocity = self.call("base_velocity", (ScriptContext::new(ctx),));
+        base_velocity.unwrap_or(Vector3::new(0.0, 0.0, 0.0))
+    }
+
+    fn modify_accelerate(&mut self, ctx: &ModifierContext, accelerate: &mut f32) {
+        if let Some(modified) = self.call("modify_accelerate", (ScriptContext::new(ctx), *accelerate)) {
+            *accelerate = modified;
+        }
+    }
+
+    fn modify_friction(&mut self, ctx: &ModifierContext, friction: &mut f32) {
+        if let Some(modified) = self.call("modify_friction", (ScriptContext::new(ctx), *friction)) {
+            *friction = modified;
+        }
+    }
+
+    fn modify_velocity(&mut self, ctx: &ModifierContext, velocity: &mut Vector3) {
+        if let Some(modified) = self.call("modify_velocity", (ScriptContext::new(ctx), *velocity)) {
+            *velocity = modified;
+        }
+    }
+}
+
+/// An engine without modules, clock or output, limited per call, with the movement types registered
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(DEFAULT_MAX_OPERATIONS);
+    engine.set_max_call_levels(16);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1024);
+    engine.set_max_array_size(1024);
+    engine.set_max_map_size(256);
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+
+    engine
+        .register_type_with_name::<Vector3>("Vector3")
+        .register_fn("vec3", Vector3::new)
+        .register_get_set("x", |v: &mut Vector3| v.x, |v: &mut Vector3, x: f32| v.x = x)
+        .register_get_set("y", |v: &mut Vector3| v.y, |v: &mut Vector3, y: f32| v.y = y)
+        .register_get_set("z", |v: &mut Vector3| v.z, |v: &mut Vector3, z: f32| v.z = z)
+        .register_fn("+", |a: Vector3, b: Vector3| a + b)
+        .register_fn("-", |a: Vector3, b: Vector3| a - b)
+        .register_fn("-", |v: Vector3| -v)
+        .register_fn("*", |v: Vector3, scale: f32| v * scale)
+        .register_fn("*", |scale: f32, v: Vector3| v * scale)
+        .register_fn("length", |v: &mut Vector3| v.length())
+        .register_fn("normalized", |v: &mut Vector3| v.normalized())
+        .register_fn("dot", |a: &mut Vector3, b: Vector3| a.dot(&b));
+
+    engine
+        .register_type_with_name::<ScriptContext>("Context")
+        .register_get("origin", |ctx: &mut ScriptContext| ctx.state.origin)
+        .register_get("velocity", |ctx: &mut ScriptContext| ctx.state.velocity)
+        .register_get("on_ground", |ctx: &mut ScriptContext| ctx.state.on_ground)
+        .register_get("ducked", |ctx: &mut ScriptContext| ctx.state.ducked)
+        .register_get("tick", |ctx: &mut ScriptContext| ctx.state.tick as i64)
+        .register_get("forward_move", |ctx: &mut ScriptContext| ctx.input.forward_move)
+        .register_get("side_move", |ctx: &mut ScriptContext| ctx.input.side_move)
+        .register_get("up_move", |ctx: &mut ScriptContext| ctx.input.up_move)
+        .register_get("yaw", |ctx: &mut ScriptContext| ctx.input.yaw)
+        .register_get("pitch", |ctx: &mut ScriptContext| ctx.input.pitch)
+        .register_get("jump", |ctx: &mut ScriptContext| ctx.input.jump)
+        .register_get("duck", |ctx: &mut ScriptContext| ctx.input.duck)
+        .register_get("delta_time", |ctx: &mut ScriptContext| ctx.delta_time)
+        .register_fn("config", ScriptContext::config);
+    engine
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::{player_tick, player_tick_with_modifiers, MovementModifiers};
+
+    fn run(script: ScriptModifier, ticks: usize) -> PlayerState {
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("script", Box::new(script));
+        let config = y_up_config();
+        let mut state = PlayerState::new(Vector3::new(0.0, 0.0, 0.0));
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        for _ in 0..ticks {
+            let ground = flat_ground(&state);
+            player_tick_with_modifiers(&mut state, &input, &config, 1.0 / 64.0, ground, &mut modifiers);
+        }
+        state
+    }
+
+    #[test]
+    fn test_hooks_change_movement() {
+        // Half friction and a speed cap at half of max_speed
+        let script = ScriptModifier::new(
+            r#"
+            fn modify_friction(ctx, friction) { friction * 0.5 }
+            fn modify_velocity(ctx, velocity) {
+                let cap = ctx.config("max_speed") * 0.5;
+                if velocity.length() > cap { velocity.normalized() * cap } else { velocity }
+            }
+            "#,
+        )
+        .unwrap();
+        assert!(script.defines("modify_velocity") && !script.defines("modify_wish"));
+        let state = run(script, 128);
+        assert!((state.velocity.length() - 160.0).abs() < 0.01);
+
+        // Wind pushing sideways
+        let wind = ScriptModifier::new("fn external_acceleration(ctx) { vec3(100.0, 0.0, 0.0) }").unwrap();
+        assert!(run(wind, 64).velocity.x > 0.0);
+    }
+
+    #[test]
+    fn test_scripts_are_sandboxed() {
+        assert!(matches!(ScriptModifier::new("fn modify_velocity(v) { v }"), Err(AirStrafeError::ScriptFailed(_))));
+        assert!(ScriptModifier::new("import \"file\" as f;").is_err());
+        let config = y_up_config();
+        let state = PlayerState::default();
+        let ctx = ModifierContext { state, input: &PlayerInput::new(), config: &config, delta_time: 1.0 / 64.0 };
+
+        // There is no clock to read
+        let mut clock = ScriptModifier::new("fn base_velocity(ctx) { timestamp(); vec3(1.0, 0.0, 0.0) }").unwrap();
+        assert_eq!(clock.base_velocity(&ctx), Vector3::new(0.0, 0.0, 0.0));
+        assert!(clock.take_error().is_some());
+
+        // A runaway loop is stopped and leaves the velocity alone
+        let mut script = ScriptModifier::new("fn modify_velocity(ctx, v) { loop {} }").unwrap().with_max_operations(100);
+        let mut velocity = Vector3::new(1.0, 2.0, 3.0);
+        script.modify_velocity(&ctx, &mut velocity);
+        assert_eq!(velocity, Vector3::new(1.0, 2.0, 3.0));
+        let error = script.take_error();
+        assert!(matches!(error, Some(AirStrafeError::ScriptFailed(message)) if message.contains("modify_velocity")));
+        assert_eq!(script.take_error(), None);
+    }
+
+    /// The error of calling `modify_velocity` of `source`, which must leave the velocity alone
+    fn velocity_hook_error(source: &str) -> Option<AirStrafeError> {
+        let config = y_up_config();
+        let state = PlayerState::default();
+        let ctx = ModifierContext { state, input: &PlayerInput::new(), config: &config, delta_time: 1.0 / 64.0 };
+        let mut script = ScriptModifier::new(source).unwrap();
+        let mut velocity = Vector3::new(1.0, 2.0, 3.0);
+        script.modify_velocity(&ctx, &mut velocity);
+        assert_eq!(velocity, Vector3::new(1.0, 2.0, 3.0));
+        script.take_error()
+    }
+
+    #[test]
+    fn test_deep_recursion_is_stopped() {
+        let error = velocity_hook_error("fn deeper(n) { deeper(n + 1) } fn modify_velocity(ctx, v) { deeper(0); v }");
+        assert!(matches!(error, Some(AirStrafeError::ScriptFailed(message)) if message.contains("Stack overflow")));
+    }
+
+    #[test]
+    fn test_huge_strings_and_arrays_are_stopped() {
+        // Doubling 16 times passes the 1024 element limit long before the operation limit
+        let string = velocity_hook_error(r#"fn modify_velocity(ctx, v) { let s = "x"; for i in 0..16 { s += s; } v }"#);
+        assert!(matches!(string, Some(AirStrafeError::ScriptFailed(message)) if message.contains("string too large")));
+        let array = velocity_hook_error("fn modify_velocity(ctx, v) { let a = [0]; for i in 0..16 { a += a; } v }");
+        let too_large = |message: &String| message.contains("array/BLOB too large");
+        assert!(matches!(array, Some(AirStrafeError::ScriptFailed(message)) if too_large(&message)));
+    }
+
+    #[test]
+    fn test_failing_script_keeps_the_tick_running() {
+        // Every tick fails, so the player moves exactly as without the script
+        let failing = ScriptModifier::new("fn modify_velocity(ctx, v) { v.missing }").unwrap();
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("script", Box::new(failing));
+        let config = y_up_config();
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let (mut scripted, mut plain) = (PlayerState::default(), PlayerState::default());
+        for _ in 0..64 {
+            let ground = flat_ground(&scripted);
+            player_tick_with_modifiers(&mut scripted, &input, &config, 1.0 / 64.0, ground, &mut modifiers);
+            player_tick(&mut plain, &input, &config, 1.0 / 64.0, ground);
+        }
+        assert_eq!(scripted, plain);
+        assert!(plain.velocity.length() > 0.0);
+
+        // After `take_error` the next call runs normally again
+        let source = "fn modify_velocity(ctx, v) { if ctx.tick == 0 { v.missing } else { v * 2.0 } }";
+        let mut script = ScriptModifier::new(source).unwrap();
+        let mut velocity = Vector3::new(1.0, 0.0, 0.0);
+        for tick in 0..3 {
+            let state = PlayerState { tick, ..PlayerState::default() };
+            let ctx = ModifierContext { state, input: &input, config: &config, delta_time: 1.0 / 64.0 };
+            script.modify_velocity(&ctx, &mut velocity);
+            assert_eq!(matches!(script.take_error(), Some(AirStrafeError::ScriptFailed(_))), tick == 0);
+        }
+        assert_eq!(velocity, Vector3::new(4.0, 0.0, 0.0));
+    }
+}
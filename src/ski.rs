@@ -0,0 +1,70 @@
+//! Tribes-style skiing
+//!
+//! While the ski button is held on the ground, `player_tick` skips ground
+//! friction and lets gravity pull the player down the slope along the
+//! surface, so speed builds up going downhill and carries over the next
+//! crest. Combined with the jetpack this gives the classic ski–jet loop.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::fixed::with_fixed;
+use crate::{MovementConfig, Vector3};
+
+/// Velocity change from gravity along a ground surface over one time step
+///
+/// This is the part of `MovementConfig::gravity_velocity_change` that lies in
+/// the surface plane; it is zero on flat ground.
+pub fn ski_slope_velocity_change(ground_normal: &Vector3, config: &MovementConfig, delta_time: f32) -> Vector3 {
+    with_fixed!(config.up_axis, config.legacy_gravity, Z_UP, LEGACY_GRAVITY => {
+        ski_slope_velocity_change_fixed::<Z_UP, LEGACY_GRAVITY>(ground_normal, config, delta_time)
+    })
+}
+
+/// `ski_slope_velocity_change` for the up axis and gravity model of a `fixed` instantiation
+pub(crate) fn ski_slope_velocity_change_fixed<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+    ground_normal: &Vector3,
+    config: &MovementConfig,
+    delta_time: f32,
+) -> Vector3 {
+    let gravity = config.gravity_velocity_change_fixed::<Z_UP, LEGACY_GRAVITY>(delta_time);
+    gravity.project_on_plane(&ground_normal.normalized())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = skiSlopeVelocityChange)]
+pub fn ski_slope_velocity_change_wasm(ground_normal: &Vector3, config: &MovementConfig, delta_time: f32) -> Vector3 {
+    ski_slope_velocity_change(ground_normal, &config.in_world_units(), delta_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, PlayerState, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_skiing_keeps_speed_and_gains_downhill() {
+        let config = y_up_config();
+        // 30 degree slope going down towards +X
+        let normal = Vector3::new(0.5, 0.75f32.sqrt(), 0.0);
+        let hit = Some(RayCollisionHit::new_native(normal, 0.0));
+        let dt = 1.0 / 64.0;
+
+        let downhill = Vector3::new(0.75f32.sqrt(), -0.5, 0.0);
+        let start = PlayerState::builder().on_ground(normal).velocity(downhill.multiply_scalar(300.0)).build().unwrap();
+        let mut skiing = start;
+        let mut walking = start;
+        for _ in 0..64 {
+            player_tick(&mut skiing, &PlayerInput { ski: true, ..PlayerInput::new() }, &config, dt, hit);
+            player_tick(&mut walking, &PlayerInput::new(), &config, dt, hit);
+        }
+        assert!(skiing.on_ground);
+        // One second of gravity along a 30 degree slope adds about gravity / 2
+        assert!(skiing.velocity.magnitude() > 300.0 + config.gravity * 0.45);
+        assert!(walking.velocity.magnitude() < 300.0);
+
+        let flat = ski_slope_velocity_change(&Vector3::new(0.0, 1.0, 0.0), &config, dt);
+        assert!(flat.magnitude() < 0.0001);
+    }
+}
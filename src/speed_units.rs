@@ -0,0 +1,118 @@
+//! Speed readouts in familiar units
+//!
+//! Simulated speeds are in world units per second, which are config units
+//! (Hammer units) times `unit_scale`. HUDs usually want Source-style u/s or
+//! real-world units instead; going through `convert_speed` and `format_speed`
+//! keeps native and WASM speedometers showing the same numbers. A Hammer unit
+//! is one inch (`HAMMER_UNITS_TO_METERS`).
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, HAMMER_UNITS_TO_METERS};
+
+/// Meters per second in one mile per hour
+const MPH_IN_METERS_PER_SECOND: f32 = 0.44704;
+
+/// Unit of a speed readout
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpeedUnit {
+    /// Hammer units per second, as Source speedometers show
+    #[default]
+    UnitsPerSecond,
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+}
+
+impl SpeedUnit {
+    /// Short label shown after the number
+    pub fn suffix(self) -> &'static str {
+        match self {
+            SpeedUnit::UnitsPerSecond => "u/s",
+            SpeedUnit::MetersPerSecond => "m/s",
+            SpeedUnit::KilometersPerHour => "km/h",
+            SpeedUnit::MilesPerHour => "mph",
+        }
+    }
+
+    /// This unit in Hammer units per second
+    fn units_per_second(self) -> f32 {
+        match self {
+            SpeedUnit::UnitsPerSecond => 1.0,
+            SpeedUnit::MetersPerSecond => 1.0 / HAMMER_UNITS_TO_METERS,
+            SpeedUnit::KilometersPerHour => 1.0 / 3.6 / HAMMER_UNITS_TO_METERS,
+            SpeedUnit::MilesPerHour => MPH_IN_METERS_PER_SECOND / HAMMER_UNITS_TO_METERS,
+        }
+    }
+}
+
+/// A speed in world units per second expressed in `unit`
+pub fn convert_speed(world_speed: f32, unit: SpeedUnit, config: &MovementConfig) -> f32 {
+    config.to_config_units(world_speed) / unit.units_per_second()
+}
+
+/// A speed in `unit` back in world units per second
+pub fn speed_to_world_units(speed: f32, unit: SpeedUnit, config: &MovementConfig) -> f32 {
+    config.to_world_units(speed * unit.units_per_second())
+}
+
+/// A speed in world units per second as text, e.g. `"285 u/s"` or `"7.24 m/s"`
+pub fn format_speed(world_speed: f32, unit: SpeedUnit, config: &MovementConfig, decimals: usize) -> String {
+    let speed = convert_speed(world_speed, unit, config);
+    let scale = 10f32.powi(decimals.min(6) as i32);
+    // Adding 0.0 turns a rounded -0.0 into 0.0 so tiny negative speeds do not show a sign
+    let rounded = (speed * scale).round() / scale + 0.0;
+    format!("{rounded:.decimals$} {}", unit.suffix())
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = convertSpeed)]
+pub fn convert_speed_wasm(world_speed: f32, unit: SpeedUnit, config: &MovementConfig) -> f32 {
+    convert_speed(world_speed, unit, config)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = speedToWorldUnits)]
+pub fn speed_to_world_units_wasm(speed: f32, unit: SpeedUnit, config: &MovementConfig) -> f32 {
+    speed_to_world_units(speed, unit, config)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = formatSpeed)]
+pub fn format_speed_wasm(world_speed: f32, unit: SpeedUnit, config: &MovementConfig, decimals: usize) -> String {
+    format_speed(world_speed, unit, config, decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_conversions_respect_unit_scale() {
+        let hammer = y_up_config();
+        let metric = MovementConfig { unit_scale: HAMMER_UNITS_TO_METERS, ..y_up_config() };
+        let units = [SpeedUnit::UnitsPerSecond, SpeedUnit::MetersPerSecond, SpeedUnit::KilometersPerHour, SpeedUnit::MilesPerHour];
+
+        // 250 u/s is the same speed whether the world is in inches or meters
+        for unit in units {
+            let inches = convert_speed(250.0, unit, &hammer);
+            let meters = convert_speed(250.0 * HAMMER_UNITS_TO_METERS, unit, &metric);
+            assert!((inches - meters).abs() < 1e-3 * inches);
+            assert!((speed_to_world_units(inches, unit, &hammer) - 250.0).abs() < 1e-3);
+        }
+        assert!((convert_speed(250.0, SpeedUnit::MetersPerSecond, &hammer) - 6.35).abs() < 1e-4);
+        assert!((convert_speed(250.0, SpeedUnit::KilometersPerHour, &hammer) - 22.86).abs() < 1e-3);
+        assert!((convert_speed(250.0, SpeedUnit::MilesPerHour, &hammer) - 14.2045).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_format_speed() {
+        let config = y_up_config();
+        assert_eq!(format_speed(285.4, SpeedUnit::UnitsPerSecond, &config, 0), "285 u/s");
+        assert_eq!(format_speed(250.0, SpeedUnit::MetersPerSecond, &config, 2), "6.35 m/s");
+        assert_eq!(format_speed(-0.01, SpeedUnit::KilometersPerHour, &config, 1), "0.0 km/h");
+    }
+}
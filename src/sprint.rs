@@ -0,0 +1,81 @@
+//! Ramped sprinting
+//!
+//! By default holding sprint applies `sprint_multiplier` instantly. With
+//! `sprint_ramp_enabled` the multiplier instead follows
+//! `PlayerState::sprint_amount`, which rises to 1.0 over `sprint_ramp_up_time`
+//! while sprinting on the ground and falls back over `sprint_ramp_down_time`.
+//! Firing and landing cancel the sprint, dropping the amount to 0.0 so the
+//! ramp starts over.
+
+use crate::{MovementConfig, PlayerInput, PlayerState};
+
+/// Move `sprint_amount` towards the sprint button, cancelling on fire or landing
+pub(crate) fn update_sprint(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    landed: bool,
+    delta_time: f32,
+) {
+    if !config.sprint_ramp_enabled {
+        state.sprint_amount = if input.sprint { 1.0 } else { 0.0 };
+        return;
+    }
+    if input.fire || landed {
+        cancel_sprint(state, config);
+        return;
+    }
+    if !state.on_ground {
+        // Airborne players keep their sprint until they land
+        return;
+    }
+    let sprinting = input.sprint && !state.ducked;
+    let ramp = |time: f32| if time > 0.0 { delta_time / time } else { 1.0 };
+    state.sprint_amount = if sprinting {
+        (state.sprint_amount + ramp(config.sprint_ramp_up_time)).min(1.0)
+    } else {
+        (state.sprint_amount - ramp(config.sprint_ramp_down_time)).max(0.0)
+    };
+}
+
+/// Drop a ramped sprint back to walking speed
+pub(crate) fn cancel_sprint(state: &mut PlayerState, config: &MovementConfig) {
+    if config.sprint_ramp_enabled {
+        state.sprint_amount = 0.0;
+    }
+}
+
+/// Wish speed multiplier for the current sprint amount
+pub fn sprint_multiplier(state: &PlayerState, config: &MovementConfig) -> f32 {
+    1.0 + (config.sprint_multiplier - 1.0) * state.sprint_amount
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_sprint_ramps_and_firing_cancels() {
+        let config = MovementConfig { sprint_ramp_enabled: true, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let sprint = PlayerInput { forward_move: 1.0, sprint: true, ..PlayerInput::new() };
+        let dt = 1.0 / 64.0;
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+
+        let half = (config.sprint_ramp_up_time / 2.0 / dt).round() as usize;
+        for _ in 0..half {
+            player_tick(&mut state, &sprint, &config, dt, ground);
+        }
+        assert!((state.sprint_amount - 0.5).abs() < 0.01);
+        assert!((sprint_multiplier(&state, &config) - (1.0 + config.sprint_multiplier) / 2.0).abs() < 0.01);
+        for _ in 0..half {
+            player_tick(&mut state, &sprint, &config, dt, ground);
+        }
+        assert_eq!(state.sprint_amount, 1.0);
+
+        player_tick(&mut state, &PlayerInput { fire: true, ..sprint }, &config, dt, ground);
+        assert_eq!(state.sprint_amount, 0.0);
+    }
+}
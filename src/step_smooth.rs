@@ -0,0 +1,176 @@
+//! Camera smoothing for stepping up stairs and ledges
+//!
+//! A tick that finds ground up to `step_height` above the origin (StepSlideMove
+//! style) lifts the player onto it in one go and reports a `SteppedUp` event.
+//! Feeding the tick's events to a `StepSmoothing` keeps the eye where it was
+//! and lets it catch up with a time constant of `step_smooth_time`, so stairs
+//! read as a climb instead of a pop. Hosts that move the player up themselves
+//! can report the height with `add_step`.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::MovementEventBuffer;
+use crate::{MovementConfig, MovementEvent, UpAxis, Vector3};
+
+/// Offsets below this (world units) snap to zero so the camera settles exactly
+const STEP_EPSILON: f32 = 1e-3;
+
+/// Eye offset left over from recent steps
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StepSmoothing {
+    offset: f32,
+}
+
+impl StepSmoothing {
+    /// Create a smoother with no pending offset
+    pub fn new() -> StepSmoothing {
+        StepSmoothing::default()
+    }
+
+    /// Current eye offset along the up axis in world units (negative after stepping up)
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// The eye offset as a world-space vector
+    pub fn offset_vector(&self, up_axis: UpAxis) -> Vector3 {
+        up_axis.up().multiply_scalar(self.offset)
+    }
+
+    /// Record that the origin was moved by `height` along the up axis this tick
+    ///
+    /// Negative heights (stepping down) are smoothed the same way.
+    pub fn add_step(&mut self, height: f32) {
+        if height.is_finite() {
+            self.offset -= height;
+        }
+    }
+
+    /// Record the `SteppedUp` events of a tick
+    pub fn add_events(&mut self, events: &[MovementEvent]) {
+        for event in events {
+            if let MovementEvent::SteppedUp { height, .. } = *event {
+                self.add_step(height);
+            }
+        }
+    }
+
+    /// Let the eye catch up over one time step
+    ///
+    /// # Returns
+    /// The eye offset to apply for this frame
+    pub fn update(&mut self, delta_time: f32, config: &MovementConfig) -> f32 {
+        let time_constant = config.step_smooth_time;
+        self.offset = if time_constant > 0.0 {
+            self.offset * (-delta_time.max(0.0) / time_constant).exp()
+        } else {
+            0.0
+        };
+        if self.offset.abs() < config.to_world_units(STEP_EPSILON) {
+            self.offset = 0.0;
+        }
+        self.offset
+    }
+
+    /// Drop any pending offset (e.g. after a teleport)
+    pub fn reset(&mut self) {
+        self.offset = 0.0;
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl StepSmoothing {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm() -> StepSmoothing {
+        StepSmoothing::new()
+    }
+
+    #[wasm_bindgen(getter = offset)]
+    pub fn offset_wasm(&self) -> f32 {
+        self.offset
+    }
+
+    #[wasm_bindgen(js_name = addStep)]
+    pub fn add_step_wasm(&mut self, height: f32) {
+        self.add_step(height);
+    }
+
+    #[wasm_bindgen(js_name = addEvents)]
+    pub fn add_events_wasm(&mut self, events: &MovementEventBuffer) {
+        self.add_events(events.events());
+    }
+
+    #[wasm_bindgen(js_name = update)]
+    pub fn update_wasm(&mut self, delta_time: f32, config: &MovementConfig) -> f32 {
+        self.update(delta_time, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, PlayerState, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_step_offset_eases_out() {
+        let config = y_up_config();
+        let mut smoothing = StepSmoothing::new();
+        smoothing.add_step(18.0);
+        assert_eq!(smoothing.offset(), -18.0);
+
+        // After one time constant about 37% of the step is left
+        let ticks = 16;
+        for _ in 0..ticks {
+            smoothing.update(config.step_smooth_time / ticks as f32, &config);
+        }
+        assert!((smoothing.offset() + 18.0 * (-1.0f32).exp()).abs() < 0.001);
+
+        // A second step while still catching up adds to the remaining offset
+        smoothing.add_step(18.0);
+        assert!(smoothing.offset() < -18.0);
+        for _ in 0..256 {
+            smoothing.update(1.0 / 128.0, &config);
+        }
+        assert_eq!(smoothing.offset(), 0.0);
+    }
+
+    #[test]
+    fn test_steps_up_stairs_from_the_tick() {
+        let config = y_up_config();
+        let mut state = PlayerState::default();
+        let input = PlayerInput { forward_move: 1.0, yaw: -std::f32::consts::FRAC_PI_2, ..PlayerInput::new() };
+        // 18 unit stairs every 64 units along +x, traced from a step height above the feet
+        let stairs = |state: &PlayerState| {
+            let floor = (state.origin.x / 64.0).floor().max(0.0) * 18.0;
+            let distance = state.origin.y - floor;
+            (distance >= -config.step_height).then(|| RayCollisionHit::new(0.0, 1.0, 0.0, distance))
+        };
+        let mut smoothing = StepSmoothing::new();
+        let mut steps = 0;
+        let mut eye_heights = Vec::new();
+        for _ in 0..128 {
+            let ground = stairs(&state);
+            let events = player_tick(&mut state, &input, &config, 1.0 / 64.0, ground);
+            steps += events.iter().filter(|event| matches!(event, MovementEvent::SteppedUp { height: 18.0, .. })).count();
+            smoothing.add_events(&events);
+            eye_heights.push(state.origin.y + smoothing.update(1.0 / 64.0, &config));
+        }
+        assert!(state.origin.x > 192.0 && steps >= 3);
+        assert_eq!(state.origin.y, (state.origin.x / 64.0).floor() * 18.0);
+        // The eye climbs without jumping a whole step in one tick
+        assert!(eye_heights.windows(2).all(|pair| pair[1] >= pair[0] && pair[1] - pair[0] < 9.0));
+    }
+
+    #[test]
+    fn test_zero_time_disables_smoothing() {
+        let config = MovementConfig { step_smooth_time: 0.0, ..y_up_config() };
+        let mut smoothing = StepSmoothing::new();
+        smoothing.add_step(18.0);
+        assert_eq!(smoothing.update(1.0 / 64.0, &config), 0.0);
+    }
+}
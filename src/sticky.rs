@@ -0,0 +1,236 @@
+//! Sticky surfaces (magnetic boots)
+//!
+//! Surfaces whose material is flagged `SURFACE_STICKY` replace gravity with an
+//! attraction of `sticky_attraction` towards the surface while the player is in
+//! contact, so flagged walls and ceilings can be walked on. The stuck surface's
+//! normal is kept in `PlayerState::stick_normal` and the tick runs through the
+//! arbitrary gravity frame (`player_tick_with_gravity`).
+//!
+//! Each tick the host calls `update_sticky_contact` with a forward ray and a
+//! ray cast along `sticky_gravity_direction`, then casts the down ray for
+//! `player_tick_sticky` along the (possibly changed) direction. Walking into a
+//! sticky wall attaches to it, walking over edges onto other sticky surfaces
+//! follows them, and losing contact or holding `PlayerInput::detach` lets go.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "wasm")]
+use crate::events::MovementEventInfo;
+use crate::events::MovementEvent;
+use crate::materials::{MaterialTable, SURFACE_STICKY};
+use crate::{player_tick_with_gravity_into, MovementConfig, PlayerInput, PlayerState, RayCollisionHit, Vector3};
+
+/// Attach to, follow or let go of sticky surfaces
+///
+/// # Arguments
+/// * `state` - The player, its `stick_normal` is updated
+/// * `input` - Walls are only grabbed while moving forward; `detach` lets go
+/// * `config` - Supplies `sticky_reach`
+/// * `forward_ray_hit` - Result of a ray cast forward along the view yaw
+/// * `down_ray_hit` - Result of a ray cast along `sticky_gravity_direction`
+/// * `materials` - Decides which surfaces are sticky
+pub fn update_sticky_contact(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    forward_ray_hit: Option<RayCollisionHit>,
+    down_ray_hit: Option<RayCollisionHit>,
+    materials: &MaterialTable,
+) {
+    let config = &config.in_world_units();
+    if input.detach {
+        state.stick_normal = Vector3::new(0.0, 0.0, 0.0);
+        return;
+    }
+    let sticky = |hit: &RayCollisionHit| {
+        hit.distance_native() <= config.sticky_reach && materials.hit_has(hit, SURFACE_STICKY)
+    };
+    if let Some(wall) = forward_ray_hit.filter(sticky).filter(|_| input.forward_move > 0.0) {
+        state.stick_normal = wall.normal_native().normalized();
+        state.on_ground = false;
+        return;
+    }
+    let stuck = state.stick_normal.length_sq() > 0.0;
+    match down_ray_hit.filter(sticky) {
+        // Follow the surface around curves and onto neighbouring sticky faces
+        Some(hit) if stuck || state.on_ground => state.stick_normal = hit.normal_native().normalized(),
+        Some(_) => {}
+        None => state.stick_normal = Vector3::new(0.0, 0.0, 0.0),
+    }
+}
+
+/// The direction the player is pulled in: into the stuck surface, or regular gravity
+pub fn sticky_gravity_direction(state: &PlayerState, gravity_direction: &Vector3) -> Vector3 {
+    if state.stick_normal.length_sq() > 0.0 {
+        -state.stick_normal
+    } else {
+        *gravity_direction
+    }
+}
+
+/// `player_tick_with_gravity` pulling towards the stuck surface, if any
+///
+/// # Arguments
+/// * `down_ray_hit` - Result of a ray cast along `sticky_gravity_direction`
+/// * `gravity_direction` - Direction of regular gravity (any length)
+pub fn player_tick_sticky(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    gravity_direction: &Vector3,
+) -> Vec<MovementEvent> {
+    let mut events = Vec::new();
+    player_tick_sticky_into(state, input, config, delta_time, down_ray_hit, gravity_direction, &mut events);
+    events
+}
+
+/// `player_tick_sticky` appending the events to a caller-owned buffer
+pub fn player_tick_sticky_into(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    gravity_direction: &Vector3,
+    events: &mut Vec<MovementEvent>,
+) {
+    if state.stick_normal.length_sq() == 0.0 {
+        return player_tick_with_gravity_into(state, input, config, delta_time, down_ray_hit, gravity_direction, events);
+    }
+    let attraction = MovementConfig { gravity: config.sticky_attraction, ..*config };
+    let direction = -state.stick_normal;
+    player_tick_with_gravity_into(state, input, &attraction, delta_time, down_ray_hit, &direction, events);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = updateStickyContact)]
+pub fn update_sticky_contact_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    forward_ray_hit: Option<RayCollisionHit>,
+    down_ray_hit: Option<RayCollisionHit>,
+    materials: &MaterialTable,
+) {
+    update_sticky_contact(state, input, config, forward_ray_hit, down_ray_hit, materials);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = stickyGravityDirection)]
+pub fn sticky_gravity_direction_wasm(state: &PlayerState, gravity_direction: &Vector3) -> Vector3 {
+    sticky_gravity_direction(state, gravity_direction)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = playerTickSticky)]
+pub fn player_tick_sticky_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    gravity_direction: &Vector3,
+) -> Vec<MovementEventInfo> {
+    player_tick_sticky(state, input, config, delta_time, down_ray_hit, gravity_direction)
+        .into_iter()
+        .map(MovementEventInfo::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_walk_onto_sticky_wall_and_detach() {
+        let config = y_up_config();
+        let materials = MaterialTable::new().with_flags(7, SURFACE_STICKY);
+        let gravity = Vector3::new(0.0, -1.0, 0.0);
+        let dt = 1.0 / 64.0;
+        // Sticky wall at z = -8 facing a Y-up player looking down -Z
+        let wall = |state: &PlayerState| {
+            Some(RayCollisionHit::new_native(Vector3::new(0.0, 0.0, 1.0), state.origin.z + 8.0).with_material(7))
+        };
+        let forward = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let mut state = PlayerState::default();
+
+        let hit = wall(&state);
+        update_sticky_contact(&mut state, &forward, &config, hit, None, &materials);
+        assert_eq!(sticky_gravity_direction(&state, &gravity), Vector3::new(0.0, 0.0, -1.0));
+        for _ in 0..32 {
+            let idle = PlayerInput::new();
+            let hit = wall(&state);
+            update_sticky_contact(&mut state, &idle, &config, None, hit, &materials);
+            player_tick_sticky(&mut state, &idle, &config, dt, hit, &gravity);
+        }
+        assert!(state.on_ground);
+        assert!((state.origin.z + 8.0).abs() < 0.001);
+        assert_eq!(state.velocity.y, 0.0);
+
+        let detach = PlayerInput { detach: true, ..PlayerInput::new() };
+        let hit = wall(&state);
+        update_sticky_contact(&mut state, &detach, &config, None, hit, &materials);
+        player_tick_sticky(&mut state, &detach, &config, dt, None, &gravity);
+        assert!(!state.on_ground);
+        assert!(state.velocity.y < 0.0);
+    }
+
+    #[test]
+    fn test_only_reachable_sticky_surfaces_attach() {
+        let config = y_up_config();
+        let materials = MaterialTable::new().with_flags(7, SURFACE_STICKY);
+        let wall_normal = Vector3::new(0.0, 0.0, 1.0);
+        let wall = |distance: f32, material: u32| {
+            Some(RayCollisionHit::new_native(wall_normal, distance).with_material(material))
+        };
+        let forward = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let attaches = |input: &PlayerInput, hit: Option<RayCollisionHit>| {
+            let mut state = PlayerState::default();
+            update_sticky_contact(&mut state, input, &config, hit, None, &materials);
+            state.stick_normal == wall_normal
+        };
+        assert!(attaches(&forward, wall(config.sticky_reach, 7)));
+        // Out of reach, not sticky, or not walking into it
+        assert!(!attaches(&forward, wall(config.sticky_reach + 0.1, 7)));
+        assert!(!attaches(&forward, wall(1.0, 0)));
+        assert!(!attaches(&PlayerInput::new(), wall(1.0, 7)));
+        assert!(!attaches(&PlayerInput { forward_move: -1.0, ..PlayerInput::new() }, wall(1.0, 7)));
+
+        // Falling onto a sticky floor does not attach, standing on one does
+        let floor = Some(RayCollisionHit::new(0.0, 1.0, 0.0, 1.0).with_material(7));
+        let mut state = PlayerState::default();
+        update_sticky_contact(&mut state, &PlayerInput::new(), &config, None, floor, &materials);
+        assert_eq!(state.stick_normal.length_sq(), 0.0);
+        state.on_ground = true;
+        update_sticky_contact(&mut state, &PlayerInput::new(), &config, None, floor, &materials);
+        assert_eq!(state.stick_normal, Vector3::new(0.0, 1.0, 0.0));
+        // Walking onto a plain surface lets go
+        let plain = Some(RayCollisionHit::new(0.0, 1.0, 0.0, 1.0));
+        update_sticky_contact(&mut state, &PlayerInput::new(), &config, None, plain, &materials);
+        assert_eq!(state.stick_normal.length_sq(), 0.0);
+    }
+
+    #[test]
+    fn test_sticky_attraction_follows_unit_scale() {
+        let config = y_up_config();
+        let meters = MovementConfig { unit_scale: 0.0254, ..config };
+        assert_eq!(meters.in_world_units().sticky_attraction, config.sticky_attraction * 0.0254);
+        let dt = 1.0 / 64.0;
+        let fall = |config: &MovementConfig| {
+            let mut state = PlayerState { stick_normal: Vector3::new(0.0, 0.0, 1.0), ..PlayerState::default() };
+            for _ in 0..16 {
+                player_tick_sticky(&mut state, &PlayerInput::new(), config, dt, None, &Vector3::new(0.0, -1.0, 0.0));
+            }
+            state
+        };
+        let (inches, meters) = (fall(&config), fall(&meters));
+        assert!(inches.velocity.z < 0.0);
+        assert!((meters.velocity.z - inches.velocity.z * 0.0254).abs() < 0.001);
+        assert!((meters.origin.z - inches.origin.z * 0.0254).abs() < 0.001);
+        assert_eq!(meters.origin.y, 0.0);
+    }
+}
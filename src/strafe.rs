@@ -0,0 +1,177 @@
+//! Optimal strafe angle math and strafe assistance
+//!
+//! For the crate's air acceleration, the speed gained in one tick is largest when
+//! the velocity projected onto the wish direction is exactly
+//! `max_air_wish_speed - air_accelerate * delta_time`. Velocity and wish
+//! direction are expected to be horizontal (see `UpAxis::horizontal`), which
+//! keeps this math independent of the world's up axis.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::Vector3;
+
+/// Angle (radians) between velocity and wish direction that gives the largest
+/// speed gain for one tick of `air_accelerate_get_acceleration`
+///
+/// # Arguments
+/// * `speed` - Current horizontal speed
+/// * `air_accelerate` - Air acceleration value as passed to the air acceleration function
+/// * `max_air_wish_speed` - Maximum air wish speed
+/// * `delta_time` - Time step in seconds
+///
+/// # Returns
+/// The optimal angle in radians (0.0 when accelerating straight ahead is best)
+pub fn optimal_strafe_angle(speed: f32, air_accelerate: f32, max_air_wish_speed: f32, delta_time: f32) -> f32 {
+    if speed <= 0.0 {
+        return 0.0;
+    }
+    let ideal_projection = (max_air_wish_speed - air_accelerate * delta_time).max(0.0);
+    (ideal_projection / speed).min(1.0).acos()
+}
+
+/// Optimal wish direction on the same side of the velocity as `wish_dir`
+///
+/// Returns the normalized direction rotated `optimal_strafe_angle` away from
+/// the horizontal velocity towards the side the player is strafing to. Falls
+/// back to `wish_dir` when there is no velocity or the player is not strafing
+/// to either side.
+pub fn optimal_strafe_wish_dir(
+    velocity: &Vector3,
+    wish_dir: &Vector3,
+    air_accelerate: f32,
+    max_air_wish_speed: f32,
+    delta_time: f32,
+) -> Vector3 {
+    let wish = wish_dir.normalized();
+    let speed = velocity.magnitude();
+    if speed <= 0.0 || wish.length_sq() == 0.0 {
+        return wish;
+    }
+
+    let forward = velocity.multiply_scalar(1.0 / speed);
+    // Component of the wish direction perpendicular to the velocity picks the side
+    let side = wish.project_on_plane(&forward);
+    if side.length_sq() < 1e-8 {
+        return wish;
+    }
+    let side = side.normalized();
+
+    let angle = optimal_strafe_angle(speed, air_accelerate, max_air_wish_speed, delta_time);
+    let mut optimal = forward.multiply_scalar(angle.cos());
+    optimal.add(&side.multiply_scalar(angle.sin()));
+    optimal
+}
+
+/// Blend the player's wish direction towards the optimal strafe direction
+///
+/// The physics are untouched; only the direction fed into air acceleration is
+/// adjusted, so partial assistance feels like the player strafing better.
+///
+/// # Arguments
+/// * `velocity` - The current horizontal velocity
+/// * `wish_dir` - The horizontal wish direction built from the player's input
+/// * `air_accelerate` - Air acceleration value as passed to the air acceleration function
+/// * `max_air_wish_speed` - Maximum air wish speed
+/// * `delta_time` - Time step in seconds
+/// * `assist_factor` - 0.0 = unassisted, 1.0 = perfect strafes (clamped to 0–1)
+///
+/// # Returns
+/// The assisted, normalized wish direction
+pub fn strafe_assist_wish_dir(
+    velocity: &Vector3,
+    wish_dir: &Vector3,
+    air_accelerate: f32,
+    max_air_wish_speed: f32,
+    delta_time: f32,
+    assist_factor: f32,
+) -> Vector3 {
+    let factor = assist_factor.clamp(0.0, 1.0);
+    let wish = wish_dir.normalized();
+    if factor == 0.0 {
+        return wish;
+    }
+
+    let optimal = optimal_strafe_wish_dir(velocity, wish_dir, air_accelerate, max_air_wish_speed, delta_time);
+    let mut blended = wish.multiply_scalar(1.0 - factor);
+    blended.add(&optimal.multiply_scalar(factor));
+    if blended.length_sq() > 0.0 {
+        blended.normalized()
+    } else {
+        optimal
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = optimalStrafeAngle)]
+pub fn optimal_strafe_angle_wasm(speed: f32, air_accelerate: f32, max_air_wish_speed: f32, delta_time: f32) -> f32 {
+    optimal_strafe_angle(speed, air_accelerate, max_air_wish_speed, delta_time)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = strafeAssistWishDir)]
+pub fn strafe_assist_wish_dir_wasm(
+    velocity: &Vector3,
+    wish_dir: &Vector3,
+    air_accelerate: f32,
+    max_air_wish_speed: f32,
+    delta_time: f32,
+    assist_factor: f32,
+) -> Vector3 {
+    strafe_assist_wish_dir(velocity, wish_dir, air_accelerate, max_air_wish_speed, delta_time, assist_factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::air_accelerate_get_acceleration;
+
+    fn speed_after(velocity: &Vector3, wish_dir: &Vector3) -> f32 {
+        let mut v = *velocity;
+        v.add(&air_accelerate_get_acceleration(velocity, wish_dir, 0.0, 100.0, 30.0, 0.01));
+        v.magnitude()
+    }
+
+    #[test]
+    fn test_optimal_angle() {
+        // Slower than the ideal projection: accelerate straight ahead
+        assert_eq!(optimal_strafe_angle(10.0, 100.0, 30.0, 0.01), 0.0);
+
+        // cos(angle) = (30 - 1) / 290
+        let angle = optimal_strafe_angle(290.0, 100.0, 30.0, 0.01);
+        assert!((angle.cos() - 29.0 / 290.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_optimal_wish_dir_beats_other_angles() {
+        let velocity = Vector3::new(0.0, 0.0, -300.0);
+        let wish = Vector3::new(1.0, 0.0, 0.0);
+        let optimal = optimal_strafe_wish_dir(&velocity, &wish, 100.0, 30.0, 0.01);
+
+        // Stays on the strafing side
+        assert!(optimal.x > 0.0);
+        let best = speed_after(&velocity, &optimal);
+        for degrees in [60.0f32, 80.0, 85.0, 89.0, 90.0] {
+            let r = degrees.to_radians();
+            let dir = Vector3::new(r.sin(), 0.0, -r.cos());
+            assert!(best >= speed_after(&velocity, &dir) - 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_assist_factor_blends() {
+        let velocity = Vector3::new(0.0, 0.0, -300.0);
+        let wish = Vector3::new(1.0, 0.0, 0.0);
+
+        let none = strafe_assist_wish_dir(&velocity, &wish, 100.0, 30.0, 0.01, 0.0);
+        assert_eq!(none, wish);
+
+        let full = strafe_assist_wish_dir(&velocity, &wish, 100.0, 30.0, 0.01, 1.0);
+        let optimal = optimal_strafe_wish_dir(&velocity, &wish, 100.0, 30.0, 0.01);
+        assert!((full.dot(&optimal) - 1.0).abs() < 0.0001);
+
+        let half = strafe_assist_wish_dir(&velocity, &wish, 100.0, 30.0, 0.01, 0.5);
+        assert!(half.dot(&optimal) > wish.dot(&optimal));
+        assert!((half.magnitude() - 1.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,87 @@
+//! Surf ramp tuning
+//!
+//! Surfing is air strafing while sliding along ground too steep to stand on
+//! (`PlayerState::sliding`). Surf servers tune ramps separately from flat
+//! ground: `surf_accelerate_multiplier` scales air acceleration on a ramp,
+//! but only while the wish direction stays within `surf_board_angle_tolerance`
+//! of the ramp surface, and `surf_max_speed_gain` limits how much speed a ramp
+//! tick may add. Away from ramps none of these apply, so flat-ground air
+//! strafing keeps its feel.
+
+use crate::{MovementConfig, Vector3};
+
+/// Air acceleration multiplier for a wish direction on the ramp with `ramp_normal`
+pub(crate) fn surf_accelerate_multiplier(ramp_normal: Option<&Vector3>, wish_dir: &Vector3, config: &MovementConfig) -> f32 {
+    let Some(normal) = ramp_normal else {
+        return 1.0;
+    };
+    // The angle between a direction and a plane is the complement of its angle to the normal
+    let board_angle = wish_dir.dot(&normal.normalized()).clamp(-1.0, 1.0).abs().asin();
+    if board_angle <= config.surf_board_angle_tolerance {
+        config.surf_accelerate_multiplier
+    } else {
+        1.0
+    }
+}
+
+/// Keep a ramp tick from adding more than `surf_max_speed_gain` per second
+pub(crate) fn limit_surf_speed_gain(
+    velocity: &mut Vector3,
+    speed_before: f32,
+    on_ramp: bool,
+    config: &MovementConfig,
+    delta_time: f32,
+) {
+    if !on_ramp || config.surf_max_speed_gain <= 0.0 {
+        return;
+    }
+    let max_speed = speed_before + config.surf_max_speed_gain * delta_time;
+    let speed = velocity.magnitude();
+    if speed > max_speed {
+        *velocity = velocity.multiply_scalar(max_speed / speed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, PlayerState, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_surf_tuning_only_applies_on_ramps() {
+        let base = MovementConfig { air_strafe_accelerate: 1.0, ..y_up_config() };
+        let config = MovementConfig { surf_accelerate_multiplier: 3.0, surf_max_speed_gain: 5.0, ..base };
+        let ramp = Some(RayCollisionHit::new(0.8, 0.6, 0.0, 1.0));
+        let strafe = PlayerInput { side_move: 1.0, ..PlayerInput::new() };
+        let start = PlayerState { velocity: Vector3::new(0.0, 0.0, -400.0), ..PlayerState::default() };
+        let tick = |config: &MovementConfig, hit| {
+            let mut state = start;
+            player_tick(&mut state, &strafe, config, 1.0 / 64.0, hit);
+            state
+        };
+
+        // Flat-ground air strafing is unchanged
+        assert_eq!(tick(&config, None), tick(&base, None));
+
+        let surfed = tick(&config, ramp);
+        let plain = tick(&base, ramp);
+        assert!(surfed.sliding);
+        assert!(surfed.velocity.magnitude() <= 400.0 + 5.0 / 64.0 + 1e-3);
+        assert!(plain.velocity.magnitude() > 400.0 + 5.0 / 64.0);
+
+        let unlimited = MovementConfig { surf_max_speed_gain: 0.0, ..config };
+        assert!((tick(&unlimited, ramp).velocity.x - 3.0 * plain.velocity.x).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_board_angle_tolerance() {
+        let config = MovementConfig { surf_accelerate_multiplier: 2.0, surf_board_angle_tolerance: 0.5, ..y_up_config() };
+        let normal = Vector3::new(0.8, 0.6, 0.0);
+        // Along the ramp's contour the board is flat on the surface
+        assert_eq!(surf_accelerate_multiplier(Some(&normal), &Vector3::new(0.0, 0.0, -1.0), &config), 2.0);
+        // Pushing straight into the ramp is asin(0.8) ~ 0.93 rad off the surface
+        assert_eq!(surf_accelerate_multiplier(Some(&normal), &Vector3::new(-1.0, 0.0, 0.0), &config), 1.0);
+        assert_eq!(surf_accelerate_multiplier(None, &Vector3::new(0.0, 0.0, -1.0), &config), 1.0);
+    }
+}
@@ -0,0 +1,1199 @@
+//! Full per-tick player movement
+//!
+//! `player_tick` combines ground detection, jumping, friction, ground and air
+//! acceleration, gravity and integration into a single deterministic step on a
+//! `PlayerState`.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::air_dash::air_dash;
+use crate::air_drag::apply_air_drag;
+use crate::bhop_penalty::{penalize_bhop, recover_bhop_stamina};
+use crate::checked::{check_delta_time, check_ray_hit, check_scalar, check_vector};
+use crate::climb::{climb_move, regen_climb_stamina};
+use crate::crouch_slide::{keep_sliding_under_ceiling, must_stay_ducked, update_crouch_slide};
+use crate::delta_time::split_delta_time;
+use crate::dodge::{air_dodge, dodge_landing, dodge_request, dodge_velocity_fixed, DodgeDirection};
+use crate::duck::{duck_slowed, duck_spam_factor, shift_air_duck, update_duck_spam};
+use crate::edgebug::edgebug;
+use crate::error::AirStrafeError;
+use crate::events::MovementEvent;
+use crate::fixed::with_fixed;
+use crate::glide::glide_velocity_fixed;
+use crate::gravity_curve::gravity_curve_multiplier_fixed;
+use crate::ground_pound::ground_pound;
+use crate::jump_charge::update_jump_charge;
+use crate::jump_cut::update_jump_cut;
+use crate::jetpack::{jetpack_thrust_fixed, update_jetpack_fuel};
+use crate::jumpbug::jumpbug;
+use crate::momentum::momentum_wish_dir;
+use crate::noclip::noclip_speed_multiplier;
+use crate::modifiers::{ModifierContext, MovementModifiers};
+use crate::overbounce::overbounce;
+use crate::ski::ski_slope_velocity_change_fixed;
+use crate::sprint::{cancel_sprint, sprint_multiplier, update_sprint};
+use crate::wall_run::decay_wall_run_chain;
+use crate::surf::{limit_surf_speed_gain, surf_accelerate_multiplier};
+use crate::water::{buoyancy, water_current_velocity, water_jump_move};
+use crate::wishdir::compute_wishdir_fixed;
+use crate::{
+    air_accelerate_get_acceleration, strafe_assist_wish_dir, MoveType, MovementConfig, PlayerInput, PlayerState,
+    RayCollisionHit, UpAxis, Vector3,
+};
+
+/// Advance a player by one tick
+///
+/// The movement code used is selected by `state.move_type` every tick. The
+/// state is in world units; `config` is converted using its `unit_scale`.
+///
+/// Invalid values never reach the state: non-finite input axes and angles are
+/// treated as 0.0, a non-finite velocity is reset, a non-finite ray hit is
+/// ignored, and the tick is skipped entirely for an invalid `delta_time` or a
+/// non-finite origin. Use `player_tick_checked` to get an error instead. A
+/// `delta_time` longer than `config.max_delta_time` is clamped, dropped or
+/// split into sub-steps according to `config.delta_time_policy`, see `delta_time`.
+///
+/// # Arguments
+/// * `state` - The player state to advance (modified in place)
+/// * `input` - The player's input for this tick
+/// * `config` - Movement tuning parameters
+/// * `delta_time` - Tick length in seconds
+/// * `down_ray_hit` - Optional result of a ray cast straight down from the player origin
+///
+/// # Returns
+/// The events (jumps, landings, slides) that happened during this tick
+pub fn player_tick(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+) -> Vec<MovementEvent> {
+    let mut events = Vec::new();
+    player_tick_into(state, input, config, delta_time, down_ray_hit, &mut events);
+    events
+}
+
+/// `player_tick` appending the events to a caller-owned buffer
+///
+/// Nothing is allocated as long as `events` has room for the tick's events,
+/// so reusing one buffer keeps the per-tick path free of heap allocations.
+pub fn player_tick_into(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    events: &mut Vec<MovementEvent>,
+) {
+    player_tick_with_modifiers_into(state, input, config, delta_time, down_ray_hit, &mut MovementModifiers::new(), events);
+}
+
+/// `player_tick` running the player's movement modifiers at each stage
+pub fn player_tick_with_modifiers(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    modifiers: &mut MovementModifiers,
+) -> Vec<MovementEvent> {
+    let mut events = Vec::new();
+    player_tick_with_modifiers_into(state, input, config, delta_time, down_ray_hit, modifiers, &mut events);
+    events
+}
+
+/// `player_tick_with_modifiers` appending the events to a caller-owned buffer
+pub fn player_tick_with_modifiers_into(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    modifiers: &mut MovementModifiers,
+    events: &mut Vec<MovementEvent>,
+) {
+    with_fixed!(config.up_axis, config.legacy_gravity, Z_UP, LEGACY_GRAVITY => {
+        tick_steps::<Z_UP, LEGACY_GRAVITY>(state, input, config, delta_time, down_ray_hit, modifiers, events)
+    })
+}
+
+/// `player_tick_into` compiled for one up axis and gravity model
+///
+/// `Z_UP` picks `UpAxis::Z` over `UpAxis::Y` and `LEGACY_GRAVITY` the legacy
+/// gravity model. `player_tick_into` runs the instantiation matching the
+/// config, this runs a given one, so both are constants in its code. A
+/// config with another `up_axis` or `legacy_gravity` takes `player_tick_into`
+/// instead, so the result is always the same as its. See `fixed`.
+pub fn player_tick_fixed_into<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    events: &mut Vec<MovementEvent>,
+) {
+    if config.up_axis != UpAxis::from_z_up(Z_UP) || config.legacy_gravity != LEGACY_GRAVITY {
+        return player_tick_into(state, input, config, delta_time, down_ray_hit, events);
+    }
+    let modifiers = &mut MovementModifiers::new();
+    tick_steps::<Z_UP, LEGACY_GRAVITY>(state, input, config, delta_time, down_ray_hit, modifiers, events);
+}
+
+/// Body of `player_tick_with_modifiers_into` for one up axis and gravity model
+fn tick_steps<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    modifiers: &mut MovementModifiers,
+    events: &mut Vec<MovementEvent>,
+) {
+    if check_delta_time(delta_time).is_err() || !state.origin.is_finite() {
+        return;
+    }
+    let axis = UpAxis::from_z_up(Z_UP);
+    let (steps, delta_time) = split_delta_time(delta_time, config);
+    let start_height = axis.height(&state.origin);
+    for _ in 0..steps {
+        // Sub-steps move the ground hit along with the player
+        let moved = axis.height(&state.origin) - start_height;
+        let distance = |hit: RayCollisionHit| (hit.distance + moved).max(hit.distance.min(0.0));
+        let down_ray_hit = down_ray_hit.map(|hit| RayCollisionHit { distance: distance(hit), ..hit });
+        simulate_tick::<Z_UP, LEGACY_GRAVITY>(state, input, config, delta_time, down_ray_hit, modifiers, events);
+    }
+}
+
+/// One tick of `player_tick_with_modifiers_into` after the time step has been checked
+#[inline(always)]
+fn simulate_tick<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    modifiers: &mut MovementModifiers,
+    events: &mut Vec<MovementEvent>,
+) {
+    if !state.velocity.is_finite() {
+        state.velocity = Vector3::new(0.0, 0.0, 0.0);
+    }
+    if !state.water_current.is_finite() {
+        state.water_current = Vector3::new(0.0, 0.0, 0.0);
+    }
+    if !state.gravity_scale.is_finite() {
+        state.gravity_scale = 1.0;
+    }
+    let input = &sanitize_input(input);
+    let down_ray_hit = down_ray_hit.filter(|hit| check_ray_hit(&Some(*hit)).is_ok());
+
+    let config = &config.in_world_units().for_move_type(state.move_type);
+    let was_climbing = state.move_type == MoveType::Climb;
+    let previous_origin = state.origin;
+    match state.move_type {
+        MoveType::Fly => fly_move::<Z_UP>(state, input, config, delta_time, modifiers),
+        MoveType::Noclip | MoveType::Spectator => free_camera_move::<Z_UP>(state, input, config, delta_time),
+        MoveType::Climb => climb_move::<Z_UP>(state, input, config, delta_time, events),
+        MoveType::Walk | MoveType::Ladder | MoveType::Swim | MoveType::Glide => {
+            walk_move::<Z_UP, LEGACY_GRAVITY>(state, input, config, delta_time, down_ray_hit, modifiers, events)
+        }
+    }
+    if !matches!(state.move_type, MoveType::Walk | MoveType::Ladder | MoveType::Swim | MoveType::Glide) {
+        set_sliding(state, false, 0, events);
+    }
+    if !was_climbing {
+        regen_climb_stamina(state, config, delta_time);
+    }
+    if !matches!(state.move_type, MoveType::Noclip | MoveType::Spectator) {
+        let ctx = ModifierContext { state: *state, input, config, delta_time };
+        modifiers.after_move(&ctx, &previous_origin, events);
+    }
+    state.jump_held = input.jump;
+    state.air_dash_held = input.air_dash;
+    state.tick += 1;
+}
+
+/// `player_tick` that rejects invalid arguments instead of sanitizing them
+///
+/// The state is left untouched when an error is returned.
+pub fn player_tick_checked(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+) -> Result<Vec<MovementEvent>, AirStrafeError> {
+    check_delta_time(delta_time)?;
+    check_vector("state.origin", &state.origin)?;
+    check_vector("state.velocity", &state.velocity)?;
+    check_vector("state.water_current", &state.water_current)?;
+    check_scalar("state.gravity_scale", state.gravity_scale)?;
+    check_scalar("input.forward_move", input.forward_move)?;
+    check_scalar("input.side_move", input.side_move)?;
+    check_scalar("input.up_move", input.up_move)?;
+    check_scalar("input.yaw", input.yaw)?;
+    check_scalar("input.pitch", input.pitch)?;
+    check_scalar("input.lean", input.lean)?;
+    check_ray_hit(&down_ray_hit)?;
+    Ok(player_tick(state, input, config, delta_time, down_ray_hit))
+}
+
+/// Input with every non-finite axis and angle replaced by 0.0 and queued jump presses folded into `jump`
+fn sanitize_input(input: &PlayerInput) -> PlayerInput {
+    let finite = |value: f32| if value.is_finite() { value } else { 0.0 };
+    PlayerInput {
+        forward_move: finite(input.forward_move),
+        side_move: finite(input.side_move),
+        up_move: finite(input.up_move),
+        yaw: finite(input.yaw),
+        pitch: finite(input.pitch),
+        lean: finite(input.lean),
+        jump: input.jump || input.jump_presses > 0,
+        ..*input
+    }
+}
+
+/// Ground and air movement with gravity
+fn walk_move<const Z_UP: bool, const LEGACY_GRAVITY: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    modifiers: &mut MovementModifiers,
+    events: &mut Vec<MovementEvent>,
+) {
+    let ctx = |state: &PlayerState| ModifierContext { state: *state, input, config, delta_time };
+    let axis = UpAxis::from_z_up(Z_UP);
+    // Ground up to `step_height` above the origin is stepped onto, anything higher is a wall and not ground
+    let down_ray_hit = down_ray_hit.filter(|hit| -hit.distance_native() <= config.step_height);
+    let ground = down_ray_hit.filter(|hit| axis.height(hit.normal_native()) >= config.min_ground_normal_up);
+    let was_on_ground = state.on_ground;
+    // A Quake 3 overbounce or an edgebug replaces the landing, leaving no ground for the rest of the tick
+    let ground = ground
+        .filter(|hit| !overbounce::<Z_UP>(state, config, hit, events) && !edgebug::<Z_UP>(state, config, hit, delta_time, events));
+
+    // Categorize position: snap onto close ground or step up onto ground above, unless moving up quickly
+    state.on_ground = false;
+    if let Some(hit) = ground {
+        if hit.distance_native() <= config.ground_snap_distance && axis.height(&state.velocity) <= config.non_jump_velocity {
+            if !was_on_ground && jumpbug(state, input, config, delta_time) {
+                let landing_speed = (-axis.height(&state.velocity)).max(0.0);
+                events.push(MovementEvent::Jumpbugged { tick: state.tick, landing_speed });
+            } else if !was_on_ground {
+                events.push(landed::<Z_UP>(state, config, &hit));
+                land_slide_boost::<Z_UP>(state, input.duck, config, hit.normal_native());
+            }
+            let height = -hit.distance_native();
+            state.origin.add(&axis.up().multiply_scalar(height));
+            if height > 0.0 && height >= config.min_step_event_height {
+                events.push(MovementEvent::SteppedUp { tick: state.tick, height });
+            }
+            state.on_ground = true;
+            state.ground_normal = *hit.normal_native();
+        }
+    }
+
+    // Gliding starts in the air and ends on landing or releasing the glide button
+    state.move_type = match state.move_type {
+        MoveType::Walk if input.glide && !state.on_ground => MoveType::Glide,
+        MoveType::Glide if !input.glide || state.on_ground => MoveType::Walk,
+        move_type => move_type,
+    };
+
+    // Close ground that is too steep to stand on (but not a ceiling) is slid along
+    let slope = down_ray_hit.filter(|hit| {
+        let up = axis.height(hit.normal_native());
+        up > 0.0 && up < config.min_ground_normal_up && hit.distance_native() <= config.ground_snap_distance
+    });
+    set_sliding(state, slope.is_some(), slope.map_or(0, |hit| hit.material()), events);
+
+    update_crouch_slide::<Z_UP>(state, input, config);
+    let was_ducked = state.ducked;
+    update_duck(state, input.duck || must_stay_ducked(state), config, delta_time);
+    update_duck_spam(state, input.duck, config, delta_time);
+    shift_air_duck::<Z_UP>(state, config, was_ducked, down_ray_hit);
+    state.jump_cooldown = (state.jump_cooldown - delta_time).max(0.0);
+    recover_bhop_stamina(state, delta_time);
+
+    update_sprint(state, input, config, state.on_ground && !was_on_ground, delta_time);
+    let (mut wish_dir, mut wish_speed) = wish_velocity::<Z_UP>(input, config, state);
+    modifiers.wish(&ctx(state), &mut wish_dir, &mut wish_speed);
+    let charged_jump = update_jump_charge(state, input, config, delta_time);
+    if state.jump_charging {
+        wish_speed *= config.jump_charge_move_multiplier;
+    }
+
+    // A water jump locks the controls until the player is out of the water
+    if water_jump_move(state, axis, delta_time) {
+        wish_speed = 0.0;
+    }
+
+    // Thrust lifts the player off the ground like a jump, without friction
+    let jetting = update_jetpack_fuel(state, state.jetpack_enabled && input.jet, config, delta_time);
+    if jetting {
+        state.on_ground = false;
+    }
+    let thrust = jetting.then(|| jetpack_thrust_fixed::<Z_UP, LEGACY_GRAVITY>(&state.velocity, &wish_dir, config, delta_time));
+
+    // External forces push before friction and air control see the velocity
+    let external = modifiers.external_acceleration(&ctx(state));
+    state.velocity.add(&external.multiply_scalar(delta_time));
+    if state.on_ground && axis.height(&external) > config.gravity {
+        state.on_ground = false;
+    }
+
+    let dodge = dodge_request(state, input, config, delta_time);
+    if state.on_ground {
+        dodge_landing(state, config);
+        state.air_dashes = 0;
+        state.ground_pounding = false;
+        decay_wall_run_chain(state, config, delta_time);
+    }
+    let recovered = state.dodge_recovery <= 0.0;
+
+    update_jump_cut::<Z_UP>(state, input.jump, config);
+    state.skidding = false;
+    if state.on_ground {
+        let jump_pressed = if config.jump_charge_time > 0.0 {
+            charged_jump.is_some()
+        } else {
+            input.jump && (config.auto_bhop || !state.jump_held)
+        };
+        if config.dodge_enabled && dodge != DodgeDirection::None && recovered {
+            // Dodging replaces the velocity and skips friction like a jump
+            state.velocity = dodge_velocity_fixed::<Z_UP>(dodge, input.yaw, config);
+            state.on_ground = false;
+            state.dodging = true;
+            events.push(MovementEvent::Dodged {
+                tick: state.tick,
+                direction: dodge,
+                wall: false,
+                speed: axis.horizontal(&state.velocity).magnitude(),
+            });
+        } else if jump_pressed && state.jump_cooldown <= 0.0 && recovered {
+            // Jumping skips friction for this tick, which is what makes bhopping work
+            let jump_speed = charged_jump.unwrap_or(1.0) * penalize_bhop::<Z_UP>(state, config) * if config.jump_gravity_scaling {
+                config.jump_speed * state.gravity_scale.max(0.0).sqrt()
+            } else {
+                config.jump_speed
+            };
+            axis.set_height(&mut state.velocity, jump_speed);
+            state.jump_cooldown = config.jump_cooldown;
+            state.on_ground = false;
+            state.jump_count += 1;
+            // A charged jump leaves on the release, so only jumps on the press can be cut short
+            state.jump_cut_ready = charged_jump.is_none();
+            events.push(MovementEvent::Jumped {
+                tick: state.tick,
+                jump_number: state.jump_count,
+                speed: axis.horizontal(&state.velocity).magnitude(),
+            });
+        } else {
+            let (wish_dir, skidding) = momentum_wish_dir::<Z_UP>(state, &wish_dir, wish_speed, config, delta_time);
+            state.skidding = skidding;
+            if input.ski {
+                let slope_change = ski_slope_velocity_change_fixed::<Z_UP, LEGACY_GRAVITY>(&state.ground_normal, config, delta_time);
+                state.velocity.add(&slope_change.multiply_scalar(state.gravity_scale));
+            } else {
+                let base_friction = if state.crouch_sliding {
+                    config.crouch_slide_friction
+                } else if skidding {
+                    config.skid_friction
+                } else {
+                    config.friction
+                };
+                let friction = modifiers.friction(&ctx(state), base_friction);
+                apply_friction(&mut state.velocity, friction, config.stop_speed, delta_time);
+                keep_sliding_under_ceiling::<Z_UP>(state, config);
+            }
+            if !skidding && !state.crouch_sliding {
+                let ground_wish = wish_dir.project_on_plane(&state.ground_normal).normalized();
+                let ground_accelerate = modifiers.accelerate(&ctx(state), config.accelerate);
+                accelerate(&mut state.velocity, &ground_wish, wish_speed, ground_accelerate, delta_time);
+            }
+            state.velocity = state.velocity.project_on_plane(&state.ground_normal);
+        }
+    } else {
+        air_dodge::<Z_UP>(state, input, config, dodge, events);
+        air_dash::<Z_UP>(state, input, config, events);
+        ground_pound::<Z_UP>(state, input, config);
+    }
+
+    if !state.on_ground {
+        let ramp_normal = slope.filter(|_| state.sliding).map(|hit| *hit.normal_native());
+        let speed_before = state.velocity.magnitude();
+        let air_accelerate = modifiers.accelerate(&ctx(state), config.air_accelerate_for(input.forward_move, input.side_move));
+        let air_accelerate = air_accelerate * wish_speed * surf_accelerate_multiplier(ramp_normal.as_ref(), &wish_dir, config);
+        // A partially deflected stick caps the projected speed below its wish speed, like Source's wishspd clamp
+        let max_air_wish_speed = config.max_air_wish_speed.min(wish_speed);
+        let wish_dir = if config.strafe_assist > 0.0 && wish_speed > 0.0 {
+            strafe_assist_wish_dir(
+                &axis.horizontal(&state.velocity),
+                &wish_dir,
+                air_accelerate,
+                max_air_wish_speed,
+                delta_time,
+                config.strafe_assist,
+            )
+        } else {
+            wish_dir
+        };
+        if state.move_type == MoveType::Glide {
+            state.velocity = glide_velocity_fixed::<Z_UP>(&state.velocity, input.yaw, config, delta_time);
+        } else if wish_speed > 0.0 {
+            let acceleration = air_accelerate_get_acceleration(
+                &state.velocity,
+                &wish_dir,
+                wish_speed,
+                air_accelerate,
+                max_air_wish_speed,
+                delta_time,
+            );
+            state.velocity.add(&acceleration);
+        }
+        let gravity_scale =
+            state.gravity_scale * (1.0 - buoyancy(state, input, config)) * gravity_curve_multiplier_fixed::<Z_UP>(state, config);
+        state.velocity.add(&config.gravity_velocity_change_fixed::<Z_UP, LEGACY_GRAVITY>(delta_time).multiply_scalar(gravity_scale));
+        if let Some(thrust) = thrust {
+            state.velocity.add(&thrust);
+        }
+        let max_fall_speed = match state.move_type {
+            MoveType::Glide => config.glide_max_fall_speed,
+            _ => config.max_fall_speed,
+        };
+        if state.move_type == MoveType::Walk {
+            apply_air_drag(&mut state.velocity, config, delta_time);
+        }
+        clamp_fall_speed(&mut state.velocity, axis, max_fall_speed);
+        limit_surf_speed_gain(&mut state.velocity, speed_before, ramp_normal.is_some(), config, delta_time);
+    }
+    modifiers.velocity(&ctx(state), &mut state.velocity);
+
+    // Integrate, landing mid-tick if the fall would pass the ground below
+    let base_velocity = modifiers.base_velocity(&ctx(state)) + water_current_velocity(state);
+    let mut displacement = (state.velocity + base_velocity).multiply_scalar(delta_time);
+    if !state.on_ground && axis.height(&state.velocity) < 0.0 {
+        if let Some(hit) = ground {
+            if -axis.height(&displacement) >= hit.distance_native() {
+                events.push(landed::<Z_UP>(state, config, &hit));
+                land_slide_boost::<Z_UP>(state, input.duck, config, hit.normal_native());
+                cancel_sprint(state, config);
+                axis.set_height(&mut displacement, -hit.distance_native());
+                state.velocity = state.velocity.project_on_plane(hit.normal_native());
+                state.ground_normal = *hit.normal_native();
+                state.on_ground = true;
+            }
+        }
+    }
+    state.origin.add(&displacement);
+
+    if state.on_ground {
+        state.time_on_ground = if was_on_ground { state.time_on_ground + delta_time } else { 0.0 };
+        state.time_in_air = 0.0;
+    } else {
+        state.time_in_air = if was_on_ground { 0.0 } else { state.time_in_air + delta_time };
+        state.time_on_ground = 0.0;
+    }
+    let surface_material = ground.map_or(0, |hit| hit.material());
+    update_footsteps::<Z_UP>(state, config, delta_time, was_on_ground, surface_material, events);
+}
+
+/// Free 3D movement along the view direction, without gravity or collision
+fn fly_move<const Z_UP: bool>(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    modifiers: &mut MovementModifiers,
+) {
+    let ctx = |state: &PlayerState| ModifierContext { state: *state, input, config, delta_time };
+    let (mut wish_dir, amount) = compute_wishdir_fixed::<Z_UP>(input.yaw, input.pitch, input, state.move_type, config);
+    let mut wish_speed = config.max_speed * amount;
+    modifiers.wish(&ctx(state), &mut wish_dir, &mut wish_speed);
+
+    let friction = modifiers.friction(&ctx(state), config.fly_friction);
+    apply_friction(&mut state.velocity, friction, config.stop_speed, delta_time);
+    let fly_accelerate = modifiers.accelerate(&ctx(state), config.accelerate);
+    accelerate(&mut state.velocity, &wish_dir, wish_speed, fly_accelerate, delta_time);
+    modifiers.velocity(&ctx(state), &mut state.velocity);
+    state.origin.add(&state.velocity.multiply_scalar(delta_time));
+
+    state.on_ground = false;
+    state.time_on_ground = 0.0;
+    state.time_in_air = 0.0;
+}
+
+/// Spectator and noclip movement with exponential speed smoothing
+///
+/// The velocity approaches the wished velocity with the move type's
+/// acceleration time constant while speeding up and its deceleration time
+/// constant while slowing down, independent of tick rate.
+fn free_camera_move<const Z_UP: bool>(state: &mut PlayerState, input: &PlayerInput, config: &MovementConfig, delta_time: f32) {
+    let (wish_dir, amount) = compute_wishdir_fixed::<Z_UP>(input.yaw, input.pitch, input, state.move_type, config);
+    let (multiplier, acceleration_time, deceleration_time) = if state.move_type == MoveType::Noclip {
+        (noclip_speed_multiplier(input, config), config.noclip_acceleration_time, config.noclip_deceleration_time)
+    } else {
+        let boost = if input.sprint { config.spectator_boost_multiplier } else { 1.0 };
+        (boost, config.spectator_acceleration_time, config.spectator_deceleration_time)
+    };
+    let target = wish_dir.multiply_scalar(config.max_speed * amount * multiplier);
+
+    let time_constant = if target.length_sq() > state.velocity.length_sq() {
+        acceleration_time
+    } else {
+        deceleration_time
+    };
+    let blend = if time_constant > 0.0 {
+        1.0 - (-delta_time / time_constant).exp()
+    } else {
+        1.0
+    };
+    let mut velocity = state.velocity.multiply_scalar(1.0 - blend);
+    velocity.add(&target.multiply_scalar(blend));
+    state.velocity = velocity;
+    state.origin.add(&state.velocity.multiply_scalar(delta_time));
+
+    state.on_ground = false;
+    state.time_on_ground = 0.0;
+    state.time_in_air = 0.0;
+}
+
+/// Move the duck transition towards the duck button
+///
+/// On the ground the view takes `duck_time` to go down and `unduck_time` to
+/// come back up, and the hull (`ducked`) only switches once the transition is
+/// complete, like Source's FinishDuck/FinishUnDuck. In the air ducking is instant.
+fn update_duck(state: &mut PlayerState, duck: bool, config: &MovementConfig, delta_time: f32) {
+    let target = if duck { 1.0 } else { 0.0 };
+    let factor = duck_spam_factor(state, config);
+    let duration = if duck { config.duck_time } else { config.unduck_time } / factor;
+    let timed = state.on_ground || factor < 1.0;
+    state.duck_amount = if !timed || duration <= 0.0 {
+        target
+    } else {
+        let max_change = delta_time / duration;
+        state.duck_amount + (target - state.duck_amount).clamp(-max_change, max_change)
+    };
+    if state.duck_amount == target {
+        state.ducked = duck;
+    }
+}
+
+/// Horizontal wish direction and wish speed for the input
+fn wish_velocity<const Z_UP: bool>(input: &PlayerInput, config: &MovementConfig, state: &PlayerState) -> (Vector3, f32) {
+    let (wish_dir, amount) = compute_wishdir_fixed::<Z_UP>(input.yaw, input.pitch, input, state.move_type, config);
+    let mut wish_speed = config.max_speed * amount;
+    if duck_slowed(state, config) {
+        wish_speed *= config.duck_multiplier;
+    } else {
+        wish_speed *= sprint_multiplier(state, config);
+    }
+    (wish_dir, wish_speed)
+}
+
+/// Quake/Source ground friction
+fn apply_friction(velocity: &mut Vector3, friction: f32, stop_speed: f32, delta_time: f32) {
+    let speed = velocity.magnitude();
+    if speed < 0.1 {
+        *velocity = Vector3::new(0.0, 0.0, 0.0);
+        return;
+    }
+    let control = speed.max(stop_speed);
+    let drop = control * friction * delta_time;
+    let new_speed = (speed - drop).max(0.0);
+    *velocity = velocity.multiply_scalar(new_speed / speed);
+}
+
+/// Quake/Source ground acceleration towards `wish_speed` along `wish_dir`
+fn accelerate(velocity: &mut Vector3, wish_dir: &Vector3, wish_speed: f32, accel: f32, delta_time: f32) {
+    let current_speed = velocity.dot(wish_dir);
+    let add_speed = wish_speed - current_speed;
+    if add_speed <= 0.0 {
+        return;
+    }
+    let accel_speed = (accel * delta_time * wish_speed).min(add_speed);
+    velocity.add(&wish_dir.multiply_scalar(accel_speed));
+}
+
+fn landed<const Z_UP: bool>(state: &PlayerState, config: &MovementConfig, hit: &RayCollisionHit) -> MovementEvent {
+    let landing_speed = (-UpAxis::from_z_up(Z_UP).height(&state.velocity)).max(0.0);
+    MovementEvent::Landed {
+        tick: state.tick,
+        landing_speed,
+        surface_material: hit.material(),
+        impact: config.landing_impact(landing_speed),
+        ground_pound: state.ground_pounding,
+    }
+}
+
+/// Convert part of the fall into ground speed when landing ducked (Apex style)
+///
+/// Adds `land_slide_ratio` of the landing speed along the horizontal velocity,
+/// or downhill when landing straight down on a slope. The vertical speed is
+/// then projected away by the landing as usual.
+fn land_slide_boost<const Z_UP: bool>(state: &mut PlayerState, duck: bool, config: &MovementConfig, ground_normal: &Vector3) {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let landing_speed = -axis.height(&state.velocity);
+    if !duck || config.land_slide_ratio <= 0.0 || landing_speed < config.land_slide_min_speed {
+        return;
+    }
+    let mut direction = axis.horizontal(&state.velocity).project_on_plane(ground_normal).normalized();
+    if direction.length_sq() == 0.0 {
+        direction = axis.up().multiply_scalar(-1.0).project_on_plane(ground_normal).normalized();
+    }
+    state.velocity.add(&direction.multiply_scalar(landing_speed * config.land_slide_ratio));
+}
+
+/// Count down to the next footstep while moving on the ground
+///
+/// The first step is emitted as soon as the player starts moving. A landing
+/// restarts the cadence, since it has its own event.
+fn update_footsteps<const Z_UP: bool>(
+    state: &mut PlayerState,
+    config: &MovementConfig,
+    delta_time: f32,
+    was_on_ground: bool,
+    surface_material: u32,
+    events: &mut Vec<MovementEvent>,
+) {
+    let speed = UpAxis::from_z_up(Z_UP).horizontal(&state.velocity).magnitude();
+    if !state.on_ground || speed < config.footstep_min_speed {
+        state.footstep_time = 0.0;
+        return;
+    }
+    let interval = config.footstep_interval(speed);
+    if !was_on_ground {
+        state.footstep_time = interval;
+        return;
+    }
+    state.footstep_time -= delta_time;
+    if state.footstep_time <= 0.0 {
+        events.push(MovementEvent::Footstep { tick: state.tick, surface_material, speed });
+        state.footstep_time = (state.footstep_time + interval).max(0.0);
+    }
+}
+
+fn set_sliding(state: &mut PlayerState, sliding: bool, surface_material: u32, events: &mut Vec<MovementEvent>) {
+    match (state.sliding, sliding) {
+        (false, true) => events.push(MovementEvent::SlideStarted { tick: state.tick, surface_material }),
+        (true, false) => events.push(MovementEvent::SlideStopped { tick: state.tick }),
+        _ => {}
+    }
+    state.sliding = sliding;
+}
+
+/// Limit downward speed to a terminal velocity (no limit when `max_fall_speed` is 0.0)
+fn clamp_fall_speed(velocity: &mut Vector3, axis: UpAxis, max_fall_speed: f32) {
+    if max_fall_speed > 0.0 && axis.height(velocity) < -max_fall_speed {
+        axis.set_height(velocity, -max_fall_speed);
+    }
+}
+
+#[cfg(feature = "wasm")]
+use crate::events::{MovementEventBuffer, MovementEventInfo};
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = playerTick)]
+pub fn player_tick_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+) -> Vec<MovementEventInfo> {
+    player_tick(state, input, config, delta_time, down_ray_hit)
+        .into_iter()
+        .map(MovementEventInfo::from)
+        .collect()
+}
+
+/// `playerTick` appending the events to a reused buffer instead of returning a new array
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = playerTickInto)]
+pub fn player_tick_into_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+    events: &mut MovementEventBuffer,
+) {
+    player_tick_into(state, input, config, delta_time, down_ray_hit, &mut events.events);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = playerTickChecked)]
+pub fn player_tick_checked_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_time: f32,
+    down_ray_hit: Option<RayCollisionHit>,
+) -> Result<Vec<MovementEventInfo>, JsValue> {
+    player_tick_checked(state, input, config, delta_time, down_ray_hit)
+        .map(|events| events.into_iter().map(MovementEventInfo::from).collect())
+        .map_err(JsValue::from)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::modifiers::MovementModifier;
+    use crate::MovementError;
+
+    /// `y_up_config()` with Y up and standard gravity, which the
+    /// movement tests are written against whatever the `fixed` features pin
+    pub(crate) fn y_up_config() -> MovementConfig {
+        MovementConfig { up_axis: UpAxis::Y, legacy_gravity: false, ..MovementConfig::default() }
+    }
+
+    /// Ray hit against an infinite floor at y = 0
+    pub(crate) fn flat_ground(state: &PlayerState) -> Option<RayCollisionHit> {
+        if state.origin.y >= 0.0 {
+            Some(RayCollisionHit::new(0.0, 1.0, 0.0, state.origin.y))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn test_ground_acceleration_reaches_max_speed() {
+        let config = y_up_config();
+        let mut state = PlayerState::default();
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+
+        for _ in 0..200 {
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &input, &config, 1.0 / 64.0, hit);
+        }
+        assert!(state.on_ground);
+        assert!((state.velocity.magnitude() - config.max_speed).abs() < 1.0);
+        assert!(state.velocity.z < 0.0);
+    }
+
+    #[test]
+    fn test_jump_and_land() {
+        let config = y_up_config();
+        let mut state = PlayerState::default();
+        let dt = 1.0 / 64.0;
+
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        let hit = flat_ground(&state);
+        player_tick(&mut state, &jump, &config, dt, hit);
+        assert!(!state.on_ground);
+        assert!(state.origin.y > 0.0);
+
+        let mut air_ticks = 0;
+        let idle = PlayerInput::new();
+        while !state.on_ground && air_ticks < 1000 {
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &idle, &config, dt, hit);
+            air_ticks += 1;
+        }
+        assert!(state.on_ground);
+        assert_eq!(state.origin.y, 0.0);
+        // Airtime of a 268.3 u/s jump under 800 u/s² gravity is ~0.67s
+        assert!((air_ticks as f32 * dt - 0.67).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_legacy_gravity_flag() {
+        let config = MovementConfig { legacy_gravity: true, ..y_up_config() };
+        let mut state = PlayerState::new(Vector3::new(0.0, 100.0, 0.0));
+        let dt = 1.0 / 64.0;
+
+        player_tick(&mut state, &PlayerInput::new(), &config, dt, None);
+        let expected = -config.gravity * dt * dt * config.legacy_gravity_scale;
+        assert!((state.velocity.y - expected).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_fixed_instantiations_use_their_constants() {
+        // The constants decide the axis and gravity model, not the config they are handed
+        let config = y_up_config();
+        let dt = 1.0 / 64.0;
+        let fall = |z_up: bool, legacy: bool| {
+            let mut state = PlayerState::new(Vector3::new(0.0, 100.0, 100.0));
+            let (state_ref, input, modifiers) = (&mut state, &PlayerInput::new(), &mut MovementModifiers::new());
+            let events = &mut Vec::new();
+            match (z_up, legacy) {
+                (false, false) => tick_steps::<false, false>(state_ref, input, &config, dt, None, modifiers, events),
+                (false, true) => tick_steps::<false, true>(state_ref, input, &config, dt, None, modifiers, events),
+                (true, false) => tick_steps::<true, false>(state_ref, input, &config, dt, None, modifiers, events),
+                (true, true) => tick_steps::<true, true>(state_ref, input, &config, dt, None, modifiers, events),
+            }
+            state.velocity
+        };
+        let (y_up, z_up, y_legacy, z_legacy) = (fall(false, false), fall(true, false), fall(false, true), fall(true, true));
+
+        assert!(y_up.y < 0.0 && y_up.z == 0.0);
+        assert!(z_up.z < 0.0 && z_up.y == 0.0);
+        assert_eq!(z_up.z, y_up.y);
+        assert!((y_legacy.y - y_up.y * dt * config.legacy_gravity_scale).abs() < 0.0001);
+        assert!(z_legacy.z == y_legacy.y && z_legacy.y == 0.0);
+    }
+
+    #[test]
+    fn test_gravity_scale_zone() {
+        let dt = 1.0 / 64.0;
+        let apex = |config: &MovementConfig, gravity_scale: f32| {
+            let mut state = PlayerState { on_ground: true, gravity_scale, ..PlayerState::default() };
+            let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &jump, config, dt, hit);
+            let mut apex: f32 = 0.0;
+            while state.velocity.y > 0.0 {
+                player_tick(&mut state, &PlayerInput::new(), config, dt, None);
+                apex = apex.max(state.origin.y);
+            }
+            apex
+        };
+        let config = y_up_config();
+        let normal = apex(&config, 1.0);
+        assert!((apex(&config, 0.25) / normal - 4.0).abs() < 0.2);
+
+        let scaled = MovementConfig { jump_gravity_scaling: true, ..config };
+        assert!((apex(&scaled, 0.25) - normal).abs() < normal * 0.05);
+
+        let mut weightless = PlayerState { gravity_scale: 0.0, ..PlayerState::new(Vector3::new(0.0, 100.0, 0.0)) };
+        player_tick(&mut weightless, &PlayerInput::new(), &config, dt, None);
+        assert_eq!(weightless.velocity.y, 0.0);
+    }
+
+    #[test]
+    fn test_terminal_velocity_for_air_and_water() {
+        let config = y_up_config();
+        let dt = 1.0 / 64.0;
+        let mut falling = PlayerState::new(Vector3::new(0.0, 1.0e6, 0.0));
+        let mut sinking = PlayerState { move_type: MoveType::Swim, ..falling };
+
+        for _ in 0..640 {
+            player_tick(&mut falling, &PlayerInput::new(), &config, dt, None);
+            player_tick(&mut sinking, &PlayerInput::new(), &config, dt, None);
+        }
+        assert_eq!(falling.velocity.y, -config.max_fall_speed);
+        assert_eq!(sinking.velocity.y, -config.max_water_fall_speed);
+
+        let unlimited = MovementConfig { max_fall_speed: 0.0, ..config };
+        player_tick(&mut falling, &PlayerInput::new(), &unlimited, dt, None);
+        assert!(falling.velocity.y < -config.max_fall_speed);
+    }
+
+    #[test]
+    fn test_jump_requires_repress_without_auto_bhop() {
+        let config = y_up_config();
+        let mut state = PlayerState { jump_held: true, on_ground: true, ..PlayerState::default() };
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+
+        let hit = flat_ground(&state);
+        player_tick(&mut state, &jump, &config, 1.0 / 64.0, hit);
+        assert!(state.on_ground);
+
+        let auto = MovementConfig { auto_bhop: true, ..config };
+        let hit = flat_ground(&state);
+        player_tick(&mut state, &jump, &auto, 1.0 / 64.0, hit);
+        assert!(!state.on_ground);
+    }
+
+    #[test]
+    fn test_unit_scale_gives_identical_motion() {
+        let scale = crate::config::HAMMER_UNITS_TO_METERS;
+        let hammer = y_up_config();
+        let meters = MovementConfig { unit_scale: scale, ..hammer };
+        let input = PlayerInput { forward_move: 1.0, jump: true, ..PlayerInput::new() };
+
+        let mut a = PlayerState::default();
+        let mut b = PlayerState::default();
+        for _ in 0..100 {
+            let hit = flat_ground(&a);
+            player_tick(&mut a, &input, &hammer, 1.0 / 64.0, hit);
+            let hit = flat_ground(&b);
+            player_tick(&mut b, &input, &meters, 1.0 / 64.0, hit);
+        }
+        assert!((a.origin.z * scale - b.origin.z).abs() < 0.01);
+        assert!((a.origin.y * scale - b.origin.y).abs() < 0.01);
+        assert_eq!(a.on_ground, b.on_ground);
+    }
+
+    #[test]
+    fn test_z_up_matches_y_up() {
+        let y_up = y_up_config();
+        let z_up = MovementConfig { up_axis: crate::UpAxis::Z, ..y_up };
+        let input = PlayerInput { forward_move: 1.0, side_move: 0.5, jump: true, ..PlayerInput::new() };
+
+        let mut a = PlayerState::default();
+        let mut b = PlayerState { ground_normal: Vector3::new(0.0, 0.0, 1.0), ..PlayerState::default() };
+        for _ in 0..100 {
+            let hit = flat_ground(&a);
+            player_tick(&mut a, &input, &y_up, 1.0 / 64.0, hit);
+            let hit = if b.origin.z >= 0.0 { Some(RayCollisionHit::new(0.0, 0.0, 1.0, b.origin.z)) } else { None };
+            player_tick(&mut b, &input, &z_up, 1.0 / 64.0, hit);
+        }
+        assert!((a.origin.y - b.origin.z).abs() < 0.01);
+        assert!((a.velocity.magnitude() - b.velocity.magnitude()).abs() < 0.01);
+        assert_eq!(a.on_ground, b.on_ground);
+    }
+
+    #[test]
+    fn test_invalid_values_never_reach_the_state() {
+        let config = y_up_config();
+        let mut state = PlayerState { velocity: Vector3::new(f32::NAN, 0.0, 0.0), ..PlayerState::default() };
+        let input = PlayerInput { forward_move: f32::NAN, yaw: f32::INFINITY, ..PlayerInput::new() };
+
+        let before = state;
+        player_tick(&mut state, &input, &config, f32::NAN, None);
+        assert_eq!(state.velocity.x.to_bits(), before.velocity.x.to_bits());
+
+        let hit = Some(RayCollisionHit::new(0.0, f32::NAN, 0.0, 0.0));
+        player_tick(&mut state, &input, &config, 1.0 / 64.0, hit);
+        assert!(state.origin.is_finite() && state.velocity.is_finite());
+
+        assert_eq!(
+            player_tick_checked(&mut state, &input, &config, 1.0 / 64.0, None),
+            Err(MovementError::NonFinite { argument: "input.forward_move" }.into())
+        );
+        assert!(matches!(
+            player_tick_checked(&mut state, &PlayerInput::new(), &config, 0.0, None),
+            Err(AirStrafeError::InvalidInput(MovementError::InvalidDeltaTime(_)))
+        ));
+    }
+
+    #[test]
+    fn test_jump_and_land_events() {
+        let config = y_up_config();
+        let mut state = PlayerState::default();
+        let dt = 1.0 / 64.0;
+
+        let hit = flat_ground(&state);
+        player_tick(&mut state, &PlayerInput::new(), &config, dt, hit);
+        let hit = flat_ground(&state);
+        let events = player_tick(&mut state, &PlayerInput { jump: true, ..PlayerInput::new() }, &config, dt, hit);
+        assert_eq!(events, vec![MovementEvent::Jumped { tick: 1, jump_number: 1, speed: 0.0 }]);
+
+        let mut landing = None;
+        while landing.is_none() && state.tick < 1000 {
+            let hit = flat_ground(&state).map(|hit| hit.with_material(7));
+            landing = player_tick(&mut state, &PlayerInput::new(), &config, dt, hit).pop();
+        }
+        match landing {
+            Some(MovementEvent::Landed { landing_speed, surface_material, impact, .. }) => {
+                assert!(landing_speed > 200.0);
+                assert_eq!(surface_material, 7);
+                assert_eq!(impact, config.landing_impact(landing_speed));
+                assert!(impact > 0.0 && impact < 0.5);
+            }
+            other => panic!("expected a landing, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_scroll_presses_jump_unless_jump_was_down() {
+        let config = y_up_config();
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let scroll = PlayerInput { jump_presses: 3, ..PlayerInput::new() };
+
+        let mut fresh = PlayerState { on_ground: true, ..PlayerState::default() };
+        player_tick(&mut fresh, &scroll, &config, 1.0 / 64.0, ground);
+        assert_eq!(fresh.jump_count, 1);
+        assert!(fresh.jump_held);
+
+        // A scroll press on the tick after another one is swallowed, like a held button
+        let mut spammed = PlayerState { on_ground: true, jump_held: true, ..PlayerState::default() };
+        player_tick(&mut spammed, &scroll, &config, 1.0 / 64.0, ground);
+        assert_eq!(spammed.jump_count, 0);
+    }
+
+    #[test]
+    fn test_slide_events_on_steep_ground() {
+        let config = y_up_config();
+        let mut state = PlayerState::new(Vector3::new(0.0, 1.0, 0.0));
+        let steep = Some(RayCollisionHit::new(0.8, 0.6, 0.0, 1.0));
+
+        let events = player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, steep);
+        assert!(matches!(events.as_slice(), [MovementEvent::SlideStarted { tick: 0, .. }]));
+        assert!(state.sliding && !state.on_ground);
+
+        let events = player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, None);
+        assert_eq!(events, vec![MovementEvent::SlideStopped { tick: 1 }]);
+    }
+
+    #[test]
+    fn test_step_events_and_ledges_above_step_height() {
+        let config = y_up_config();
+        let dt = 1.0 / 64.0;
+        let floor = |height: f32| Some(RayCollisionHit::new(0.0, 1.0, 0.0, -height));
+        let stepped = |events: &[MovementEvent]| {
+            events.iter().find_map(|event| match *event {
+                MovementEvent::SteppedUp { height, .. } => Some(height),
+                _ => None,
+            })
+        };
+        // Steps lower than the snap distance are reported too, down to `min_step_event_height`
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+        assert_eq!(stepped(&player_tick(&mut state, &PlayerInput::new(), &config, dt, floor(1.5))), Some(1.5));
+        assert_eq!(stepped(&player_tick(&mut state, &PlayerInput::new(), &config, dt, floor(0.5))), None);
+        assert!(state.on_ground && state.origin.y == 2.0);
+
+        // A ledge just above the step height is a wall: it neither lifts nor grounds the player
+        let mut state = PlayerState { on_ground: true, ..PlayerState::default() };
+        let jump = PlayerInput { jump: true, ..PlayerInput::new() };
+        let events = player_tick(&mut state, &jump, &config, dt, floor(config.step_height + 0.1));
+        assert!(events.is_empty());
+        assert!(!state.on_ground && state.origin.y <= 0.0);
+        // Back over the floor it lands where it would have without the ledge
+        let hit = floor(-state.origin.y);
+        player_tick(&mut state, &PlayerInput::new(), &config, dt, hit);
+        assert!(state.on_ground);
+        assert_eq!(state.origin.y, 0.0);
+    }
+
+    #[test]
+    fn test_ducked_landing_converts_fall_into_slide_speed() {
+        let config = MovementConfig { land_slide_ratio: 0.5, ..y_up_config() };
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 4.0));
+        let falling = PlayerState { velocity: Vector3::new(0.0, -600.0, -200.0), ..PlayerState::default() };
+        let duck = PlayerInput { duck: true, ..PlayerInput::new() };
+
+        let mut standing = falling;
+        let mut ducked = falling;
+        player_tick(&mut standing, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+        player_tick(&mut ducked, &duck, &config, 1.0 / 64.0, ground);
+        assert!(standing.on_ground && ducked.on_ground);
+        assert_eq!(standing.velocity.z, -200.0);
+        // Half of the 600 fall speed plus one tick of gravity
+        assert_eq!(ducked.velocity.y, 0.0);
+        assert!((ducked.velocity.z + 200.0 + 0.5 * (600.0 + config.gravity / 64.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_footsteps_follow_speed_on_ground() {
+        let config = y_up_config();
+        let mut state = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).build().unwrap();
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let dt = 1.0 / 64.0;
+
+        let step = |state: &mut PlayerState| {
+            let hit = flat_ground(state).map(|hit| hit.with_material(3));
+            player_tick(state, &input, &config, dt, hit)
+        };
+
+        // Get up to full speed, then count the steps over three seconds
+        for _ in 0..128 {
+            step(&mut state);
+        }
+        let steps: Vec<MovementEvent> = (0..192).flat_map(|_| step(&mut state)).collect();
+        assert_eq!(steps.len(), 10);
+        assert!(matches!(steps[0], MovementEvent::Footstep { surface_material: 3, speed, .. } if speed > 300.0));
+        assert!((config.footstep_interval(config.max_speed) - config.footstep_interval_fast).abs() < 0.0001);
+
+        // No steps while standing still or in the air
+        let mut idle = PlayerState::builder().on_ground(Vector3::new(0.0, 1.0, 0.0)).build().unwrap();
+        for _ in 0..64 {
+            let hit = flat_ground(&idle);
+            assert!(player_tick(&mut idle, &PlayerInput::new(), &config, dt, hit).is_empty());
+        }
+        let mut airborne = PlayerState { velocity: Vector3::new(300.0, 0.0, 0.0), ..PlayerState::new(Vector3::new(0.0, 500.0, 0.0)) };
+        for _ in 0..64 {
+            assert!(player_tick(&mut airborne, &input, &config, dt, None).is_empty());
+        }
+    }
+
+    /// Slow field that halves wish speed and stops all upward velocity
+    struct SlowField;
+
+    impl MovementModifier for SlowField {
+        fn modify_wish(&mut self, _ctx: &ModifierContext, _wish_dir: &mut Vector3, wish_speed: &mut f32) {
+            *wish_speed *= 0.5;
+        }
+
+        fn modify_velocity(&mut self, _ctx: &ModifierContext, velocity: &mut Vector3) {
+            velocity.set_y_native(velocity.y.min(0.0));
+        }
+    }
+
+    #[test]
+    fn test_modifiers_run_at_each_stage() {
+        let config = y_up_config();
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("slow", Box::new(SlowField));
+        let mut state = PlayerState::default();
+        let input = PlayerInput { forward_move: 1.0, jump: true, ..PlayerInput::new() };
+
+        for _ in 0..200 {
+            let hit = flat_ground(&state);
+            player_tick_with_modifiers(&mut state, &input, &config, 1.0 / 64.0, hit, &mut modifiers);
+        }
+        // No jump ever gets off the ground and ground speed is capped at half
+        assert!(state.on_ground);
+        assert!((state.velocity.magnitude() - config.max_speed * 0.5).abs() < 1.0);
+
+        assert!(modifiers.remove("slow"));
+        assert!(modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_fly_follows_view_without_gravity() {
+        let config = y_up_config();
+        let mut state = PlayerState { move_type: MoveType::Fly, ..PlayerState::default() };
+        // Looking 45 degrees up
+        let input = PlayerInput { forward_move: 1.0, pitch: std::f32::consts::FRAC_PI_4, ..PlayerInput::new() };
+
+        for _ in 0..200 {
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &input, &config, 1.0 / 64.0, hit);
+        }
+        assert!(!state.on_ground);
+        assert!((state.velocity.magnitude() - config.max_speed).abs() < 1.0);
+        assert!((state.velocity.y + state.velocity.z).abs() < 0.01);
+        assert!(state.origin.y > 0.0);
+
+        // Releasing input stops the player through fly friction, without falling
+        for _ in 0..200 {
+            player_tick(&mut state, &PlayerInput::new(), &config, 1.0 / 64.0, None);
+        }
+        assert_eq!(state.velocity.magnitude(), 0.0);
+    }
+
+    #[test]
+    fn test_noclip_ignores_ground() {
+        let config = y_up_config();
+        let mut state = PlayerState { move_type: MoveType::Noclip, ..PlayerState::default() };
+        let input = PlayerInput { up_move: -1.0, ..PlayerInput::new() };
+
+        for _ in 0..64 {
+            let hit = flat_ground(&state);
+            player_tick(&mut state, &input, &config, 1.0 / 64.0, hit);
+        }
+        assert!(state.origin.y < -100.0);
+    }
+
+    #[test]
+    fn test_spectator_smoothing_is_tick_rate_independent() {
+        let config = y_up_config();
+        let input = PlayerInput { forward_move: 1.0, sprint: true, ..PlayerInput::new() };
+        let boosted = config.spectator_speed * config.spectator_boost_multiplier;
+
+        let mut fast = PlayerState { move_type: MoveType::Spectator, ..PlayerState::default() };
+        let mut slow = fast;
+        for _ in 0..256 {
+            player_tick(&mut fast, &input, &config, 1.0 / 128.0, None);
+        }
+        for _ in 0..64 {
+            player_tick(&mut slow, &input, &config, 1.0 / 32.0, None);
+        }
+        // After two seconds both have converged to the boosted speed
+        assert!((fast.velocity.magnitude() - boosted).abs() < 1.0);
+        assert!((fast.velocity.magnitude() - slow.velocity.magnitude()).abs() < 0.01);
+
+        // One deceleration time constant removes ~63% of the speed
+        let ticks = (config.spectator_deceleration_time * 128.0) as usize;
+        for _ in 0..ticks {
+            player_tick(&mut fast, &PlayerInput::new(), &config, 1.0 / 128.0, None);
+        }
+        let ratio = fast.velocity.magnitude() / boosted;
+        assert!((ratio - (-1.0f32).exp()).abs() < 0.02);
+    }
+}
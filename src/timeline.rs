@@ -0,0 +1,252 @@
+//! Timestamped event timelines
+//!
+//! Video overlays and run reviews want to know when things happened, not
+//! which tick of which frame emitted them. A `Timeline` collects the events
+//! returned by `player_tick` together with markers the host adds for things
+//! the movement does not see (zones crossed, checkpoints, map triggers), and
+//! exports them as JSON with each entry's time in seconds since tick 0:
+//!
+//! ```json
+//! {
+//!   "tick_rate": 64,
+//!   "entries": [
+//!     {"time": 0.5, "tick": 32, "kind": "jumped", "speed": 250, "jump_number": 1},
+//!     {"time": 1.25, "tick": 80, "kind": "marker", "label": "checkpoint 1"},
+//!     {"time": 1.3125, "tick": 84, "kind": "slide_started", "surface_material": 0}
+//!   ]
+//! }
+//! ```
+//!
+//! Ramps touched show up as `slide_started` / `slide_stopped`. Footsteps are
+//! left out unless `with_footsteps` is set, since they would drown out
+//! everything else on an overlay.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::config_format::json_string;
+use crate::{MovementEvent, MovementEventInfo, MovementEventKind};
+
+/// One entry of a `Timeline`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineEntry {
+    /// A movement event
+    Event(MovementEventInfo),
+    /// A host-defined marker, e.g. a zone the player entered
+    Marker { tick: u64, label: String },
+}
+
+impl TimelineEntry {
+    /// The tick the entry happened on
+    pub fn tick(&self) -> u64 {
+        match self {
+            TimelineEntry::Event(event) => event.tick,
+            TimelineEntry::Marker { tick, .. } => *tick,
+        }
+    }
+}
+
+/// Movement events and markers in tick order
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    tick_rate: f32,
+    footsteps: bool,
+    entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    /// Create an empty timeline for a simulation running at `tick_rate` ticks per second
+    pub fn new(tick_rate: f32) -> Timeline {
+        Timeline { tick_rate, footsteps: false, entries: Vec::new() }
+    }
+
+    /// Keep `Footstep` events instead of dropping them
+    pub fn with_footsteps(mut self, footsteps: bool) -> Timeline {
+        self.footsteps = footsteps;
+        self
+    }
+
+    /// Add the events of a tick
+    pub fn record(&mut self, events: &[MovementEvent]) {
+        for &event in events {
+            self.record_event(MovementEventInfo::from(event));
+        }
+    }
+
+    /// Add a single event
+    pub fn record_event(&mut self, event: MovementEventInfo) {
+        if event.kind != MovementEventKind::Footstep || self.footsteps {
+            self.insert(TimelineEntry::Event(event));
+        }
+    }
+
+    /// Add a marker at `tick`, after any entry already on that tick
+    pub fn mark(&mut self, tick: u64, label: &str) {
+        self.insert(TimelineEntry::Marker { tick, label: label.to_string() });
+    }
+
+    fn insert(&mut self, entry: TimelineEntry) {
+        // Markers may be added late, so keep the entries sorted on insertion
+        let index = self.entries.partition_point(|e| e.tick() <= entry.tick());
+        self.entries.insert(index, entry);
+    }
+
+    /// The entries in tick order
+    pub fn entries(&self) -> &[TimelineEntry] {
+        &self.entries
+    }
+
+    /// Seconds since tick 0 at which `tick` happened
+    pub fn time(&self, tick: u64) -> f32 {
+        tick as f32 / self.tick_rate
+    }
+
+    /// The timeline as JSON, see the module docs
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let tick = entry.tick();
+                let mut json = format!("    {{\"time\": {}, \"tick\": {tick}", self.time(tick));
+                match entry {
+                    TimelineEntry::Event(event) => {
+                        json += &format!(", \"kind\": \"{}\"", event.kind.name());
+                        json += &event_fields(event);
+                    }
+                    TimelineEntry::Marker { label, .. } => {
+                        json += &format!(", \"kind\": \"marker\", \"label\": {}", json_string(label));
+                    }
+                }
+                json + "}"
+            })
+            .collect();
+        format!("{{\n  \"tick_rate\": {},\n  \"entries\": [\n{}\n  ]\n}}\n", self.tick_rate, entries.join(",\n"))
+    }
+}
+
+/// The fields of `event` that apply to its kind, as JSON members
+fn event_fields(event: &MovementEventInfo) -> String {
+    let speed = format!(", \"speed\": {}", event.speed);
+    let material = format!(", \"surface_material\": {}", event.surface_material);
+    match event.kind {
+        MovementEventKind::Jumped => format!("{speed}, \"jump_number\": {}", event.jump_number),
+        MovementEventKind::Landed => {
+            format!("{speed}{material}, \"impact\": {}, \"ground_pound\": {}", event.impact, event.ground_pound)
+        }
+        MovementEventKind::SlideStarted => material,
+        MovementEventKind::SlideStopped => String::new(),
+        MovementEventKind::Footstep => format!("{speed}{material}"),
+        MovementEventKind::Dodged
+        | MovementEventKind::AirDashed
+        | MovementEventKind::Jumpbugged
+        | MovementEventKind::Edgebugged
+        | MovementEventKind::Overbounced => speed,
+        MovementEventKind::SteppedUp => format!(", \"height\": {}", event.height),
+        MovementEventKind::TriggerEntered | MovementEventKind::TriggerStayed | MovementEventKind::TriggerExited => {
+            format!(", \"trigger\": {}", event.trigger)
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Timeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(tick_rate: f32, footsteps: bool) -> Timeline {
+        Timeline::new(tick_rate).with_footsteps(footsteps)
+    }
+
+    /// Add one of the events returned by `playerTick`
+    #[wasm_bindgen(js_name = recordEvent)]
+    pub fn record_event_wasm(&mut self, event: &MovementEventInfo) {
+        self.record_event(*event);
+    }
+
+    #[wasm_bindgen(js_name = mark)]
+    pub fn mark_wasm(&mut self, tick: u64, label: &str) {
+        self.mark(tick, label);
+    }
+
+    #[wasm_bindgen(getter = length)]
+    pub fn len_wasm(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json_wasm(&self) -> String {
+        self.to_json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_format::{parse_json_value, JsonValue};
+    use crate::events::tests::every_event;
+    use crate::tick::tests::{flat_ground, y_up_config};
+    use crate::{player_tick, PlayerInput, PlayerState, Vector3};
+
+    #[test]
+    fn test_exports_jumps_landings_and_markers_in_order() {
+        let config = y_up_config();
+        let mut state = PlayerState { on_ground: true, velocity: Vector3::new(250.0, 0.0, 0.0), ..PlayerState::default() };
+        let mut timeline = Timeline::new(64.0);
+        for tick in 0..128 {
+            let input = PlayerInput { forward_move: 1.0, jump: tick == 32, ..PlayerInput::new() };
+            let ground = flat_ground(&state);
+            timeline.record(&player_tick(&mut state, &input, &config, 1.0 / 64.0, ground));
+        }
+        timeline.mark(40, "zone start");
+
+        let kinds: Vec<_> = timeline
+            .entries()
+            .iter()
+            .map(|entry| match entry {
+                TimelineEntry::Event(event) => event.kind.name(),
+                TimelineEntry::Marker { .. } => "marker",
+            })
+            .collect();
+        assert_eq!(kinds, ["jumped", "marker", "landed"]);
+
+        let json = parse_json_value(&timeline.to_json()).unwrap();
+        let Some(JsonValue::Array(entries)) = json.get("entries") else { panic!("no entries") };
+        assert_eq!(entries[0].get("time"), Some(&JsonValue::Number(0.5)));
+        assert_eq!(entries[0].get("jump_number"), Some(&JsonValue::Number(1.0)));
+        assert!(entries[2].get("impact").is_some() && entries[2].get("jump_number").is_none());
+    }
+    #[test]
+    fn test_footsteps_late_markers_and_every_kind() {
+        let footstep = MovementEvent::Footstep { tick: 3, surface_material: 0, speed: 250.0 };
+        let mut timeline = Timeline::new(64.0);
+        timeline.record(&[footstep]);
+        assert!(timeline.entries().is_empty());
+        let json = parse_json_value(&timeline.to_json()).unwrap();
+        assert_eq!(json.get("entries"), Some(&JsonValue::Array(Vec::new())));
+
+        // Late markers are sorted in, after the entries already on their tick
+        let mut timeline = Timeline::new(64.0).with_footsteps(true);
+        timeline.record(&[footstep]);
+        timeline.mark(5, "b");
+        timeline.mark(3, "a \"quoted\"");
+        timeline.mark(0, "start");
+        let ticks: Vec<u64> = timeline.entries().iter().map(TimelineEntry::tick).collect();
+        assert_eq!(ticks, [0, 3, 3, 5]);
+        assert!(matches!(timeline.entries()[1], TimelineEntry::Event(_)));
+        let json = parse_json_value(&timeline.to_json()).unwrap();
+        let Some(JsonValue::Array(entries)) = json.get("entries") else { panic!("no entries") };
+        assert_eq!(entries[2].get("label"), Some(&JsonValue::String("a \"quoted\"".to_string())));
+
+        // Every kind exports as valid JSON with its name
+        let mut timeline = Timeline::new(128.0).with_footsteps(true);
+        timeline.record(&every_event());
+        let json = parse_json_value(&timeline.to_json()).unwrap();
+        let Some(JsonValue::Array(entries)) = json.get("entries") else { panic!("no entries") };
+        assert_eq!(entries.len(), every_event().len());
+        for (entry, event) in entries.iter().zip(every_event()) {
+            let kind = MovementEventInfo::from(event).kind.name().to_string();
+            assert_eq!(entry.get("kind"), Some(&JsonValue::String(kind)));
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Trigger volumes
+//!
+//! Start zones, teleports and boosters need to know when a player enters or
+//! leaves a region, which hosts would otherwise wire up in their engine. A
+//! `TriggerVolumes` holds boxes, spheres and convex volumes and is a
+//! `MovementModifier`: register it on a player with `MovementModifiers::push`
+//! and after every move it pushes `TriggerEntered`, `TriggerStayed` and
+//! `TriggerExited` events onto the tick's events.
+//!
+//! Triggers test the player's origin (the feet), along the whole segment it
+//! moved during the tick rather than only where it ended up, so a fast player
+//! crossing a thin trigger within one tick still gets an enter and an exit on
+//! that tick. Volumes are in world units. Like other modifiers they are not
+//! available through WASM.
+
+use crate::events::MovementEvent;
+use crate::modifiers::{ModifierContext, MovementModifier};
+use crate::Vector3;
+
+/// Id of a trigger in a `TriggerVolumes`, as reported by its events
+pub type TriggerId = u32;
+
+/// Region covered by a trigger
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerShape {
+    /// Axis-aligned box between two corners, see `TriggerShape::aabb`
+    Aabb { min: Vector3, max: Vector3 },
+    Sphere { center: Vector3, radius: f32 },
+    /// Intersection of half-spaces, each given by an outward normal and the
+    /// distance of its plane from the world origin along that normal
+    Convex { planes: Vec<(Vector3, f32)> },
+}
+
+impl TriggerShape {
+    /// A box spanning two corners in any order
+    pub fn aabb(corner_a: Vector3, corner_b: Vector3) -> TriggerShape {
+        TriggerShape::Aabb {
+            min: Vector3::new(corner_a.x.min(corner_b.x), corner_a.y.min(corner_b.y), corner_a.z.min(corner_b.z)),
+            max: Vector3::new(corner_a.x.max(corner_b.x), corner_a.y.max(corner_b.y), corner_a.z.max(corner_b.z)),
+        }
+    }
+
+    /// Whether a point lies inside the shape or on its surface
+    pub fn contains(&self, point: &Vector3) -> bool {
+        self.segment_overlap(point, point).is_some()
+    }
+
+    /// The part of the segment from `from` to `to` inside the shape
+    ///
+    /// # Returns
+    /// The first and last fraction of the segment (0.0 at `from`, 1.0 at `to`)
+    /// inside the shape, or `None` if the segment misses it
+    pub fn segment_overlap(&self, from: &Vector3, to: &Vector3) -> Option<(f32, f32)> {
+        let direction = *to - *from;
+        let (mut enter, mut exit) = (0.0f32, 1.0f32);
+        // Clip the segment against one half-space `normal · point <= distance`
+        let mut clip = |normal: Vector3, distance: f32| {
+            let outside = normal.dot(from) - distance;
+            let along = normal.dot(&direction);
+            if along == 0.0 {
+                return outside <= 0.0;
+            }
+            let t = -outside / along;
+            if along < 0.0 {
+                enter = enter.max(t);
+            } else {
+                exit = exit.min(t);
+            }
+            enter <= exit
+        };
+        let inside = match self {
+            TriggerShape::Aabb { min, max } => {
+                let axes = [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)];
+                axes.iter().all(|axis| clip(*axis, axis.dot(max)) && clip(-*axis, -axis.dot(min)))
+            }
+            TriggerShape::Convex { planes } => planes.iter().all(|&(normal, distance)| clip(normal, distance)),
+            TriggerShape::Sphere { center, radius } => {
+                let offset = *from - *center;
+                let a = direction.length_sq();
+                let c = offset.length_sq() - radius * radius;
+                if a == 0.0 {
+                    c <= 0.0
+                } else {
+                    let b = offset.dot(&direction);
+                    let discriminant = b * b - a * c;
+                    discriminant >= 0.0 && {
+                        let root = discriminant.sqrt();
+                        enter = enter.max((-b - root) / a);
+                        exit = exit.min((-b + root) / a);
+                        enter <= exit
+                    }
+                }
+            }
+        };
+        inside.then_some((enter, exit))
+    }
+}
+
+/// The trigger volumes of a level, checked as a movement modifier
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TriggerVolumes {
+    volumes: Vec<(TriggerId, TriggerShape)>,
+    /// Triggers the player was inside after the last move
+    inside: Vec<TriggerId>,
+    next_id: TriggerId,
+}
+
+impl TriggerVolumes {
+    /// Create an empty trigger list
+    pub fn new() -> TriggerVolumes {
+        TriggerVolumes::default()
+    }
+
+    /// Register a trigger and return the id its events carry
+    pub fn add(&mut self, shape: TriggerShape) -> TriggerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.volumes.push((id, shape));
+        id
+    }
+
+    /// Remove a trigger without reporting an exit, returning whether it existed
+    pub fn remove(&mut self, id: TriggerId) -> bool {
+        self.inside.retain(|&inside| inside != id);
+        let before = self.volumes.len();
+        self.volumes.retain(|&(existing, _)| existing != id);
+        self.volumes.len() != before
+    }
+
+    /// Remove every trigger
+    pub fn clear(&mut self) {
+        self.volumes.clear();
+        self.inside.clear();
+    }
+
+    /// Number of registered triggers
+    pub fn len(&self) -> usize {
+        self.volumes.len()
+    }
+
+    /// Whether no trigger is registered
+    pub fn is_empty(&self) -> bool {
+        self.volumes.is_empty()
+    }
+
+    /// The triggers the player was inside after the last move
+    pub fn inside(&self) -> &[TriggerId] {
+        &self.inside
+    }
+
+    /// Check the move from `from` to `to` and push the events of tick `tick`
+    pub fn touch(&mut self, from: &Vector3, to: &Vector3, tick: u64, events: &mut Vec<MovementEvent>) {
+        for (id, shape) in &self.volumes {
+            let trigger = *id;
+            let was_inside = self.inside.contains(&trigger);
+            let overlap = shape.segment_overlap(from, to);
+            let now_inside = overlap.is_some_and(|(_, exit)| exit >= 1.0);
+            match (was_inside, now_inside) {
+                (false, true) => {
+                    events.push(MovementEvent::TriggerEntered { tick, trigger });
+                    self.inside.push(trigger);
+                }
+                (true, true) => events.push(MovementEvent::TriggerStayed { tick, trigger }),
+                (true, false) => {
+                    events.push(MovementEvent::TriggerExited { tick, trigger });
+                    self.inside.retain(|&inside| inside != trigger);
+                }
+                (false, false) if overlap.is_some() => {
+                    events.push(MovementEvent::TriggerEntered { tick, trigger });
+                    events.push(MovementEvent::TriggerExited { tick, trigger });
+                }
+                (false, false) => {}
+            }
+        }
+    }
+}
+
+impl MovementModifier for TriggerVolumes {
+    fn after_move(&mut self, ctx: &ModifierContext, previous_origin: &Vector3, events: &mut Vec<MovementEvent>) {
+        self.touch(previous_origin, &ctx.state.origin, ctx.state.tick, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick_with_modifiers, MovementConfig, MovementModifiers, PlayerInput, PlayerState};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_shapes_clip_segments() {
+        let slab = TriggerShape::aabb(Vector3::new(12.0, 10.0, 10.0), Vector3::new(8.0, -10.0, -10.0));
+        let (from, to) = (Vector3::new(0.0, 0.0, 0.0), Vector3::new(20.0, 0.0, 0.0));
+        assert_eq!(slab.segment_overlap(&from, &to), Some((0.4, 0.6)));
+        assert!(slab.contains(&Vector3::new(10.0, 0.0, 0.0)) && !slab.contains(&from));
+
+        let ball = TriggerShape::Sphere { center: Vector3::new(10.0, 0.0, 0.0), radius: 5.0 };
+        assert_eq!(ball.segment_overlap(&from, &to), Some((0.25, 0.75)));
+        assert_eq!(ball.segment_overlap(&Vector3::new(0.0, 6.0, 0.0), &Vector3::new(20.0, 6.0, 0.0)), None);
+
+        // A wedge below the plane x + y = 10 and above y = 0
+        let diagonal = Vector3::new(1.0, 1.0, 0.0).normalized();
+        let planes = vec![(diagonal, 10.0 * diagonal.x), (Vector3::new(0.0, -1.0, 0.0), 0.0)];
+        let wedge = TriggerShape::Convex { planes };
+        assert!(wedge.contains(&Vector3::new(5.0, 4.0, 0.0)) && !wedge.contains(&Vector3::new(5.0, 6.0, 0.0)));
+        let (enter, exit) = wedge.segment_overlap(&Vector3::new(0.0, 5.0, 0.0), &Vector3::new(10.0, 5.0, 0.0)).unwrap();
+        assert!(enter == 0.0 && (exit - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_moves_report_enter_stay_and_exit() {
+        let mut triggers = TriggerVolumes::new();
+        let big = 1000.0;
+        let thin = triggers.add(TriggerShape::aabb(Vector3::new(110.0, -big, -big), Vector3::new(112.0, big, big)));
+        let zone = triggers.add(TriggerShape::aabb(Vector3::new(200.0, -big, -big), Vector3::new(300.0, big, big)));
+        let mut modifiers = MovementModifiers::new();
+        modifiers.push("triggers", Box::new(triggers));
+
+        // Fly through the air at 20 units per tick
+        let config = MovementConfig { gravity: 0.0, ..y_up_config() };
+        let mut state = PlayerState::new(Vector3::new(1.0, 100.0, 0.0));
+        state.velocity.x = 1280.0;
+        let mut events = Vec::new();
+        for _ in 0..20 {
+            let input = PlayerInput::new();
+            events.extend(player_tick_with_modifiers(&mut state, &input, &config, 1.0 / 64.0, None, &mut modifiers));
+        }
+
+        // The thin trigger is crossed within one tick
+        let entered = MovementEvent::TriggerEntered { tick: 5, trigger: thin };
+        let exited = MovementEvent::TriggerExited { tick: 5, trigger: thin };
+        let position = events.iter().position(|event| *event == entered).unwrap();
+        assert_eq!(events[position + 1], exited);
+        let stayed = events.iter().filter(|event| matches!(event, MovementEvent::TriggerStayed { .. })).count();
+        assert_eq!(stayed, 4);
+        assert!(events.contains(&MovementEvent::TriggerEntered { tick: 9, trigger: zone }));
+        assert!(events.contains(&MovementEvent::TriggerExited { tick: 14, trigger: zone }));
+    }
+}
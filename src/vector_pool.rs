@@ -0,0 +1,240 @@
+//! Vector handles for allocation-free WASM game loops
+//!
+//! Every `Vector3` handed across the WASM boundary is a JS object wrapping a
+//! boxed vector, so a game loop calling `airAccelerate` every frame feeds the
+//! garbage collector. A `Vector3Pool` keeps its vectors in WASM memory and
+//! gives JS plain `u32` handles to them: acquire scratch handles, run the
+//! movement functions on handles, read the components back and call
+//! `release_all` once per frame. Once the pool has grown to the frame's peak
+//! usage nothing is allocated on either side.
+//!
+//! Handles stay valid until the next `release_all`. Passing a handle the pool
+//! never gave out panics.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    air_accelerate_get_acceleration, gravity_velocity_change_native, player_move_core, RayCollisionHit, Vector3,
+};
+
+/// Preallocated vectors addressed by handle
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vector3Pool {
+    vectors: Vec<Vector3>,
+    used: usize,
+}
+
+impl Vector3Pool {
+    /// Create a pool with room for `capacity` vectors before it has to grow
+    pub fn new(capacity: usize) -> Vector3Pool {
+        Vector3Pool { vectors: vec![Vector3::new(0.0, 0.0, 0.0); capacity], used: 0 }
+    }
+
+    /// Handle of a zeroed scratch vector, valid until the next `release_all`
+    pub fn acquire(&mut self) -> u32 {
+        if self.used == self.vectors.len() {
+            // Grows only while warming up to the frame's peak usage
+            self.vectors.push(Vector3::new(0.0, 0.0, 0.0));
+        }
+        self.vectors[self.used] = Vector3::new(0.0, 0.0, 0.0);
+        self.used += 1;
+        (self.used - 1) as u32
+    }
+
+    /// Hand every vector back to the pool, invalidating all handles
+    pub fn release_all(&mut self) {
+        self.used = 0;
+    }
+
+    /// Number of handles given out since the last `release_all`
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Number of vectors the pool holds
+    pub fn capacity(&self) -> usize {
+        self.vectors.len()
+    }
+
+    /// The vector behind a handle
+    pub fn get(&self, handle: u32) -> Vector3 {
+        self.vectors[..self.used][handle as usize]
+    }
+
+    /// Overwrite the vector behind a handle
+    pub fn set(&mut self, handle: u32, vector: Vector3) {
+        self.vectors[..self.used][handle as usize] = vector;
+    }
+
+    /// Write `a + b` into `out`
+    pub fn add(&mut self, a: u32, b: u32, out: u32) {
+        let sum = self.get(a) + self.get(b);
+        self.set(out, sum);
+    }
+
+    /// `air_accelerate_get_acceleration` on handles, writing the acceleration into `out`
+    #[allow(clippy::too_many_arguments)]
+    pub fn air_accelerate(
+        &mut self,
+        current_vel: u32,
+        wish_dir: u32,
+        wish_speed: f32,
+        air_accelerate: f32,
+        max_air_wish_speed: f32,
+        delta_time: f32,
+        out: u32,
+    ) {
+        let acceleration = air_accelerate_get_acceleration(
+            &self.get(current_vel),
+            &self.get(wish_dir),
+            wish_speed,
+            air_accelerate,
+            max_air_wish_speed,
+            delta_time,
+        );
+        self.set(out, acceleration);
+    }
+
+    /// `player_move_core` on handles, writing the movement into `out`
+    pub fn player_move(
+        &mut self,
+        direction: u32,
+        delta_time: f32,
+        speed_multiplier: f32,
+        down_ray_hit: Option<RayCollisionHit>,
+        out: u32,
+    ) {
+        let movement = player_move_core(&self.get(direction), delta_time, speed_multiplier, down_ray_hit);
+        self.set(out, movement);
+    }
+
+    /// `gravity_velocity_change_native` on handles, writing the velocity change into `out`
+    pub fn gravity_velocity_change(&mut self, gravity: u32, delta: f32, legacy_scale: Option<f32>, out: u32) {
+        let change = gravity_velocity_change_native(&self.get(gravity), delta, legacy_scale);
+        self.set(out, change);
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Vector3Pool {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(capacity: u32) -> Vector3Pool {
+        Vector3Pool::new(capacity as usize)
+    }
+
+    #[wasm_bindgen(js_name = acquire)]
+    pub fn acquire_wasm(&mut self) -> u32 {
+        self.acquire()
+    }
+
+    #[wasm_bindgen(js_name = releaseAll)]
+    pub fn release_all_wasm(&mut self) {
+        self.release_all();
+    }
+
+    #[wasm_bindgen(js_name = set)]
+    pub fn set_wasm(&mut self, handle: u32, x: f32, y: f32, z: f32) {
+        self.set(handle, Vector3::new(x, y, z));
+    }
+
+    #[wasm_bindgen(js_name = x)]
+    pub fn x_wasm(&self, handle: u32) -> f32 {
+        self.get(handle).x
+    }
+
+    #[wasm_bindgen(js_name = y)]
+    pub fn y_wasm(&self, handle: u32) -> f32 {
+        self.get(handle).y
+    }
+
+    #[wasm_bindgen(js_name = z)]
+    pub fn z_wasm(&self, handle: u32) -> f32 {
+        self.get(handle).z
+    }
+
+    #[wasm_bindgen(js_name = add)]
+    pub fn add_wasm(&mut self, a: u32, b: u32, out: u32) {
+        self.add(a, b, out);
+    }
+
+    #[wasm_bindgen(js_name = airAccelerate)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn air_accelerate_wasm(
+        &mut self,
+        current_vel: u32,
+        wish_dir: u32,
+        wish_speed: f32,
+        air_accelerate: f32,
+        max_air_wish_speed: f32,
+        delta_time: f32,
+        out: u32,
+    ) {
+        self.air_accelerate(current_vel, wish_dir, wish_speed, air_accelerate, max_air_wish_speed, delta_time, out);
+    }
+
+    #[wasm_bindgen(js_name = playerMove)]
+    pub fn player_move_wasm(
+        &mut self,
+        direction: u32,
+        delta_time: f32,
+        speed_multiplier: f32,
+        down_ray_hit: Option<RayCollisionHit>,
+        out: u32,
+    ) {
+        self.player_move(direction, delta_time, speed_multiplier, down_ray_hit, out);
+    }
+
+    #[wasm_bindgen(js_name = gravityVelocityChange)]
+    pub fn gravity_velocity_change_wasm(&mut self, gravity: u32, delta: f32, legacy_scale: Option<f32>, out: u32) {
+        self.gravity_velocity_change(gravity, delta, legacy_scale, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_run_movement_without_growing() {
+        let mut pool = Vector3Pool::new(2);
+        for _ in 0..3 {
+            let velocity = pool.acquire();
+            let wish_dir = pool.acquire();
+            let out = pool.acquire();
+            pool.set(velocity, Vector3::new(5.0, 0.0, 0.0));
+            pool.set(wish_dir, Vector3::new(0.0, 0.0, 1.0));
+            pool.air_accelerate(velocity, wish_dir, 100.0, 10.0, 30.0, 0.1, out);
+            let expected = air_accelerate_get_acceleration(
+                &Vector3::new(5.0, 0.0, 0.0),
+                &Vector3::new(0.0, 0.0, 1.0),
+                100.0,
+                10.0,
+                30.0,
+                0.1,
+            );
+            assert_eq!(pool.get(out), expected);
+            pool.add(velocity, out, velocity);
+            assert_eq!(pool.get(velocity), Vector3::new(5.0, 0.0, 0.0) + expected);
+
+            pool.set(wish_dir, Vector3::new(0.0, -800.0, 0.0));
+            pool.gravity_velocity_change(wish_dir, 0.5, None, out);
+            assert_eq!(pool.get(out), Vector3::new(0.0, -400.0, 0.0));
+            pool.release_all();
+        }
+        // Grew once to the frame's three vectors, then reused them
+        assert_eq!(pool.capacity(), 3);
+        assert_eq!(pool.used(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_released_handles_are_rejected() {
+        let mut pool = Vector3Pool::new(4);
+        let handle = pool.acquire();
+        pool.release_all();
+        pool.get(handle);
+    }
+}
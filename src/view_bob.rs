@@ -0,0 +1,127 @@
+//! View bobbing and strafe roll for the camera
+//!
+//! Classic Quake `V_CalcBob` / `V_CalcRoll`: the camera bobs along the up axis
+//! with horizontal speed over a repeating cycle and rolls towards the side the
+//! player is moving to. Clients feed in the velocity from `player_tick`, so the
+//! HUD camera follows the authoritative speed instead of a local estimate.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, UpAxis, Vector3};
+
+/// Lowest bob offset in Hammer units (Quake clamps to -7..4)
+const MIN_BOB: f32 = -7.0;
+/// Highest bob offset in Hammer units
+const MAX_BOB: f32 = 4.0;
+
+/// Camera offset and roll for one frame
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ViewBob {
+    /// Offset along the up axis in world units
+    pub height: f32,
+    /// Roll in radians, positive when moving to the right
+    pub roll: f32,
+}
+
+impl ViewBob {
+    /// The bob offset as a world-space vector
+    pub fn offset(&self, up_axis: UpAxis) -> Vector3 {
+        up_axis.up().multiply_scalar(self.height)
+    }
+}
+
+/// Bob offset along the up axis (`V_CalcBob`)
+///
+/// # Arguments
+/// * `time` - Client time in seconds
+/// * `velocity` - Player velocity in world units
+/// * `config` - Supplies `bob_scale`, `bob_cycle`, `bob_up`, `unit_scale` and `up_axis`
+///
+/// # Returns
+/// The offset in world units, clamped to the Quake range
+pub fn view_bob_height(time: f32, velocity: &Vector3, config: &MovementConfig) -> f32 {
+    if config.bob_cycle <= 0.0 {
+        return 0.0;
+    }
+    let phase = (time / config.bob_cycle).rem_euclid(1.0);
+    // Rise quickly over the first `bob_up` of the cycle, then fall over the rest
+    let cycle = if phase < config.bob_up {
+        std::f32::consts::PI * phase / config.bob_up
+    } else {
+        std::f32::consts::PI + std::f32::consts::PI * (phase - config.bob_up) / (1.0 - config.bob_up)
+    };
+    let bob = config.up_axis.horizontal(velocity).magnitude() * config.bob_scale;
+    let bob = bob * 0.3 + bob * 0.7 * cycle.sin();
+    bob.clamp(config.to_world_units(MIN_BOB), config.to_world_units(MAX_BOB))
+}
+
+/// Camera roll from sideways movement (`V_CalcRoll`)
+///
+/// Rolls linearly with the velocity along the view's right direction until
+/// `roll_speed`, then stays at `roll_angle`.
+pub fn view_roll(yaw: f32, velocity: &Vector3, config: &MovementConfig) -> f32 {
+    let side = velocity.dot(&config.up_axis.right(yaw));
+    let roll_speed = config.to_world_units(config.roll_speed);
+    let roll = if side.abs() < roll_speed {
+        side.abs() * config.roll_angle / roll_speed
+    } else {
+        config.roll_angle
+    };
+    roll.copysign(side)
+}
+
+/// Bob offset and roll for the camera of a player looking along `yaw`
+pub fn view_bob(time: f32, yaw: f32, velocity: &Vector3, config: &MovementConfig) -> ViewBob {
+    ViewBob {
+        height: view_bob_height(time, velocity, config),
+        roll: view_roll(yaw, velocity, config),
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = viewBob)]
+pub fn view_bob_wasm(time: f32, yaw: f32, velocity: &Vector3, config: &MovementConfig) -> ViewBob {
+    view_bob(time, yaw, velocity, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_bob_follows_quake_cycle() {
+        let config = y_up_config();
+        let velocity = Vector3::new(200.0, 0.0, 0.0);
+        let bob = 200.0 * config.bob_scale;
+
+        // Cycle start, peak of the rise and bottom of the fall
+        assert!((view_bob_height(0.0, &velocity, &config) - bob * 0.3).abs() < 0.0001);
+        assert!((view_bob_height(0.15, &velocity, &config) - bob).abs() < 0.0001);
+        assert!((view_bob_height(0.45, &velocity, &config) + bob * 0.4).abs() < 0.0001);
+        // Repeats every `bob_cycle` seconds
+        assert!((view_bob_height(0.75, &velocity, &config) - bob).abs() < 0.0001);
+
+        // Vertical speed does not bob the view and fast movement is clamped
+        assert_eq!(view_bob_height(0.15, &Vector3::new(0.0, 500.0, 0.0), &config), 0.0);
+        assert_eq!(view_bob_height(0.15, &Vector3::new(1000.0, 0.0, 0.0), &config), 4.0);
+    }
+
+    #[test]
+    fn test_roll_leans_into_strafe_direction() {
+        let config = y_up_config();
+        let right = config.up_axis.right(0.0);
+
+        let half = view_roll(0.0, &right.multiply_scalar(100.0), &config);
+        assert!((half - config.roll_angle * 0.5).abs() < 0.0001);
+        assert_eq!(view_roll(0.0, &right.multiply_scalar(-400.0), &config), -config.roll_angle);
+        assert_eq!(view_roll(0.0, &config.up_axis.forward(0.0).multiply_scalar(300.0), &config), 0.0);
+
+        // Roll speed is in config units
+        let meters = MovementConfig { unit_scale: 0.0254, ..config };
+        let roll = view_roll(0.0, &right.multiply_scalar(100.0 * 0.0254), &meters);
+        assert!((roll - config.roll_angle * 0.5).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,201 @@
+//! View punch: short camera kicks from landings, knockback and damage
+//!
+//! Punch angles are added on top of the view angles and decay back to zero
+//! exponentially. Decaying with `exp(-rate * dt)` keeps the kick the same
+//! length at any tick rate, and feeding it the `Landed` events of
+//! `player_tick` ties landing kicks to the simulated fall speed.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::MovementEvent;
+#[cfg(feature = "wasm")]
+use crate::{MovementEventInfo, MovementEventKind};
+
+/// Default decay rate (1/s); a punch falls to ~5% within a third of a second
+pub const DEFAULT_PUNCH_DECAY_RATE: f32 = 9.0;
+
+/// Landing speed above which landings kick the view (PLAYER_FALL_PUNCH_THRESHHOLD)
+pub const LANDING_PUNCH_THRESHOLD: f32 = 350.0;
+
+/// Roll kick per unit of landing speed, 0.013 degrees as in Half-Life
+const LANDING_PUNCH_ROLL: f32 = 0.013 * std::f32::consts::PI / 180.0;
+
+/// Angles below this (radians) snap to zero so the punch settles exactly
+const PUNCH_EPSILON: f32 = 1e-5;
+
+/// Accumulated punch angles with exponential decay
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewPunch {
+    pitch: f32,
+    yaw: f32,
+    roll: f32,
+    decay_rate: f32,
+}
+
+impl Default for ViewPunch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ViewPunch {
+    /// Create a punch at rest using `DEFAULT_PUNCH_DECAY_RATE`
+    pub fn new() -> ViewPunch {
+        Self::with_decay_rate(DEFAULT_PUNCH_DECAY_RATE)
+    }
+
+    /// Create a punch at rest decaying by `exp(-decay_rate * delta_time)` per tick
+    pub fn with_decay_rate(decay_rate: f32) -> ViewPunch {
+        ViewPunch { pitch: 0.0, yaw: 0.0, roll: 0.0, decay_rate: decay_rate.max(0.0) }
+    }
+
+    /// Current pitch offset in radians (positive looks up)
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// Current yaw offset in radians (positive turns left)
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    /// Current roll offset in radians
+    pub fn roll(&self) -> f32 {
+        self.roll
+    }
+
+    /// Whether all angles have decayed to zero
+    pub fn is_settled(&self) -> bool {
+        self.pitch == 0.0 && self.yaw == 0.0 && self.roll == 0.0
+    }
+
+    /// Add a kick in radians, e.g. a damage flinch or knockback
+    pub fn add(&mut self, pitch: f32, yaw: f32, roll: f32) {
+        self.pitch += pitch;
+        self.yaw += yaw;
+        self.roll += roll;
+    }
+
+    /// Add the roll kick for landing at `landing_speed` (no kick below the threshold)
+    pub fn add_landing(&mut self, landing_speed: f32) {
+        if landing_speed > LANDING_PUNCH_THRESHOLD {
+            self.roll += landing_speed * LANDING_PUNCH_ROLL;
+        }
+    }
+
+    /// Add the kicks for the events of one `player_tick`
+    pub fn apply_events(&mut self, events: &[MovementEvent]) {
+        for event in events {
+            if let MovementEvent::Landed { landing_speed, .. } = *event {
+                self.add_landing(landing_speed);
+            }
+        }
+    }
+
+    /// Decay the punch over one time step
+    pub fn decay(&mut self, delta_time: f32) {
+        let factor = (-self.decay_rate * delta_time.max(0.0)).exp();
+        for angle in [&mut self.pitch, &mut self.yaw, &mut self.roll] {
+            *angle *= factor;
+            if angle.abs() < PUNCH_EPSILON {
+                *angle = 0.0;
+            }
+        }
+    }
+
+    /// Clear all punch angles (e.g. on respawn)
+    pub fn reset(&mut self) {
+        self.pitch = 0.0;
+        self.yaw = 0.0;
+        self.roll = 0.0;
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl ViewPunch {
+    #[wasm_bindgen(constructor)]
+    pub fn new_wasm(decay_rate: f32) -> ViewPunch {
+        ViewPunch::with_decay_rate(decay_rate)
+    }
+
+    #[wasm_bindgen(getter = pitch)]
+    pub fn pitch_wasm(&self) -> f32 {
+        self.pitch
+    }
+
+    #[wasm_bindgen(getter = yaw)]
+    pub fn yaw_wasm(&self) -> f32 {
+        self.yaw
+    }
+
+    #[wasm_bindgen(getter = roll)]
+    pub fn roll_wasm(&self) -> f32 {
+        self.roll
+    }
+
+    #[wasm_bindgen(js_name = add)]
+    pub fn add_wasm(&mut self, pitch: f32, yaw: f32, roll: f32) {
+        self.add(pitch, yaw, roll);
+    }
+
+    #[wasm_bindgen(js_name = addLanding)]
+    pub fn add_landing_wasm(&mut self, landing_speed: f32) {
+        self.add_landing(landing_speed);
+    }
+
+    #[wasm_bindgen(js_name = applyEvent)]
+    pub fn apply_event_wasm(&mut self, event: &MovementEventInfo) {
+        if event.kind == MovementEventKind::Landed {
+            self.add_landing(event.speed);
+        }
+    }
+
+    #[wasm_bindgen(js_name = decay)]
+    pub fn decay_wasm(&mut self, delta_time: f32) {
+        self.decay(delta_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decay_is_tick_rate_independent() {
+        let mut fast = ViewPunch::new();
+        let mut slow = ViewPunch::new();
+        fast.add(0.1, -0.05, 0.02);
+        slow.add(0.1, -0.05, 0.02);
+
+        for _ in 0..128 {
+            fast.decay(1.0 / 128.0);
+        }
+        for _ in 0..32 {
+            slow.decay(1.0 / 32.0);
+        }
+        // One second at the default rate leaves exp(-9) of the kick
+        assert!((fast.pitch() - 0.1 * (-9.0f32).exp()).abs() < 1e-6);
+        assert!((fast.pitch() - slow.pitch()).abs() < 1e-6);
+
+        for _ in 0..128 {
+            fast.decay(1.0 / 128.0);
+        }
+        assert!(fast.is_settled());
+    }
+
+    #[test]
+    fn test_landing_events_kick_roll() {
+        let mut punch = ViewPunch::new();
+        let landed = |landing_speed| MovementEvent::Landed { tick: 0, landing_speed, surface_material: 0, impact: 0.0, ground_pound: false };
+
+        punch.apply_events(&[landed(200.0)]);
+        assert!(punch.is_settled());
+
+        punch.apply_events(&[MovementEvent::SlideStopped { tick: 0 }, landed(600.0)]);
+        assert!((punch.roll().to_degrees() - 7.8).abs() < 0.001);
+        assert_eq!(punch.pitch(), 0.0);
+    }
+}
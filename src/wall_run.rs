@@ -0,0 +1,89 @@
+//! Wall-run momentum chaining (Titanfall style)
+//!
+//! The crate has no wall-run move type; hosts that implement wall running
+//! report each jump off a wall run through `wall_run_chain_jump`. Every jump
+//! in a chain adds a larger speed bonus along the horizontal velocity, up to
+//! `wall_run_chain_max_bonus` above `max_speed`. Standing on the ground
+//! decays the chain at `wall_run_chain_decay` links per second, so only
+//! uninterrupted chains keep stacking.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerState};
+
+/// Speed bonus granted by the next link of the player's chain
+pub fn wall_run_chain_bonus(state: &PlayerState, config: &MovementConfig) -> f32 {
+    let config = config.in_world_units();
+    ((state.wall_run_chain.floor() + 1.0) * config.wall_run_chain_bonus).min(config.wall_run_chain_max_bonus)
+}
+
+/// Extend the chain after a jump off a wall run and apply its speed bonus
+///
+/// Call after the host applied the wall jump velocity. The bonus never pushes
+/// horizontal speed past `max_speed + wall_run_chain_max_bonus`, and never
+/// slows down a player who is already faster.
+///
+/// # Returns
+/// The horizontal speed gained
+pub fn wall_run_chain_jump(state: &mut PlayerState, config: &MovementConfig) -> f32 {
+    let bonus = wall_run_chain_bonus(state, config);
+    state.wall_run_chain = state.wall_run_chain.floor() + 1.0;
+
+    let config = config.in_world_units();
+    let axis = config.up_axis;
+    let horizontal = axis.horizontal(&state.velocity);
+    let speed = horizontal.magnitude();
+    if speed == 0.0 {
+        return 0.0;
+    }
+    let cap = config.max_speed + config.wall_run_chain_max_bonus;
+    let boosted = (speed + bonus).min(cap.max(speed));
+    let mut velocity = horizontal.multiply_scalar(boosted / speed);
+    axis.set_height(&mut velocity, axis.height(&state.velocity));
+    state.velocity = velocity;
+    boosted - speed
+}
+
+/// Decay the chain while standing on the ground
+pub(crate) fn decay_wall_run_chain(state: &mut PlayerState, config: &MovementConfig, delta_time: f32) {
+    state.wall_run_chain = (state.wall_run_chain - config.wall_run_chain_decay * delta_time).max(0.0);
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = wallRunChainJump)]
+pub fn wall_run_chain_jump_wasm(state: &mut PlayerState, config: &MovementConfig) -> f32 {
+    wall_run_chain_jump(state, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, PlayerInput, RayCollisionHit, Vector3};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_chain_bonus_stacks_caps_and_decays_on_ground() {
+        let config = y_up_config();
+        let mut state = PlayerState { velocity: Vector3::new(0.0, 200.0, -300.0), ..PlayerState::default() };
+
+        let first = wall_run_chain_jump(&mut state, &config);
+        let second = wall_run_chain_jump(&mut state, &config);
+        assert!((first - config.wall_run_chain_bonus).abs() < 0.001);
+        assert!((second - 2.0 * config.wall_run_chain_bonus).abs() < 0.001);
+        assert_eq!(state.velocity.y, 200.0);
+        for _ in 0..10 {
+            wall_run_chain_jump(&mut state, &config);
+        }
+        assert!(state.velocity.z.abs() <= config.max_speed + config.wall_run_chain_max_bonus + 0.001);
+
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let mut grounded = PlayerState { on_ground: true, wall_run_chain: 2.0, ..PlayerState::default() };
+        let ticks = (2.0 / config.wall_run_chain_decay * 64.0) as usize + 1;
+        for _ in 0..ticks {
+            player_tick(&mut grounded, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+        }
+        assert_eq!(grounded.wall_run_chain, 0.0);
+        assert!((wall_run_chain_bonus(&grounded, &config) - config.wall_run_chain_bonus).abs() < 0.001);
+    }
+}
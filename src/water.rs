@@ -0,0 +1,206 @@
+//! Water
+//!
+//! The host reports how deep the player is in water through
+//! `PlayerState::water_level`, using Quake's levels: 0 = dry, 1 = feet,
+//! 2 = waist, 3 = eyes. Buoyancy cancels part of gravity in proportion to the
+//! level, so a player floats up until enough of them is above the surface and
+//! then bobs there. Swimming down (ducking or negative `up_move`) uses the
+//! weaker sink buoyancy so the player can dive.
+//!
+//! Rivers and flow maps report the flow of the volume the player is in through
+//! `PlayerState::water_current`. Like a conveyor, the current carries the
+//! player without becoming part of their velocity, and it carries them more
+//! the deeper they are in the water.
+//!
+//! Swimming at waist depth into a low ledge triggers a GoldSrc/Source style
+//! water jump (`check_water_jump`): the player is thrown up and pushed over the
+//! edge with the controls locked until they are out of the water.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{MovementConfig, PlayerInput, PlayerState, RayCollisionHit, UpAxis, Vector3};
+
+/// Deepest water level (eyes under water)
+pub const MAX_WATER_LEVEL: u8 = 3;
+
+/// Fraction of gravity cancelled by buoyancy this tick
+///
+/// `water_float_buoyancy` (or `water_sink_buoyancy` while swimming down)
+/// scaled by the submerged fraction `water_level / 3`. Above 1.0 the player
+/// is pushed up.
+pub fn buoyancy(state: &PlayerState, input: &PlayerInput, config: &MovementConfig) -> f32 {
+    let submerged = state.water_level.min(MAX_WATER_LEVEL) as f32 / MAX_WATER_LEVEL as f32;
+    let swimming_down = input.duck || input.up_move < 0.0;
+    let buoyancy = if swimming_down { config.water_sink_buoyancy } else { config.water_float_buoyancy };
+    buoyancy * submerged
+}
+
+/// Base velocity from the water current, scaled by the submerged fraction
+pub fn water_current_velocity(state: &PlayerState) -> Vector3 {
+    let submerged = state.water_level.min(MAX_WATER_LEVEL) as f32 / MAX_WATER_LEVEL as f32;
+    state.water_current.multiply_scalar(submerged)
+}
+
+/// Start a water jump when swimming into a low ledge
+///
+/// Call before `player_tick` with two rays cast forward along the view yaw:
+/// one from waist height and one from eye height. A wall in reach at the
+/// waist with nothing in front of the eyes is a ledge the player can climb.
+///
+/// # Returns
+/// Whether a water jump started
+pub fn check_water_jump(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    waist_ray_hit: Option<RayCollisionHit>,
+    eye_ray_hit: Option<RayCollisionHit>,
+) -> bool {
+    let config = &config.in_world_units();
+    let axis = config.up_axis;
+    if state.water_jump_time > 0.0 || state.water_level != 2 || input.forward_move <= 0.0 {
+        return false;
+    }
+    let in_reach = |hit: &RayCollisionHit| hit.distance_native() <= config.water_jump_reach;
+    let Some(wall) = waist_ray_hit.filter(in_reach) else {
+        return false;
+    };
+    let flat_normal = axis.horizontal(wall.normal_native()).normalized();
+    if eye_ray_hit.filter(in_reach).is_some() || axis.height(wall.normal_native()) >= config.min_ground_normal_up {
+        return false;
+    }
+    state.water_jump_velocity = flat_normal.multiply_scalar(-config.water_jump_push);
+    state.water_jump_time = config.water_jump_time;
+    state.velocity = state.water_jump_velocity;
+    axis.set_height(&mut state.velocity, config.water_jump_speed);
+    state.on_ground = false;
+    true
+}
+
+/// Keep pushing over the ledge during a water jump
+///
+/// # Returns
+/// Whether the player is water jumping this tick (controls are locked)
+pub(crate) fn water_jump_move(state: &mut PlayerState, axis: UpAxis, delta_time: f32) -> bool {
+    if state.water_jump_time <= 0.0 {
+        return false;
+    }
+    if state.water_level == 0 {
+        state.water_jump_time = 0.0;
+        return false;
+    }
+    state.water_jump_time = (state.water_jump_time - delta_time).max(0.0);
+    let height = axis.height(&state.velocity);
+    state.velocity = state.water_jump_velocity;
+    axis.set_height(&mut state.velocity, height);
+    true
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = buoyancy)]
+pub fn buoyancy_wasm(state: &PlayerState, input: &PlayerInput, config: &MovementConfig) -> f32 {
+    buoyancy(state, input, config)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = checkWaterJump)]
+pub fn check_water_jump_wasm(
+    state: &mut PlayerState,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    waist_ray_hit: Option<RayCollisionHit>,
+    eye_ray_hit: Option<RayCollisionHit>,
+) -> bool {
+    check_water_jump(state, input, config, waist_ray_hit, eye_ray_hit)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = waterCurrentVelocity)]
+pub fn water_current_velocity_wasm(state: &PlayerState) -> Vector3 {
+    water_current_velocity(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{player_tick, MoveType, RayCollisionHit};
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_buoyancy_follows_depth() {
+        let config = y_up_config();
+        let idle = PlayerInput::new();
+        let dive = PlayerInput { duck: true, ..PlayerInput::new() };
+        let state = |water_level| PlayerState { water_level, ..PlayerState::default() };
+
+        assert_eq!(buoyancy(&state(0), &idle, &config), 0.0);
+        assert!((buoyancy(&state(3), &idle, &config) - config.water_float_buoyancy).abs() < 0.0001);
+        assert!((buoyancy(&state(2), &idle, &config) - config.water_float_buoyancy * 2.0 / 3.0).abs() < 0.0001);
+        assert!((buoyancy(&state(3), &dive, &config) - config.water_sink_buoyancy).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_submerged_player_floats_and_dives() {
+        let config = y_up_config();
+        let dt = 1.0 / 64.0;
+        let origin = Vector3::new(0.0, -200.0, 0.0);
+        let submerged = PlayerState { move_type: MoveType::Swim, water_level: 3, ..PlayerState::new(origin) };
+
+        let mut floating = submerged;
+        let mut diving = submerged;
+        for _ in 0..32 {
+            player_tick(&mut floating, &PlayerInput::new(), &config, dt, None);
+            player_tick(&mut diving, &PlayerInput { up_move: -1.0, ..PlayerInput::new() }, &config, dt, None);
+        }
+        assert!(floating.velocity.y > 0.0);
+        assert!(diving.velocity.y < 0.0);
+    }
+
+    #[test]
+    fn test_current_carries_deeper_players_further() {
+        let config = y_up_config();
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let river = |water_level| PlayerState {
+            on_ground: true,
+            water_level,
+            water_current: Vector3::new(90.0, 0.0, 0.0),
+            ..PlayerState::default()
+        };
+
+        let mut wading = river(1);
+        let mut submerged = river(3);
+        for _ in 0..64 {
+            player_tick(&mut wading, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+            player_tick(&mut submerged, &PlayerInput::new(), &config, 1.0 / 64.0, ground);
+        }
+        assert!((wading.origin.x - 30.0).abs() < 0.01);
+        assert!((submerged.origin.x - 90.0).abs() < 0.01);
+        assert_eq!(submerged.velocity, Vector3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_water_jump_over_low_ledge() {
+        let config = y_up_config();
+        let input = PlayerInput { forward_move: 1.0, side_move: 1.0, ..PlayerInput::new() };
+        // Pool edge in front of a Y-up player looking down -Z
+        let ledge = Some(RayCollisionHit::new_native(Vector3::new(0.0, 0.0, 1.0), 16.0));
+        let mut state = PlayerState { move_type: MoveType::Swim, water_level: 2, ..PlayerState::default() };
+
+        // A wall at eye height is not a ledge
+        assert!(!check_water_jump(&mut state, &input, &config, ledge, ledge));
+        assert!(check_water_jump(&mut state, &input, &config, ledge, None));
+        assert_eq!(state.velocity, Vector3::new(0.0, config.water_jump_speed, -config.water_jump_push));
+
+        player_tick(&mut state, &input, &config, 1.0 / 64.0, None);
+        assert_eq!(state.velocity.x, 0.0);
+        assert_eq!(state.velocity.z, -config.water_jump_push);
+
+        // Leaving the water gives the controls back
+        state.water_level = 0;
+        let strafe = PlayerInput { side_move: 1.0, ..PlayerInput::new() };
+        player_tick(&mut state, &strafe, &config, 1.0 / 64.0, None);
+        assert_eq!(state.water_jump_time, 0.0);
+        assert!(state.velocity.x > 0.0);
+    }
+}
@@ -0,0 +1,117 @@
+//! Wish direction from view angles and movement input
+//!
+//! Every move type turns `forward_move`, `side_move` and `up_move` into a
+//! direction and an amount the same way `player_tick` does, so client-side
+//! prediction, bots and UI can call `compute_wishdir` instead of rebuilding
+//! the basis themselves. Walking move types combine the horizontal forward
+//! and right vectors of the view yaw. Swimming and flying also follow the view
+//! pitch, so looking down and pressing forward descends, and add `up_move`
+//! along the up axis. `classic_swim` switches swimming to the older controls
+//! where forward stays level and only `up_move` changes depth. The stick response of `analog`
+//! applies to forward and side movement, and the amount is capped at 1.0 so
+//! diagonal input is not faster than straight input.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::analog::analog_move;
+use crate::fixed::with_fixed;
+use crate::{MoveType, MovementConfig, PlayerInput, UpAxis, Vector3};
+
+/// Normalized wish direction and move amount (0.0 to 1.0) for view angles in radians
+///
+/// The wish speed of a tick is the move type's max speed times the amount.
+pub fn compute_wishdir(
+    yaw: f32,
+    pitch: f32,
+    input: &PlayerInput,
+    move_type: MoveType,
+    config: &MovementConfig,
+) -> (Vector3, f32) {
+    with_fixed!(config.up_axis, Z_UP => compute_wishdir_fixed::<Z_UP>(yaw, pitch, input, move_type, config))
+}
+
+/// `compute_wishdir` for the up axis of a `fixed` instantiation
+pub(crate) fn compute_wishdir_fixed<const Z_UP: bool>(
+    yaw: f32,
+    pitch: f32,
+    input: &PlayerInput,
+    move_type: MoveType,
+    config: &MovementConfig,
+) -> (Vector3, f32) {
+    let axis = UpAxis::from_z_up(Z_UP);
+    let (forward_move, side_move) = analog_move(input, config);
+    let mut wish = match move_type {
+        MoveType::Swim if config.classic_swim => {
+            let mut wish = axis.forward(yaw).multiply_scalar(forward_move);
+            wish.add(&axis.up().multiply_scalar(input.up_move));
+            wish
+        }
+        MoveType::Swim | MoveType::Fly | MoveType::Noclip | MoveType::Spectator => {
+            let mut wish = axis.view_forward(yaw, pitch).multiply_scalar(forward_move);
+            wish.add(&axis.up().multiply_scalar(input.up_move));
+            wish
+        }
+        MoveType::Walk | MoveType::Ladder | MoveType::Glide | MoveType::Climb => {
+            axis.forward(yaw).multiply_scalar(forward_move)
+        }
+    };
+    wish.add(&axis.right(yaw).multiply_scalar(side_move));
+    (wish.normalized(), wish.magnitude().min(1.0))
+}
+
+/// Wish direction scaled by the move amount
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = computeWishdir)]
+pub fn compute_wishdir_wasm(
+    yaw: f32,
+    pitch: f32,
+    input: &PlayerInput,
+    move_type: MoveType,
+    config: &MovementConfig,
+) -> Vector3 {
+    let (wish_dir, amount) = compute_wishdir(yaw, pitch, input, move_type, config);
+    wish_dir.multiply_scalar(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_walking_ignores_pitch_and_swimming_follows_it() {
+        let config = y_up_config();
+        let axis = config.up_axis;
+        let input = PlayerInput { forward_move: 1.0, side_move: 1.0, up_move: 1.0, ..PlayerInput::new() };
+        let pitch = 0.5;
+
+        let (walk, amount) = compute_wishdir(0.3, pitch, &input, MoveType::Walk, &config);
+        assert_eq!(amount, 1.0);
+        assert_eq!(axis.height(&walk), 0.0);
+        assert!((walk.dot(&axis.forward(0.3)) - walk.dot(&axis.right(0.3))).abs() < 1e-6);
+
+        let (swim, _) = compute_wishdir(0.3, pitch, &input, MoveType::Swim, &config);
+        let mut expected = axis.view_forward(0.3, pitch);
+        expected.add(&axis.right(0.3));
+        expected.add(&axis.up());
+        assert!((swim.dot(&expected.normalized()) - 1.0).abs() < 1e-6);
+
+        let idle = compute_wishdir(0.3, pitch, &PlayerInput::new(), MoveType::Fly, &config);
+        assert_eq!(idle.1, 0.0);
+    }
+
+    #[test]
+    fn test_classic_swim_keeps_forward_level() {
+        let config = MovementConfig { classic_swim: true, ..y_up_config() };
+        let axis = config.up_axis;
+        let forward = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let (dir, _) = compute_wishdir(0.3, -1.0, &forward, MoveType::Swim, &config);
+        assert_eq!(axis.height(&dir), 0.0);
+        let (dir, _) = compute_wishdir(0.3, -1.0, &PlayerInput { up_move: -1.0, ..forward }, MoveType::Swim, &config);
+        assert!(axis.height(&dir) < 0.0);
+        // Flying still follows the view
+        let (dir, _) = compute_wishdir(0.3, -1.0, &forward, MoveType::Fly, &config);
+        assert_ne!(axis.height(&dir), 0.0);
+    }
+}
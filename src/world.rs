@@ -0,0 +1,644 @@
+//! Many players in structure-of-arrays form
+//!
+//! Between ticks a server mostly reads its players' positions, velocities and
+//! ground flags: to build the next ray casts and to send snapshots. `World`
+//! keeps those in separate `f32`/`u8` arrays so such read-only passes walk
+//! contiguous memory, and keeps the rest of each `PlayerState` in a cold array.
+//! The arrays do not speed up the tick itself: `advance_all` and `apply_input`
+//! put each player's full `PlayerState` back together, tick it and split it up
+//! again, which costs about as much as ticking a `Vec<PlayerState>`. Players
+//! are addressed by index; removing one moves the last player into its slot.
+//!
+//! Every player also gets a `PlayerId` when spawned. Ids stay valid until the
+//! player is despawned and are never reused, so servers and JS playgrounds can
+//! keep them in their own tables instead of tracking indices as they move.
+//!
+//! `snapshot` captures every player in a `WorldSnapshot` and `restore` puts
+//! the world back into that state, for rollback netcode, server checkpoints
+//! and rewinding a whole match.
+//!
+//! With the `rayon` feature `advance_all` simulates the players in parallel:
+//! each player only depends on its own input and ray hits, so the results are
+//! the same as ticking them one by one, and events are still reported in
+//! index order. Lockstep servers that must not depend on a thread pool can
+//! switch to deterministic mode, which always ticks on the calling thread.
+//! Ticking on the calling thread reuses the world's event buffers, so once
+//! they have grown to fit a busy tick `advance_all` no longer allocates.
+//!
+//! For lag compensation, `record_history` stores every player's state after
+//! a server tick under the server time, and `state_at` reads it back for a
+//! past time: the origin, velocity and hull are interpolated between the two
+//! recorded ticks around it, so hit registration rewinds to exactly the
+//! states the mover produced. History older than `history_length` is dropped.
+//!
+//! Like the modifiers, the world is not available through WASM.
+
+use std::collections::{HashMap, VecDeque};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::events::MovementEvent;
+use crate::hull::{current_hull, Hull};
+use crate::{player_tick_into, MovementConfig, PlayerInput, PlayerState, RayCollisionHit, Vector3};
+
+/// Stable id of a player in a `World`
+pub type PlayerId = u32;
+
+/// Bit of `World::flags` set while the player stands on the ground
+pub const PLAYER_ON_GROUND: u8 = 1 << 0;
+/// Bit of `World::flags` set while the player has the ducked hull
+pub const PLAYER_DUCKED: u8 = 1 << 1;
+
+/// Seconds of lag compensation history a new `World` keeps
+pub const DEFAULT_HISTORY_LENGTH: f64 = 1.0;
+
+/// Player states of a server, with the fields read between ticks stored as structure of arrays
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    config: MovementConfig,
+    origin_x: Vec<f32>,
+    origin_y: Vec<f32>,
+    origin_z: Vec<f32>,
+    velocity_x: Vec<f32>,
+    velocity_y: Vec<f32>,
+    velocity_z: Vec<f32>,
+    flags: Vec<u8>,
+    /// Everything else; the origin, velocity and flags in here are stale
+    cold: Vec<PlayerState>,
+    ids: Vec<PlayerId>,
+    indices: HashMap<PlayerId, usize>,
+    next_id: PlayerId,
+    events: Vec<(usize, MovementEvent)>,
+    /// Events of the player being ticked, before they are tagged
+    scratch: Vec<MovementEvent>,
+    /// Per-player events and states of a parallel tick, kept to reuse their allocations
+    #[cfg(feature = "rayon")]
+    parallel_events: Vec<Vec<MovementEvent>>,
+    #[cfg(feature = "rayon")]
+    parallel_states: Vec<PlayerState>,
+    deterministic: bool,
+    /// Recorded states per player, oldest first
+    history: HashMap<PlayerId, VecDeque<(f64, PlayerState)>>,
+    history_length: f64,
+}
+
+/// A player's state at a past time, see `World::state_at`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PastState {
+    /// The state with interpolated origin and velocity; other fields come from the nearest recorded tick
+    pub state: PlayerState,
+    /// The hull, interpolated when the player ducked or stood up between the ticks
+    pub hull: Hull,
+    /// 1.0 within the recorded history, falling to 0.0 over one recording interval outside of it
+    pub confidence: f32,
+}
+
+/// Every player of a `World` at one point in time, see `World::snapshot`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WorldSnapshot {
+    players: Vec<PlayerState>,
+    ids: Vec<PlayerId>,
+    next_id: PlayerId,
+}
+
+impl WorldSnapshot {
+    /// Number of players captured
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Whether no players were captured
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// The captured players, in index order
+    pub fn players(&self) -> &[PlayerState] {
+        &self.players
+    }
+
+    /// The captured players' ids, in index order
+    pub fn ids(&self) -> &[PlayerId] {
+        &self.ids
+    }
+}
+
+impl World {
+    /// Create an empty world simulating every player with `config`
+    pub fn new(config: MovementConfig) -> World {
+        World { config, history_length: DEFAULT_HISTORY_LENGTH, ..World::default() }
+    }
+
+    /// The config every player is simulated with
+    pub fn config(&self) -> &MovementConfig {
+        &self.config
+    }
+
+    /// Tick players one by one on the calling thread, even with the `rayon` feature
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    /// Whether `advance_all` ticks players on the calling thread
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic || cfg!(not(feature = "rayon"))
+    }
+
+    /// Capture every player, including their ids and indices
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            players: (0..self.len()).map(|index| self.player(index)).collect(),
+            ids: self.ids.clone(),
+            next_id: self.next_id,
+        }
+    }
+
+    /// Put every player back the way `snapshot` captured them
+    ///
+    /// Players spawned since are gone and the id counter is rewound too, so
+    /// re-simulating the same spawns hands out the same ids again. The config
+    /// and deterministic mode are kept.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        for array in [
+            &mut self.origin_x,
+            &mut self.origin_y,
+            &mut self.origin_z,
+            &mut self.velocity_x,
+            &mut self.velocity_y,
+            &mut self.velocity_z,
+        ] {
+            array.clear();
+            array.resize(snapshot.len(), 0.0);
+        }
+        self.flags.clear();
+        self.flags.resize(snapshot.len(), 0);
+        self.cold.clear();
+        self.cold.extend_from_slice(&snapshot.players);
+        for (index, state) in snapshot.players.iter().enumerate() {
+            self.scatter(index, state);
+        }
+        self.ids.clone_from(&snapshot.ids);
+        self.indices.clear();
+        self.indices.extend(snapshot.ids.iter().enumerate().map(|(index, &id)| (id, index)));
+        self.next_id = snapshot.next_id;
+    }
+
+    /// Number of players
+    pub fn len(&self) -> usize {
+        self.cold.len()
+    }
+
+    /// Whether the world has no players
+    pub fn is_empty(&self) -> bool {
+        self.cold.is_empty()
+    }
+
+    /// Add a player and return its id; it is ticked at index `len() - 1`
+    pub fn spawn(&mut self, state: PlayerState) -> PlayerId {
+        self.origin_x.push(0.0);
+        self.origin_y.push(0.0);
+        self.origin_z.push(0.0);
+        self.velocity_x.push(0.0);
+        self.velocity_y.push(0.0);
+        self.velocity_z.push(0.0);
+        self.flags.push(0);
+        self.cold.push(state);
+        let index = self.cold.len() - 1;
+        self.scatter(index, &state);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.ids.push(id);
+        self.indices.insert(id, index);
+        id
+    }
+
+    /// Remove the player with `id`
+    ///
+    /// # Returns
+    /// The player's final state, or `None` if no player has this id
+    pub fn despawn(&mut self, id: PlayerId) -> Option<PlayerState> {
+        let index = self.index_of(id)?;
+        self.history.remove(&id);
+        Some(self.remove(index))
+    }
+
+    /// The current index of the player with `id`
+    pub fn index_of(&self, id: PlayerId) -> Option<usize> {
+        self.indices.get(&id).copied()
+    }
+
+    /// The id of the player at `index`
+    ///
+    /// # Panics
+    /// If `index` is out of bounds
+    pub fn id(&self, index: usize) -> PlayerId {
+        self.ids[index]
+    }
+
+    /// The full state of the player with `id`
+    pub fn get_state(&self, id: PlayerId) -> Option<PlayerState> {
+        self.index_of(id).map(|index| self.player(index))
+    }
+
+    /// Tick only the player with `id`, e.g. when its input arrives
+    ///
+    /// # Returns
+    /// The tick's events, or `None` if no player has this id
+    pub fn apply_input(
+        &mut self,
+        id: PlayerId,
+        input: &PlayerInput,
+        down_ray_hit: Option<RayCollisionHit>,
+        delta_time: f32,
+    ) -> Option<&[MovementEvent]> {
+        let index = self.index_of(id)?;
+        let mut state = self.player(index);
+        self.scratch.clear();
+        player_tick_into(&mut state, input, &self.config, delta_time, down_ray_hit, &mut self.scratch);
+        self.set_player(index, state);
+        Some(&self.scratch)
+    }
+
+    /// Remove a player, moving the last player into its index
+    ///
+    /// # Panics
+    /// If `index` is out of bounds
+    pub fn remove(&mut self, index: usize) -> PlayerState {
+        let state = self.player(index);
+        self.origin_x.swap_remove(index);
+        self.origin_y.swap_remove(index);
+        self.origin_z.swap_remove(index);
+        self.velocity_x.swap_remove(index);
+        self.velocity_y.swap_remove(index);
+        self.velocity_z.swap_remove(index);
+        self.flags.swap_remove(index);
+        self.cold.swap_remove(index);
+        self.indices.remove(&self.ids.swap_remove(index));
+        if let Some(&moved) = self.ids.get(index) {
+            self.indices.insert(moved, index);
+        }
+        state
+    }
+
+    /// The full state of a player
+    ///
+    /// # Panics
+    /// If `index` is out of bounds
+    pub fn player(&self, index: usize) -> PlayerState {
+        PlayerState {
+            origin: self.origin(index),
+            velocity: self.velocity(index),
+            on_ground: self.flags[index] & PLAYER_ON_GROUND != 0,
+            ducked: self.flags[index] & PLAYER_DUCKED != 0,
+            ..self.cold[index]
+        }
+    }
+
+    /// Replace the full state of a player
+    ///
+    /// # Panics
+    /// If `index` is out of bounds
+    pub fn set_player(&mut self, index: usize, state: PlayerState) {
+        self.cold[index] = state;
+        self.scatter(index, &state);
+    }
+
+    /// Position of a player
+    pub fn origin(&self, index: usize) -> Vector3 {
+        Vector3::new(self.origin_x[index], self.origin_y[index], self.origin_z[index])
+    }
+
+    /// Velocity of a player
+    pub fn velocity(&self, index: usize) -> Vector3 {
+        Vector3::new(self.velocity_x[index], self.velocity_y[index], self.velocity_z[index])
+    }
+
+    /// X, Y and Z coordinates of every player's origin
+    pub fn origins(&self) -> (&[f32], &[f32], &[f32]) {
+        (&self.origin_x, &self.origin_y, &self.origin_z)
+    }
+
+    /// X, Y and Z components of every player's velocity
+    pub fn velocities(&self) -> (&[f32], &[f32], &[f32]) {
+        (&self.velocity_x, &self.velocity_y, &self.velocity_z)
+    }
+
+    /// `PLAYER_ON_GROUND` and `PLAYER_DUCKED` bits of every player
+    pub fn flags(&self) -> &[u8] {
+        &self.flags
+    }
+
+    /// Seconds of history `record_history` keeps
+    pub fn history_length(&self) -> f64 {
+        self.history_length
+    }
+
+    /// Keep `seconds` of history from the next `record_history` on
+    pub fn set_history_length(&mut self, seconds: f64) {
+        self.history_length = seconds.max(0.0);
+    }
+
+    /// Record every player's current state for lag compensation
+    ///
+    /// Call this after each server tick with the server time in seconds.
+    /// Records at or after `time` are replaced, so a world that was restored
+    /// and re-simulated records its new states over the old ones.
+    pub fn record_history(&mut self, time: f64) {
+        for index in 0..self.len() {
+            let state = self.player(index);
+            let history = self.history.entry(self.ids[index]).or_default();
+            while history.back().is_some_and(|&(recorded, _)| recorded >= time) {
+                history.pop_back();
+            }
+            history.push_back((time, state));
+            // Keep the newest record at or before the cut, so queries right at it still interpolate
+            while history.get(1).is_some_and(|&(recorded, _)| recorded <= time - self.history_length) {
+                history.pop_front();
+            }
+        }
+    }
+
+    /// Drop all recorded history, e.g. when the map changes
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// The player with `id` as it was at server time `time`
+    ///
+    /// Between two recorded ticks the origin, velocity and hull are
+    /// interpolated with full confidence. Before the oldest or after the
+    /// newest record, that record is returned and the confidence falls to 0.0
+    /// over one recording interval.
+    ///
+    /// # Returns
+    /// The past state, or `None` if nothing was recorded for `id` or `time` is not finite
+    pub fn state_at(&self, id: PlayerId, time: f64) -> Option<PastState> {
+        if !time.is_finite() {
+            return None;
+        }
+        let history = self.history.get(&id)?;
+        let &(first_time, first) = history.front()?;
+        let &(last_time, last) = history.back()?;
+        let interval = |a: f64, b: f64| if b > a { b - a } else { f64::INFINITY };
+        if time <= first_time || time >= last_time {
+            let (state, distance, interval) = if time <= first_time {
+                let next = history.get(1).map_or(first_time, |&(recorded, _)| recorded);
+                (first, first_time - time, interval(first_time, next))
+            } else {
+                let previous = history.len().checked_sub(2).map_or(last_time, |index| history[index].0);
+                (last, time - last_time, interval(previous, last_time))
+            };
+            let confidence = if distance == 0.0 { 1.0 } else { (1.0 - distance / interval).max(0.0) as f32 };
+            return Some(PastState { state, hull: current_hull(&state, &self.config), confidence });
+        }
+
+        let after = history.partition_point(|&(recorded, _)| recorded <= time);
+        let (from_time, from) = history[after - 1];
+        let (to_time, to) = history[after];
+        let fraction = ((time - from_time) / (to_time - from_time)) as f32;
+        let lerp = |a: Vector3, b: Vector3| a + (b - a) * fraction;
+        let (from_hull, to_hull) = (current_hull(&from, &self.config), current_hull(&to, &self.config));
+        let nearest = if fraction < 0.5 { from } else { to };
+        let state =
+            PlayerState { origin: lerp(from.origin, to.origin), velocity: lerp(from.velocity, to.velocity), ..nearest };
+        let hull = Hull {
+            radius: from_hull.radius + (to_hull.radius - from_hull.radius) * fraction,
+            height: from_hull.height + (to_hull.height - from_hull.height) * fraction,
+            capsule: to_hull.capsule,
+        };
+        Some(PastState { state, hull, confidence: 1.0 })
+    }
+
+    /// Advance every player by one tick
+    ///
+    /// # Arguments
+    /// * `inputs` - Input per player index; players past the end get no input
+    /// * `down_ray_hits` - Down ray hit per player index; players past the end get none
+    /// * `delta_time` - Tick length in seconds
+    ///
+    /// # Returns
+    /// The tick's events, tagged with the index of the player they happened to
+    pub fn advance_all(
+        &mut self,
+        inputs: &[PlayerInput],
+        down_ray_hits: &[Option<RayCollisionHit>],
+        delta_time: f32,
+    ) -> &[(usize, MovementEvent)] {
+        self.events.clear();
+        #[cfg(feature = "rayon")]
+        if !self.deterministic {
+            let mut events = std::mem::take(&mut self.parallel_events);
+            let mut states = std::mem::take(&mut self.parallel_states);
+            events.resize_with(self.len(), Vec::new);
+            states.resize(self.len(), PlayerState::default());
+            let world = &*self;
+            states.par_iter_mut().zip(events.par_iter_mut()).enumerate().for_each(|(index, (state, events))| {
+                *state = world.tick_player(index, inputs, down_ray_hits, delta_time, events);
+            });
+            for (index, (state, player_events)) in states.iter().zip(&mut events).enumerate() {
+                self.events.extend(player_events.drain(..).map(|event| (index, event)));
+                self.set_player(index, *state);
+            }
+            self.parallel_events = events;
+            self.parallel_states = states;
+            return &self.events;
+        }
+        let mut scratch = std::mem::take(&mut self.scratch);
+        for index in 0..self.len() {
+            let state = self.tick_player(index, inputs, down_ray_hits, delta_time, &mut scratch);
+            self.events.extend(scratch.drain(..).map(|event| (index, event)));
+            self.set_player(index, state);
+        }
+        self.scratch = scratch;
+        &self.events
+    }
+
+    /// The state of a player after one tick, without storing it; events are appended to `events`
+    fn tick_player(
+        &self,
+        index: usize,
+        inputs: &[PlayerInput],
+        down_ray_hits: &[Option<RayCollisionHit>],
+        delta_time: f32,
+        events: &mut Vec<MovementEvent>,
+    ) -> PlayerState {
+        let input = inputs.get(index).copied().unwrap_or_default();
+        let down_ray_hit = down_ray_hits.get(index).copied().flatten();
+        let mut state = self.player(index);
+        player_tick_into(&mut state, &input, &self.config, delta_time, down_ray_hit, events);
+        state
+    }
+
+    /// Copy a player's hot fields into the arrays
+    fn scatter(&mut self, index: usize, state: &PlayerState) {
+        self.origin_x[index] = state.origin.x;
+        self.origin_y[index] = state.origin.y;
+        self.origin_z[index] = state.origin.z;
+        self.velocity_x[index] = state.velocity.x;
+        self.velocity_y[index] = state.velocity.y;
+        self.velocity_z[index] = state.velocity.z;
+        let mut flags = 0;
+        if state.on_ground {
+            flags |= PLAYER_ON_GROUND;
+        }
+        if state.ducked {
+            flags |= PLAYER_DUCKED;
+        }
+        self.flags[index] = flags;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_tick;
+    use crate::tick::tests::y_up_config;
+
+    #[test]
+    fn test_advance_all_matches_individual_ticks() {
+        let config = y_up_config();
+        let mut world = World::new(config);
+        let mut players = [
+            PlayerState::new(Vector3::new(0.0, 0.0, 0.0)),
+            PlayerState::new(Vector3::new(100.0, 50.0, 0.0)),
+            PlayerState { on_ground: true, ..PlayerState::new(Vector3::new(-40.0, 0.0, 8.0)) },
+        ];
+        for state in players {
+            world.spawn(state);
+        }
+        let inputs = [
+            PlayerInput { forward_move: 1.0, ..PlayerInput::new() },
+            PlayerInput { side_move: -1.0, ..PlayerInput::new() },
+        ];
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let hits = [None, None, ground];
+
+        for _ in 0..32 {
+            world.advance_all(&inputs, &hits, 1.0 / 64.0);
+            for (index, state) in players.iter_mut().enumerate() {
+                let input = inputs.get(index).copied().unwrap_or_default();
+                player_tick(state, &input, &config, 1.0 / 64.0, hits[index]);
+            }
+        }
+        for (index, state) in players.iter().enumerate() {
+            assert_eq!(world.player(index), *state);
+        }
+        assert_eq!(world.flags()[2], PLAYER_ON_GROUND);
+
+        assert_eq!(world.remove(0), players[0]);
+        assert_eq!(world.len(), 2);
+        assert_eq!(world.player(0), players[2]);
+        assert_eq!(world.origins().0[1], players[1].origin.x);
+    }
+
+    #[test]
+    fn test_ids_survive_removal() {
+        let config = y_up_config();
+        let mut world = World::new(config);
+        let ids: Vec<_> = (0..3).map(|i| world.spawn(PlayerState::new(Vector3::new(i as f32, 0.0, 0.0)))).collect();
+
+        assert_eq!(world.despawn(ids[0]).map(|state| state.origin.x), Some(0.0));
+        assert_eq!(world.despawn(ids[0]), None);
+        assert_eq!(world.index_of(ids[2]), Some(0));
+        assert_eq!(world.id(1), ids[1]);
+        assert_eq!(world.get_state(ids[2]).map(|state| state.origin.x), Some(2.0));
+        // Ids are never reused
+        assert!(!ids.contains(&world.spawn(PlayerState::default())));
+
+        let input = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+        let mut expected = world.get_state(ids[1]).unwrap();
+        player_tick(&mut expected, &input, &config, 1.0 / 64.0, None);
+        assert!(world.apply_input(ids[1], &input, None, 1.0 / 64.0).is_some());
+        assert_eq!(world.get_state(ids[1]), Some(expected));
+        assert!(world.apply_input(ids[0], &input, None, 1.0 / 64.0).is_none());
+    }
+
+    #[test]
+    fn test_restore_rewinds_the_whole_world() {
+        let mut world = World::new(y_up_config());
+        let first = world.spawn(PlayerState::new(Vector3::new(0.0, 0.0, 0.0)));
+        let second = world.spawn(PlayerState::new(Vector3::new(50.0, 0.0, 0.0)));
+        let inputs = [PlayerInput { forward_move: 1.0, ..PlayerInput::new() }; 2];
+        world.advance_all(&inputs, &[], 1.0 / 64.0);
+        let snapshot = world.snapshot();
+        let checkpoint: Vec<_> = (0..world.len()).map(|index| world.player(index)).collect();
+
+        for _ in 0..32 {
+            world.advance_all(&inputs, &[], 1.0 / 64.0);
+        }
+        world.despawn(first);
+        let late = world.spawn(PlayerState::default());
+
+        world.restore(&snapshot);
+        assert_eq!(snapshot.players(), checkpoint.as_slice());
+        assert_eq!(world.snapshot(), snapshot);
+        assert_eq!(world.index_of(first), Some(0));
+        assert_eq!(world.get_state(second), Some(checkpoint[1]));
+        assert_eq!(world.origins().0[1], checkpoint[1].origin.x);
+        assert_eq!(world.index_of(late), None);
+        assert_eq!(world.spawn(PlayerState::default()), late);
+    }
+
+    #[test]
+    fn test_deterministic_mode_matches_default_mode() {
+        let mut parallel = World::new(y_up_config());
+        for i in 0..64 {
+            parallel.spawn(PlayerState::new(Vector3::new(i as f32 * 10.0, 0.0, 0.0)));
+        }
+        let mut lockstep = parallel.clone();
+        lockstep.set_deterministic(true);
+        assert!(lockstep.is_deterministic());
+
+        let inputs: Vec<_> =
+            (0..64).map(|i| PlayerInput { yaw: i as f32 * 0.1, forward_move: 1.0, ..PlayerInput::new() }).collect();
+        let ground = Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0));
+        let hits = vec![ground; 64];
+        for _ in 0..16 {
+            let events = parallel.advance_all(&inputs, &hits, 1.0 / 64.0).to_vec();
+            assert_eq!(events, lockstep.advance_all(&inputs, &hits, 1.0 / 64.0));
+            assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        }
+        for index in 0..64 {
+            assert_eq!(parallel.player(index), lockstep.player(index));
+        }
+    }
+
+    #[test]
+    fn test_state_at_interpolates_recorded_ticks() {
+        let config = y_up_config();
+        let mut world = World::new(config);
+        let id = world.spawn(PlayerState { on_ground: true, ..PlayerState::default() });
+        let inputs = [PlayerInput { forward_move: 1.0, ..PlayerInput::new() }];
+        let ground = [Some(RayCollisionHit::new_native(Vector3::new(0.0, 1.0, 0.0), 0.0))];
+        let mut recorded = Vec::new();
+        for tick in 0..128 {
+            world.advance_all(&inputs, &ground, 1.0 / 64.0);
+            if tick == 100 {
+                let ducked = PlayerState { ducked: true, ..world.player(0) };
+                world.set_player(0, ducked);
+            }
+            world.record_history(tick as f64 / 64.0);
+            recorded.push(world.get_state(id).unwrap());
+        }
+
+        // Only the last second is kept; stored ticks come back exactly
+        assert!(world.state_at(id, 0.5).unwrap().confidence < 1.0);
+        let stored = world.state_at(id, 90.0 / 64.0).unwrap();
+        assert_eq!((stored.state, stored.confidence), (recorded[90], 1.0));
+
+        // Halfway between ticks, including the one the player ducked on
+        let between = world.state_at(id, 99.5 / 64.0).unwrap();
+        assert_eq!(between.state.origin, (recorded[99].origin + recorded[100].origin) * 0.5);
+        let (standing, ducked) = (current_hull(&recorded[99], &config), current_hull(&recorded[100], &config));
+        assert!(ducked.height < standing.height);
+        assert_eq!(between.hull.height, (standing.height + ducked.height) * 0.5);
+
+        // Past the newest tick the confidence runs out
+        assert_eq!(world.state_at(id, 127.5 / 64.0).unwrap().confidence, 0.5);
+        assert_eq!(world.state_at(id, 2.0).unwrap().confidence, 0.0);
+        assert_eq!(world.state_at(id, f64::NAN), None);
+        world.despawn(id);
+        assert_eq!(world.state_at(id, 1.5), None);
+    }
+}
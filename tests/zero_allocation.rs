@@ -0,0 +1,93 @@
+//! The per-tick path must not allocate once its buffers have grown
+//!
+//! This lives in its own test binary because it replaces the global
+//! allocator: it counts every allocation of the process, including those of
+//! the `rayon` worker threads. The binary has no libtest harness (see
+//! `Cargo.toml`), so no test runner thread allocates while it measures.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use movement_logic::{
+    player_tick_into, MovementConfig, MovementEvent, PlayerInput, PlayerState, RayCollisionHit, UpAxis, Vector3, World,
+};
+
+/// Counts the allocations made by any thread
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn flat_ground(state: &PlayerState) -> Option<RayCollisionHit> {
+    let height = state.origin.y();
+    (height >= 0.0).then(|| RayCollisionHit::new(0.0, 1.0, 0.0, height))
+}
+
+fn main() {
+    test_ticks_into_reused_buffers_do_not_allocate();
+    println!("test_ticks_into_reused_buffers_do_not_allocate ... ok");
+}
+
+fn test_ticks_into_reused_buffers_do_not_allocate() {
+    // Y up and standard gravity, whatever the `fixed` features pin
+    let config = MovementConfig { up_axis: UpAxis::Y, legacy_gravity: false, ..MovementConfig::default() };
+    let mut state = PlayerState::default();
+    let run = PlayerInput { forward_move: 1.0, ..PlayerInput::new() };
+    let hop = PlayerInput { jump: true, ..run };
+    let mut events = Vec::with_capacity(16);
+    // Both the one-by-one and, with the `rayon` feature, the parallel world path
+    let mut lockstep = World::new(config);
+    lockstep.set_deterministic(true);
+    let mut parallel = World::new(config);
+    for i in 0..8 {
+        lockstep.spawn(PlayerState::new(Vector3::new(i as f32 * 50.0, 0.0, 0.0)));
+        parallel.spawn(PlayerState::new(Vector3::new(i as f32 * 50.0, 0.0, 0.0)));
+    }
+    let inputs = [hop; 8];
+    let hits = [Some(RayCollisionHit::new(0.0, 1.0, 0.0, 0.0)); 8];
+    // Let the worlds' buffers grow to fit a tick where everyone jumps, and the worker threads start up
+    for _ in 0..64 {
+        lockstep.advance_all(&inputs, &hits, 1.0 / 64.0);
+        parallel.advance_all(&inputs, &hits, 1.0 / 64.0);
+    }
+
+    on_pool_thread(|| {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let mut jumps = 0;
+        for tick in 0..512 {
+            events.clear();
+            let hit = flat_ground(&state);
+            let input = if tick % 64 < 32 { hop } else { run };
+            player_tick_into(&mut state, &input, &config, 1.0 / 64.0, hit, &mut events);
+            jumps += events.iter().filter(|event| matches!(event, MovementEvent::Jumped { .. })).count();
+            lockstep.advance_all(&inputs, &hits, 1.0 / 64.0);
+            parallel.advance_all(&inputs, &hits, 1.0 / 64.0);
+        }
+        assert!(jumps > 1);
+        assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+    });
+}
+
+/// Run `body` on a `rayon` pool thread, if the feature is on
+///
+/// Rayon's queue for jobs sent in from outside its pool allocates a block
+/// every 63 jobs; jobs spawned from a pool thread reuse its own queue.
+fn on_pool_thread(body: impl FnOnce() + Send) {
+    #[cfg(feature = "rayon")]
+    rayon::scope(|_| body());
+    #[cfg(not(feature = "rayon"))]
+    body();
+}